@@ -546,6 +546,20 @@ bool evocore_context_reset(
  */
 void evocore_context_reset_all(evocore_context_system_t *system);
 
+/**
+ * Remove a context
+ *
+ * Deletes a context and all of its learning data, freeing its memory.
+ *
+ * @param system Context system
+ * @param context_key Context key
+ * @return true if the context existed and was removed
+ */
+bool evocore_context_remove_key(
+    evocore_context_system_t *system,
+    const char *context_key
+);
+
 /**
  * Get context confidence
  *
@@ -51,6 +51,29 @@ evocore_log_level_t evocore_log_get_level(void);
  */
 void evocore_log_close(void);
 
+/**
+ * Log callback
+ *
+ * Receives each message at or above the current level, already formatted.
+ * The strings are only valid for the duration of the call.
+ */
+typedef void (*evocore_log_callback_t)(evocore_log_level_t level,
+                                       const char *file,
+                                       int line,
+                                       const char *message,
+                                       void *user_data);
+
+/**
+ * Route log messages to a callback instead of stderr
+ *
+ * File logging is unaffected. Not thread-safe: set it before other
+ * threads start logging.
+ *
+ * @param callback  Callback, or NULL to log to stderr again
+ * @param user_data Passed to every call of the callback
+ */
+void evocore_log_set_callback(evocore_log_callback_t callback, void *user_data);
+
 /*========================================================================
  * Logging Macros
  *========================================================================*/
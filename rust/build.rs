@@ -1,19 +1,198 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn main() {
     // Get the absolute path to the evocore-sys crate directory
     let crate_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
     let evocore_root = crate_dir.join("..");
+    println!("cargo:rustc-check-cfg=cfg(evocore_bindgen)");
+    println!("cargo:rustc-check-cfg=cfg(evocore_no_native)");
+    println!("cargo:rerun-if-env-changed=EVOCORE_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=EVOCORE_INCLUDE_DIR");
+    println!("cargo:rerun-if-env-changed=EVOCORE_FRAMEWORK_DIR");
+
+    // WebAssembly outside Emscripten has no C library unless one built for
+    // it is supplied; the crate then only offers the Rust backend
+    let target_arch = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    if target_arch == "wasm32" && target_os() != "emscripten" && std::env::var_os("EVOCORE_LIB_DIR").is_none() {
+        println!("cargo:rustc-cfg=evocore_no_native");
+        return;
+    }
+
+    // Loaded at runtime (src/dlopen.rs): nothing to link
+    if std::env::var_os("CARGO_FEATURE_DLOPEN").is_some() {
+        if dynamic() || std::env::var_os("CARGO_FEATURE_VENDORED").is_some() {
+            println!("cargo:warning=`dlopen` loads libevocore at runtime; `vendored` and `dynamic` have no effect");
+        }
+        let include_path =
+            std::env::var_os("EVOCORE_INCLUDE_DIR").map_or_else(|| evocore_root.join("include"), PathBuf::from);
+        println!("cargo:include={}", include_path.display());
+        check_bindings(&crate_dir, &include_path);
+        return;
+    }
+
+    if std::env::var_os("CARGO_FEATURE_VENDORED").is_some() {
+        if dynamic() {
+            println!("cargo:warning=the `dynamic` feature has no effect together with `vendored`");
+        }
+        let include_path = build_vendored(&crate_dir, &evocore_root);
+        check_bindings(&crate_dir, &include_path);
+        return;
+    }
+
+    // An installed library: an explicit directory or (on Apple targets)
+    // framework first, then pkg-config, then the repository's own build
+    // directory
+    let framework_dir = std::env::var_os("EVOCORE_FRAMEWORK_DIR").filter(|_| target_is_apple());
+    let include_path = if let Some(lib_dir) = std::env::var_os("EVOCORE_LIB_DIR") {
+        link_lib_dir(Path::new(&lib_dir))
+    } else if let Some(framework_dir) = framework_dir {
+        link_framework(Path::new(&framework_dir))
+    } else if let Some(include_path) = probe_pkg_config() {
+        include_path
+    } else {
+        link_repository_build(&evocore_root)
+    };
+    let include_path = std::env::var_os("EVOCORE_INCLUDE_DIR").map_or(include_path, PathBuf::from);
+
+    // Also add include path for any direct C header includes
+    println!("cargo:include={}", include_path.display());
+
+    check_bindings(&crate_dir, &include_path);
+}
+
+/// Whether the `dynamic` feature asks for the shared library instead of the static archive
+fn dynamic() -> bool {
+    std::env::var_os("CARGO_FEATURE_DYNAMIC").is_some()
+}
+
+fn target_os() -> String {
+    std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default()
+}
+
+fn target_is_apple() -> bool {
+    matches!(target_os().as_str(), "macos" | "ios")
+}
+
+/// File names the library to link may have on the target
+///
+/// On Windows a dynamic link goes through an import library; the DLL
+/// itself is only needed at run time.
+fn library_file_names() -> &'static [&'static str] {
+    let msvc = std::env::var("CARGO_CFG_TARGET_ENV").as_deref() == Ok("msvc");
+    match (target_os().as_str(), dynamic()) {
+        ("windows", _) if msvc => &["evocore.lib"],
+        ("windows", false) => &["libevocore.a", "evocore.lib"],
+        ("windows", true) => &["libevocore.dll.a", "evocore.lib"],
+        ("macos" | "ios", true) => &["libevocore.dylib"],
+        (_, true) => &["libevocore.so"],
+        (_, false) => &["libevocore.a"],
+    }
+}
+
+/// The library to link in `dir`, if it has one
+fn find_library(dir: &Path) -> Option<PathBuf> {
+    library_file_names().iter().map(|name| dir.join(name)).find(|path| path.exists())
+}
+
+/// Link `evocore` from a directory already on the search path
+fn link_evocore(lib_dir: &Path) {
+    if dynamic() {
+        println!("cargo:rustc-link-lib=dylib=evocore");
+        emit_rpath(lib_dir);
+    } else {
+        println!("cargo:rustc-link-lib=static=evocore");
+    }
+}
+
+/// Let binaries find the shared library where it was linked from
+///
+/// The rpath is set on this crate's own tests, examples and binaries.
+/// Dependents get the directory as `DEP_EVOCORE_RPATH` in their build
+/// scripts, or rely on the system library path (`ldconfig`,
+/// `LD_LIBRARY_PATH`). Windows has no rpath: `evocore.dll` has to be next
+/// to the executable or on `PATH`.
+fn emit_rpath(lib_dir: &Path) {
+    if target_os() == "windows" {
+        return;
+    }
+    println!("cargo:rustc-link-arg=-Wl,-rpath,{}", lib_dir.display());
+    println!("cargo:rpath={}", lib_dir.display());
+}
+
+/// Link `libevocore.a`/`evocore.lib` (or, with `dynamic`, the shared
+/// library) from `EVOCORE_LIB_DIR`; headers default to `../include` next
+/// to it
+fn link_lib_dir(lib_dir: &Path) -> PathBuf {
+    let lib_path = find_library(lib_dir).unwrap_or_else(|| {
+        panic!(
+            "EVOCORE_LIB_DIR is set to {}, but it does not contain {}",
+            lib_dir.display(),
+            library_file_names().join(" or ")
+        )
+    });
+
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    link_evocore(lib_dir);
+    println!("cargo:rerun-if-changed={}", lib_path.display());
+    lib_dir.join("..").join("include")
+}
+
+/// Link `evocore.framework` from `EVOCORE_FRAMEWORK_DIR`, returning its headers
+///
+/// Frameworks are always linked dynamically, so the directory is also
+/// added to the rpath.
+fn link_framework(framework_dir: &Path) -> PathBuf {
+    let framework = framework_dir.join("evocore.framework");
+    if !framework.is_dir() {
+        panic!(
+            "EVOCORE_FRAMEWORK_DIR is set to {}, but it does not contain evocore.framework",
+            framework_dir.display()
+        );
+    }
+
+    println!("cargo:rustc-link-search=framework={}", framework_dir.display());
+    println!("cargo:rustc-link-lib=framework=evocore");
+    emit_rpath(framework_dir);
+    println!("cargo:rerun-if-changed={}", framework.display());
+    framework.join("Headers")
+}
+
+/// Ask pkg-config for an installed `evocore`, returning its include directory
+///
+/// pkg-config emits the link flags itself, asking for static linking unless
+/// the `dynamic` feature is on. Set `EVOCORE_NO_PKG_CONFIG` to skip this
+/// step.
+fn probe_pkg_config() -> Option<PathBuf> {
+    let library = pkg_config::Config::new()
+        .cargo_metadata(true)
+        .statik(!dynamic())
+        .probe("evocore")
+        .ok()?;
+    if dynamic() {
+        // System directories are not listed, and need no rpath
+        library.link_paths.iter().for_each(|dir| emit_rpath(dir));
+    }
+    // Headers in a default search path are not listed
+    Some(library.include_paths.into_iter().next().unwrap_or_else(|| PathBuf::from("/usr/include")))
+}
+
+/// Link `libevocore.a` (or, with `dynamic`, the shared library) from the
+/// repository's `build/` directory
+fn link_repository_build(evocore_root: &Path) -> PathBuf {
     let build_path = evocore_root.join("build");
-    let lib_path = build_path.join("libevocore.a");
+    let lib_path = find_library(&build_path).unwrap_or_else(|| build_path.join(library_file_names()[0]));
+    let make_target = if dynamic() { "make libevocore.so" } else { "make" };
 
     // Canonicalize to get absolute paths for linking
     let build_path = build_path.canonicalize().unwrap_or_else(|_| {
         panic!(
             "EvoCore build directory not found at {}. \
-            Please build EvoCore first:\n  cd {} && make",
+            Please build EvoCore first:\n  cd {} && {}\n\
+            set EVOCORE_LIB_DIR to an installed copy, \
+            or enable the `vendored` feature to compile it with the crate",
             build_path.display(),
-            evocore_root.display()
+            evocore_root.display(),
+            make_target
         )
     });
 
@@ -21,16 +200,166 @@ fn main() {
     if !lib_path.exists() {
         panic!(
             "EvoCore library not found at {}. \
-            Please build EvoCore first:\n  cd {} && make",
+            Please build EvoCore first:\n  cd {} && {}\n\
+            set EVOCORE_LIB_DIR to an installed copy, \
+            or enable the `vendored` feature to compile it with the crate",
             lib_path.display(),
-            evocore_root.display()
+            evocore_root.display(),
+            make_target
         );
     }
 
     println!("cargo:rustc-link-search={}", build_path.display());
-    println!("cargo:rustc-link-lib=static=evocore");
+    link_evocore(&build_path);
+    println!("cargo:rerun-if-changed={}", lib_path.display());
+    evocore_root.join("include")
+}
 
-    // Also add include path for any direct C header includes
-    let include_path = evocore_root.join("include");
-    println!("cargo:include={}", include_path.display());
+/// Compile the C sources with `cc` instead of linking a pre-built library
+///
+/// Published crates carry a copy of `src/` and `include/` in
+/// `vendor/evocore` (see `make rust-vendor`); in a checkout the
+/// repository's own sources are used.
+fn build_vendored(crate_dir: &Path, evocore_root: &Path) -> PathBuf {
+    if target_os() == "windows" {
+        panic!(
+            "The `vendored` feature is not supported on Windows: the C sources need POSIX headers \
+            (pthread.h, sys/resource.h). Build evocore.lib separately and set EVOCORE_LIB_DIR"
+        );
+    }
+    let vendor_root = crate_dir.join("vendor").join("evocore");
+    let source_root = if vendor_root.join("src").is_dir() {
+        vendor_root
+    } else {
+        evocore_root.to_path_buf()
+    };
+    let src_dir = source_root.join("src");
+    let include_dir = source_root.join("include");
+
+    let mut sources: Vec<PathBuf> = std::fs::read_dir(&src_dir)
+        .unwrap_or_else(|e| panic!("EvoCore sources not found at {}: {}", src_dir.display(), e))
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "c"))
+        .collect();
+    sources.sort();
+
+    // Same flags as the Makefile, minus OpenMP, which the Rust side does
+    // not link against
+    cc::Build::new()
+        .files(&sources)
+        .include(&include_dir)
+        .include(&src_dir)
+        .flag_if_supported("-std=gnu99")
+        .flag_if_supported("-Wno-unknown-pragmas")
+        .define("EVOCORE_HAVE_PTHREADS", None)
+        .warnings(false)
+        .compile("evocore");
+
+    if target_os() == "linux" {
+        println!("cargo:rustc-link-lib=m");
+        println!("cargo:rustc-link-lib=pthread");
+    }
+    println!("cargo:rerun-if-changed={}", src_dir.display());
+    println!("cargo:rerun-if-changed={}", include_dir.display());
+    println!("cargo:include={}", include_dir.display());
+    include_dir
 }
+
+/// Regenerate `src/bindings.rs` with bindgen, and write the struct layout
+/// comparisons `src/ffi_check.rs` compiles
+///
+/// The crate is built against the regenerated declarations, which are
+/// also written back to `src/bindings.rs` when `EVOCORE_UPDATE_BINDINGS`
+/// is set. The functions bound are the ones the committed file declares.
+/// Fails the build if libclang or the headers cannot be found.
+#[cfg(feature = "bindgen")]
+fn check_bindings(crate_dir: &Path, include_dir: &Path) {
+    use std::fmt::Write;
+
+    println!("cargo:rerun-if-env-changed=EVOCORE_UPDATE_BINDINGS");
+    if let Err(e) = clang_sys::load() {
+        panic!("the `bindgen` feature needs libclang, which could not be loaded: {}", e);
+    }
+    // A framework's headers are not laid out for -I
+    let header = include_dir.join("evocore").join("evocore.h");
+    if !header.exists() {
+        panic!("the `bindgen` feature needs the EvoCore headers; {} not found", header.display());
+    }
+
+    let declared = |path: &Path, prefix: &str| -> Vec<String> {
+        let source = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{} not readable: {}", path.display(), e));
+        source
+            .lines()
+            .filter_map(|line| line.trim_start().strip_prefix(prefix))
+            .map(|rest| {
+                let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+                format!("evocore_{}", name)
+            })
+            .collect()
+    };
+    let bindings_path = crate_dir.join("src").join("bindings.rs");
+    let lib_rs_path = crate_dir.join("src").join("lib.rs");
+    let functions = declared(&bindings_path, "pub fn evocore_");
+    let structs = declared(&lib_rs_path, "pub struct evocore_");
+
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    let builder = || {
+        bindgen::Builder::default()
+            .header(header.to_string_lossy())
+            .clang_arg(format!("-I{}", include_dir.display()))
+            .layout_tests(false)
+            .generate_comments(false)
+    };
+
+    // Functions only, over the crate's own types, in one block for `evocore_functions!`
+    let generated = functions
+        .iter()
+        .fold(builder().blocklist_type("evocore_.*").merge_extern_blocks(true), |b, f| {
+            b.allowlist_function(f)
+        })
+        .generate()
+        .expect("bindgen failed on the EvoCore headers")
+        .to_string();
+    let bindings = format!(
+        "// Generated by build.rs with bindgen from include/evocore; do not edit.\n\
+         // Regenerate with `EVOCORE_UPDATE_BINDINGS=1 cargo build --features bindgen`.\n\
+         evocore_functions! {{\n{}}}\n",
+        generated
+    );
+    std::fs::write(out_dir.join("bindings.rs"), &bindings).expect("failed to write generated bindings");
+    if std::env::var_os("EVOCORE_UPDATE_BINDINGS").is_some() {
+        std::fs::write(&bindings_path, &bindings).expect("failed to update src/bindings.rs");
+    } else if std::fs::read_to_string(&bindings_path).ok().as_deref() != Some(bindings.as_str()) {
+        println!(
+            "cargo:warning=bindgen: src/bindings.rs differs from the headers; set EVOCORE_UPDATE_BINDINGS=1 to rewrite it"
+        );
+    }
+
+    structs
+        .iter()
+        .fold(builder(), |b, s| b.allowlist_type(s))
+        .generate()
+        .expect("bindgen failed on the EvoCore headers")
+        .write_to_file(out_dir.join("ffi_types.rs"))
+        .expect("failed to write generated types");
+
+    let mut check = String::from("const _: () = {\n");
+    for s in &structs {
+        writeln!(
+            check,
+            "    assert!(std::mem::size_of::<crate::{s}>() == std::mem::size_of::<types::{s}>(), \"{s}: size differs from the header\");\n    \
+             assert!(std::mem::align_of::<crate::{s}>() == std::mem::align_of::<types::{s}>(), \"{s}: alignment differs from the header\");"
+        )
+        .unwrap();
+    }
+    check.push_str("};\n");
+    std::fs::write(out_dir.join("ffi_check.rs"), check).expect("failed to write FFI checks");
+
+    println!("cargo:rustc-cfg=evocore_bindgen");
+    println!("cargo:rerun-if-changed={}", bindings_path.display());
+    println!("cargo:rerun-if-changed={}", lib_rs_path.display());
+    println!("cargo:rerun-if-changed={}", include_dir.display());
+}
+
+#[cfg(not(feature = "bindgen"))]
+fn check_bindings(_crate_dir: &Path, _include_dir: &Path) {}
@@ -0,0 +1,41 @@
+//! Per-parameter bounds
+
+/// Inclusive range of valid values for one parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamBounds {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ParamBounds {
+    pub fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    /// Whether `value` lies within the bounds
+    pub fn contains(&self, value: f64) -> bool {
+        value >= self.min && value <= self.max
+    }
+
+    /// Nearest value within the bounds (NaN maps to `min`)
+    pub fn clamp(&self, value: f64) -> f64 {
+        if value.is_nan() {
+            self.min
+        } else {
+            value.clamp(self.min, self.max)
+        }
+    }
+}
+
+/// How samples outside their bounds are brought into range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundsMode {
+    /// Replace out-of-range values with the nearest bound
+    Clamp,
+    /// Redraw out-of-range values up to `max_attempts` times, then clamp
+    ///
+    /// Keeps the shape of the distribution inside the range instead of
+    /// piling mass on the bounds, at the cost of extra samples (and an
+    /// allocation per call to `sample_into`).
+    Reject { max_attempts: usize },
+}
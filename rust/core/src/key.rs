@@ -0,0 +1,38 @@
+//! Context keys
+
+use alloc::format;
+use alloc::string::String;
+use core::ffi::CStr;
+
+/// Maximum context key length accepted by the C library (including NUL)
+pub const MAX_KEY_LENGTH: usize = 256;
+
+/// Join dimension values into `buf` as a NUL-terminated key, the way the C
+/// library does, without allocating
+///
+/// `None` if the key is too long or a value contains a NUL byte.
+pub fn key_into<'a>(dimension_values: &[&str], buf: &'a mut [u8; MAX_KEY_LENGTH]) -> Option<&'a CStr> {
+    let mut len = 0;
+    for (i, value) in dimension_values.iter().enumerate() {
+        if i > 0 {
+            *buf.get_mut(len)? = b':';
+            len += 1;
+        }
+        let bytes = value.as_bytes();
+        buf.get_mut(len..len + bytes.len())?.copy_from_slice(bytes);
+        len += bytes.len();
+    }
+    *buf.get_mut(len)? = 0;
+    CStr::from_bytes_with_nul(&buf[..=len]).ok()
+}
+
+/// Reject dimension values containing the `:` key separator
+///
+/// Such a value would read back as extra dimension values when a saved
+/// key is split, so a checkpoint holding it could not be loaded.
+pub fn check_key_values(dimension_values: &[&str]) -> Result<(), String> {
+    match dimension_values.iter().find(|v| v.contains(':')) {
+        Some(value) => Err(format!("Invalid dimension value: {:?}", value)),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,40 @@
+//! Backend-agnostic learner interface
+//!
+//! [`ContextLearner`] is implemented by the pure-Rust
+//! [`RustContextSystem`](crate::RustContextSystem) and, in `evocore-sys`,
+//! by the FFI-backed `EvoCoreContextSystem`, so wrappers and test harnesses
+//! can work with either.
+
+use crate::ContextState;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Operations shared by every context-learning backend
+pub trait ContextLearner {
+    /// Learn from experience with parameters
+    fn learn(&mut self, dimension_values: &[&str], parameters: &[f64], fitness: f64)
+        -> Result<(), String>;
+
+    /// Sample parameters for a context
+    fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String>;
+
+    /// Number of parameters tracked per context
+    fn param_count(&self) -> usize;
+
+    /// Get number of contexts stored
+    fn context_count(&self) -> usize;
+
+    /// Get all stored context keys
+    fn context_keys(&self) -> Vec<String>;
+
+    /// Copy out the learned state of one context
+    fn context_state(&self, key: &str) -> Option<ContextState>;
+
+    /// Copy out the learned state of every context
+    fn context_states(&self) -> Vec<ContextState> {
+        self.context_keys()
+            .iter()
+            .filter_map(|key| self.context_state(key))
+            .collect()
+    }
+}
@@ -0,0 +1,36 @@
+//! Platform-independent core of the EvoCore Rust bindings
+//!
+//! The algorithmic types shared by both backends, with no FFI, no file
+//! I/O and no `std`: context keys, parameter specs (bounds and kinds),
+//! learned state and the pure-Rust learner [`RustContextSystem`]. Built
+//! with `default-features = false` it needs only `alloc`, so the learner
+//! runs on embedded controllers; give it a clock with
+//! [`with_clock`](RustContextSystem::with_clock) and a seed through
+//! [`deterministic`](RustContextSystem::deterministic).
+//!
+//! Feature `std` (default) adds OS entropy for seeding and the system
+//! clock. `evocore-sys` re-exports everything here and adds the C library,
+//! persistence and everything else that needs `std`.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod bounds;
+mod key;
+mod learner;
+mod math;
+mod param;
+mod rust_backend;
+mod seed;
+mod state;
+
+pub use bounds::{BoundsMode, ParamBounds};
+pub use key::{check_key_values, key_into, MAX_KEY_LENGTH};
+pub use learner::ContextLearner;
+pub use param::{ParamKind, ParamSpec, ParamValue};
+pub use rust_backend::RustContextSystem;
+pub use seed::SeedStream;
+pub use state::{ContextState, ParamStats};
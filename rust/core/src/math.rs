@@ -0,0 +1,34 @@
+//! Float functions `core` lacks
+//!
+//! With `std` these are the platform's, so results match the rest of the
+//! bindings bit for bit; without it they come from `libm`.
+
+#[cfg(feature = "std")]
+mod imp {
+    pub fn sqrt(x: f64) -> f64 {
+        x.sqrt()
+    }
+
+    pub fn ln(x: f64) -> f64 {
+        x.ln()
+    }
+
+    pub fn cos(x: f64) -> f64 {
+        x.cos()
+    }
+
+    pub fn round(x: f64) -> f64 {
+        x.round()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod imp {
+    pub use libm::{cos, round, sqrt};
+
+    pub fn ln(x: f64) -> f64 {
+        libm::log(x)
+    }
+}
+
+pub(crate) use imp::*;
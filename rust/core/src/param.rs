@@ -0,0 +1,106 @@
+//! Parameter specs: kinds and everything configured about a parameter
+
+use crate::{math, ParamBounds};
+use alloc::string::String;
+
+/// How a parameter's `f64` value is interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParamKind {
+    #[default]
+    Float,
+    Int,
+    Bool,
+    /// One of `n` options, identified by index
+    Categorical(usize),
+}
+
+/// A decoded parameter value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamValue {
+    Float(f64),
+    Int(i64),
+    Bool(bool),
+    /// Index of the chosen option
+    Categorical(usize),
+}
+
+impl ParamValue {
+    /// The value as the C library stores it
+    pub fn to_f64(self) -> f64 {
+        match self {
+            ParamValue::Float(v) => v,
+            ParamValue::Int(v) => v as f64,
+            ParamValue::Bool(v) => {
+                if v {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ParamValue::Categorical(i) => i as f64,
+        }
+    }
+
+    pub fn as_f64(self) -> Option<f64> {
+        match self {
+            ParamValue::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(self) -> Option<i64> {
+        match self {
+            ParamValue::Int(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(self) -> Option<bool> {
+        match self {
+            ParamValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_index(self) -> Option<usize> {
+        match self {
+            ParamValue::Categorical(i) => Some(i),
+            _ => None,
+        }
+    }
+}
+
+impl ParamKind {
+    /// Decode a sampled value
+    pub fn decode(self, value: f64) -> ParamValue {
+        let value = if value.is_nan() { 0.0 } else { value };
+        match self {
+            ParamKind::Float => ParamValue::Float(value),
+            ParamKind::Int => ParamValue::Int(math::round(value) as i64),
+            ParamKind::Bool => ParamValue::Bool(value >= 0.5),
+            ParamKind::Categorical(n) => {
+                ParamValue::Categorical(math::round(value).clamp(0.0, n.saturating_sub(1) as f64) as usize)
+            }
+        }
+    }
+
+    /// Whether `value` is a valid value of this kind
+    pub fn accepts(self, value: ParamValue) -> bool {
+        match (self, value) {
+            (ParamKind::Float, ParamValue::Float(_))
+            | (ParamKind::Int, ParamValue::Int(_))
+            | (ParamKind::Bool, ParamValue::Bool(_)) => true,
+            (ParamKind::Categorical(n), ParamValue::Categorical(i)) => i < n,
+            _ => false,
+        }
+    }
+}
+
+/// Everything configured about one parameter
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSpec {
+    pub index: usize,
+    pub name: Option<String>,
+    pub bounds: Option<ParamBounds>,
+    pub kind: ParamKind,
+}
@@ -0,0 +1,305 @@
+//! Pure-Rust context learning backend
+//!
+//! [`RustContextSystem`] reimplements the C library's context learner
+//! (fitness-weighted online statistics per context, Gaussian sampling mixed
+//! with uniform exploration) without any FFI. Its state is made of
+//! [`ContextState`]s, so it moves to and from the C backend through the
+//! checkpoints of `evocore-sys`.
+//!
+//! It is the only backend on WebAssembly targets (`wasm32-unknown-unknown`,
+//! `wasm32-wasip1`) unless a libevocore built for the target is supplied
+//! through `EVOCORE_LIB_DIR`, and on `no_std` targets. In browsers,
+//! randomness and the clock come from JavaScript; without `std` there is
+//! no clock unless one is set with [`with_clock`](RustContextSystem::with_clock).
+
+use crate::{check_key_values, math, ContextLearner, ContextState, ParamStats, SeedStream};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// Minimum weight applied to an update (mirrors the C library)
+const MIN_WEIGHT: f64 = 0.0001;
+/// Observations needed before a parameter is sampled from its distribution
+const MIN_SAMPLES: usize = 3;
+/// Sample count at which confidence reaches 1.0
+const MAX_SAMPLES_FOR_CONFIDENCE: f64 = 100.0;
+/// Standard deviation below which sampling returns the mean
+const MIN_STD: f64 = 0.0001;
+
+impl ParamStats {
+    /// Fold in one observation with the given weight (West's algorithm)
+    pub fn update(&mut self, value: f64, weight: f64) {
+        let weight = weight.max(MIN_WEIGHT);
+
+        self.min_value = self.min_value.min(value);
+        self.max_value = self.max_value.max(value);
+
+        if self.count == 0 {
+            self.mean = value;
+            self.sum_weights = weight;
+            self.m2 = 0.0;
+            self.sum_weighted_x = value * weight;
+        } else {
+            let prev = self.sum_weights;
+            let total = prev + weight;
+            let delta = value - self.mean;
+            self.mean += (weight / total) * delta;
+            self.m2 += prev * weight * delta * delta / total;
+            self.sum_weights = total;
+            self.sum_weighted_x += value * weight;
+        }
+        self.count += 1;
+
+        self.variance = if self.sum_weights > 0.0 {
+            self.m2 / self.sum_weights
+        } else {
+            0.0
+        };
+    }
+
+    /// Draw from the learned Gaussian, mixed with uniform noise by `exploration`
+    pub fn sample(&self, exploration: f64, rng: &mut StdRng) -> f64 {
+        if self.count < MIN_SAMPLES {
+            return rng.gen::<f64>();
+        }
+
+        let std = self.std();
+        let learned = if std < MIN_STD {
+            self.mean
+        } else {
+            let u1: f64 = rng.gen::<f64>().max(0.0001);
+            let u2: f64 = rng.gen();
+            self.mean + std * math::sqrt(-2.0 * math::ln(u1)) * math::cos(2.0 * core::f64::consts::PI * u2)
+        };
+
+        if exploration > 0.0 {
+            (1.0 - exploration) * learned + exploration * rng.gen::<f64>()
+        } else {
+            learned
+        }
+    }
+}
+
+impl ContextState {
+    /// Fresh state for a context that has never been learned
+    pub fn empty(key: String, param_count: usize) -> Self {
+        Self {
+            key,
+            total_experiences: 0,
+            confidence: 0.0,
+            avg_fitness: 0.0,
+            best_fitness: 0.0,
+            first_update: 0,
+            last_update: 0,
+            params: vec![ParamStats::default(); param_count],
+        }
+    }
+
+    /// Apply one learning update at Unix time `now`, exactly as the C library does
+    pub fn learn(&mut self, parameters: &[f64], fitness: f64, now: i64) {
+        for (stats, &value) in self.params.iter_mut().zip(parameters) {
+            stats.update(value, fitness);
+        }
+
+        if self.total_experiences == 0 {
+            self.first_update = now;
+        }
+        self.last_update = now;
+        self.total_experiences += 1;
+
+        let n = self.total_experiences as f64;
+        self.avg_fitness = (self.avg_fitness * (n - 1.0) + fitness) / n;
+        if fitness > self.best_fitness {
+            self.best_fitness = fitness;
+        }
+
+        self.confidence = match self.params.first() {
+            Some(p) if p.count > 0 => math::sqrt(p.count as f64 / MAX_SAMPLES_FOR_CONFIDENCE).min(1.0),
+            _ => 0.0,
+        };
+    }
+}
+
+/// Current Unix time in seconds
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+pub fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Browsers have no system clock for `SystemTime`; ask JavaScript
+#[cfg(all(feature = "std", target_arch = "wasm32", target_os = "unknown"))]
+pub fn unix_now() -> i64 {
+    (js_sys::Date::now() / 1000.0) as i64
+}
+
+/// Without `std` there is no clock: timestamps stay 0
+#[cfg(not(feature = "std"))]
+pub fn unix_now() -> i64 {
+    0
+}
+
+/// Context learner implemented entirely in Rust
+pub struct RustContextSystem {
+    dimensions: Vec<(String, Vec<String>)>,
+    param_count: usize,
+    contexts: BTreeMap<String, ContextState>,
+    seeds: SeedStream,
+    clock: fn() -> i64,
+}
+
+impl RustContextSystem {
+    /// Create a new context system, seeded from OS entropy
+    ///
+    /// Takes the same arguments as `EvoCoreContextSystem::new`. Without
+    /// `std`, use [`deterministic`](Self::deterministic).
+    #[cfg(feature = "std")]
+    pub fn new(
+        dimension_names: &[&str],
+        dimension_values: &[Vec<&str>],
+        param_count: usize,
+    ) -> Result<Self, String> {
+        Self::deterministic(dimension_names, dimension_values, param_count, rand::random())
+    }
+
+    /// Create a context system whose sampling randomness derives from `master_seed`
+    pub fn deterministic(
+        dimension_names: &[&str],
+        dimension_values: &[Vec<&str>],
+        param_count: usize,
+        master_seed: u64,
+    ) -> Result<Self, String> {
+        if dimension_names.len() != dimension_values.len() {
+            return Err("Dimension names and values must have same length".to_string());
+        }
+        if dimension_names.is_empty() || param_count == 0 {
+            return Err("Failed to create context system".to_string());
+        }
+
+        let dimensions = dimension_names
+            .iter()
+            .zip(dimension_values)
+            .map(|(name, values)| (name.to_string(), values.iter().map(|v| v.to_string()).collect()))
+            .collect();
+        Ok(Self::from_parts(dimensions, param_count, Vec::new(), master_seed))
+    }
+
+    /// Build a system from its dimensions and previously learned contexts
+    pub fn from_parts(
+        dimensions: Vec<(String, Vec<String>)>,
+        param_count: usize,
+        contexts: Vec<ContextState>,
+        master_seed: u64,
+    ) -> Self {
+        Self {
+            dimensions,
+            param_count,
+            contexts: contexts.into_iter().map(|state| (state.key.clone(), state)).collect(),
+            seeds: SeedStream::new(master_seed),
+            clock: unix_now,
+        }
+    }
+
+    /// Timestamp learning updates with `clock` (Unix seconds) instead of the system clock
+    pub fn with_clock(mut self, clock: fn() -> i64) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Get the dimension definitions as `(name, values)` pairs
+    pub fn dimensions(&self) -> &[(String, Vec<String>)] {
+        &self.dimensions
+    }
+
+    /// Build the context key for these dimension values
+    pub fn context_key(&self, dimension_values: &[&str]) -> Result<String, String> {
+        if dimension_values.len() != self.dimensions.len() {
+            return Err(format!(
+                "Dimension count mismatch: expected {}, got {}",
+                self.dimensions.len(),
+                dimension_values.len()
+            ));
+        }
+        Ok(dimension_values.join(":"))
+    }
+
+    /// Overwrite (or create) a context with previously captured state
+    pub fn restore_context_state(&mut self, state: &ContextState) -> Result<(), String> {
+        if state.params.len() != self.param_count {
+            return Err(format!(
+                "Parameter count mismatch: expected {}, got {}",
+                self.param_count,
+                state.params.len()
+            ));
+        }
+        self.contexts.insert(state.key.clone(), state.clone());
+        Ok(())
+    }
+
+    /// Get an RNG derived from this system's seed stream
+    pub fn rng(&self) -> StdRng {
+        self.seeds.rng()
+    }
+}
+
+impl ContextLearner for RustContextSystem {
+    fn learn(
+        &mut self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+    ) -> Result<(), String> {
+        if parameters.len() != self.param_count {
+            return Err(format!(
+                "Parameter count mismatch: expected {}, got {}",
+                self.param_count,
+                parameters.len()
+            ));
+        }
+
+        let key = self.context_key(dimension_values)?;
+        check_key_values(dimension_values)?;
+        let param_count = self.param_count;
+        let now = (self.clock)();
+        self.contexts
+            .entry(key)
+            .or_insert_with_key(|key| ContextState::empty(key.clone(), param_count))
+            .learn(parameters, fitness, now);
+        Ok(())
+    }
+
+    fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        let key = self.context_key(dimension_values)?;
+        let mut rng = self.seeds.rng();
+
+        Ok(match self.contexts.get(&key) {
+            Some(state) => {
+                let exploration = exploration.clamp(0.0, 1.0);
+                state.params.iter().map(|p| p.sample(exploration, &mut rng)).collect()
+            }
+            None => (0..self.param_count).map(|_| rng.gen::<f64>()).collect(),
+        })
+    }
+
+    fn param_count(&self) -> usize {
+        self.param_count
+    }
+
+    fn context_count(&self) -> usize {
+        self.contexts.len()
+    }
+
+    fn context_keys(&self) -> Vec<String> {
+        self.contexts.keys().cloned().collect()
+    }
+
+    fn context_state(&self, key: &str) -> Option<ContextState> {
+        self.contexts.get(key).cloned()
+    }
+}
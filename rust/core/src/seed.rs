@@ -0,0 +1,55 @@
+//! Seed derivation for reproducible runs
+//!
+//! In deterministic mode every random decision the wrapper makes draws its
+//! seed from one [`SeedStream`], so a whole learning trajectory can be
+//! replayed from a single master seed.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Lock-free splitmix64 stream of seeds derived from a master seed
+///
+/// Targets without 64-bit atomics keep the state in a `Cell`, so there the
+/// stream (and a learner holding one) is not `Sync`.
+#[derive(Debug)]
+pub struct SeedStream {
+    #[cfg(target_has_atomic = "64")]
+    state: AtomicU64,
+    #[cfg(not(target_has_atomic = "64"))]
+    state: core::cell::Cell<u64>,
+}
+
+impl SeedStream {
+    pub fn new(master_seed: u64) -> Self {
+        Self { state: master_seed.into() }
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let mut z = self.advance().wrapping_add(GOLDEN_GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Step the state, returning its previous value
+    #[cfg(target_has_atomic = "64")]
+    fn advance(&self) -> u64 {
+        self.state.fetch_add(GOLDEN_GAMMA, Ordering::Relaxed)
+    }
+
+    #[cfg(not(target_has_atomic = "64"))]
+    fn advance(&self) -> u64 {
+        let state = self.state.get();
+        self.state.set(state.wrapping_add(GOLDEN_GAMMA));
+        state
+    }
+
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.next_u64())
+    }
+}
@@ -0,0 +1,89 @@
+//! Learned state of a context
+//!
+//! Copies of what a backend has learned per context, so it can be
+//! persisted, compared, or moved between systems and backends.
+
+use crate::math;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Weighted statistics for a single parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamStats {
+    pub mean: f64,
+    pub variance: f64,
+    pub sum_weights: f64,
+    pub m2: f64,
+    pub count: usize,
+    pub min_value: f64,
+    pub max_value: f64,
+    pub sum_weighted_x: f64,
+}
+
+impl ParamStats {
+    /// Weighted standard deviation (0 with fewer than two observations)
+    pub fn std(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            math::sqrt(self.variance.max(0.0))
+        }
+    }
+
+    /// Fold another set of observations into these, as if both had been learned together
+    pub fn merge(&mut self, other: &ParamStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+
+        let total = self.sum_weights + other.sum_weights;
+        if total > 0.0 {
+            let delta = other.mean - self.mean;
+            let mean = self.mean + delta * other.sum_weights / total;
+            self.m2 += other.m2 + delta * delta * self.sum_weights * other.sum_weights / total;
+            self.mean = mean;
+            self.variance = self.m2 / total;
+        }
+        self.sum_weights = total;
+        self.sum_weighted_x += other.sum_weighted_x;
+        self.count += other.count;
+        self.min_value = self.min_value.min(other.min_value);
+        self.max_value = self.max_value.max(other.max_value);
+    }
+}
+
+impl Default for ParamStats {
+    fn default() -> Self {
+        Self {
+            mean: 0.0,
+            variance: 0.0,
+            sum_weights: 0.0,
+            m2: 0.0,
+            count: 0,
+            min_value: f64::INFINITY,
+            max_value: f64::NEG_INFINITY,
+            sum_weighted_x: 0.0,
+        }
+    }
+}
+
+/// Everything a backend has learned for one context
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextState {
+    /// Context key (dimension values joined with `:`)
+    pub key: String,
+    pub total_experiences: usize,
+    pub confidence: f64,
+    pub avg_fitness: f64,
+    pub best_fitness: f64,
+    /// Unix timestamp of the first update
+    pub first_update: i64,
+    /// Unix timestamp of the most recent update
+    pub last_update: i64,
+    /// Per-parameter statistics
+    pub params: Vec<ParamStats>,
+}
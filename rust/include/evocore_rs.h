@@ -0,0 +1,134 @@
+/**
+ * Evocore Rust Safe Layer C API
+ *
+ * C ABI exported by the evocore-sys cdylib when built with the `capi`
+ * feature (cargo build --release --features capi). Wraps the Rust context
+ * system, so callers get parameter count and bounds validation, fitness
+ * checks, the key cache and descriptive error messages on top of the
+ * evocore_context_* API.
+ *
+ * Functions returning int return EVOCORE_RS_OK or a negative status code;
+ * constructors return NULL on failure. evocore_rs_last_error() then
+ * describes the failure on the calling thread.
+ *
+ * A system may move between threads but must only be used by one at a time.
+ */
+
+#ifndef EVOCORE_RS_H
+#define EVOCORE_RS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define EVOCORE_RS_ABI_VERSION 1
+
+/*========================================================================
+ * Status Codes
+ *========================================================================*/
+
+#define EVOCORE_RS_OK                 0
+#define EVOCORE_RS_INVALID_ARGUMENT  -1  /* Null pointer, bad UTF-8, bad bounds */
+#define EVOCORE_RS_PARAM_COUNT       -2  /* Wrong number of parameters */
+#define EVOCORE_RS_OUT_OF_BOUNDS     -3  /* Learned parameter outside its bounds */
+#define EVOCORE_RS_INVALID_FITNESS   -4  /* Fitness rejected by the fitness spec */
+#define EVOCORE_RS_FAILED            -5  /* Rejected by the library or I/O error */
+#define EVOCORE_RS_PANIC             -6  /* Internal error caught at the boundary */
+
+/*========================================================================
+ * Types
+ *========================================================================*/
+
+/** Opaque context system handle */
+typedef struct evocore_rs_system_t evocore_rs_system_t;
+
+/*========================================================================
+ * Functions
+ *========================================================================*/
+
+/** ABI version of the loaded library; compare with EVOCORE_RS_ABI_VERSION */
+uint32_t evocore_rs_abi_version(void);
+
+/**
+ * Message for the last failure on this thread, or ""
+ *
+ * Valid until the next failing call on the same thread.
+ */
+const char *evocore_rs_last_error(void);
+
+/**
+ * Create a context system
+ *
+ * @param names           dimension_count dimension names
+ * @param value_counts    Number of values of each dimension
+ * @param values          All dimension values, dimension by dimension
+ * @param dimension_count Number of dimensions
+ * @param param_count     Parameters tracked per context
+ * @return New system, or NULL on failure
+ */
+evocore_rs_system_t *evocore_rs_create(const char *const *names,
+                                       const size_t *value_counts,
+                                       const char *const *values,
+                                       size_t dimension_count,
+                                       size_t param_count);
+
+/** Load a system saved in JSON or binary format; NULL on failure */
+evocore_rs_system_t *evocore_rs_load(const char *path);
+
+/** Free a system; NULL is ignored */
+void evocore_rs_free(evocore_rs_system_t *system);
+
+/** Save as JSON */
+int evocore_rs_save(const evocore_rs_system_t *system, const char *path);
+
+/** Save in the compact binary format */
+int evocore_rs_save_binary(const evocore_rs_system_t *system, const char *path);
+
+/** Reject learns and clamp samples outside [min, max] for parameter index */
+int evocore_rs_set_bounds(evocore_rs_system_t *system, size_t index,
+                          double min, double max);
+
+/** Remove the bounds of parameter index */
+int evocore_rs_clear_bounds(evocore_rs_system_t *system, size_t index);
+
+/** Cache prebuilt keys for up to capacity contexts; 0 disables the cache */
+int evocore_rs_enable_key_cache(evocore_rs_system_t *system, size_t capacity);
+
+/** Seed sampling so the same sequence of calls draws the same parameters */
+int evocore_rs_set_master_seed(evocore_rs_system_t *system, uint64_t seed);
+
+/**
+ * Learn from one experience
+ *
+ * @param values      One value per dimension
+ * @param params      param_count parameters, as many as the system tracks
+ */
+int evocore_rs_learn(evocore_rs_system_t *system,
+                     const char *const *values, size_t value_count,
+                     const double *params, size_t param_count,
+                     double fitness);
+
+/**
+ * Sample parameters for a context
+ *
+ * @param exploration 0.0 = pure exploit, 1.0 = pure explore
+ * @param out         Receives out_len parameters, as many as the system tracks
+ */
+int evocore_rs_sample(const evocore_rs_system_t *system,
+                      const char *const *values, size_t value_count,
+                      double exploration, double *out, size_t out_len);
+
+/** Number of contexts learned so far; 0 for NULL */
+size_t evocore_rs_context_count(const evocore_rs_system_t *system);
+
+/** Parameters per context; 0 for NULL */
+size_t evocore_rs_param_count(const evocore_rs_system_t *system);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* EVOCORE_RS_H */
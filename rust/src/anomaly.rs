@@ -0,0 +1,187 @@
+//! Fitness anomaly detection
+//!
+//! A broken evaluator that occasionally reports a wildly wrong fitness can
+//! drag a context's learned means anywhere, since every example is weighted
+//! by its fitness. With anomaly detection enabled, each context tracks the
+//! running mean and deviation of the fitness it has learned, and a fitness
+//! more than `threshold` standard deviations from the mean is flagged once
+//! the context has `min_samples` values. Flagged values never enter the
+//! running statistics, so a burst of bad values cannot widen the band that
+//! judges them. With quarantine on, they are not learned either.
+//!
+//! Recent flags are kept for inspection with
+//! [`fitness_anomalies`](EvoCoreContextSystem::fitness_anomalies).
+
+use crate::rename::move_entry;
+use crate::EvoCoreContextSystem;
+use std::collections::{HashMap, VecDeque};
+
+/// Anomaly detection settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyConfig {
+    threshold: f64,
+    min_samples: u64,
+    quarantine: bool,
+    history: usize,
+}
+
+impl AnomalyConfig {
+    /// Flag fitness more than `threshold` standard deviations from the context mean
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold: threshold.max(0.0),
+            min_samples: 30,
+            quarantine: false,
+            history: 100,
+        }
+    }
+
+    /// Values a context needs before anything is flagged (default 30)
+    pub fn with_min_samples(mut self, min_samples: u64) -> Self {
+        self.min_samples = min_samples.max(2);
+        self
+    }
+
+    /// Skip learning flagged examples (default false: flag only)
+    pub fn with_quarantine(mut self, quarantine: bool) -> Self {
+        self.quarantine = quarantine;
+        self
+    }
+
+    /// Most recent flags kept (default 100)
+    pub fn with_history(mut self, history: usize) -> Self {
+        self.history = history;
+        self
+    }
+}
+
+/// A learn whose fitness was flagged as an outlier
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitnessAnomaly {
+    pub key: String,
+    pub fitness: f64,
+    /// Context mean and standard deviation it was judged against
+    pub mean: f64,
+    pub std_dev: f64,
+    pub z_score: f64,
+    /// Whether the example was kept out of the learned statistics
+    pub quarantined: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Distribution {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+/// Anomaly bookkeeping for one system
+#[derive(Debug, Clone)]
+pub(crate) struct AnomalyDetector {
+    config: AnomalyConfig,
+    contexts: HashMap<String, Distribution>,
+    recent: VecDeque<FitnessAnomaly>,
+    flagged: u64,
+}
+
+impl AnomalyDetector {
+    pub(crate) fn forget(&mut self, key: &str) {
+        self.contexts.remove(key);
+    }
+
+    pub(crate) fn rename_key(&mut self, from: &str, to: &str) {
+        move_entry(&mut self.contexts, from, to);
+    }
+
+
+    /// Judge `fitness` for `key`; returns whether to skip learning it
+    fn screen(&mut self, key: &str, fitness: f64) -> bool {
+        if !fitness.is_finite() {
+            return false;
+        }
+        let dist = match self.contexts.get_mut(key) {
+            Some(dist) => dist,
+            None => self.contexts.entry(key.to_string()).or_default(),
+        };
+        if dist.count >= self.config.min_samples {
+            let std_dev = (dist.m2 / (dist.count - 1) as f64).sqrt();
+            let z_score = if std_dev > 0.0 {
+                (fitness - dist.mean) / std_dev
+            } else if fitness == dist.mean {
+                0.0
+            } else {
+                (fitness - dist.mean).signum() * f64::INFINITY
+            };
+            if z_score.abs() > self.config.threshold {
+                let anomaly = FitnessAnomaly {
+                    key: key.to_string(),
+                    fitness,
+                    mean: dist.mean,
+                    std_dev,
+                    z_score,
+                    quarantined: self.config.quarantine,
+                };
+                self.flagged += 1;
+                if self.config.history > 0 {
+                    if self.recent.len() >= self.config.history {
+                        self.recent.pop_front();
+                    }
+                    self.recent.push_back(anomaly);
+                }
+                return self.config.quarantine;
+            }
+        }
+        dist.count += 1;
+        let delta = fitness - dist.mean;
+        dist.mean += delta / dist.count as f64;
+        dist.m2 += delta * (fitness - dist.mean);
+        false
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Flag (and optionally quarantine) outlying fitness values
+    pub fn with_anomaly_detection(mut self, config: AnomalyConfig) -> Self {
+        self.set_anomaly_detection(Some(config));
+        self
+    }
+
+    /// Change or remove (`None`) anomaly detection, dropping its statistics and flags
+    pub fn set_anomaly_detection(&mut self, config: Option<AnomalyConfig>) {
+        self.anomalies = config.map(|config| AnomalyDetector {
+            config,
+            contexts: HashMap::new(),
+            recent: VecDeque::new(),
+            flagged: 0,
+        });
+    }
+
+    pub fn anomaly_detection(&self) -> Option<AnomalyConfig> {
+        self.anomalies.as_ref().map(|a| a.config)
+    }
+
+    /// Most recent flagged learns, oldest first
+    pub fn fitness_anomalies(&self) -> Vec<FitnessAnomaly> {
+        self.anomalies.as_ref().map_or_else(Vec::new, |a| a.recent.iter().cloned().collect())
+    }
+
+    /// Learns flagged since anomaly detection was enabled
+    pub fn anomaly_count(&self) -> u64 {
+        self.anomalies.as_ref().map_or(0, |a| a.flagged)
+    }
+
+    /// Forget the recent flags, keeping the learned distributions
+    pub fn clear_fitness_anomalies(&mut self) {
+        if let Some(anomalies) = &mut self.anomalies {
+            anomalies.recent.clear();
+        }
+    }
+
+    /// Screen a fitness for `key`; returns whether it is quarantined
+    pub(crate) fn quarantine_fitness(&mut self, key: &str, fitness: f64) -> bool {
+        match &mut self.anomalies {
+            Some(anomalies) => anomalies.screen(key, fitness),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Async persistence (feature `tokio`)
+//!
+//! The C save/load calls block for as long as it takes to write or parse
+//! the whole system. These wrappers move them onto tokio's blocking thread
+//! pool so async runtimes keep serving other tasks meanwhile.
+
+use crate::{EvoCoreContextSystem, SaveOptions, SharedContextSystem};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+fn join_err(e: tokio::task::JoinError) -> String {
+    format!("Persistence task failed: {}", e)
+}
+
+impl EvoCoreContextSystem {
+    /// Load context system from file without blocking the async executor
+    pub async fn load_async<P: AsRef<Path>>(filepath: P) -> Result<Self, String> {
+        let path = filepath.as_ref().to_string_lossy().into_owned();
+        tokio::task::spawn_blocking(move || Self::load(&path))
+            .await
+            .map_err(join_err)?
+    }
+}
+
+impl SharedContextSystem {
+    /// Save context system to file without blocking the async executor
+    ///
+    /// Holds the read lock for the duration of the save, so sampling
+    /// continues while learning waits.
+    pub async fn save_async<P: AsRef<Path>>(self: &Arc<Self>, filepath: P) -> Result<(), String> {
+        let system = Arc::clone(self);
+        let path = filepath.as_ref().to_path_buf();
+        tokio::task::spawn_blocking(move || save_atomic(&system, &path))
+            .await
+            .map_err(join_err)?
+    }
+
+    /// Load a shared context system from file without blocking the async executor
+    pub async fn load_async<P: AsRef<Path>>(filepath: P) -> Result<Self, String> {
+        EvoCoreContextSystem::load_async(filepath).await.map(Self::new)
+    }
+
+    /// Save to `filepath` every `interval` on the current tokio runtime
+    ///
+    /// Each save goes to a temporary file that is renamed into place, so a
+    /// crash mid-save never leaves a truncated checkpoint behind. The
+    /// returned handle stops the task when dropped.
+    pub fn spawn_autosave<P: AsRef<Path>>(
+        self: &Arc<Self>,
+        filepath: P,
+        interval: Duration,
+    ) -> AutosaveHandle {
+        self.spawn_autosave_with(filepath, interval, SaveOptions::default())
+    }
+
+    /// Like [`spawn_autosave`](Self::spawn_autosave), saving with `options`
+    /// (format, checksum, compression)
+    ///
+    /// Autosaves always go through a temporary file, whatever
+    /// [`with_atomic`](SaveOptions::with_atomic) says.
+    pub fn spawn_autosave_with<P: AsRef<Path>>(
+        self: &Arc<Self>,
+        filepath: P,
+        interval: Duration,
+        options: SaveOptions,
+    ) -> AutosaveHandle {
+        let system = Arc::clone(self);
+        let path = filepath.as_ref().to_path_buf();
+        let options = options.with_atomic(true);
+        let last_error = Arc::new(Mutex::new(None));
+        let errors = Arc::clone(&last_error);
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately; skip it so the first save
+            // happens one interval after start.
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let system = Arc::clone(&system);
+                let path = path.clone();
+                let result = tokio::task::spawn_blocking(move || system.read(|s| s.save_as(&path, &options)))
+                    .await
+                    .map_err(join_err)
+                    .and_then(|r| r);
+                *errors.lock().unwrap_or_else(PoisonError::into_inner) = result.err();
+            }
+        });
+
+        AutosaveHandle { task, last_error }
+    }
+}
+
+fn save_atomic(system: &SharedContextSystem, path: &Path) -> Result<(), String> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    system.save(&tmp.to_string_lossy())?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to save context system: {}", e))
+}
+
+/// Handle to a background autosave task; stops the task when dropped
+pub struct AutosaveHandle {
+    task: JoinHandle<()>,
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl AutosaveHandle {
+    /// Error from the most recent save, if it failed
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Stop autosaving
+    pub fn stop(self) {}
+}
+
+impl Drop for AutosaveHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
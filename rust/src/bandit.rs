@@ -0,0 +1,242 @@
+//! Per-context bandit over discrete strategies
+//!
+//! Often the choice is not a parameter vector but one of a few named
+//! options: which prompt template, which tool chain, which model.
+//! [`StrategyBandit`] keeps, per context, how often each strategy was
+//! chosen and how well it did, and picks the next one with a
+//! [`BanditPolicy`]. Rewards are expected in `[0, 1]` (e.g. 1 for success,
+//! 0 for failure) and are clamped to it.
+//!
+//! ```ignore
+//! let mut bandit = StrategyBandit::new(&["task"], &[vec!["code", "chat"]], &["terse", "cot", "few-shot"])?;
+//! let strategy = bandit.choose(&["code"])?.to_string();
+//! let success = run(&strategy);
+//! bandit.reward(&["code"], &strategy, if success { 1.0 } else { 0.0 })?;
+//! ```
+
+use crate::strategy::gaussian;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+/// How a [`StrategyBandit`] trades exploring against exploiting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BanditPolicy {
+    /// Sample each strategy's Beta posterior and pick the highest draw
+    Thompson,
+    /// Pick the highest `mean + c * sqrt(ln N / n)`; untried strategies first
+    Ucb1 { c: f64 },
+    /// Pick at random with probability `epsilon`, otherwise the best mean
+    EpsilonGreedy { epsilon: f64 },
+}
+
+/// Learned statistics of one strategy in one context
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ArmStats {
+    pub pulls: u64,
+    pub total_reward: f64,
+}
+
+impl ArmStats {
+    /// Mean reward (0 if never rewarded)
+    pub fn mean(&self) -> f64 {
+        if self.pulls == 0 {
+            0.0
+        } else {
+            self.total_reward / self.pulls as f64
+        }
+    }
+}
+
+/// Chooses among named strategies per context from observed rewards
+pub struct StrategyBandit {
+    dimensions: Vec<(String, Vec<String>)>,
+    strategies: Vec<String>,
+    policy: BanditPolicy,
+    arms: HashMap<String, Vec<ArmStats>>,
+    rng: Mutex<StdRng>,
+}
+
+impl StrategyBandit {
+    /// Create a bandit over `strategies` for the given context dimensions, using Thompson sampling
+    pub fn new(
+        dimension_names: &[&str],
+        dimension_values: &[Vec<&str>],
+        strategies: &[&str],
+    ) -> Result<Self, String> {
+        if dimension_names.len() != dimension_values.len() {
+            return Err("Dimension names and values length mismatch".to_string());
+        }
+        if strategies.is_empty() {
+            return Err("Strategy bandit needs at least one strategy".to_string());
+        }
+        if let Some(duplicate) = strategies.iter().enumerate().find(|(i, s)| strategies[..*i].contains(s)) {
+            return Err(format!("Duplicate strategy: {}", duplicate.1));
+        }
+
+        Ok(Self {
+            dimensions: dimension_names
+                .iter()
+                .zip(dimension_values)
+                .map(|(name, values)| (name.to_string(), values.iter().map(|v| v.to_string()).collect()))
+                .collect(),
+            strategies: strategies.iter().map(|s| s.to_string()).collect(),
+            policy: BanditPolicy::Thompson,
+            arms: HashMap::new(),
+            rng: Mutex::new(StdRng::from_entropy()),
+        })
+    }
+
+    /// Choose with `policy` (default [`BanditPolicy::Thompson`])
+    pub fn with_policy(mut self, policy: BanditPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Seed the random number generator, for reproducible runs
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Mutex::new(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    pub fn strategies(&self) -> &[String] {
+        &self.strategies
+    }
+
+    pub fn policy(&self) -> BanditPolicy {
+        self.policy
+    }
+
+    fn key(&self, dimension_values: &[&str]) -> Result<String, String> {
+        if dimension_values.len() != self.dimensions.len() {
+            return Err(format!(
+                "Dimension count mismatch: expected {}, got {}",
+                self.dimensions.len(),
+                dimension_values.len()
+            ));
+        }
+        for (value, (name, allowed)) in dimension_values.iter().zip(&self.dimensions) {
+            if !allowed.iter().any(|a| a == value) {
+                return Err(format!("Unknown value {:?} for dimension {}", value, name));
+            }
+        }
+        Ok(dimension_values.join(":"))
+    }
+
+    /// Pick a strategy for a context
+    pub fn choose(&self, dimension_values: &[&str]) -> Result<&str, String> {
+        let key = self.key(dimension_values)?;
+        let arms = self.arms.get(&key).map_or(&[][..], Vec::as_slice);
+        let arm = |i: usize| arms.get(i).copied().unwrap_or_default();
+        let mut rng = self.rng.lock().unwrap_or_else(PoisonError::into_inner);
+        let n = self.strategies.len();
+
+        let index = match self.policy {
+            BanditPolicy::Thompson => (0..n)
+                .map(|i| {
+                    let a = arm(i);
+                    let successes = a.total_reward;
+                    let failures = a.pulls as f64 - a.total_reward;
+                    (i, beta(&mut rng, 1.0 + successes, 1.0 + failures))
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map_or(0, |(i, _)| i),
+            BanditPolicy::Ucb1 { c } => match (0..n).find(|&i| arm(i).pulls == 0) {
+                Some(untried) => untried,
+                None => {
+                    let total: u64 = (0..n).map(|i| arm(i).pulls).sum();
+                    let ln_total = (total as f64).ln();
+                    (0..n)
+                        .map(|i| {
+                            let a = arm(i);
+                            (i, a.mean() + c * (ln_total / a.pulls as f64).sqrt())
+                        })
+                        .max_by(|a, b| a.1.total_cmp(&b.1))
+                        .map_or(0, |(i, _)| i)
+                }
+            },
+            BanditPolicy::EpsilonGreedy { epsilon } => {
+                if rng.gen::<f64>() < epsilon {
+                    rng.gen_range(0..n)
+                } else {
+                    (0..n).max_by(|&a, &b| arm(a).mean().total_cmp(&arm(b).mean())).unwrap_or(0)
+                }
+            }
+        };
+        Ok(&self.strategies[index])
+    }
+
+    /// Record the reward a strategy earned in a context
+    pub fn reward(&mut self, dimension_values: &[&str], strategy: &str, reward: f64) -> Result<(), String> {
+        let key = self.key(dimension_values)?;
+        let index = self
+            .strategies
+            .iter()
+            .position(|s| s == strategy)
+            .ok_or_else(|| format!("Unknown strategy: {}", strategy))?;
+        if !reward.is_finite() {
+            return Err(format!("Reward must be finite, got {}", reward));
+        }
+
+        let arms = self
+            .arms
+            .entry(key)
+            .or_insert_with(|| vec![ArmStats::default(); self.strategies.len()]);
+        arms[index].pulls += 1;
+        arms[index].total_reward += reward.clamp(0.0, 1.0);
+        Ok(())
+    }
+
+    /// Statistics of every strategy in a context, in declaration order
+    pub fn stats(&self, dimension_values: &[&str]) -> Result<Vec<(&str, ArmStats)>, String> {
+        let key = self.key(dimension_values)?;
+        let arms = self.arms.get(&key);
+        Ok(self
+            .strategies
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.as_str(), arms.and_then(|a| a.get(i)).copied().unwrap_or_default()))
+            .collect())
+    }
+
+    /// Strategy with the best mean reward in a context, if any was rewarded
+    pub fn best(&self, dimension_values: &[&str]) -> Result<Option<&str>, String> {
+        Ok(self
+            .stats(dimension_values)?
+            .into_iter()
+            .filter(|(_, a)| a.pulls > 0)
+            .max_by(|a, b| a.1.mean().total_cmp(&b.1.mean()))
+            .map(|(s, _)| s))
+    }
+
+    /// Forget everything learned in a context
+    pub fn reset(&mut self, dimension_values: &[&str]) -> Result<(), String> {
+        let key = self.key(dimension_values)?;
+        self.arms.remove(&key);
+        Ok(())
+    }
+}
+
+/// Gamma(shape, 1) draw for `shape >= 1` (Marsaglia and Tsang)
+fn gamma(rng: &mut StdRng, shape: f64) -> f64 {
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let x = gaussian(rng);
+        let v = (1.0 + c * x).powi(3);
+        if v <= 0.0 {
+            continue;
+        }
+        let u: f64 = rng.gen::<f64>().max(1e-300);
+        if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+            return d * v;
+        }
+    }
+}
+
+fn beta(rng: &mut StdRng, a: f64, b: f64) -> f64 {
+    let x = gamma(rng, a);
+    let y = gamma(rng, b);
+    x / (x + y)
+}
@@ -0,0 +1,308 @@
+//! Batched learning and sampling
+//!
+//! Replaying a large log one `learn()` call at a time spends most of its
+//! time building C strings and crossing the FFI boundary. The batch APIs
+//! validate and marshal every example up front, converting each distinct
+//! context key to a C string once, then apply the updates by key.
+//! [`sample_batch`](EvoCoreContextSystem::sample_batch) does the same for
+//! sampling many contexts per tick.
+
+use crate::ffi_timing::FfiOp;
+use crate::{
+    check_key_values, evocore_context_learn_key, evocore_context_sample_key, EvoCoreContextSystem, MAX_KEY_LENGTH,
+};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+
+/// One learning example: a context, the parameters used and the fitness achieved
+#[derive(Debug, Clone, PartialEq)]
+pub struct LearnExample {
+    pub dimension_values: Vec<String>,
+    pub parameters: Vec<f64>,
+    pub fitness: f64,
+}
+
+impl LearnExample {
+    /// Create an example from borrowed dimension values
+    pub fn new(dimension_values: &[&str], parameters: &[f64], fitness: f64) -> Self {
+        Self {
+            dimension_values: dimension_values.iter().map(|v| v.to_string()).collect(),
+            parameters: parameters.to_vec(),
+            fitness,
+        }
+    }
+}
+
+/// Validate one example and build its context key
+fn example_key<S: AsRef<str>>(
+    index: usize,
+    dimension_values: &[S],
+    parameters: &[f64],
+    dimension_count: usize,
+    param_count: usize,
+) -> Result<String, String> {
+    if dimension_values.len() != dimension_count {
+        return Err(format!(
+            "Example {}: dimension count mismatch: expected {}, got {}",
+            index,
+            dimension_count,
+            dimension_values.len()
+        ));
+    }
+    if parameters.len() != param_count {
+        return Err(format!(
+            "Example {}: parameter count mismatch: expected {}, got {}",
+            index,
+            param_count,
+            parameters.len()
+        ));
+    }
+
+    let values: Vec<&str> = dimension_values.iter().map(AsRef::as_ref).collect();
+    check_key_values(&values).map_err(|e| format!("Example {}: {}", index, e))?;
+    let key = values.join(":");
+    if key.len() >= MAX_KEY_LENGTH || key.contains('\0') {
+        return Err(format!("Example {}: invalid context key {:?}", index, key));
+    }
+    Ok(key)
+}
+
+impl EvoCoreContextSystem {
+    /// Feed pre-validated examples to the C library
+    ///
+    /// Each distinct context key is converted to a C string once and shared
+    /// by all of its examples. Examples are applied in their original order,
+    /// so the learned statistics match learning them one at a time.
+    fn learn_grouped<'a>(
+        &mut self,
+        keys: &[String],
+        updates: impl Iterator<Item = (&'a [f64], f64)>,
+    ) -> Result<(), String> {
+        let mut slots: HashMap<&str, usize> = HashMap::new();
+        let mut c_keys: Vec<CString> = Vec::new();
+        let mut example_slots = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            let slot = match slots.get(key.as_str()) {
+                Some(&slot) => slot,
+                None => {
+                    c_keys.push(CString::new(key.as_str()).map_err(|e| e.to_string())?);
+                    slots.insert(key, c_keys.len() - 1);
+                    c_keys.len() - 1
+                }
+            };
+            example_slots.push(slot);
+        }
+
+        for (((parameters, fitness), slot), key) in updates.zip(example_slots).zip(keys) {
+            if self.quarantine_fitness(key, fitness) {
+                continue;
+            }
+            self.decay_before_learn(&c_keys[slot]);
+            let fitness = self.normalize_fitness(key, fitness);
+            let snapshot = self.update_snapshot(&c_keys[slot]);
+            let mut timer = self.ffi_timer(FfiOp::Learn);
+            timer.marshalled();
+            let ok = unsafe {
+                evocore_context_learn_key(
+                    self.inner.as_ptr(),
+                    c_keys[slot].as_ptr(),
+                    parameters.as_ptr(),
+                    self.param_count,
+                    fitness,
+                )
+            };
+            self.finish_ffi_timer(&mut timer);
+            if !ok {
+                return Err("Failed to learn from context".to_string());
+            }
+            if let Some(snapshot) = snapshot {
+                self.damp_update(&c_keys[slot], snapshot);
+            }
+            self.after_learn(key, parameters, fitness);
+            #[cfg(feature = "metrics")]
+            self.record_learn(true);
+        }
+
+        Ok(())
+    }
+
+    /// Learn from many `(dimension_values, parameters, fitness)` examples in one call
+    ///
+    /// The C library has no batch entry point, so examples are validated
+    /// and keyed up front and then applied in a tight loop. Every example is
+    /// validated before any is learned, so an invalid example leaves the
+    /// system unchanged.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", name = "evocore.learn_batch", skip_all, fields(examples = examples.len()), err(level = "warn", Display))
+    )]
+    pub fn learn_batch(&mut self, examples: &[(&[&str], &[f64], f64)]) -> Result<(), String> {
+        let dimension_count = unsafe { self.inner.as_ref().dimension_count };
+        let keys = examples
+            .iter()
+            .enumerate()
+            .map(|(i, (dims, params, _))| {
+                example_key(i, dims, params, dimension_count, self.param_count)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, (_, params, fitness)) in examples.iter().enumerate() {
+            self.check_bounds(params).map_err(|e| format!("Example {}: {}", i, e))?;
+            self.check_fitness(*fitness).map_err(|e| format!("Example {}: {}", i, e))?;
+        }
+
+        self.learn_grouped(&keys, examples.iter().map(|&(_, params, fitness)| (params, fitness)))
+    }
+
+    /// Learn from many examples, marshalling them in parallel (feature `rayon`)
+    ///
+    /// Every example is validated before any is learned, so an invalid
+    /// example leaves the system unchanged.
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", name = "evocore.learn_batch", skip_all, fields(examples = examples.len()), err(level = "warn", Display))
+    )]
+    pub fn learn_batch_par(&mut self, examples: &[LearnExample]) -> Result<(), String> {
+        use rayon::prelude::*;
+
+        let dimension_count = unsafe { self.inner.as_ref().dimension_count };
+        let param_count = self.param_count;
+        let keys = examples
+            .par_iter()
+            .enumerate()
+            .map(|(i, e)| {
+                example_key(i, &e.dimension_values, &e.parameters, dimension_count, param_count)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, e) in examples.iter().enumerate() {
+            self.check_bounds(&e.parameters).map_err(|e| format!("Example {}: {}", i, e))?;
+            self.check_fitness(e.fitness).map_err(|e| format!("Example {}: {}", i, e))?;
+        }
+
+        self.learn_grouped(&keys, examples.iter().map(|e| (e.parameters.as_slice(), e.fitness)))
+    }
+
+    /// Whether `sample()` applies a hook the batched fast path does not
+    ///
+    /// The fast path handles overrides, the wildcard fallback (which
+    /// [coarsening](EvoCoreContextSystem::with_coarsening) turns on), decay
+    /// and bounds itself. Everything else `sample()` consults is listed
+    /// here. Canary and holdout mode only act through their own `sample_*`
+    /// entry points, so plain sampling is the same with or without them.
+    fn needs_slow_path(&self) -> bool {
+        self.strategy.is_some()
+            || self.uptime_ramp.is_some()
+            || self.marginals.is_some()
+            || self.observer.is_some()
+            || self.lru.is_some()
+            || self.hot.is_tracking()
+            || self.explanations.is_some()
+    }
+
+    /// Sample parameters for many contexts in one call
+    ///
+    /// Keys are built in a single reused buffer rather than allocating a C
+    /// string per dimension value. Each context gets its own seed, exactly
+    /// as with repeated `sample()` calls, so a deterministic system returns
+    /// the same values either way. When `sample()` would do more than the
+    /// batched path knows how to (see `needs_slow_path`), each context is
+    /// simply sampled through `sample()` in turn.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "evocore.sample_batch",
+            skip_all,
+            fields(contexts = contexts.len(), exploration = exploration),
+            err(level = "warn", Display)
+        )
+    )]
+    pub fn sample_batch(
+        &self,
+        contexts: &[&[&str]],
+        exploration: f64,
+    ) -> Result<Vec<Vec<f64>>, String> {
+        let dimension_count = unsafe { self.inner.as_ref().dimension_count };
+        if let Some((i, dims)) = contexts
+            .iter()
+            .enumerate()
+            .find(|(_, dims)| dims.len() != dimension_count)
+        {
+            return Err(format!(
+                "Context {}: dimension count mismatch: expected {}, got {}",
+                i,
+                dimension_count,
+                dims.len()
+            ));
+        }
+
+        if self.needs_slow_path() {
+            return contexts.iter().map(|dims| self.sample(dims, exploration)).collect();
+        }
+
+        let mut key: Vec<u8> = Vec::with_capacity(MAX_KEY_LENGTH);
+        let result = contexts
+            .iter()
+            .enumerate()
+            .map(|(i, dims)| {
+                if let Some(pinned) = self.active_override(dims) {
+                    return Ok(pinned.to_vec());
+                }
+
+                let mut timer = self.ffi_timer(FfiOp::Sample);
+                key.clear();
+                for (j, value) in dims.iter().enumerate() {
+                    if j > 0 {
+                        key.push(b':');
+                    }
+                    key.extend_from_slice(value.as_bytes());
+                }
+                if key.len() >= MAX_KEY_LENGTH || key.contains(&0) {
+                    return Err(format!(
+                        "Context {}: invalid context key {:?}",
+                        i,
+                        String::from_utf8_lossy(&key)
+                    ));
+                }
+                key.push(0);
+                let c_key = CStr::from_bytes_with_nul(&key).map_err(|e| e.to_string())?;
+                let mut fallback_buf = [0u8; MAX_KEY_LENGTH];
+                let c_key = self.wildcard_fallback_key(dims, &mut fallback_buf).unwrap_or(c_key);
+
+                let mut draw = |out: &mut [f64]| {
+                    let mut seed = self.next_seed();
+                    let exploration = self.decayed_exploration(c_key, exploration);
+                    timer.marshalled();
+                    let ok = unsafe {
+                        evocore_context_sample_key(
+                            self.inner.as_ptr(),
+                            c_key.as_ptr(),
+                            out.as_mut_ptr(),
+                            self.param_count,
+                            exploration,
+                            &mut seed,
+                        )
+                    };
+                    self.finish_ffi_timer(&mut timer);
+                    if ok {
+                        Ok(())
+                    } else {
+                        Err("Failed to sample parameters".to_string())
+                    }
+                };
+
+                let mut params = vec![0.0; self.param_count];
+                self.exploration.record(exploration);
+                draw(&mut params)?;
+                if self.has_bounds() {
+                    self.apply_bounds(&mut params, &mut draw)?;
+                }
+                Ok(params)
+            })
+            .collect::<Result<Vec<_>, String>>();
+        #[cfg(feature = "metrics")]
+        self.record_samples(result.is_ok(), if result.is_ok() { contexts.len() as u64 } else { 1 });
+        result
+    }
+}
@@ -0,0 +1,314 @@
+//! Bayesian optimization sampling strategy
+//!
+//! When every fitness evaluation is expensive, drawing from a learned
+//! Gaussian wastes budget on points that are already known to be poor.
+//! [`BayesOpt`] keeps the most recent observations of each context and
+//! fits a small Gaussian-process surrogate over the parameter vector
+//! (squared-exponential kernel, standardized fitness). Each `sample()`
+//! scores random candidates, half uniform over the domain and half
+//! perturbations of the best observation, by expected improvement and
+//! returns the best one.
+//!
+//! The domain of each parameter is its [bounds](crate::ParamBounds) if set,
+//! otherwise `[0, 1]`. Until a context has `min_observations` observations
+//! it is sampled from the learned distribution as usual. Observations are
+//! collected through [`SamplingStrategy::observe`], so only examples
+//! learned while the strategy is active are used, and they are not saved
+//! in checkpoints. A sampling temperature scales `xi`, so colder requests
+//! stay closer to the current best.
+
+use crate::rename::move_entry;
+use crate::strategy::gaussian;
+use crate::{SamplingStrategy, StrategyInput};
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+
+type History = VecDeque<(Vec<f64>, f64)>;
+
+/// Gaussian-process surrogate with expected-improvement proposals
+#[derive(Debug)]
+pub struct BayesOpt {
+    length_scale: f64,
+    noise: f64,
+    xi: f64,
+    history: usize,
+    candidates: usize,
+    min_observations: usize,
+    observations: Mutex<HashMap<String, History>>,
+}
+
+impl Default for BayesOpt {
+    fn default() -> Self {
+        Self {
+            length_scale: 0.2,
+            noise: 1e-2,
+            xi: 0.01,
+            history: 64,
+            candidates: 256,
+            min_observations: 5,
+            observations: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl BayesOpt {
+    /// Length scale 0.2, noise 0.01, 64 observations, 256 candidates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Kernel length scale, as a fraction of each parameter's range (default 0.2)
+    pub fn with_length_scale(mut self, length_scale: f64) -> Self {
+        self.length_scale = length_scale.max(1e-6);
+        self
+    }
+
+    /// Observation noise, relative to the fitness variance (default 0.01)
+    pub fn with_noise(mut self, noise: f64) -> Self {
+        self.noise = noise.max(1e-9);
+        self
+    }
+
+    /// Improvement margin for expected improvement (default 0.01)
+    ///
+    /// Larger values favour uncertain regions over the current best.
+    pub fn with_xi(mut self, xi: f64) -> Self {
+        self.xi = xi.max(0.0);
+        self
+    }
+
+    /// Most recent observations kept per context (default 64)
+    ///
+    /// Fitting is cubic in this number.
+    pub fn with_history(mut self, history: usize) -> Self {
+        self.history = history.max(1);
+        self
+    }
+
+    /// Candidates scored per sample (default 256)
+    pub fn with_candidates(mut self, candidates: usize) -> Self {
+        self.candidates = candidates.max(1);
+        self
+    }
+
+    /// Observations a context needs before the surrogate is used (default 5)
+    pub fn with_min_observations(mut self, min_observations: usize) -> Self {
+        self.min_observations = min_observations.max(1);
+        self
+    }
+
+    /// Observations currently held for `key`
+    pub fn observations(&self, key: &str) -> usize {
+        self.observations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .map_or(0, VecDeque::len)
+    }
+
+    /// Forget every observation
+    pub fn clear(&self) {
+        self.observations.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+}
+
+impl SamplingStrategy for BayesOpt {
+    fn name(&self) -> &str {
+        "bayes-opt"
+    }
+
+    fn sample(&self, input: &StrategyInput<'_>, out: &mut [f64]) -> Result<(), String> {
+        let history: Vec<(Vec<f64>, f64)> = {
+            let observations = self.observations.lock().unwrap_or_else(PoisonError::into_inner);
+            match observations.get(input.key()) {
+                Some(h) if h.len() >= self.min_observations => h.iter().cloned().collect(),
+                _ => return input.sample_learned(input.exploration, out),
+            }
+        };
+
+        let domain: Vec<(f64, f64)> = (0..out.len())
+            .map(|i| input.bounds(i).map_or((0.0, 1.0), |b| (b.min, b.max)))
+            .collect();
+        let Some(model) = Surrogate::fit(&history, &domain, self.length_scale, self.noise) else {
+            return input.sample_learned(input.exploration, out);
+        };
+
+        let mut rng = input.rng();
+        let mut candidate = vec![0.0; out.len()];
+        let mut best_score = f64::NEG_INFINITY;
+        for i in 0..self.candidates {
+            if i % 2 == 0 {
+                for (x, &(lo, hi)) in candidate.iter_mut().zip(&domain) {
+                    *x = lo + rng.gen::<f64>() * (hi - lo);
+                }
+            } else {
+                for ((x, &(lo, hi)), &b) in candidate.iter_mut().zip(&domain).zip(&model.best_x) {
+                    let step = gaussian(&mut rng) * self.length_scale * (hi - lo);
+                    *x = (b + step).clamp(lo, hi);
+                }
+            }
+            let score = model.expected_improvement(&candidate, self.xi * input.temperature());
+            if score > best_score {
+                best_score = score;
+                out.copy_from_slice(&candidate);
+            }
+        }
+        Ok(())
+    }
+
+    fn observe(&self, key: &str, parameters: &[f64], fitness: f64) {
+        if !fitness.is_finite() || parameters.iter().any(|p| !p.is_finite()) {
+            return;
+        }
+        let mut observations = self.observations.lock().unwrap_or_else(PoisonError::into_inner);
+        let history = observations.entry(key.to_string()).or_default();
+        history.push_back((parameters.to_vec(), fitness));
+        while history.len() > self.history {
+            history.pop_front();
+        }
+    }
+
+    fn forget(&self, key: &str) {
+        self.observations.lock().unwrap_or_else(PoisonError::into_inner).remove(key);
+    }
+
+    fn rename_key(&self, from: &str, to: &str) {
+        move_entry(&mut self.observations.lock().unwrap_or_else(PoisonError::into_inner), from, to);
+    }
+}
+
+/// Fitted GP posterior over normalized inputs and standardized fitness
+struct Surrogate {
+    xs: Vec<Vec<f64>>,
+    /// Lower Cholesky factor of the kernel matrix
+    chol: Vec<Vec<f64>>,
+    alpha: Vec<f64>,
+    best: f64,
+    best_x: Vec<f64>,
+    domain: Vec<(f64, f64)>,
+    length_scale: f64,
+}
+
+impl Surrogate {
+    fn fit(history: &[(Vec<f64>, f64)], domain: &[(f64, f64)], length_scale: f64, noise: f64) -> Option<Self> {
+        let n = history.len();
+        let mean = history.iter().map(|(_, y)| y).sum::<f64>() / n as f64;
+        let var = history.iter().map(|(_, y)| (y - mean).powi(2)).sum::<f64>() / n as f64;
+        let std = if var > 0.0 { var.sqrt() } else { 1.0 };
+        let ys: Vec<f64> = history.iter().map(|(_, y)| (y - mean) / std).collect();
+        let (best_index, _) = history
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1))?;
+
+        let mut model = Self {
+            xs: Vec::with_capacity(n),
+            chol: Vec::new(),
+            alpha: Vec::new(),
+            best: ys[best_index],
+            best_x: history[best_index].0.clone(),
+            domain: domain.to_vec(),
+            length_scale,
+        };
+        model.xs = history.iter().map(|(x, _)| model.normalize(x)).collect();
+
+        let kernel: Vec<Vec<f64>> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| model.kernel(&model.xs[i], &model.xs[j]) + if i == j { noise } else { 0.0 })
+                    .collect()
+            })
+            .collect();
+        let chol = cholesky(&kernel)?;
+        let z = forward(&chol, &ys);
+        model.alpha = backward(&chol, &z);
+        model.chol = chol;
+        Some(model)
+    }
+
+    fn normalize(&self, x: &[f64]) -> Vec<f64> {
+        x.iter()
+            .zip(&self.domain)
+            .map(|(&v, &(lo, hi))| if hi > lo { (v - lo) / (hi - lo) } else { 0.0 })
+            .collect()
+    }
+
+    fn kernel(&self, a: &[f64], b: &[f64]) -> f64 {
+        let d2: f64 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+        (-0.5 * d2 / (self.length_scale * self.length_scale)).exp()
+    }
+
+    fn expected_improvement(&self, x: &[f64], xi: f64) -> f64 {
+        let x = self.normalize(x);
+        let k: Vec<f64> = self.xs.iter().map(|xi| self.kernel(xi, &x)).collect();
+        let mu: f64 = k.iter().zip(&self.alpha).map(|(a, b)| a * b).sum();
+        let v = forward(&self.chol, &k);
+        let sigma = (1.0 - v.iter().map(|x| x * x).sum::<f64>()).max(1e-12).sqrt();
+
+        let improvement = mu - self.best - xi;
+        let z = improvement / sigma;
+        improvement * normal_cdf(z) + sigma * normal_pdf(z)
+    }
+}
+
+/// Lower Cholesky factor of a symmetric positive-definite matrix
+pub(crate) fn cholesky(a: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let mut l = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum = a[i][j] - (0..j).map(|k| l[i][k] * l[j][k]).sum::<f64>();
+            if i == j {
+                if sum <= 0.0 || !sum.is_finite() {
+                    return None;
+                }
+                l[i][i] = sum.sqrt();
+            } else {
+                l[i][j] = sum / l[j][j];
+            }
+        }
+    }
+    Some(l)
+}
+
+/// Solve `L y = b` for lower-triangular `L`
+pub(crate) fn forward(l: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let mut y = vec![0.0; b.len()];
+    for i in 0..b.len() {
+        let sum: f64 = (0..i).map(|k| l[i][k] * y[k]).sum();
+        y[i] = (b[i] - sum) / l[i][i];
+    }
+    y
+}
+
+/// Solve `L^T x = y` for lower-triangular `L`
+fn backward(l: &[Vec<f64>], y: &[f64]) -> Vec<f64> {
+    let n = y.len();
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let sum: f64 = (i + 1..n).map(|k| l[k][i] * x[k]).sum();
+        x[i] = (y[i] - sum) / l[i][i];
+    }
+    x
+}
+
+fn normal_pdf(z: f64) -> f64 {
+    (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz and Stegun 7.1.26 (absolute error below 1.5e-7)
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    if x >= 0.0 {
+        y
+    } else {
+        -y
+    }
+}
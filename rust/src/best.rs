@@ -0,0 +1,44 @@
+//! Exploit-only parameter estimates
+//!
+//! `sample(dims, 0.0)` still draws from the learned distribution, and for a
+//! context with too little data it returns uniform noise that looks like
+//! any other answer. [`best_parameters`](EvoCoreContextSystem::best_parameters)
+//! returns the learned means instead, and nothing until the C library
+//! considers the context to have data (3 experiences by default).
+
+use crate::{evocore_context_get_stats_key, evocore_context_has_data, state, EvoCoreContextSystem};
+use std::ffi::CString;
+
+impl EvoCoreContextSystem {
+    /// Best known parameters for this context, or `None` while it is cold
+    ///
+    /// These are the learned (fitness-weighted) means, clamped to any
+    /// [bounds](crate::ParamBounds). An active
+    /// [override](EvoCoreContextSystem::override_params) is returned as is.
+    pub fn best_parameters(&self, dimension_values: &[&str]) -> Result<Option<Vec<f64>>, String> {
+        let key = self.context_key(dimension_values)?;
+        if let Some(pinned) = self.active_override(dimension_values) {
+            return Ok(Some(pinned.to_vec()));
+        }
+
+        let c_key = CString::new(key).map_err(|e| e.to_string())?;
+        let mut stats = std::ptr::null_mut();
+        let state = unsafe {
+            if !evocore_context_get_stats_key(self.inner.as_ptr(), c_key.as_ptr(), &mut stats)
+                || !evocore_context_has_data(stats, 0)
+            {
+                return Ok(None);
+            }
+            state::state_from_raw(&*stats)
+        };
+
+        Ok(Some(
+            state
+                .params
+                .iter()
+                .enumerate()
+                .map(|(i, p)| self.bounds(i).map_or(p.mean, |b| b.clamp(p.mean)))
+                .collect(),
+        ))
+    }
+}
@@ -0,0 +1,182 @@
+//! Inspect saved EvoCore systems (feature `cli`)
+//!
+//! Reads JSON and binary saves (with or without an envelope) through the
+//! validating [`Checkpoint`] parser, so no C context system is created.
+//!
+//! ```text
+//! evocore-inspect list <file>
+//! evocore-inspect show <file> <context-key>
+//! evocore-inspect top <file> [--by fitness|best|experiences|confidence] [-n <count>]
+//! evocore-inspect diff <a> <b> [--json]
+//! evocore-inspect convert <in> <out> [--to binary|json]
+//! ```
+
+use evocore_sys::{Checkpoint, ContextState, Format};
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage:
+  evocore-inspect list <file>
+  evocore-inspect show <file> <context-key>
+  evocore-inspect top <file> [--by fitness|best|experiences|confidence] [-n <count>]
+  evocore-inspect diff <a> <b> [--json]
+  evocore-inspect convert <in> <out> [--to binary|json]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("evocore-inspect: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (command, rest) = args.split_first().ok_or(USAGE)?;
+    let (positional, options) = split_options(rest)?;
+    let positional: Vec<&str> = positional.iter().map(String::as_str).collect();
+    match (command.as_str(), positional.as_slice()) {
+        ("list", [file]) => list(&open(file)?),
+        ("show", [file, key]) => show(&open(file)?, key),
+        ("top", [file]) => {
+            let by = options.get("by").map_or("fitness", String::as_str);
+            let n = match options.get("n") {
+                Some(n) => n.parse().map_err(|_| format!("Invalid count: {}", n))?,
+                None => 10,
+            };
+            top(&open(file)?, by, n)
+        }
+        ("diff", [a, b]) => {
+            let diff = open(a)?.diff(&open(b)?);
+            if options.contains_key("json") {
+                let json = serde_json::to_string_pretty(&diff.to_json()).map_err(|e| e.to_string())?;
+                println!("{}", json);
+            } else {
+                println!("{}", diff);
+            }
+            Ok(())
+        }
+        ("convert", [input, output]) => {
+            let format = match options.get("to").map_or("binary", String::as_str) {
+                "binary" => Format::Binary,
+                "json" => Format::Json,
+                other => return Err(format!("Unknown format: {}", other)),
+            };
+            let data = format.serializer().serialize(&open(input)?)?;
+            std::fs::write(output, data).map_err(|e| format!("Failed to write {}: {}", output, e))
+        }
+        ("help" | "--help" | "-h", _) => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+/// Options that take no value
+const FLAGS: &[&str] = &["json"];
+
+/// Separate `--name value` / `-n value` options and `--flag`s from positional arguments
+fn split_options(args: &[String]) -> Result<(Vec<String>, BTreeMap<String, String>), String> {
+    let mut positional = Vec::new();
+    let mut options = BTreeMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix("--").or_else(|| arg.strip_prefix('-')) {
+            Some(name) if FLAGS.contains(&name) => {
+                options.insert(name.to_string(), String::new());
+            }
+            Some(name) if !name.is_empty() => {
+                let value = args.next().ok_or_else(|| format!("Missing value for {}", arg))?;
+                options.insert(name.to_string(), value.clone());
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+    Ok((positional, options))
+}
+
+fn open(path: &str) -> Result<Checkpoint, String> {
+    Checkpoint::from_file(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn means(state: &ContextState) -> String {
+    let means: Vec<String> = state.params.iter().map(|p| format!("{:.4}", p.mean)).collect();
+    format!("[{}]", means.join(", "))
+}
+
+fn row(state: &ContextState) {
+    println!(
+        "{:<40} {:>8} {:>10.4} {:>10.4} {:>6.3}  {}",
+        state.key,
+        state.total_experiences,
+        state.avg_fitness,
+        state.best_fitness,
+        state.confidence,
+        means(state)
+    );
+}
+
+fn header() {
+    println!(
+        "{:<40} {:>8} {:>10} {:>10} {:>6}  means",
+        "context", "exps", "avg", "best", "conf"
+    );
+}
+
+fn list(checkpoint: &Checkpoint) -> Result<(), String> {
+    for (name, values) in &checkpoint.dimensions {
+        println!("dimension {}: {}", name, values.join(", "));
+    }
+    println!(
+        "{} parameters, {} contexts, {} stable slots",
+        checkpoint.param_count,
+        checkpoint.contexts.len(),
+        checkpoint.stable.len()
+    );
+    let mut contexts: Vec<&ContextState> = checkpoint.contexts.iter().collect();
+    contexts.sort_by(|a, b| a.key.cmp(&b.key));
+    header();
+    contexts.into_iter().for_each(row);
+    Ok(())
+}
+
+fn show(checkpoint: &Checkpoint, key: &str) -> Result<(), String> {
+    let state = checkpoint
+        .contexts
+        .iter()
+        .find(|s| s.key == key)
+        .ok_or_else(|| format!("No context {}", key))?;
+    println!("context      {}", state.key);
+    println!("experiences  {}", state.total_experiences);
+    println!("confidence   {:.4}", state.confidence);
+    println!("avg fitness  {:.6}", state.avg_fitness);
+    println!("best fitness {:.6}", state.best_fitness);
+    println!("first update {}", state.first_update);
+    println!("last update  {}", state.last_update);
+    if let Some(stable) = checkpoint.stable.iter().find(|s| s.key == key) {
+        println!("stable slot  {} experiences, means {}", stable.total_experiences, means(stable));
+    }
+    println!("{:>5} {:>12} {:>12} {:>12} {:>8}", "param", "mean", "std", "weight", "count");
+    for (i, p) in state.params.iter().enumerate() {
+        println!("{:>5} {:>12.6} {:>12.6} {:>12.6} {:>8}", i, p.mean, p.std(), p.sum_weights, p.count);
+    }
+    Ok(())
+}
+
+fn top(checkpoint: &Checkpoint, by: &str, n: usize) -> Result<(), String> {
+    let score: fn(&ContextState) -> f64 = match by {
+        "fitness" => |s| s.avg_fitness,
+        "best" => |s| s.best_fitness,
+        "experiences" => |s| s.total_experiences as f64,
+        "confidence" => |s| s.confidence,
+        other => return Err(format!("Unknown ranking: {}", other)),
+    };
+    let mut contexts: Vec<&ContextState> = checkpoint.contexts.iter().collect();
+    contexts.sort_by(|a, b| score(b).total_cmp(&score(a)).then_with(|| a.key.cmp(&b.key)));
+    header();
+    contexts.into_iter().take(n).for_each(row);
+    Ok(())
+}
@@ -0,0 +1,140 @@
+// Generated by build.rs with bindgen from include/evocore; do not edit.
+// Regenerate with `EVOCORE_UPDATE_BINDINGS=1 cargo build --features bindgen`.
+evocore_functions! {
+/* automatically generated by rust-bindgen 0.70.1 */
+
+extern "C" {
+    pub fn evocore_error_string(err: evocore_error_t) -> *const ::std::os::raw::c_char;
+    pub fn evocore_genome_from_data(
+        genome: *mut evocore_genome_t,
+        data: *const ::std::os::raw::c_void,
+        size: usize,
+    ) -> evocore_error_t;
+    pub fn evocore_genome_cleanup(genome: *mut evocore_genome_t);
+    pub fn evocore_population_init(pop: *mut evocore_population_t, capacity: usize) -> evocore_error_t;
+    pub fn evocore_population_cleanup(pop: *mut evocore_population_t);
+    pub fn evocore_population_clear(pop: *mut evocore_population_t);
+    pub fn evocore_population_add(
+        pop: *mut evocore_population_t,
+        genome: *const evocore_genome_t,
+        fitness: f64,
+    ) -> evocore_error_t;
+    pub fn evocore_population_increment_generation(pop: *mut evocore_population_t);
+    pub fn evocore_population_update_stats(pop: *mut evocore_population_t) -> evocore_error_t;
+    pub fn evocore_population_sort(pop: *mut evocore_population_t) -> evocore_error_t;
+    pub fn evocore_population_tournament_select(
+        pop: *const evocore_population_t,
+        tournament_size: usize,
+        seed: *mut ::std::os::raw::c_uint,
+    ) -> usize;
+    pub fn evocore_population_truncate(pop: *mut evocore_population_t, n: usize) -> evocore_error_t;
+    pub fn evocore_genome_crossover(
+        parent1: *const evocore_genome_t,
+        parent2: *const evocore_genome_t,
+        child1: *mut evocore_genome_t,
+        child2: *mut evocore_genome_t,
+        seed: *mut ::std::os::raw::c_uint,
+    ) -> evocore_error_t;
+    pub fn evocore_genome_mutate(
+        genome: *mut evocore_genome_t,
+        rate: f64,
+        seed: *mut ::std::os::raw::c_uint,
+    ) -> evocore_error_t;
+    pub fn evocore_log_set_level(level: evocore_log_level_t);
+    pub fn evocore_log_get_level() -> evocore_log_level_t;
+    pub fn evocore_log_set_callback(
+        callback: evocore_log_callback_t,
+        user_data: *mut ::std::os::raw::c_void,
+    );
+    pub fn evocore_context_system_create(
+        dimensions: *const evocore_context_dimension_t,
+        dimension_count: usize,
+        param_count: usize,
+    ) -> *mut evocore_context_system_t;
+    pub fn evocore_context_system_free(system: *mut evocore_context_system_t);
+    pub fn evocore_context_add_dimension(
+        system: *mut evocore_context_system_t,
+        name: *const ::std::os::raw::c_char,
+        values: *mut *const ::std::os::raw::c_char,
+        value_count: usize,
+    ) -> bool;
+    pub fn evocore_context_build_key(
+        system: *const evocore_context_system_t,
+        dimension_values: *mut *const ::std::os::raw::c_char,
+        out_key: *mut ::std::os::raw::c_char,
+        key_size: usize,
+    ) -> bool;
+    pub fn evocore_context_learn(
+        system: *mut evocore_context_system_t,
+        dimension_values: *mut *const ::std::os::raw::c_char,
+        parameters: *const f64,
+        param_count: usize,
+        fitness: f64,
+    ) -> bool;
+    pub fn evocore_context_learn_key(
+        system: *mut evocore_context_system_t,
+        context_key: *const ::std::os::raw::c_char,
+        parameters: *const f64,
+        param_count: usize,
+        fitness: f64,
+    ) -> bool;
+    pub fn evocore_context_get_stats(
+        system: *mut evocore_context_system_t,
+        dimension_values: *mut *const ::std::os::raw::c_char,
+        out_stats: *mut *mut evocore_context_stats_t,
+    ) -> bool;
+    pub fn evocore_context_get_stats_key(
+        system: *const evocore_context_system_t,
+        context_key: *const ::std::os::raw::c_char,
+        out_stats: *mut *mut evocore_context_stats_t,
+    ) -> bool;
+    pub fn evocore_context_has_data(stats: *const evocore_context_stats_t, min_samples: usize) -> bool;
+    pub fn evocore_context_sample(
+        system: *const evocore_context_system_t,
+        dimension_values: *mut *const ::std::os::raw::c_char,
+        out_parameters: *mut f64,
+        param_count: usize,
+        exploration_factor: f64,
+        seed: *mut ::std::os::raw::c_uint,
+    ) -> bool;
+    pub fn evocore_context_sample_key(
+        system: *const evocore_context_system_t,
+        context_key: *const ::std::os::raw::c_char,
+        out_parameters: *mut f64,
+        param_count: usize,
+        exploration_factor: f64,
+        seed: *mut ::std::os::raw::c_uint,
+    ) -> bool;
+    pub fn evocore_context_count(system: *const evocore_context_system_t) -> usize;
+    pub fn evocore_context_get_param_count(system: *const evocore_context_system_t) -> usize;
+    pub fn evocore_context_get_keys(
+        system: *const evocore_context_system_t,
+        out_keys: *mut *mut ::std::os::raw::c_char,
+        max_keys: usize,
+    ) -> usize;
+    pub fn evocore_context_save_json(
+        system: *const evocore_context_system_t,
+        filepath: *const ::std::os::raw::c_char,
+    ) -> bool;
+    pub fn evocore_context_load_json(
+        filepath: *const ::std::os::raw::c_char,
+        out_system: *mut *mut evocore_context_system_t,
+    ) -> bool;
+    pub fn evocore_context_save_binary(
+        system: *const evocore_context_system_t,
+        filepath: *const ::std::os::raw::c_char,
+    ) -> bool;
+    pub fn evocore_context_load_binary(
+        filepath: *const ::std::os::raw::c_char,
+        out_system: *mut *mut evocore_context_system_t,
+    ) -> bool;
+    pub fn evocore_context_export_csv(
+        system: *const evocore_context_system_t,
+        filepath: *const ::std::os::raw::c_char,
+    ) -> bool;
+    pub fn evocore_context_remove_key(
+        system: *mut evocore_context_system_t,
+        context_key: *const ::std::os::raw::c_char,
+    ) -> bool;
+}
+}
@@ -0,0 +1,188 @@
+//! Per-parameter bounds
+//!
+//! Most parameters have a valid range (a probability in `[0, 1]`, a
+//! positive timeout), but the learned distributions are unbounded, so
+//! samples near the edge of the range regularly fall outside it. Bounds
+//! registered with [`with_bounds`](EvoCoreContextSystem::with_bounds) are
+//! enforced in both directions: learning an out-of-range value fails with
+//! [`LearnError::OutOfBounds`], and samples are brought into range
+//! according to the [`BoundsMode`].
+//!
+//! Bounds and parameter names are configuration, not learned state, so
+//! they are not saved in checkpoints; register them again after loading.
+
+use crate::{BoundsMode, EvoCoreContextSystem, ParamBounds};
+use std::fmt;
+
+/// Why [`learn_checked`](EvoCoreContextSystem::learn_checked) refused an experience
+#[derive(Debug, Clone, PartialEq)]
+pub enum LearnError {
+    /// The experience has the wrong number of parameters
+    ParamCountMismatch { expected: usize, found: usize },
+    /// A parameter lies outside its registered bounds
+    OutOfBounds {
+        index: usize,
+        name: Option<String>,
+        value: f64,
+        bounds: ParamBounds,
+    },
+    /// The fitness is not finite, outside the declared range, or in the wrong unit
+    InvalidFitness(String),
+    /// The C library rejected the experience
+    Failed(String),
+}
+
+impl fmt::Display for LearnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LearnError::ParamCountMismatch { expected, found } => {
+                write!(f, "Parameter count mismatch: expected {}, got {}", expected, found)
+            }
+            LearnError::OutOfBounds { index, name, value, bounds } => {
+                match name {
+                    Some(name) => write!(f, "Parameter {} ({})", index, name)?,
+                    None => write!(f, "Parameter {}", index)?,
+                }
+                write!(f, " = {} is outside [{}, {}]", value, bounds.min, bounds.max)
+            }
+            LearnError::InvalidFitness(e) | LearnError::Failed(e) => f.write_str(e),
+        }
+    }
+}
+
+impl std::error::Error for LearnError {}
+
+impl EvoCoreContextSystem {
+    /// Name the parameters, so bounds can be registered and reported by name
+    pub fn with_param_names(mut self, names: &[&str]) -> Result<Self, String> {
+        if names.len() != self.param_count {
+            return Err(format!(
+                "Parameter count mismatch: expected {}, got {}",
+                self.param_count,
+                names.len()
+            ));
+        }
+        self.param_names = names.iter().map(|n| n.to_string()).collect();
+        Ok(self)
+    }
+
+    /// Parameter names, if registered
+    pub fn param_names(&self) -> &[String] {
+        &self.param_names
+    }
+
+    /// Index of a named parameter
+    pub fn param_index(&self, name: &str) -> Result<usize, String> {
+        self.param_names
+            .iter()
+            .position(|n| n == name)
+            .ok_or_else(|| format!("Unknown parameter: {}", name))
+    }
+
+    /// Register bounds for the parameter at `index`
+    pub fn with_bounds(mut self, index: usize, bounds: ParamBounds) -> Result<Self, String> {
+        self.set_bounds(index, Some(bounds))?;
+        Ok(self)
+    }
+
+    /// Register bounds for a parameter named by [`with_param_names`](Self::with_param_names)
+    pub fn with_named_bounds(self, name: &str, bounds: ParamBounds) -> Result<Self, String> {
+        let index = self.param_index(name)?;
+        self.with_bounds(index, bounds)
+    }
+
+    /// How out-of-range samples are handled (default [`BoundsMode::Clamp`])
+    pub fn with_bounds_mode(mut self, mode: BoundsMode) -> Self {
+        self.bounds_mode = mode;
+        self
+    }
+
+    /// Change or remove (`None`) the bounds of the parameter at `index`
+    pub fn set_bounds(&mut self, index: usize, bounds: Option<ParamBounds>) -> Result<(), String> {
+        if index >= self.param_count {
+            return Err(format!("Parameter index {} out of range ({} parameters)", index, self.param_count));
+        }
+        if let Some(b) = bounds {
+            if b.min.is_nan() || b.max.is_nan() || b.min > b.max {
+                return Err(format!("Invalid bounds for parameter {}: [{}, {}]", index, b.min, b.max));
+            }
+        }
+
+        if self.bounds.is_empty() {
+            self.bounds = vec![None; self.param_count];
+        }
+        self.bounds[index] = bounds;
+        if self.bounds.iter().all(Option::is_none) {
+            self.bounds.clear();
+        }
+        Ok(())
+    }
+
+    /// Bounds of the parameter at `index`, if any
+    pub fn bounds(&self, index: usize) -> Option<ParamBounds> {
+        self.bounds.get(index).copied().flatten()
+    }
+
+    pub fn bounds_mode(&self) -> BoundsMode {
+        self.bounds_mode
+    }
+
+    pub(crate) fn has_bounds(&self) -> bool {
+        !self.bounds.is_empty()
+    }
+
+    /// Reject parameters outside their bounds
+    pub(crate) fn check_bounds(&self, parameters: &[f64]) -> Result<(), LearnError> {
+        for (index, (&value, bounds)) in parameters.iter().zip(&self.bounds).enumerate() {
+            if let Some(bounds) = bounds.filter(|b| !b.contains(value)) {
+                return Err(LearnError::OutOfBounds {
+                    index,
+                    name: self.param_names.get(index).cloned(),
+                    value,
+                    bounds,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Bring a sample into bounds, drawing replacements with `resample` in
+    /// [`BoundsMode::Reject`]
+    pub(crate) fn apply_bounds(
+        &self,
+        out: &mut [f64],
+        mut resample: impl FnMut(&mut [f64]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let out_of_bounds = |out: &[f64]| {
+            out.iter()
+                .zip(&self.bounds)
+                .any(|(&v, b)| b.is_some_and(|b| !b.contains(v)))
+        };
+
+        if let BoundsMode::Reject { max_attempts } = self.bounds_mode {
+            if out_of_bounds(out) {
+                let mut scratch = vec![0.0; out.len()];
+                for _ in 0..max_attempts {
+                    resample(&mut scratch)?;
+                    for ((value, &candidate), bounds) in out.iter_mut().zip(&scratch).zip(&self.bounds) {
+                        if let Some(b) = bounds {
+                            if !b.contains(*value) && b.contains(candidate) {
+                                *value = candidate;
+                            }
+                        }
+                    }
+                    if !out_of_bounds(out) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        for (value, bounds) in out.iter_mut().zip(&self.bounds) {
+            if let Some(b) = bounds {
+                *value = b.clamp(*value);
+            }
+        }
+        Ok(())
+    }
+}
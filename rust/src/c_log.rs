@@ -0,0 +1,90 @@
+//! Route libevocore's log output through the `log` crate
+//!
+//! The C library writes its diagnostics (failed loads, allocation errors,
+//! ...) to stderr, bypassing whatever logger the application set up.
+//! [`capture_c_logs`] installs a callback that forwards every message as a
+//! `log` record with target `"evocore"`, the matching level, and the C
+//! source file and line. `EVOCORE_LOG_FATAL` maps to [`log::Level::Error`].
+//!
+//! The C side keeps the callback and its level in unsynchronized globals,
+//! so call these functions once at startup, after installing the logger
+//! and before any other thread uses the library.
+
+use crate::{
+    evocore_log_set_callback, evocore_log_set_level, EVOCORE_LOG_DEBUG, EVOCORE_LOG_ERROR, EVOCORE_LOG_INFO,
+    EVOCORE_LOG_TRACE, EVOCORE_LOG_WARN,
+};
+use log::{Level, LevelFilter, Record};
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::ptr;
+
+fn level(level: c_int) -> Level {
+    match level {
+        EVOCORE_LOG_TRACE => Level::Trace,
+        EVOCORE_LOG_DEBUG => Level::Debug,
+        EVOCORE_LOG_INFO => Level::Info,
+        EVOCORE_LOG_WARN => Level::Warn,
+        _ => Level::Error,
+    }
+}
+
+/// Lowest C level that can pass `filter`; `Off` still lets errors through to the logger
+fn c_level(filter: LevelFilter) -> c_int {
+    match filter {
+        LevelFilter::Trace => EVOCORE_LOG_TRACE,
+        LevelFilter::Debug => EVOCORE_LOG_DEBUG,
+        LevelFilter::Info => EVOCORE_LOG_INFO,
+        LevelFilter::Warn => EVOCORE_LOG_WARN,
+        LevelFilter::Error | LevelFilter::Off => EVOCORE_LOG_ERROR,
+    }
+}
+
+unsafe extern "C" fn forward(
+    level_raw: c_int,
+    file: *const c_char,
+    line: c_int,
+    message: *const c_char,
+    _user_data: *mut c_void,
+) {
+    if message.is_null() {
+        return;
+    }
+    let message = CStr::from_ptr(message).to_string_lossy();
+    let file = (!file.is_null()).then(|| CStr::from_ptr(file).to_string_lossy());
+    log::logger().log(
+        &Record::builder()
+            .level(level(level_raw))
+            .target("evocore")
+            .file(file.as_deref())
+            .line(u32::try_from(line).ok())
+            .args(format_args!("{}", message))
+            .build(),
+    );
+}
+
+/// Forward libevocore's log messages to the `log` crate instead of stderr
+///
+/// The C library's level is lowered or raised to match
+/// [`log::max_level`] at the time of the call, so messages the logger
+/// would discard are not formatted.
+///
+/// Does nothing if the C library is not [available](crate::native_available).
+pub fn capture_c_logs() {
+    if !crate::native_available() {
+        return;
+    }
+    unsafe {
+        evocore_log_set_level(c_level(log::max_level()));
+        evocore_log_set_callback(Some(forward), ptr::null_mut());
+    }
+}
+
+/// Send libevocore's log messages back to stderr
+pub fn release_c_logs() {
+    if !crate::native_available() {
+        return;
+    }
+    unsafe {
+        evocore_log_set_callback(None, ptr::null_mut());
+    }
+}
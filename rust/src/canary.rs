@@ -0,0 +1,244 @@
+//! Canary rollout of newly learned parameters
+//!
+//! Canary mode builds on the stable slots of
+//! [`promote`](EvoCoreContextSystem::promote) and
+//! [`rollback`](EvoCoreContextSystem::rollback): the stable arm is a
+//! context's stable slot and the canary arm is its live state.
+//! [`sample_canary`](EvoCoreContextSystem::sample_canary) serves the canary
+//! for only a configured fraction of calls and
+//! [`sample_stable`](EvoCoreContextSystem::sample_stable) for the rest.
+//! Fitness reported through
+//! [`learn_canary`](EvoCoreContextSystem::learn_canary) is tracked per arm,
+//! and once the canary has enough samples and its mean fitness is no worse
+//! than the stable arm's (within a margin), the live state is promoted to
+//! become the new stable slot.
+
+use crate::rename::move_entry;
+use crate::EvoCoreContextSystem;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Which distribution served a canary sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanaryArm {
+    /// The frozen stable snapshot
+    Stable,
+    /// The live, still-learning state
+    Canary,
+}
+
+/// Canary rollout settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanaryConfig {
+    fraction: f64,
+    min_samples: usize,
+    margin: f64,
+}
+
+impl CanaryConfig {
+    /// Serve the canary for `fraction` (0.0 - 1.0) of sample calls
+    pub fn new(fraction: f64) -> Self {
+        Self {
+            fraction: fraction.clamp(0.0, 1.0),
+            min_samples: 30,
+            margin: 0.0,
+        }
+    }
+
+    /// Require this many results on each arm before promoting (default 30)
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.max(1);
+        self
+    }
+
+    /// Promote when canary mean fitness is at least stable mean minus `margin` (default 0)
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin.max(0.0);
+        self
+    }
+}
+
+/// Running fitness of one arm since the last promotion
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ArmStats {
+    samples: usize,
+    fitness_sum: f64,
+}
+
+impl ArmStats {
+    fn record(&mut self, fitness: f64) {
+        self.samples += 1;
+        self.fitness_sum += fitness;
+    }
+
+    fn mean(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            self.fitness_sum / self.samples as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ContextCanary {
+    stable_arm: ArmStats,
+    canary_arm: ArmStats,
+    promotions: usize,
+}
+
+/// Canary bookkeeping for one system
+#[derive(Debug, Clone)]
+pub(crate) struct Canary {
+    config: CanaryConfig,
+    contexts: HashMap<String, ContextCanary>,
+}
+
+impl Canary {
+    pub(crate) fn forget(&mut self, key: &str) {
+        self.contexts.remove(key);
+    }
+
+    pub(crate) fn rename_key(&mut self, from: &str, to: &str) {
+        move_entry(&mut self.contexts, from, to);
+    }
+
+    /// Start both arms over after the stable slot of `key` changed
+    pub(crate) fn reset_arms(&mut self, key: &str) {
+        if let Some(context) = self.contexts.get_mut(key) {
+            context.stable_arm = ArmStats::default();
+            context.canary_arm = ArmStats::default();
+        }
+    }
+}
+
+/// Progress of the canary in one context
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanaryStatus {
+    /// Whether a stable slot exists (without one, every call serves the canary)
+    pub has_stable: bool,
+    pub stable_samples: usize,
+    pub stable_mean_fitness: f64,
+    pub canary_samples: usize,
+    pub canary_mean_fitness: f64,
+    /// Times the canary has been promoted in this context
+    pub promotions: usize,
+}
+
+impl EvoCoreContextSystem {
+    /// Turn on canary mode, promoting every context that has no stable slot yet
+    ///
+    /// Existing stable slots are kept. Replaces any previous canary
+    /// configuration and its statistics.
+    pub fn enable_canary(&mut self, config: CanaryConfig) {
+        for state in self.context_states() {
+            self.stable_slots.entry(state.key.clone()).or_insert(state);
+        }
+        self.canary = Some(Canary {
+            config,
+            contexts: HashMap::new(),
+        });
+    }
+
+    /// Turn off canary mode, keeping the stable slots
+    pub fn disable_canary(&mut self) {
+        self.canary = None;
+    }
+
+    /// Sample from the canary or stable arm, returning which one served
+    ///
+    /// Pass the returned arm to [`learn_canary`](Self::learn_canary) with
+    /// the fitness the parameters achieved. Without canary mode enabled,
+    /// this is `sample()` and always reports [`CanaryArm::Canary`].
+    pub fn sample_canary(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+    ) -> Result<(Vec<f64>, CanaryArm), String> {
+        let fraction = match &self.canary {
+            Some(canary) if self.stable_slots.contains_key(&self.context_key(dimension_values)?) => {
+                canary.config.fraction
+            }
+            _ => return Ok((self.sample(dimension_values, exploration)?, CanaryArm::Canary)),
+        };
+
+        if self.rng().gen::<f64>() >= fraction {
+            Ok((self.sample_stable(dimension_values, exploration)?, CanaryArm::Stable))
+        } else {
+            Ok((self.sample(dimension_values, exploration)?, CanaryArm::Canary))
+        }
+    }
+
+    /// Learn from a canary sample and promote the canary if it has proven itself
+    ///
+    /// Returns `true` if this call promoted the live state to stable.
+    pub fn learn_canary(
+        &mut self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+        arm: CanaryArm,
+    ) -> Result<bool, String> {
+        self.learn(dimension_values, parameters, fitness)?;
+
+        let key = self.context_key(dimension_values)?;
+        let Some(canary) = &mut self.canary else {
+            return Ok(false);
+        };
+        let config = canary.config;
+        let context = canary.contexts.entry(key.clone()).or_default();
+        match arm {
+            CanaryArm::Stable => context.stable_arm.record(fitness),
+            CanaryArm::Canary => context.canary_arm.record(fitness),
+        }
+
+        let ready = match self.stable_slots.get(&key) {
+            // No baseline to compare against: adopt once the canary has enough results
+            None => context.canary_arm.samples >= config.min_samples,
+            Some(_) => {
+                context.canary_arm.samples >= config.min_samples
+                    && context.stable_arm.samples >= config.min_samples
+                    && context.canary_arm.mean() >= context.stable_arm.mean() - config.margin
+            }
+        };
+        if ready {
+            self.promote_canary_key(&key);
+        }
+        Ok(ready)
+    }
+
+    /// Promote the live state of this context to stable now
+    pub fn promote_canary(&mut self, dimension_values: &[&str]) -> Result<(), String> {
+        let key = self.context_key(dimension_values)?;
+        if self.canary.is_none() {
+            return Err("Canary mode is not enabled".to_string());
+        }
+        self.promote_canary_key(&key);
+        Ok(())
+    }
+
+    /// Promote through the stable slot that `promote()` and `sample_stable()` use
+    fn promote_canary_key(&mut self, key: &str) {
+        if let Some(live) = self.context_state(key) {
+            self.stable_slots.insert(key.to_string(), live);
+        }
+        if let Some(canary) = &mut self.canary {
+            canary.contexts.entry(key.to_string()).or_default().promotions += 1;
+            canary.reset_arms(key);
+        }
+    }
+
+    /// Canary progress for this context, if canary mode is enabled
+    pub fn canary_status(&self, dimension_values: &[&str]) -> Option<CanaryStatus> {
+        let key = self.context_key(dimension_values).ok()?;
+        let context = self.canary.as_ref()?.contexts.get(&key).cloned().unwrap_or_default();
+        Some(CanaryStatus {
+            has_stable: self.stable_slots.contains_key(&key),
+            stable_samples: context.stable_arm.samples,
+            stable_mean_fitness: context.stable_arm.mean(),
+            canary_samples: context.canary_arm.samples,
+            canary_mean_fitness: context.canary_arm.mean(),
+            promotions: context.promotions,
+        })
+    }
+}
@@ -0,0 +1,118 @@
+//! Canonical JSON saves
+//!
+//! Two saves of the same state should produce the same bytes, so that
+//! checkpoints diff cleanly in git and deduplicate in content-addressed
+//! storage. [`CanonicalJsonSerializer`] writes the same document as
+//! [`JsonSerializer`](crate::JsonSerializer) with object keys sorted
+//! byte-wise and every float rounded to a fixed number of decimal places
+//! (trailing zeros trimmed, `-0` written as `0`, non-finite values as
+//! `null`). Layout is fixed at two-space indentation with a trailing
+//! newline. The output is plain JSON and loads with `load()`.
+
+use crate::checkpoint::parse_json;
+use crate::serializer::json_document;
+use crate::{Checkpoint, SystemSerializer};
+use serde_json::Value;
+
+/// JSON with sorted keys and fixed float precision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanonicalJsonSerializer {
+    precision: usize,
+}
+
+impl Default for CanonicalJsonSerializer {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl CanonicalJsonSerializer {
+    /// Nine decimal places
+    pub const DEFAULT: Self = Self { precision: 9 };
+
+    /// Nine decimal places
+    pub fn new() -> Self {
+        Self::DEFAULT
+    }
+
+    /// Decimal places kept for floats (default 9, at most 17)
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision.min(17);
+        self
+    }
+
+    pub fn precision(&self) -> usize {
+        self.precision
+    }
+
+    fn write_float(&self, x: f64, out: &mut String) {
+        if !x.is_finite() {
+            out.push_str("null");
+            return;
+        }
+        let mut text = format!("{:.*}", self.precision, x);
+        if text.contains('.') {
+            let trimmed = text.trim_end_matches('0').trim_end_matches('.').len();
+            text.truncate(trimmed);
+        }
+        if text == "-0" {
+            text.remove(0);
+        }
+        out.push_str(&text);
+        if !text.contains('.') {
+            out.push_str(".0");
+        }
+    }
+
+    fn write_value(&self, value: &Value, indent: usize, out: &mut String) {
+        let newline = |out: &mut String, indent: usize| {
+            out.push('\n');
+            out.extend(std::iter::repeat_n(' ', indent));
+        };
+        match value {
+            Value::Number(n) if n.is_f64() => self.write_float(n.as_f64().unwrap_or(f64::NAN), out),
+            Value::Array(items) if !items.is_empty() => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, indent + 2);
+                    self.write_value(item, indent + 2, out);
+                }
+                newline(out, indent);
+                out.push(']');
+            }
+            Value::Object(map) if !map.is_empty() => {
+                let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+                out.push('{');
+                for (i, (key, item)) in entries.into_iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, indent + 2);
+                    out.push_str(&Value::from(key.as_str()).to_string());
+                    out.push_str(": ");
+                    self.write_value(item, indent + 2, out);
+                }
+                newline(out, indent);
+                out.push('}');
+            }
+            other => out.push_str(&other.to_string()),
+        }
+    }
+}
+
+impl SystemSerializer for CanonicalJsonSerializer {
+    fn serialize(&self, checkpoint: &Checkpoint) -> Result<Vec<u8>, String> {
+        let mut out = String::new();
+        self.write_value(&json_document(checkpoint), 0, &mut out);
+        out.push('\n');
+        Ok(out.into_bytes())
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<Checkpoint, String> {
+        parse_json(data).map_err(|e| e.to_string())
+    }
+}
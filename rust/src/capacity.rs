@@ -0,0 +1,188 @@
+//! Bounded context capacity with LRU eviction
+//!
+//! When dimension values come from user input, the number of contexts is
+//! unbounded. [`with_max_contexts`](EvoCoreContextSystem::with_max_contexts)
+//! caps it: every `learn()`, and every `sample()` of a context that
+//! exists, marks the context as recently used, and once a learn pushes the
+//! count over the cap, the least recently used contexts are removed. [Pinned](EvoCoreContextSystem::pin_context)
+//! contexts are never evicted, and with [coarsening](EvoCoreContextSystem::with_coarsening)
+//! cold contexts are folded into coarser parents before anything is evicted.
+
+use crate::{evocore_context_get_stats_key, EvoCoreContextSystem};
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::CStr;
+use std::sync::{Mutex, PoisonError};
+
+/// Capacity limit and how often it has forced evictions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapacityStats {
+    pub max_contexts: usize,
+    pub contexts: usize,
+    /// Contexts evicted since the limit was set
+    pub evictions: u64,
+}
+
+#[derive(Debug, Default)]
+struct Recency {
+    next_tick: u64,
+    ticks: HashMap<String, u64>,
+    order: BTreeSet<(u64, String)>,
+}
+
+/// Recency tracking for one system
+///
+/// Lives behind a mutex because `sample()` takes `&self` but still counts
+/// as a use.
+#[derive(Debug)]
+pub(crate) struct Lru {
+    max_contexts: usize,
+    recency: Mutex<Recency>,
+    evictions: u64,
+}
+
+impl Lru {
+    pub(crate) fn forget(&self, key: &str) {
+        let mut recency = self.recency.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(tick) = recency.ticks.remove(key) {
+            recency.order.remove(&(tick, key.to_string()));
+        }
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Keep at most `max_contexts` contexts, evicting the least recently used
+    pub fn with_max_contexts(mut self, max_contexts: usize) -> Self {
+        self.set_max_contexts(Some(max_contexts));
+        self
+    }
+
+    /// Change or remove (`None`) the context limit, evicting at once if needed
+    ///
+    /// Contexts that existed before the limit was set count as least
+    /// recently used until they are next touched.
+    pub fn set_max_contexts(&mut self, max_contexts: Option<usize>) {
+        self.lru = max_contexts.map(|max_contexts| Lru {
+            max_contexts,
+            recency: Mutex::new(Recency {
+                next_tick: 1,
+                ..Recency::default()
+            }),
+            evictions: self.lru.as_ref().map_or(0, |lru| lru.evictions),
+        });
+        self.enforce_capacity();
+    }
+
+    /// Limit, current count, and evictions, if a limit is set
+    pub fn capacity_stats(&self) -> Option<CapacityStats> {
+        self.lru.as_ref().map(|lru| CapacityStats {
+            max_contexts: lru.max_contexts,
+            contexts: self.context_count(),
+            evictions: lru.evictions,
+        })
+    }
+
+    /// Mark a context as just used
+    pub(crate) fn touch_key(&self, key: &str) {
+        let Some(lru) = &self.lru else {
+            return;
+        };
+        let mut recency = lru.recency.lock().unwrap_or_else(PoisonError::into_inner);
+        let recency = &mut *recency;
+        let tick = recency.next_tick;
+        recency.next_tick += 1;
+
+        match recency.ticks.get_mut(key) {
+            Some(old) => {
+                let entry = recency.order.take(&(*old, key.to_string())).map(|(_, k)| k);
+                *old = tick;
+                recency.order.insert((tick, entry.unwrap_or_else(|| key.to_string())));
+            }
+            None => {
+                recency.ticks.insert(key.to_string(), tick);
+                recency.order.insert((tick, key.to_string()));
+            }
+        }
+    }
+
+    /// Evict least recently used contexts until the count fits the limit
+    pub(crate) fn enforce_capacity(&mut self) {
+        let Some(max_contexts) = self.lru.as_ref().map(|lru| lru.max_contexts) else {
+            return;
+        };
+        self.coarsen_if_needed();
+
+        let mut evicted = 0;
+        loop {
+            let count = self.context_count();
+            if count <= max_contexts {
+                break;
+            }
+            let victims = self.pick_victims(count, max_contexts);
+            if victims.is_empty() {
+                break;
+            }
+            // Only successful removals count; a stale key frees nothing
+            evicted += victims.iter().filter(|key| self.remove_key(key)).count();
+        }
+        if let Some(lru) = &mut self.lru {
+            lru.evictions += evicted as u64;
+        }
+    }
+
+    /// Take up to `count - max_contexts` least recently used, unpinned keys
+    /// out of the recency order
+    fn pick_victims(&self, count: usize, max_contexts: usize) -> Vec<String> {
+        let Some(lru) = &self.lru else {
+            return Vec::new();
+        };
+        let mut recency = lru.recency.lock().unwrap_or_else(PoisonError::into_inner);
+
+        self.track_untouched(&mut recency, count);
+
+        // Pinned contexts are skipped and put back afterwards
+        let mut victims = Vec::new();
+        let mut pinned = Vec::new();
+        while count - victims.len() > max_contexts {
+            let Some(entry) = recency.order.pop_first() else {
+                break;
+            };
+            if self.hot.is_pinned(&entry.1) {
+                pinned.push(entry);
+                continue;
+            }
+            recency.ticks.remove(&entry.1);
+            victims.push(entry.1);
+        }
+        recency.order.extend(pinned);
+        victims
+    }
+
+    /// Whether the C table holds a context under `key`
+    pub(crate) fn has_context(&self, key: &CStr) -> bool {
+        let mut stats = std::ptr::null_mut();
+        unsafe { evocore_context_get_stats_key(self.inner.as_ptr(), key.as_ptr(), &mut stats) && !stats.is_null() }
+    }
+
+    /// Context keys, least recently used first
+    pub(crate) fn least_recent_keys(&self) -> Vec<String> {
+        let Some(lru) = &self.lru else {
+            return Vec::new();
+        };
+        let mut recency = lru.recency.lock().unwrap_or_else(PoisonError::into_inner);
+        self.track_untouched(&mut recency, self.context_count());
+        recency.order.iter().map(|(_, key)| key.clone()).collect()
+    }
+
+    /// Contexts created without a learn or sample (restored, loaded) were
+    /// never touched; they count as least recently used.
+    fn track_untouched(&self, recency: &mut Recency, count: usize) {
+        if recency.ticks.len() < count {
+            for key in self.context_keys() {
+                if !recency.ticks.contains_key(&key) {
+                    recency.ticks.insert(key.clone(), 0);
+                    recency.order.insert((0, key));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! C ABI over the safe layer (feature `capi`)
+//!
+//! The crate's cdylib exports these functions so hosts in other languages
+//! get what the Rust wrapper adds on top of libevocore (input validation,
+//! parameter bounds, the key cache, error messages) instead of calling the
+//! raw C API. Declarations are in `include/evocore_rs.h`; every symbol is
+//! prefixed `evocore_rs_` so it cannot clash with libevocore's own, which
+//! the same cdylib also exports.
+//!
+//! Functions that can fail return `EVOCORE_RS_OK` or a negative status
+//! code, or a null handle, and [`evocore_rs_last_error`] then describes the
+//! failure on the calling thread. Panics are caught at the boundary and
+//! reported as `EVOCORE_RS_PANIC`. A system may move between threads but
+//! must only be used by one at a time.
+//!
+//! # Safety
+//!
+//! Pointers must be null or valid for the lengths passed with them,
+//! strings NUL-terminated UTF-8, and handles come from `evocore_rs_create`
+//! or `evocore_rs_load` and are used until `evocore_rs_free` only.
+
+#![allow(non_camel_case_types, clippy::missing_safety_doc)]
+
+use crate::{EvoCoreContextSystem, LearnError, ParamBounds};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Version of the exported ABI, bumped on incompatible changes
+pub const EVOCORE_RS_ABI_VERSION: u32 = 1;
+
+pub const EVOCORE_RS_OK: c_int = 0;
+pub const EVOCORE_RS_INVALID_ARGUMENT: c_int = -1;
+pub const EVOCORE_RS_PARAM_COUNT: c_int = -2;
+pub const EVOCORE_RS_OUT_OF_BOUNDS: c_int = -3;
+pub const EVOCORE_RS_INVALID_FITNESS: c_int = -4;
+pub const EVOCORE_RS_FAILED: c_int = -5;
+pub const EVOCORE_RS_PANIC: c_int = -6;
+
+/// Opaque handle owning a context system
+pub struct evocore_rs_system_t {
+    system: EvoCoreContextSystem,
+}
+
+/// A status code and the message for `evocore_rs_last_error`
+type Failure = (c_int, String);
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+fn invalid(message: impl Into<String>) -> Failure {
+    (EVOCORE_RS_INVALID_ARGUMENT, message.into())
+}
+
+fn failed(message: String) -> Failure {
+    (EVOCORE_RS_FAILED, message)
+}
+
+fn learn_failure(e: LearnError) -> Failure {
+    let code = match e {
+        LearnError::ParamCountMismatch { .. } => EVOCORE_RS_PARAM_COUNT,
+        LearnError::OutOfBounds { .. } => EVOCORE_RS_OUT_OF_BOUNDS,
+        LearnError::InvalidFitness(_) => EVOCORE_RS_INVALID_FITNESS,
+        LearnError::Failed(_) => EVOCORE_RS_FAILED,
+    };
+    (code, e.to_string())
+}
+
+/// Run `f`, recording its error or panic for `evocore_rs_last_error`
+fn guard<T>(f: impl FnOnce() -> Result<T, Failure>) -> Result<T, c_int> {
+    let (code, message) = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => return Ok(value),
+        Ok(Err(failure)) => failure,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            (EVOCORE_RS_PANIC, format!("panic: {}", message))
+        }
+    };
+    set_last_error(&message);
+    Err(code)
+}
+
+/// [`guard`] for functions returning only a status code
+fn status(f: impl FnOnce() -> Result<(), Failure>) -> c_int {
+    guard(f).err().unwrap_or(EVOCORE_RS_OK)
+}
+
+unsafe fn string<'a>(ptr: *const c_char, what: &str) -> Result<&'a str, Failure> {
+    if ptr.is_null() {
+        return Err(invalid(format!("{} is null", what)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| invalid(format!("{} is not valid UTF-8", what)))
+}
+
+unsafe fn slice<'a, T>(ptr: *const T, len: usize, what: &str) -> Result<&'a [T], Failure> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(invalid(format!("{} is null", what))),
+        (false, _) => Ok(std::slice::from_raw_parts(ptr, len)),
+    }
+}
+
+unsafe fn strings<'a>(ptr: *const *const c_char, len: usize, what: &str) -> Result<Vec<&'a str>, Failure> {
+    slice(ptr, len, what)?.iter().map(|&s| string(s, what)).collect()
+}
+
+unsafe fn system<'a>(handle: *const evocore_rs_system_t) -> Result<&'a EvoCoreContextSystem, Failure> {
+    handle.as_ref().map(|h| &h.system).ok_or_else(|| invalid("system is null"))
+}
+
+unsafe fn system_mut<'a>(handle: *mut evocore_rs_system_t) -> Result<&'a mut EvoCoreContextSystem, Failure> {
+    handle.as_mut().map(|h| &mut h.system).ok_or_else(|| invalid("system is null"))
+}
+
+fn into_handle(system: EvoCoreContextSystem) -> *mut evocore_rs_system_t {
+    Box::into_raw(Box::new(evocore_rs_system_t { system }))
+}
+
+/// Version of the exported ABI
+#[no_mangle]
+pub extern "C" fn evocore_rs_abi_version() -> u32 {
+    EVOCORE_RS_ABI_VERSION
+}
+
+/// Message for the last failure on this thread, or an empty string
+///
+/// Valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn evocore_rs_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+/// Create a system; `values` holds each dimension's values back to back,
+/// `value_counts[i]` of them for dimension `i`
+///
+/// Returns null on failure.
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_create(
+    names: *const *const c_char,
+    value_counts: *const usize,
+    values: *const *const c_char,
+    dimension_count: usize,
+    param_count: usize,
+) -> *mut evocore_rs_system_t {
+    guard(|| {
+        let names = strings(names, dimension_count, "dimension name")?;
+        let counts = slice(value_counts, dimension_count, "value_counts")?;
+        let total = counts
+            .iter()
+            .try_fold(0usize, |sum, &n| sum.checked_add(n))
+            .ok_or_else(|| invalid("value_counts overflow"))?;
+        let mut values = strings(values, total, "dimension value")?.into_iter();
+        let values: Vec<Vec<&str>> = counts.iter().map(|&n| values.by_ref().take(n).collect()).collect();
+        EvoCoreContextSystem::new(&names, &values, param_count).map_err(failed)
+    })
+    .map_or(ptr::null_mut(), into_handle)
+}
+
+/// Load a system saved in either format; returns null on failure
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_load(path: *const c_char) -> *mut evocore_rs_system_t {
+    guard(|| EvoCoreContextSystem::load(string(path, "path")?).map_err(failed)).map_or(ptr::null_mut(), into_handle)
+}
+
+/// Free a system; null is ignored
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_free(system: *mut evocore_rs_system_t) {
+    if !system.is_null() {
+        let _ = guard(|| {
+            drop(Box::from_raw(system));
+            Ok(())
+        });
+    }
+}
+
+/// Save as JSON
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_save(system: *const evocore_rs_system_t, path: *const c_char) -> c_int {
+    status(|| self::system(system)?.save(string(path, "path")?).map_err(failed))
+}
+
+/// Save in the compact binary format
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_save_binary(system: *const evocore_rs_system_t, path: *const c_char) -> c_int {
+    status(|| self::system(system)?.save_binary(string(path, "path")?).map_err(failed))
+}
+
+/// Reject learns and clamp samples outside `[min, max]` for parameter `index`
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_set_bounds(
+    system: *mut evocore_rs_system_t,
+    index: usize,
+    min: f64,
+    max: f64,
+) -> c_int {
+    status(|| {
+        system_mut(system)?
+            .set_bounds(index, Some(ParamBounds::new(min, max)))
+            .map_err(invalid)
+    })
+}
+
+/// Remove the bounds of parameter `index`
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_clear_bounds(system: *mut evocore_rs_system_t, index: usize) -> c_int {
+    status(|| system_mut(system)?.set_bounds(index, None).map_err(invalid))
+}
+
+/// Cache prebuilt keys for up to `capacity` contexts; 0 disables the cache
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_enable_key_cache(system: *mut evocore_rs_system_t, capacity: usize) -> c_int {
+    status(|| {
+        let system = system_mut(system)?;
+        match capacity {
+            0 => system.disable_key_cache(),
+            n => system.enable_key_cache(n),
+        }
+        Ok(())
+    })
+}
+
+/// Seed sampling so the same sequence of calls draws the same parameters
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_set_master_seed(system: *mut evocore_rs_system_t, seed: u64) -> c_int {
+    status(|| {
+        system_mut(system)?.set_master_seed(seed);
+        Ok(())
+    })
+}
+
+/// Learn from one experience
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_learn(
+    system: *mut evocore_rs_system_t,
+    values: *const *const c_char,
+    value_count: usize,
+    params: *const f64,
+    param_count: usize,
+    fitness: f64,
+) -> c_int {
+    status(|| {
+        let system = system_mut(system)?;
+        let values = strings(values, value_count, "dimension value")?;
+        let params = slice(params, param_count, "params")?;
+        system.learn_checked(&values, params, fitness).map_err(learn_failure)
+    })
+}
+
+/// Sample parameters into `out`, which must hold exactly the system's parameter count
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_sample(
+    system: *const evocore_rs_system_t,
+    values: *const *const c_char,
+    value_count: usize,
+    exploration: f64,
+    out: *mut f64,
+    out_len: usize,
+) -> c_int {
+    status(|| {
+        let system = self::system(system)?;
+        let values = strings(values, value_count, "dimension value")?;
+        if out_len != system.param_count() {
+            return Err((
+                EVOCORE_RS_PARAM_COUNT,
+                format!("Parameter count mismatch: expected {}, got {}", system.param_count(), out_len),
+            ));
+        }
+        if out.is_null() {
+            return Err(invalid("out is null"));
+        }
+        let out = std::slice::from_raw_parts_mut(out, out_len);
+        system.sample_into(&values, exploration, out).map_err(failed)
+    })
+}
+
+/// Number of contexts learned so far; 0 for a null system
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_context_count(system: *const evocore_rs_system_t) -> usize {
+    guard(|| Ok(self::system(system)?.context_count())).unwrap_or(0)
+}
+
+/// Parameters per context; 0 for a null system
+#[no_mangle]
+pub unsafe extern "C" fn evocore_rs_param_count(system: *const evocore_rs_system_t) -> usize {
+    guard(|| Ok(self::system(system)?.param_count())).unwrap_or(0)
+}
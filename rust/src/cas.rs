@@ -0,0 +1,238 @@
+//! Content-addressed checkpoint store (feature `cas`)
+//!
+//! Daily snapshots of a large system are mostly identical: only the
+//! contexts that learned since yesterday differ. [`CasStore`] splits each
+//! snapshot into one segment per context plus a schema segment, stores
+//! every segment under the SHA-256 of its bytes, and records a snapshot as
+//! a small manifest listing segment hashes. Unchanged contexts hash to the
+//! same segment and are stored once, no matter how many snapshots keep them.
+//!
+//! Directory layout:
+//!
+//! ```text
+//! <root>/objects/<2 hex>/<62 hex>   segments (binary checkpoint format)
+//! <root>/snapshots/<name>           manifests
+//! ```
+//!
+//! A manifest is text: an `evocore-cas 1` header, one `schema <hash>`
+//! line, then `context <hash>` and `stable <hash>` lines in order. Every
+//! segment is itself a valid binary checkpoint (so snapshots keep exactly
+//! what `save_binary()` keeps), and is re-hashed when read so a corrupted
+//! object is detected rather than loaded. Removing a
+//! snapshot leaves its segments in place until [`gc`](CasStore::gc), which
+//! must not run while another process is writing to the store.
+
+use crate::checkpoint::parse_binary;
+use crate::serializer::write_file;
+use crate::{Checkpoint, ContextState, EvoCoreContextSystem};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+const MANIFEST_HEADER: &str = "evocore-cas 1";
+
+/// Outcome of [`CasStore::put`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CasPutStats {
+    /// Segments referenced by the snapshot
+    pub segments: usize,
+    /// Segments that were new and written
+    pub written: usize,
+    /// Segments already present in the store
+    pub reused: usize,
+    /// Bytes written for new segments
+    pub bytes_written: u64,
+}
+
+/// Directory of deduplicated checkpoint snapshots
+#[derive(Debug, Clone)]
+pub struct CasStore {
+    root: PathBuf,
+}
+
+fn hash_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().fold(String::with_capacity(64), |mut hex, b| {
+        let _ = write!(hex, "{:02x}", b);
+        hex
+    })
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(format!("Invalid snapshot name: {:?}", name));
+    }
+    Ok(())
+}
+
+fn check_hash(hash: &str) -> Result<(), String> {
+    if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("Invalid segment hash: {:?}", hash));
+    }
+    Ok(())
+}
+
+impl CasStore {
+    /// Open a store rooted at `root`, creating its directories if needed
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self, String> {
+        let root = root.as_ref().to_path_buf();
+        for dir in ["objects", "snapshots"] {
+            std::fs::create_dir_all(root.join(dir)).map_err(|e| format!("Failed to open store: {}", e))?;
+        }
+        Ok(Self { root })
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.root.join("objects").join(&hash[..2]).join(&hash[2..])
+    }
+
+    fn manifest_path(&self, name: &str) -> PathBuf {
+        self.root.join("snapshots").join(name)
+    }
+
+    /// Store a segment unless it is already present; returns its hash and size if written
+    fn put_segment(&self, data: &[u8]) -> Result<(String, Option<u64>), String> {
+        let hash = hash_hex(data);
+        let path = self.object_path(&hash);
+        if path.exists() {
+            return Ok((hash, None));
+        }
+        let err = |e: std::io::Error| format!("Failed to write segment {}: {}", hash, e);
+        std::fs::create_dir_all(path.parent().unwrap()).map_err(err)?;
+        write_file(&path, data, true)?;
+        Ok((hash, Some(data.len() as u64)))
+    }
+
+    /// Read a segment, checking it still matches its hash
+    fn get_segment(&self, hash: &str) -> Result<Checkpoint, String> {
+        check_hash(hash)?;
+        let data = std::fs::read(self.object_path(hash))
+            .map_err(|e| format!("Failed to read segment {}: {}", hash, e))?;
+        if hash_hex(&data) != hash {
+            return Err(format!("Segment {} is corrupted", hash));
+        }
+        parse_binary(&data, false)
+            .map(|(segment, _)| segment)
+            .map_err(|e| format!("Invalid segment {}: {}", hash, e))
+    }
+
+    /// Save a snapshot of `system` under `name`, replacing any snapshot of that name
+    pub fn put(&self, name: &str, system: &EvoCoreContextSystem) -> Result<CasPutStats, String> {
+        self.put_checkpoint(name, &system.checkpoint())
+    }
+
+    /// Save a checkpoint under `name`, replacing any snapshot of that name
+    pub fn put_checkpoint(&self, name: &str, checkpoint: &Checkpoint) -> Result<CasPutStats, String> {
+        check_name(name)?;
+        let mut stats = CasPutStats::default();
+        let mut manifest = format!("{}\n", MANIFEST_HEADER);
+
+        let segment = |contexts: Vec<ContextState>, dimensions| Checkpoint {
+            dimensions,
+            param_count: checkpoint.param_count,
+            contexts,
+            stable: Vec::new(),
+        };
+        let schema = segment(Vec::new(), checkpoint.dimensions.clone());
+        let sections = std::iter::once(("schema", schema))
+            .chain(checkpoint.contexts.iter().map(|s| ("context", segment(vec![s.clone()], Vec::new()))))
+            .chain(checkpoint.stable.iter().map(|s| ("stable", segment(vec![s.clone()], Vec::new()))));
+
+        for (kind, segment) in sections {
+            let (hash, written) = self.put_segment(&segment.to_binary())?;
+            stats.segments += 1;
+            match written {
+                Some(bytes) => {
+                    stats.written += 1;
+                    stats.bytes_written += bytes;
+                }
+                None => stats.reused += 1,
+            }
+            let _ = writeln!(manifest, "{} {}", kind, hash);
+        }
+
+        write_file(&self.manifest_path(name), manifest.as_bytes(), true)?;
+        Ok(stats)
+    }
+
+    /// Reassemble and validate the checkpoint saved under `name`
+    pub fn get_checkpoint(&self, name: &str) -> Result<Checkpoint, String> {
+        check_name(name)?;
+        let manifest = std::fs::read_to_string(self.manifest_path(name))
+            .map_err(|e| format!("Failed to read snapshot {}: {}", name, e))?;
+        let mut lines = manifest.lines();
+        if lines.next() != Some(MANIFEST_HEADER) {
+            return Err(format!("Snapshot {} has an unsupported manifest", name));
+        }
+
+        let mut checkpoint: Option<Checkpoint> = None;
+        for line in lines {
+            let (kind, hash) = line
+                .split_once(' ')
+                .ok_or_else(|| format!("Snapshot {}: malformed line {:?}", name, line))?;
+            let mut segment = self.get_segment(hash)?;
+            match (kind, checkpoint.as_mut()) {
+                ("schema", None) => checkpoint = Some(segment),
+                ("context", Some(cp)) => cp.contexts.append(&mut segment.contexts),
+                ("stable", Some(cp)) => cp.stable.append(&mut segment.contexts),
+                _ => return Err(format!("Snapshot {}: unexpected line {:?}", name, line)),
+            }
+        }
+
+        let checkpoint = checkpoint.ok_or_else(|| format!("Snapshot {} has no schema", name))?;
+        for state in checkpoint.contexts.iter().chain(&checkpoint.stable) {
+            checkpoint.validate_context(state).map_err(|e| format!("Snapshot {}: {}", name, e))?;
+        }
+        Ok(checkpoint)
+    }
+
+    /// Load the system saved under `name`
+    pub fn get(&self, name: &str) -> Result<EvoCoreContextSystem, String> {
+        self.get_checkpoint(name)?.into_system().map_err(|e| format!("Snapshot {}: {}", name, e))
+    }
+
+    /// Names of all snapshots, sorted
+    pub fn snapshots(&self) -> Result<Vec<String>, String> {
+        let entries = std::fs::read_dir(self.root.join("snapshots"))
+            .map_err(|e| format!("Failed to list snapshots: {}", e))?;
+        let mut names: Vec<String> = entries
+            .filter_map(Result::ok)
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|n| check_name(n).is_ok() && !n.ends_with(".tmp"))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Delete a snapshot's manifest (its segments stay until [`gc`](Self::gc))
+    pub fn remove(&self, name: &str) -> Result<(), String> {
+        check_name(name)?;
+        std::fs::remove_file(self.manifest_path(name)).map_err(|e| format!("Failed to remove snapshot {}: {}", name, e))
+    }
+
+    /// Delete segments no snapshot refers to; returns the number deleted
+    pub fn gc(&self) -> Result<usize, String> {
+        let mut live = HashSet::new();
+        for name in self.snapshots()? {
+            let manifest = std::fs::read_to_string(self.manifest_path(&name))
+                .map_err(|e| format!("Failed to read snapshot {}: {}", name, e))?;
+            live.extend(manifest.lines().skip(1).filter_map(|l| l.split_once(' ')).map(|(_, h)| h.to_string()));
+        }
+
+        let err = |e: std::io::Error| format!("Failed to collect garbage: {}", e);
+        let mut removed = 0;
+        for prefix in std::fs::read_dir(self.root.join("objects")).map_err(err)? {
+            let prefix = prefix.map_err(err)?;
+            let prefix_name = prefix.file_name().to_string_lossy().into_owned();
+            for object in std::fs::read_dir(prefix.path()).map_err(err)? {
+                let object = object.map_err(err)?;
+                let hash = format!("{}{}", prefix_name, object.file_name().to_string_lossy());
+                if !live.contains(&hash) {
+                    std::fs::remove_file(object.path()).map_err(err)?;
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,169 @@
+//! Cold/warm/converged/stale context counts
+//!
+//! [`context_count`](EvoCoreContextSystem::context_count) says how many
+//! contexts exist but not whether they are any use.
+//! [`context_census`](EvoCoreContextSystem::context_census) classifies every
+//! context in one pass over the C library's statistics, without copying
+//! them out:
+//!
+//! - **warm**: at least `min_samples` experiences; the rest are cold
+//! - **converged**: warm, and every parameter's standard deviation is at
+//!   most `converged_spread` of its [bounds](crate::ParamBounds), or of
+//!   the range it has been observed over when it has none
+//! - **stale**: not learned for at least `stale_after`
+//!
+//! A context can be both converged and stale.
+//! [`context_census_private`](EvoCoreContextSystem::context_census_private)
+//! adds differential-privacy noise to the counts.
+
+use crate::decay::unix_now;
+use crate::{evocore_context_get_stats_key, EvoCoreContextSystem, PrivacyBudget};
+use std::ffi::CString;
+use std::fmt;
+use std::time::Duration;
+
+/// Thresholds for [`EvoCoreContextSystem::context_census`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CensusConfig {
+    min_samples: usize,
+    converged_spread: f64,
+    stale_after: Duration,
+}
+
+impl CensusConfig {
+    /// Warm at 10 experiences, converged at a spread of 0.1, stale after a day
+    pub fn new() -> Self {
+        Self {
+            min_samples: 10,
+            converged_spread: 0.1,
+            stale_after: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    /// Experiences that make a context warm (default 10)
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    /// Largest standard deviation, as a fraction of the bounds or observed range, of a converged parameter (default 0.1)
+    pub fn with_converged_spread(mut self, spread: f64) -> Self {
+        self.converged_spread = spread.max(0.0);
+        self
+    }
+
+    /// Time without learning after which a context is stale (default one day)
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+}
+
+impl Default for CensusConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How many contexts are in each tier
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContextCensus {
+    pub total: usize,
+    /// Contexts with at least `min_samples` experiences
+    pub warm: usize,
+    pub converged: usize,
+    pub stale: usize,
+}
+
+impl ContextCensus {
+    /// Contexts with fewer than `min_samples` experiences
+    pub fn cold(&self) -> usize {
+        self.total - self.warm
+    }
+}
+
+impl fmt::Display for ContextCensus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} contexts: {} cold, {} warm, {} converged, {} stale",
+            self.total,
+            self.cold(),
+            self.warm,
+            self.converged,
+            self.stale
+        )
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Count contexts by tier
+    pub fn context_census(&self, config: &CensusConfig) -> ContextCensus {
+        let now = unix_now();
+        let stale_after = i64::try_from(config.stale_after.as_secs()).unwrap_or(i64::MAX);
+        let ranges: Vec<Option<f64>> = (0..self.param_count).map(|i| self.bounds(i).map(|b| b.max - b.min)).collect();
+        let mut census = ContextCensus::default();
+
+        for key in self.context_keys() {
+            let Ok(c_key) = CString::new(key) else {
+                continue;
+            };
+            unsafe {
+                let mut stats = std::ptr::null_mut();
+                if !evocore_context_get_stats_key(self.inner.as_ptr(), c_key.as_ptr(), &mut stats) || stats.is_null() {
+                    continue;
+                }
+                let stats = &*stats;
+                census.total += 1;
+                if now.saturating_sub(stats.last_update as i64) >= stale_after {
+                    census.stale += 1;
+                }
+                if stats.total_experiences < config.min_samples {
+                    continue;
+                }
+                census.warm += 1;
+
+                let params = if stats.stats.is_null() || (*stats.stats).stats.is_null() {
+                    &[][..]
+                } else {
+                    std::slice::from_raw_parts((*stats.stats).stats, (*stats.stats).count)
+                };
+                let converged = params.iter().zip(&ranges).all(|(p, range)| {
+                    let std = if p.count < 2 { 0.0 } else { p.variance.max(0.0).sqrt() };
+                    std <= config.converged_spread * range.unwrap_or(p.max_value - p.min_value)
+                });
+                if converged {
+                    census.converged += 1;
+                }
+            }
+        }
+        census
+    }
+
+    /// Count contexts by tier, with differential-privacy noise on every count
+    ///
+    /// Spends `epsilon` from `budget`. One observation moves each count by
+    /// at most one, so each gets Laplace noise for a sensitivity of 1 and a
+    /// quarter of `epsilon`. Noisy counts are rounded and kept consistent:
+    /// no tier exceeds the total, and converged never exceeds warm.
+    pub fn context_census_private(
+        &self,
+        config: &CensusConfig,
+        budget: &mut PrivacyBudget,
+        epsilon: f64,
+    ) -> Result<ContextCensus, String> {
+        let exact = self.context_census(config);
+        budget.spend(epsilon)?;
+        let mut noisy = |count: usize| budget.laplace(count as f64, 1.0, epsilon / 4.0).round().max(0.0) as usize;
+        let total = noisy(exact.total);
+        let warm = noisy(exact.warm).min(total);
+        let converged = noisy(exact.converged).min(warm);
+        let stale = noisy(exact.stale).min(total);
+        Ok(ContextCensus {
+            total,
+            warm,
+            converged,
+            stale,
+        })
+    }
+}
@@ -0,0 +1,175 @@
+//! Fault injection for chaos testing
+//!
+//! [`FaultInjector`] wraps any [`ContextLearner`] and adds configurable
+//! latency and random failures to its calls, so fallback paths can be
+//! exercised against the real backend in staging.
+
+use evocore_core::SeedStream;
+use crate::{ContextLearner, ContextState};
+use rand::Rng;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Latency and failure settings for one kind of operation
+#[derive(Debug, Clone, Copy, Default)]
+struct Faults {
+    failure_rate: f64,
+    latency: Duration,
+    jitter: Duration,
+}
+
+/// A [`ContextLearner`] decorator that injects latency and failures
+///
+/// Only `learn` and `sample` are affected; introspection calls
+/// (`context_count`, `context_state`, ...) always pass straight through.
+/// Injected failures return `Err` without touching the inner learner.
+pub struct FaultInjector<L> {
+    inner: L,
+    learn: Faults,
+    sample: Faults,
+    enabled: AtomicBool,
+    seeds: SeedStream,
+    injected_failures: AtomicU64,
+}
+
+impl<L: ContextLearner> FaultInjector<L> {
+    /// Wrap `inner` with no faults configured
+    pub fn new(inner: L) -> Self {
+        Self {
+            inner,
+            learn: Faults::default(),
+            sample: Faults::default(),
+            enabled: AtomicBool::new(true),
+            seeds: SeedStream::new(rand::random()),
+            injected_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Fail `learn` and `sample` calls with this probability (0.0 - 1.0)
+    pub fn with_failure_rate(self, rate: f64) -> Self {
+        self.with_learn_failure_rate(rate).with_sample_failure_rate(rate)
+    }
+
+    /// Fail `learn` calls with this probability (0.0 - 1.0)
+    pub fn with_learn_failure_rate(mut self, rate: f64) -> Self {
+        self.learn.failure_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Fail `sample` calls with this probability (0.0 - 1.0)
+    pub fn with_sample_failure_rate(mut self, rate: f64) -> Self {
+        self.sample.failure_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Delay `learn` and `sample` calls by `latency` plus up to `jitter`
+    pub fn with_latency(self, latency: Duration, jitter: Duration) -> Self {
+        self.with_learn_latency(latency, jitter)
+            .with_sample_latency(latency, jitter)
+    }
+
+    /// Delay `learn` calls by `latency` plus up to `jitter`
+    pub fn with_learn_latency(mut self, latency: Duration, jitter: Duration) -> Self {
+        self.learn.latency = latency;
+        self.learn.jitter = jitter;
+        self
+    }
+
+    /// Delay `sample` calls by `latency` plus up to `jitter`
+    pub fn with_sample_latency(mut self, latency: Duration, jitter: Duration) -> Self {
+        self.sample.latency = latency;
+        self.sample.jitter = jitter;
+        self
+    }
+
+    /// Seed the fault decisions so a chaos run can be replayed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seeds = SeedStream::new(seed);
+        self
+    }
+
+    /// Turn fault injection on or off without rebuilding the wrapper
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether faults are currently being injected
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Number of failures injected so far
+    pub fn injected_failures(&self) -> u64 {
+        self.injected_failures.load(Ordering::Relaxed)
+    }
+
+    /// Borrow the wrapped learner
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    /// Mutably borrow the wrapped learner
+    pub fn inner_mut(&mut self) -> &mut L {
+        &mut self.inner
+    }
+
+    /// Unwrap the wrapped learner
+    pub fn into_inner(self) -> L {
+        self.inner
+    }
+
+    /// Apply the configured delay, then decide whether this call fails
+    fn inject(&self, faults: &Faults, operation: &str) -> Result<(), String> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let mut rng = self.seeds.rng();
+        let mut delay = faults.latency;
+        if !faults.jitter.is_zero() {
+            delay += faults.jitter.mul_f64(rng.gen::<f64>());
+        }
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+
+        if faults.failure_rate > 0.0 && rng.gen::<f64>() < faults.failure_rate {
+            self.injected_failures.fetch_add(1, Ordering::Relaxed);
+            return Err(format!("Injected failure in {}", operation));
+        }
+        Ok(())
+    }
+}
+
+impl<L: ContextLearner> ContextLearner for FaultInjector<L> {
+    fn learn(
+        &mut self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+    ) -> Result<(), String> {
+        self.inject(&self.learn, "learn")?;
+        self.inner.learn(dimension_values, parameters, fitness)
+    }
+
+    fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        self.inject(&self.sample, "sample")?;
+        self.inner.sample(dimension_values, exploration)
+    }
+
+    fn param_count(&self) -> usize {
+        self.inner.param_count()
+    }
+
+    fn context_count(&self) -> usize {
+        self.inner.context_count()
+    }
+
+    fn context_keys(&self) -> Vec<String> {
+        self.inner.context_keys()
+    }
+
+    fn context_state(&self, key: &str) -> Option<ContextState> {
+        self.inner.context_state(key)
+    }
+}
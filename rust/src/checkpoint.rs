@@ -0,0 +1,648 @@
+//! Validating checkpoint loader
+//!
+//! Parses saved systems (the C library's JSON and binary formats) in Rust
+//! before anything is handed to the C library, so a bad file produces a
+//! precise [`LoadError`] instead of a bare "Failed to load".
+
+use crate::decay::unix_now;
+use crate::serializer::unwrap_envelope;
+use crate::{ContextState, EvoCoreContextSystem, ParamStats};
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+
+/// Magic bytes at the start of a binary checkpoint
+const BINARY_MAGIC: &[u8; 4] = b"EVCX";
+/// Binary format version understood by this loader
+const BINARY_VERSION: u32 = 1;
+/// Marks the optional stable-slot section after the contexts
+///
+/// The C loader stops after the contexts, so older readers ignore it.
+const STABLE_MAGIC: &[u8; 4] = b"STBL";
+
+/// Why a checkpoint could not be loaded
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    /// The file could not be read
+    Io(String),
+    /// The file ended early
+    Truncated { offset: usize, expected: &'static str },
+    /// The file is not a JSON or binary checkpoint
+    BadMagic,
+    /// The binary format version is not supported
+    UnsupportedVersion(u32),
+    /// The file is syntactically or structurally invalid
+    Malformed { location: String, message: String },
+    /// A context key does not have one value per dimension
+    DimensionCountMismatch { context: String, expected: usize, found: usize },
+    /// A context stores a different number of parameters than the system
+    ParamCountMismatch { context: String, expected: usize, found: usize },
+    /// A context holds a NaN or infinite statistic
+    NonFinite { context: String, field: &'static str },
+    /// The C library rejected the validated data
+    Create(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "I/O error: {}", e),
+            LoadError::Truncated { offset, expected } => {
+                write!(f, "File truncated at byte {} while reading {}", offset, expected)
+            }
+            LoadError::BadMagic => write!(f, "Not an EvoCore checkpoint"),
+            LoadError::UnsupportedVersion(v) => write!(f, "Unsupported binary version: {}", v),
+            LoadError::Malformed { location, message } => {
+                write!(f, "Malformed checkpoint at {}: {}", location, message)
+            }
+            LoadError::DimensionCountMismatch { context, expected, found } => write!(
+                f,
+                "Context {:?} has {} dimension values, expected {}",
+                context, found, expected
+            ),
+            LoadError::ParamCountMismatch { context, expected, found } => write!(
+                f,
+                "Context {:?} has {} parameters, expected {}",
+                context, found, expected
+            ),
+            LoadError::NonFinite { context, field } => {
+                write!(f, "Context {:?} has non-finite {}", context, field)
+            }
+            LoadError::Create(e) => write!(f, "Failed to create context system: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Predicate over `(dimensions, context)` used while reading a checkpoint
+pub(crate) type ContextFilter<'a> = dyn FnMut(&[(String, Vec<String>)], &ContextState) -> bool + 'a;
+
+/// A parsed, not yet validated, saved system
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// Dimension definitions as `(name, values)` pairs
+    pub dimensions: Vec<(String, Vec<String>)>,
+    /// Parameters tracked per context
+    pub param_count: usize,
+    /// Learned contexts
+    pub contexts: Vec<ContextState>,
+    /// Stable slots of promoted contexts (see [`EvoCoreContextSystem::promote`])
+    pub stable: Vec<ContextState>,
+}
+
+impl Checkpoint {
+    /// Parse a JSON or binary checkpoint (detected from its first bytes)
+    ///
+    /// In strict mode parsing stops at the first error. In lenient mode a
+    /// truncated context section keeps the contexts read so far; the
+    /// truncation is returned alongside the checkpoint.
+    fn read(path: &Path, lenient: bool) -> Result<(Self, Option<LoadError>), LoadError> {
+        let data = std::fs::read(path).map_err(|e| LoadError::Io(e.to_string()))?;
+        Self::parse(&unwrap_envelope(data)?, lenient)
+    }
+
+    fn parse(data: &[u8], lenient: bool) -> Result<(Self, Option<LoadError>), LoadError> {
+        if data.starts_with(BINARY_MAGIC) {
+            parse_binary(data, lenient)
+        } else if data.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{') {
+            parse_json(data).map(|cp| (cp, None))
+        } else if data.len() < BINARY_MAGIC.len() {
+            Err(LoadError::Truncated { offset: data.len(), expected: "header" })
+        } else {
+            Err(LoadError::BadMagic)
+        }
+    }
+
+    /// Read a checkpoint file keeping only the contexts (and stable slots) `keep` accepts
+    ///
+    /// Contexts are not validated.
+    pub(crate) fn read_filtered(path: &Path, keep: &mut ContextFilter<'_>) -> Result<Self, LoadError> {
+        let data = unwrap_envelope(std::fs::read(path).map_err(|e| LoadError::Io(e.to_string()))?)?;
+        if data.starts_with(BINARY_MAGIC) {
+            return parse_binary_filtered(&data, false, keep).map(|(checkpoint, _)| checkpoint);
+        }
+        let (mut checkpoint, _) = Self::parse(&data, false)?;
+        let dimensions = checkpoint.dimensions.clone();
+        checkpoint.contexts.retain(|state| keep(&dimensions, state));
+        checkpoint.stable.retain(|state| keep(&dimensions, state));
+        Ok(checkpoint)
+    }
+
+    /// Parse and fully validate a checkpoint file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+        let (checkpoint, _) = Self::read(path.as_ref(), false)?;
+        for state in checkpoint.contexts.iter().chain(&checkpoint.stable) {
+            checkpoint.validate_context(state)?;
+        }
+        Ok(checkpoint)
+    }
+
+    /// Parse and fully validate an in-memory checkpoint
+    pub fn from_bytes(data: &[u8]) -> Result<Self, LoadError> {
+        let (checkpoint, _) = Self::parse(data, false)?;
+        for state in checkpoint.contexts.iter().chain(&checkpoint.stable) {
+            checkpoint.validate_context(state)?;
+        }
+        Ok(checkpoint)
+    }
+
+    /// Encode in the C library's binary format
+    pub fn to_binary(&self) -> Vec<u8> {
+        fn string(out: &mut Vec<u8>, s: &str) {
+            out.extend_from_slice(&(s.len() as u32).to_be_bytes());
+            out.extend_from_slice(s.as_bytes());
+        }
+
+        fn context(out: &mut Vec<u8>, state: &ContextState) {
+            string(out, &state.key);
+            out.extend_from_slice(&(state.params.len() as u32).to_be_bytes());
+            out.extend_from_slice(&(state.total_experiences as u32).to_be_bytes());
+            out.extend_from_slice(&state.confidence.to_ne_bytes());
+            out.extend_from_slice(&state.avg_fitness.to_ne_bytes());
+            out.extend_from_slice(&state.best_fitness.to_ne_bytes());
+            out.extend_from_slice(&(state.first_update as u64).to_be_bytes());
+            out.extend_from_slice(&(state.last_update as u64).to_be_bytes());
+            for p in &state.params {
+                out.extend_from_slice(&p.mean.to_ne_bytes());
+                out.extend_from_slice(&p.variance.to_ne_bytes());
+                out.extend_from_slice(&p.sum_weights.to_ne_bytes());
+                out.extend_from_slice(&(p.count as u32).to_be_bytes());
+            }
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(BINARY_MAGIC);
+        out.extend_from_slice(&BINARY_VERSION.to_be_bytes());
+        out.extend_from_slice(&(self.dimensions.len() as u32).to_be_bytes());
+        out.extend_from_slice(&(self.param_count as u32).to_be_bytes());
+
+        for (name, values) in &self.dimensions {
+            string(&mut out, name);
+            out.extend_from_slice(&(values.len() as u32).to_be_bytes());
+            for value in values {
+                string(&mut out, value);
+            }
+        }
+
+        out.extend_from_slice(&(self.contexts.len() as u32).to_be_bytes());
+        for state in &self.contexts {
+            context(&mut out, state);
+        }
+
+        if !self.stable.is_empty() {
+            out.extend_from_slice(STABLE_MAGIC);
+            out.extend_from_slice(&(self.stable.len() as u32).to_be_bytes());
+            for state in &self.stable {
+                context(&mut out, state);
+            }
+        }
+
+        out
+    }
+
+    /// Check one context against the checkpoint's dimensions and parameter count
+    ///
+    /// Any value is accepted in every dimension, declared or not, since
+    /// `learn()` accepts them too; only the number of values is checked.
+    pub fn validate_context(&self, state: &ContextState) -> Result<(), LoadError> {
+        let values: Vec<&str> = state.key.split(':').collect();
+        if values.len() != self.dimensions.len() {
+            return Err(LoadError::DimensionCountMismatch {
+                context: state.key.clone(),
+                expected: self.dimensions.len(),
+                found: values.len(),
+            });
+        }
+
+        if state.params.len() != self.param_count {
+            return Err(LoadError::ParamCountMismatch {
+                context: state.key.clone(),
+                expected: self.param_count,
+                found: state.params.len(),
+            });
+        }
+
+        let non_finite = |field| LoadError::NonFinite { context: state.key.clone(), field };
+        if !state.confidence.is_finite() {
+            return Err(non_finite("confidence"));
+        }
+        if !state.avg_fitness.is_finite() {
+            return Err(non_finite("avg_fitness"));
+        }
+        if !state.best_fitness.is_finite() {
+            return Err(non_finite("best_fitness"));
+        }
+        for p in &state.params {
+            if !p.mean.is_finite() {
+                return Err(non_finite("parameter mean"));
+            }
+            if !p.variance.is_finite() {
+                return Err(non_finite("parameter variance"));
+            }
+            if !p.sum_weights.is_finite() {
+                return Err(non_finite("parameter weight"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build a live system holding every context in this checkpoint
+    pub fn into_system(self) -> Result<EvoCoreContextSystem, LoadError> {
+        let names: Vec<&str> = self.dimensions.iter().map(|(n, _)| n.as_str()).collect();
+        let values: Vec<Vec<&str>> = self
+            .dimensions
+            .iter()
+            .map(|(_, vals)| vals.iter().map(String::as_str).collect())
+            .collect();
+
+        let mut system =
+            EvoCoreContextSystem::new(&names, &values, self.param_count).map_err(LoadError::Create)?;
+        for state in &self.contexts {
+            system.restore_context_state(state).map_err(LoadError::Create)?;
+        }
+        for state in &self.stable {
+            system.restore_stable_state(state).map_err(LoadError::Create)?;
+        }
+
+        Ok(system)
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Load a saved system, validating every byte before building it
+    ///
+    /// Accepts both the JSON and binary formats.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", name = "evocore.load", skip_all, fields(path = %filepath.as_ref().display()), err(Display))
+    )]
+    pub fn load_validated<P: AsRef<Path>>(filepath: P) -> Result<Self, LoadError> {
+        Checkpoint::from_file(filepath)?.into_system()
+    }
+
+    /// Load a saved system, skipping contexts that fail validation
+    ///
+    /// Returns the system together with one error per skipped context (and a
+    /// trailing [`LoadError::Truncated`] if the file ended mid-context).
+    /// Errors in the header or dimension table are still fatal.
+    pub fn load_lenient<P: AsRef<Path>>(filepath: P) -> Result<(Self, Vec<LoadError>), LoadError> {
+        let (mut checkpoint, truncation) = Checkpoint::read(filepath.as_ref(), true)?;
+
+        let mut errors = Vec::new();
+        let contexts = std::mem::take(&mut checkpoint.contexts);
+        for state in contexts {
+            match checkpoint.validate_context(&state) {
+                Ok(()) => checkpoint.contexts.push(state),
+                Err(e) => errors.push(e),
+            }
+        }
+        let stable = std::mem::take(&mut checkpoint.stable);
+        for state in stable {
+            match checkpoint.validate_context(&state) {
+                Ok(()) => checkpoint.stable.push(state),
+                Err(e) => errors.push(e),
+            }
+        }
+        errors.extend(truncation);
+
+        Ok((checkpoint.into_system()?, errors))
+    }
+
+    /// Capture the full learned state as a [`Checkpoint`]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            dimensions: self.dimensions(),
+            param_count: self.param_count(),
+            contexts: self.context_states(),
+            stable: self.stable_states(),
+        }
+    }
+
+    /// Replace every context and stable slot with those of `checkpoint`
+    ///
+    /// Unlike [`into_system`](Checkpoint::into_system) this keeps the
+    /// system's configuration (bounds, overrides, strategy, observer,
+    /// limits, seed). The checkpoint must have the same dimension names and
+    /// parameter count, and every context must validate against it.
+    pub(crate) fn replace_learned_state(&mut self, checkpoint: &Checkpoint) -> Result<(), String> {
+        if checkpoint.param_count != self.param_count {
+            return Err(format!(
+                "Parameter count mismatch: expected {}, got {}",
+                self.param_count, checkpoint.param_count
+            ));
+        }
+        let names: Vec<String> = self.dimensions().into_iter().map(|(name, _)| name).collect();
+        if checkpoint.dimensions.iter().map(|(name, _)| name).ne(names.iter()) {
+            return Err(format!("Dimension mismatch: expected {:?}", names));
+        }
+        for state in checkpoint.contexts.iter().chain(&checkpoint.stable) {
+            checkpoint.validate_context(state).map_err(|e| e.to_string())?;
+        }
+
+        self.reset_all();
+        self.stable_slots.clear();
+        for state in &checkpoint.contexts {
+            self.restore_context_state(state)?;
+        }
+        for state in &checkpoint.stable {
+            self.restore_stable_state(state)?;
+        }
+        Ok(())
+    }
+}
+
+/// Cursor over a binary checkpoint
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize, expected: &'static str) -> Result<&'a [u8], LoadError> {
+        if self.data.len() - self.offset < n {
+            return Err(LoadError::Truncated { offset: self.data.len(), expected });
+        }
+        let bytes = &self.data[self.offset..self.offset + n];
+        self.offset += n;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self, expected: &'static str) -> Result<u32, LoadError> {
+        Ok(u32::from_be_bytes(self.take(4, expected)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self, expected: &'static str) -> Result<u64, LoadError> {
+        Ok(u64::from_be_bytes(self.take(8, expected)?.try_into().unwrap()))
+    }
+
+    // Doubles are written in native byte order by the C library
+    fn f64(&mut self, expected: &'static str) -> Result<f64, LoadError> {
+        Ok(f64::from_ne_bytes(self.take(8, expected)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self, expected: &'static str) -> Result<String, LoadError> {
+        let len = self.u32(expected)? as usize;
+        let at = self.offset;
+        let bytes = self.take(len, expected)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| LoadError::Malformed {
+            location: format!("byte {}", at),
+            message: format!("{} is not valid UTF-8", expected),
+        })
+    }
+}
+
+pub(crate) fn parse_binary(data: &[u8], lenient: bool) -> Result<(Checkpoint, Option<LoadError>), LoadError> {
+    parse_binary_filtered(data, lenient, &mut |_, _| true)
+}
+
+/// Parse a binary checkpoint, keeping only the contexts `keep` accepts
+///
+/// `keep` also receives the checkpoint's dimensions. Rejected contexts are
+/// dropped as they are read, so they never accumulate in memory.
+fn parse_binary_filtered(
+    data: &[u8],
+    lenient: bool,
+    keep: &mut ContextFilter<'_>,
+) -> Result<(Checkpoint, Option<LoadError>), LoadError> {
+    let mut r = Reader { data, offset: BINARY_MAGIC.len() };
+
+    let version = r.u32("version")?;
+    if version != BINARY_VERSION {
+        return Err(LoadError::UnsupportedVersion(version));
+    }
+
+    let dim_count = r.u32("dimension count")? as usize;
+    let param_count = r.u32("parameter count")? as usize;
+
+    let mut dimensions = Vec::with_capacity(dim_count.min(1024));
+    for _ in 0..dim_count {
+        let name = r.string("dimension name")?;
+        let value_count = r.u32("dimension value count")? as usize;
+        let mut values = Vec::with_capacity(value_count.min(1024));
+        for _ in 0..value_count {
+            values.push(r.string("dimension value")?);
+        }
+        dimensions.push((name, values));
+    }
+
+    let context_count = r.u32("context count")? as usize;
+    let mut checkpoint = Checkpoint {
+        dimensions,
+        param_count,
+        contexts: Vec::with_capacity(context_count.min(1 << 16)),
+        stable: Vec::new(),
+    };
+
+    for _ in 0..context_count {
+        match read_binary_context(&mut r) {
+            Ok(state) if keep(&checkpoint.dimensions, &state) => checkpoint.contexts.push(state),
+            Ok(_) => {}
+            Err(e @ LoadError::Truncated { .. }) if lenient => return Ok((checkpoint, Some(e))),
+            Err(e) => return Err(e),
+        }
+    }
+
+    if r.offset < r.data.len() {
+        if r.take(STABLE_MAGIC.len(), "stable section")? != STABLE_MAGIC {
+            return Err(LoadError::Malformed {
+                location: format!("byte {}", r.offset - STABLE_MAGIC.len()),
+                message: "unexpected data after contexts".to_string(),
+            });
+        }
+        let stable_count = r.u32("stable count")? as usize;
+        for _ in 0..stable_count {
+            match read_binary_context(&mut r) {
+                Ok(state) if keep(&checkpoint.dimensions, &state) => checkpoint.stable.push(state),
+                Ok(_) => {}
+                Err(e @ LoadError::Truncated { .. }) if lenient => return Ok((checkpoint, Some(e))),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    Ok((checkpoint, None))
+}
+
+fn read_binary_context(r: &mut Reader<'_>) -> Result<ContextState, LoadError> {
+    let key = r.string("context key")?;
+    let param_count = r.u32("context parameter count")? as usize;
+    let total_experiences = r.u32("context experiences")? as usize;
+    let confidence = r.f64("context confidence")?;
+    let avg_fitness = r.f64("context average fitness")?;
+    let best_fitness = r.f64("context best fitness")?;
+    let first_update = r.u64("context first update")? as i64;
+    let last_update = r.u64("context last update")? as i64;
+
+    let mut params = Vec::with_capacity(param_count.min(1024));
+    for _ in 0..param_count {
+        let mean = r.f64("parameter mean")?;
+        let variance = r.f64("parameter variance")?;
+        let sum_weights = r.f64("parameter weight")?;
+        let count = r.u32("parameter count")? as usize;
+        params.push(ParamStats {
+            mean,
+            variance,
+            sum_weights,
+            m2: variance * sum_weights,
+            count,
+            ..ParamStats::default()
+        });
+    }
+
+    Ok(ContextState {
+        key,
+        total_experiences,
+        confidence,
+        avg_fitness,
+        best_fitness,
+        first_update,
+        last_update,
+        params,
+    })
+}
+
+pub(crate) fn parse_json(data: &[u8]) -> Result<Checkpoint, LoadError> {
+    let root: Value = serde_json::from_slice(data).map_err(|e| {
+        if e.is_eof() {
+            LoadError::Truncated { offset: data.len(), expected: "JSON document" }
+        } else {
+            LoadError::Malformed {
+                location: format!("line {}, column {}", e.line(), e.column()),
+                message: e.to_string(),
+            }
+        }
+    })?;
+
+    let malformed = |location: &str, message: &str| LoadError::Malformed {
+        location: location.to_string(),
+        message: message.to_string(),
+    };
+
+    let param_count = root
+        .get("param_count")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| malformed("param_count", "missing or not an integer"))? as usize;
+
+    let mut dimensions = Vec::new();
+    let dims = root
+        .get("dimensions")
+        .and_then(Value::as_array)
+        .ok_or_else(|| malformed("dimensions", "missing or not an array"))?;
+    for (i, dim) in dims.iter().enumerate() {
+        let location = format!("dimensions[{}]", i);
+        let name = dim
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| malformed(&location, "missing name"))?;
+        let values = dim
+            .get("values")
+            .and_then(Value::as_array)
+            .ok_or_else(|| malformed(&location, "missing values"))?
+            .iter()
+            .map(|v| v.as_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| malformed(&location, "values must be strings"))?;
+        dimensions.push((name.to_string(), values));
+    }
+
+    let contexts = root
+        .get("contexts")
+        .and_then(Value::as_object)
+        .ok_or_else(|| malformed("contexts", "missing or not an object"))?;
+    let contexts = json_contexts(contexts, "contexts")?;
+
+    let stable = match root.get("stable") {
+        None => Vec::new(),
+        Some(stable) => {
+            let stable = stable
+                .as_object()
+                .ok_or_else(|| malformed("stable", "not an object"))?;
+            json_contexts(stable, "stable")?
+        }
+    };
+
+    Ok(Checkpoint { dimensions, param_count, contexts, stable })
+}
+
+fn json_contexts(
+    contexts: &serde_json::Map<String, Value>,
+    section: &str,
+) -> Result<Vec<ContextState>, LoadError> {
+    let malformed = |location: &str, message: &str| LoadError::Malformed {
+        location: location.to_string(),
+        message: message.to_string(),
+    };
+    let loaded_at = unix_now();
+
+    let mut states = Vec::with_capacity(contexts.len());
+    for (key, ctx) in contexts {
+        let location = format!("{}[{:?}]", section, key);
+        let number = |field: &str| {
+            ctx.get(field)
+                .and_then(Value::as_f64)
+                .ok_or_else(|| malformed(&location, &format!("missing {}", field)))
+        };
+        let list = |field: &str| {
+            ctx.get(field)
+                .and_then(Value::as_array)
+                .and_then(|a| a.iter().map(Value::as_f64).collect::<Option<Vec<_>>>())
+                .ok_or_else(|| malformed(&location, &format!("missing {}", field)))
+        };
+
+        let total_experiences = number("total_experiences")? as usize;
+        let avg_fitness = number("avg_fitness")?;
+        let means = list("means")?;
+        let stds = list("stds")?;
+        if means.len() != stds.len() {
+            return Err(malformed(&location, "means and stds differ in length"));
+        }
+
+        // Files written before weights were saved get them approximated from
+        // the context totals
+        let weights = match ctx.get("sum_weights") {
+            Some(_) => list("sum_weights")?,
+            None => vec![total_experiences as f64 * avg_fitness.max(0.0001); means.len()],
+        };
+        if weights.len() != means.len() {
+            return Err(malformed(&location, "means and sum_weights differ in length"));
+        }
+        let params = means
+            .iter()
+            .zip(&stds)
+            .zip(&weights)
+            .map(|((&mean, &std), &sum_weights)| ParamStats {
+                mean,
+                variance: std * std,
+                sum_weights,
+                m2: std * std * sum_weights,
+                count: total_experiences,
+                ..ParamStats::default()
+            })
+            .collect();
+
+        // Likewise timestamps: a context loaded from such a file counts as seen now
+        let timestamp = |field: &str| match ctx.get(field) {
+            Some(value) => value
+                .as_i64()
+                .ok_or_else(|| malformed(&location, &format!("{} is not an integer", field))),
+            None => Ok(loaded_at),
+        };
+        let first_update = timestamp("first_update")?;
+        let last_update = timestamp("last_update")?;
+
+        states.push(ContextState {
+            key: key.clone(),
+            total_experiences,
+            confidence: number("confidence")?,
+            avg_fitness,
+            best_fitness: number("best_fitness")?,
+            first_update,
+            last_update,
+            params,
+        });
+    }
+
+    Ok(states)
+}
@@ -0,0 +1,272 @@
+//! CMA-ES sampling strategy
+//!
+//! The default sampler keeps an independent Gaussian per parameter, so it
+//! cannot follow a ridge that runs diagonally across two parameters.
+//! [`CmaEs`] keeps a full covariance matrix per context, together with a
+//! mean and a global step size, and adapts them with the covariance matrix
+//! adaptation evolution strategy: `sample()` draws
+//! `mean + step_size * N(0, C)`, and every `learn()` adds the example to
+//! the current generation. Once a generation holds `lambda` examples the
+//! best `mu` of them move the mean, reshape the covariance along the
+//! directions that worked, and grow or shrink the step size.
+//!
+//! Samples are clamped to each parameter's [bounds](crate::ParamBounds),
+//! or `[0, 1]` without bounds; the initial step size is relative to that
+//! range. A context's search starts from its learned means if it has data
+//! when first sampled, otherwise from its first learned example. The
+//! caller's `exploration` is ignored: the step size plays that role, scaled
+//! by the sampling temperature. Search state is held by the strategy and is
+//! not saved in checkpoints.
+
+use crate::bayesopt::{cholesky, forward};
+use crate::rename::move_entry;
+use crate::strategy::gaussian;
+use crate::{SamplingStrategy, StrategyInput};
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+/// Covariance matrix adaptation per context
+#[derive(Debug)]
+pub struct CmaEs {
+    initial_step_size: f64,
+    population: Option<usize>,
+    contexts: Mutex<HashMap<String, CmaState>>,
+}
+
+impl Default for CmaEs {
+    fn default() -> Self {
+        Self {
+            initial_step_size: 0.3,
+            population: None,
+            contexts: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl CmaEs {
+    /// Step size 0.3 of each parameter's range, standard population size
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Initial step size, as a fraction of each parameter's range (default 0.3)
+    pub fn with_initial_step_size(mut self, step_size: f64) -> Self {
+        self.initial_step_size = step_size.max(1e-9);
+        self
+    }
+
+    /// Examples per generation, `lambda` (default `4 + 3 ln n` for `n` parameters)
+    pub fn with_population(mut self, lambda: usize) -> Self {
+        self.population = Some(lambda.max(2));
+        self
+    }
+
+    /// Current search mean for `key`
+    pub fn mean(&self, key: &str) -> Option<Vec<f64>> {
+        self.lock().get(key).map(|s| s.mean.clone())
+    }
+
+    /// Current step size for `key`
+    pub fn step_size(&self, key: &str) -> Option<f64> {
+        self.lock().get(key).map(|s| s.sigma)
+    }
+
+    /// Completed generations for `key`
+    pub fn generation(&self, key: &str) -> Option<usize> {
+        self.lock().get(key).map(|s| s.generation)
+    }
+
+    /// Forget every context's search state
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CmaState>> {
+        self.contexts.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn start(&self, mean: Vec<f64>, ranges: &[f64]) -> CmaState {
+        CmaState::new(mean, ranges, self.initial_step_size, self.population)
+    }
+}
+
+impl SamplingStrategy for CmaEs {
+    fn name(&self) -> &str {
+        "cma-es"
+    }
+
+    fn sample(&self, input: &StrategyInput<'_>, out: &mut [f64]) -> Result<(), String> {
+        let domain: Vec<(f64, f64)> = (0..out.len())
+            .map(|i| input.bounds(i).map_or((0.0, 1.0), |b| (b.min, b.max)))
+            .collect();
+        let mut contexts = self.lock();
+        let state = match contexts.get_mut(input.key()) {
+            Some(state) => state,
+            None => {
+                let mean = match input.state().filter(|s| s.total_experiences > 0) {
+                    Some(learned) => learned.params.iter().map(|p| p.mean).collect(),
+                    None => domain.iter().map(|(lo, hi)| (lo + hi) / 2.0).collect(),
+                };
+                let ranges: Vec<f64> = domain.iter().map(|(lo, hi)| hi - lo).collect();
+                contexts.entry(input.key().to_string()).or_insert(self.start(mean, &ranges))
+            }
+        };
+
+        let mut rng = input.rng();
+        let z: Vec<f64> = (0..out.len()).map(|_| gaussian(&mut rng)).collect();
+        for (i, (value, &(lo, hi))) in out.iter_mut().zip(&domain).enumerate() {
+            let step: f64 = (0..=i).map(|k| state.chol[i][k] * z[k]).sum();
+            *value = (state.mean[i] + input.temperature() * state.sigma * step).clamp(lo, hi);
+        }
+        Ok(())
+    }
+
+    fn observe(&self, key: &str, parameters: &[f64], fitness: f64) {
+        if !fitness.is_finite() || parameters.iter().any(|p| !p.is_finite()) {
+            return;
+        }
+        let mut contexts = self.lock();
+        let state = contexts
+            .entry(key.to_string())
+            .or_insert_with(|| self.start(parameters.to_vec(), &vec![1.0; parameters.len()]));
+        if state.mean.len() != parameters.len() {
+            return;
+        }
+        state.pending.push((parameters.to_vec(), fitness));
+        if state.pending.len() >= state.lambda {
+            state.update();
+        }
+    }
+
+    fn forget(&self, key: &str) {
+        self.lock().remove(key);
+    }
+
+    fn rename_key(&self, from: &str, to: &str) {
+        move_entry(&mut self.lock(), from, to);
+    }
+}
+
+/// Search state of one context
+#[derive(Debug, Clone)]
+struct CmaState {
+    mean: Vec<f64>,
+    sigma: f64,
+    cov: Vec<Vec<f64>>,
+    /// Lower Cholesky factor of `cov`
+    chol: Vec<Vec<f64>>,
+    path_c: Vec<f64>,
+    path_sigma: Vec<f64>,
+    lambda: usize,
+    weights: Vec<f64>,
+    mueff: f64,
+    generation: usize,
+    pending: Vec<(Vec<f64>, f64)>,
+}
+
+impl CmaState {
+    fn new(mean: Vec<f64>, ranges: &[f64], step_size: f64, population: Option<usize>) -> Self {
+        let n = mean.len();
+        let lambda = population.unwrap_or(4 + (3.0 * (n.max(1) as f64).ln()) as usize);
+        let mu = lambda / 2;
+        let raw: Vec<f64> = (1..=mu).map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln()).collect();
+        let total: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|w| w / total).collect();
+        let mueff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let cov: Vec<Vec<f64>> = (0..n)
+            .map(|i| (0..n).map(|j| if i == j { ranges[i].max(1e-12).powi(2) } else { 0.0 }).collect())
+            .collect();
+        let chol = cholesky(&cov).unwrap_or_else(|| identity(n));
+        Self {
+            mean,
+            sigma: step_size,
+            cov,
+            chol,
+            path_c: vec![0.0; n],
+            path_sigma: vec![0.0; n],
+            lambda,
+            weights,
+            mueff,
+            generation: 0,
+            pending: Vec::with_capacity(lambda),
+        }
+    }
+
+    /// One CMA-ES generation from the pending examples
+    fn update(&mut self) {
+        let n = self.mean.len();
+        let nf = n as f64;
+        let mueff = self.mueff;
+        let cc = (4.0 + mueff / nf) / (nf + 4.0 + 2.0 * mueff / nf);
+        let cs = (mueff + 2.0) / (nf + mueff + 5.0);
+        let c1 = 2.0 / ((nf + 1.3).powi(2) + mueff);
+        let cmu = (1.0 - c1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((nf + 2.0).powi(2) + mueff));
+        let damps = 1.0 + 2.0 * (((mueff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+        let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let steps: Vec<Vec<f64>> = pending
+            .iter()
+            .take(self.weights.len())
+            .map(|(x, _)| x.iter().zip(&self.mean).map(|(x, m)| (x - m) / self.sigma).collect())
+            .collect();
+        let mut step = vec![0.0; n];
+        for (w, y) in self.weights.iter().zip(&steps) {
+            for (s, v) in step.iter_mut().zip(y) {
+                *s += w * v;
+            }
+        }
+
+        for (m, s) in self.mean.iter_mut().zip(&step) {
+            *m += self.sigma * s;
+        }
+
+        let whitened = forward(&self.chol, &step);
+        let norm_c = (cs * (2.0 - cs) * mueff).sqrt();
+        for (p, w) in self.path_sigma.iter_mut().zip(&whitened) {
+            *p = (1.0 - cs) * *p + norm_c * w;
+        }
+        let ps_norm = self.path_sigma.iter().map(|p| p * p).sum::<f64>().sqrt();
+        let decay = 1.0 - (1.0 - cs).powi(2 * (self.generation as i32 + 1));
+        let hsig = ps_norm / decay.max(1e-12).sqrt() / chi_n < 1.4 + 2.0 / (nf + 1.0);
+        let hsig = if hsig { 1.0 } else { 0.0 };
+
+        let norm_cc = (cc * (2.0 - cc) * mueff).sqrt();
+        for (p, s) in self.path_c.iter_mut().zip(&step) {
+            *p = (1.0 - cc) * *p + hsig * norm_cc * s;
+        }
+
+        let keep = 1.0 - c1 - cmu;
+        let correction = (1.0 - hsig) * cc * (2.0 - cc);
+        for i in 0..n {
+            for j in 0..n {
+                let rank_mu: f64 = self.weights.iter().zip(&steps).map(|(w, y)| w * y[i] * y[j]).sum();
+                self.cov[i][j] = keep * self.cov[i][j]
+                    + c1 * (self.path_c[i] * self.path_c[j] + correction * self.cov[i][j])
+                    + cmu * rank_mu;
+            }
+        }
+        self.sigma *= ((cs / damps) * (ps_norm / chi_n - 1.0)).exp();
+        self.sigma = self.sigma.clamp(1e-12, 1e6);
+
+        match cholesky(&self.cov) {
+            Some(chol) => self.chol = chol,
+            None => {
+                // Numerical breakdown: restart the shape, keep the mean
+                self.cov = identity(n);
+                self.chol = identity(n);
+                self.path_c = vec![0.0; n];
+                self.path_sigma = vec![0.0; n];
+            }
+        }
+        self.generation += 1;
+        pending.clear();
+        self.pending = pending;
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect()
+}
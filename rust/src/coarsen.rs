@@ -0,0 +1,232 @@
+//! Adaptive key-space coarsening under capacity pressure
+//!
+//! Evicting a context for [capacity](EvoCoreContextSystem::with_max_contexts)
+//! throws away everything it learned. With coarsening enabled, once the
+//! context count reaches a high-water mark of the cap, the least recently
+//! used contexts are instead folded into a coarser parent: the context with
+//! its least important dimension replaced by [`WILDCARD`], e.g.
+//! `code:rust:vim` into `code:rust:*`. The parent's statistics are merged
+//! as if it had learned every example of its children, and the children
+//! are removed, until the count is back down to the target. Ordinary
+//! eviction still applies if that is not enough.
+//!
+//! A dimension's importance is how much the learned parameter means differ
+//! between contexts that agree on every other dimension; the dimension
+//! whose values matter least is dropped. Dimensions that cannot be compared
+//! yet are kept, and when none can, the last dimension is dropped first,
+//! matching the [wildcard fallback](EvoCoreContextSystem::with_wildcard_fallback)
+//! order. Sampling a folded context only reaches its parent through that
+//! fallback, so enabling coarsening also enables it (with `min_samples` 1)
+//! unless it is already configured. Pinned contexts, and contexts whose
+//! dropped dimension is already `*`, are never folded.
+
+use crate::{ContextState, EvoCoreContextSystem, WILDCARD};
+use std::collections::HashMap;
+
+/// When and how far to coarsen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoarseningPolicy {
+    high_water: f64,
+    target: f64,
+}
+
+impl CoarseningPolicy {
+    /// Coarsen once the count reaches 90% of the cap, down to 75%
+    pub fn new() -> Self {
+        Self {
+            high_water: 0.9,
+            target: 0.75,
+        }
+    }
+
+    /// Fraction of the cap at which coarsening starts (default 0.9)
+    pub fn with_high_water(mut self, fraction: f64) -> Self {
+        self.high_water = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Fraction of the cap coarsening brings the count down to (default 0.75)
+    pub fn with_target(mut self, fraction: f64) -> Self {
+        self.target = fraction.clamp(0.0, 1.0);
+        self
+    }
+}
+
+impl Default for CoarseningPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What coarsening has done so far
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoarseningStats {
+    /// Times the high-water mark triggered a pass
+    pub passes: u64,
+    /// Contexts folded into a parent
+    pub folded: u64,
+    /// Name of the dimension dropped by the most recent pass
+    pub last_dropped: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Coarsening {
+    policy: CoarseningPolicy,
+    stats: CoarseningStats,
+}
+
+/// Fold `child` into `parent` as if both had been learned together
+pub(crate) fn fold(parent: &mut ContextState, child: &ContextState) {
+    let total = parent.total_experiences + child.total_experiences;
+    if total > 0 {
+        parent.avg_fitness = (parent.avg_fitness * parent.total_experiences as f64
+            + child.avg_fitness * child.total_experiences as f64)
+            / total as f64;
+    }
+    if parent.total_experiences == 0 {
+        parent.best_fitness = child.best_fitness;
+        parent.first_update = child.first_update;
+    } else {
+        parent.best_fitness = parent.best_fitness.max(child.best_fitness);
+        parent.first_update = parent.first_update.min(child.first_update);
+    }
+    parent.total_experiences = total;
+    parent.confidence = parent.confidence.max(child.confidence);
+    parent.last_update = parent.last_update.max(child.last_update);
+    for (p, c) in parent.params.iter_mut().zip(&child.params) {
+        p.merge(c);
+    }
+}
+
+/// Mean within-group variance of parameter means when dimension `d` varies
+/// and every other dimension is held fixed; `None` if no group has two members
+fn importance(states: &[(Vec<&str>, &ContextState)], d: usize) -> Option<f64> {
+    let mut groups: HashMap<Vec<&str>, Vec<&ContextState>> = HashMap::new();
+    for (values, state) in states {
+        if values[d] == WILDCARD || state.total_experiences == 0 {
+            continue;
+        }
+        let mut rest = values.clone();
+        rest.remove(d);
+        groups.entry(rest).or_default().push(state);
+    }
+
+    let mut total = 0.0;
+    let mut compared = 0;
+    for members in groups.values().filter(|m| m.len() > 1) {
+        let params = members[0].params.len();
+        let n = members.len() as f64;
+        for i in 0..params {
+            let mean = members.iter().map(|s| s.params[i].mean).sum::<f64>() / n;
+            total += members.iter().map(|s| (s.params[i].mean - mean).powi(2)).sum::<f64>() / n;
+        }
+        compared += params.max(1);
+    }
+    (compared > 0).then(|| total / compared as f64)
+}
+
+impl EvoCoreContextSystem {
+    /// Fold cold contexts into coarser parents before evicting them
+    ///
+    /// Only has an effect together with [`with_max_contexts`](Self::with_max_contexts).
+    pub fn with_coarsening(mut self, policy: CoarseningPolicy) -> Self {
+        self.set_coarsening(Some(policy));
+        self
+    }
+
+    /// Change or disable (`None`) coarsening
+    pub fn set_coarsening(&mut self, policy: Option<CoarseningPolicy>) {
+        self.coarsening = policy.map(|policy| Coarsening {
+            policy,
+            stats: self.coarsening.take().map(|c| c.stats).unwrap_or_default(),
+        });
+        if self.coarsening.is_some() && self.wildcard_fallback.is_none() {
+            self.wildcard_fallback = Some(1);
+        }
+    }
+
+    /// Coarsening counters, if coarsening is enabled
+    pub fn coarsening_stats(&self) -> Option<CoarseningStats> {
+        self.coarsening.as_ref().map(|c| c.stats.clone())
+    }
+
+    /// Dimension whose values matter least to what was learned
+    pub fn least_important_dimension(&self) -> Option<String> {
+        let names: Vec<String> = self.dimensions().into_iter().map(|(name, _)| name).collect();
+        let states = self.context_states();
+        self.least_important_in(&names, &states).map(|d| names[d].clone())
+    }
+
+    fn least_important_in(&self, names: &[String], states: &[ContextState]) -> Option<usize> {
+        if names.is_empty() {
+            return None;
+        }
+        let split: Vec<(Vec<&str>, &ContextState)> = states
+            .iter()
+            .map(|s| (s.key.split(':').collect::<Vec<_>>(), s))
+            .filter(|(values, _)| values.len() == names.len())
+            .collect();
+        let scored = (0..names.len())
+            .filter_map(|d| importance(&split, d).map(|score| (d, score)))
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)));
+        Some(scored.map_or(names.len() - 1, |(d, _)| d))
+    }
+
+    /// Run a coarsening pass if the count has reached the high-water mark
+    pub(crate) fn coarsen_if_needed(&mut self) {
+        let (Some(coarsening), Some(capacity)) = (&self.coarsening, self.capacity_stats()) else {
+            return;
+        };
+        let policy = coarsening.policy;
+        let (max_contexts, mut count) = (capacity.max_contexts, capacity.contexts);
+        if (count as f64) < policy.high_water * max_contexts as f64 {
+            return;
+        }
+        let target = (policy.target * max_contexts as f64).floor() as usize;
+
+        let names: Vec<String> = self.dimensions().into_iter().map(|(name, _)| name).collect();
+        let states = self.context_states();
+        let Some(dropped) = self.least_important_in(&names, &states) else {
+            return;
+        };
+        let mut states: HashMap<String, ContextState> = states.into_iter().map(|s| (s.key.clone(), s)).collect();
+
+        let mut folded = 0;
+        for key in self.least_recent_keys() {
+            if count <= target {
+                break;
+            }
+            let mut values: Vec<&str> = key.split(':').collect();
+            if values.len() != names.len() || values[dropped] == WILDCARD || self.hot.is_pinned(&key) {
+                continue;
+            }
+            let Some(child) = states.remove(&key) else {
+                continue;
+            };
+            values[dropped] = WILDCARD;
+            let parent_key = values.join(":");
+            let parent_exists = states.contains_key(&parent_key);
+            let param_count = self.param_count;
+            let parent = states
+                .entry(parent_key.clone())
+                .or_insert_with_key(|key| ContextState::empty(key.clone(), param_count));
+            fold(parent, &child);
+            let parent = parent.clone();
+            if self.restore_context_state(&parent).is_err() || !self.remove_key(&key) {
+                continue;
+            }
+            self.touch_key(&parent_key);
+            folded += 1;
+            count -= 1;
+            if !parent_exists {
+                count += 1;
+            }
+        }
+
+        if let Some(coarsening) = &mut self.coarsening {
+            coarsening.stats.passes += 1;
+            coarsening.stats.folded += folded;
+            coarsening.stats.last_dropped = Some(names[dropped].clone());
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Encrypted persistence
+//!
+//! Saves are encoded in the binary checkpoint format and sealed with
+//! AES-256-GCM, so learned parameters shipped to untrusted machines cannot be
+//! read or modified without the key. The plaintext never touches disk.
+//!
+//! File layout: `EVCE` magic, a `u32` format version, a 12-byte nonce, then
+//! the ciphertext with its authentication tag.
+
+use crate::serializer::write_file;
+use crate::{Checkpoint, EvoCoreContextSystem};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use rand::RngCore;
+use std::path::Path;
+
+const ENCRYPTED_MAGIC: &[u8; 4] = b"EVCE";
+const ENCRYPTED_VERSION: u32 = 1;
+const NONCE_SIZE: usize = 12;
+const HEADER_SIZE: usize = 4 + 4 + NONCE_SIZE;
+
+impl EvoCoreContextSystem {
+    /// Save context system to an AES-256-GCM encrypted file
+    ///
+    /// The file is written to a temporary file and renamed into place, so a
+    /// crash mid-save leaves the previous checkpoint intact.
+    pub fn save_encrypted(&self, filepath: &str, key: &[u8; 32]) -> Result<(), String> {
+        let plaintext = self.checkpoint().to_binary();
+
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_ref())
+            .map_err(|_| "Failed to encrypt context system".to_string())?;
+
+        let mut out = Vec::with_capacity(HEADER_SIZE + ciphertext.len());
+        out.extend_from_slice(ENCRYPTED_MAGIC);
+        out.extend_from_slice(&ENCRYPTED_VERSION.to_be_bytes());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+
+        write_file(Path::new(filepath), &out, true)
+    }
+
+    /// Load context system from a file written by [`save_encrypted`](Self::save_encrypted)
+    ///
+    /// Fails if the key is wrong or the file was modified.
+    pub fn load_encrypted(filepath: &str, key: &[u8; 32]) -> Result<Self, String> {
+        let data =
+            std::fs::read(filepath).map_err(|e| format!("Failed to load context system: {}", e))?;
+
+        if data.len() < HEADER_SIZE || &data[..4] != ENCRYPTED_MAGIC {
+            return Err("Failed to load context system: not an encrypted checkpoint".to_string());
+        }
+        let version = u32::from_be_bytes(data[4..8].try_into().unwrap());
+        if version != ENCRYPTED_VERSION {
+            return Err(format!(
+                "Failed to load context system: unsupported encryption version {}",
+                version
+            ));
+        }
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&data[8..HEADER_SIZE]), &data[HEADER_SIZE..])
+            .map_err(|_| "Failed to load context system: wrong key or corrupted file".to_string())?;
+
+        Checkpoint::from_bytes(&plaintext)
+            .and_then(Checkpoint::into_system)
+            .map_err(|e| format!("Failed to load context system: {}", e))
+    }
+}
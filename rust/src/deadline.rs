@@ -0,0 +1,189 @@
+//! Deadline-aware sampling for real-time callers
+//!
+//! A [sampling strategy](crate::SamplingStrategy) may fit a covariance or a
+//! surrogate model on every call, which is fine on average but not when a
+//! request has a hard latency budget.
+//! [`sample_with_deadline`](EvoCoreContextSystem::sample_with_deadline)
+//! picks the most thorough [`SamplePath`] that fits in the time left:
+//!
+//! - [`Full`](SamplePath::Full): the same as `sample()`
+//! - [`Direct`](SamplePath::Direct): one draw from the C library's learned
+//!   distribution, skipping the strategy, bound rejection and explanations;
+//!   out-of-range values are clamped
+//! - [`Exploit`](SamplePath::Exploit): the context's learned means, read in
+//!   place without sampling (a cold context gets a direct draw, which the C
+//!   library answers without touching a distribution)
+//!
+//! A path is taken when at least twice its estimated latency is left. The
+//! estimates come from timing earlier deadline calls: they jump up to any
+//! slower call and decay slowly, so one slow call makes later ones cautious
+//! for a while. Until a path has been timed it is assumed to fit. Pinned
+//! overrides are honoured on every path.
+
+use crate::{evocore_context_get_stats_key, key_into, EvoCoreContextSystem, MAX_KEY_LENGTH};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Headroom a path's estimated latency needs in the time left
+const DEADLINE_MARGIN: u32 = 2;
+/// Share of a faster call that a latency estimate moves towards
+const LATENCY_DECAY: u64 = 8;
+
+/// How a deadline-aware sample was produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SamplePath {
+    /// The full sampling pipeline, strategy included
+    Full,
+    /// One draw from the C library, skipping the strategy
+    Direct,
+    /// The context's learned means
+    Exploit,
+}
+
+/// Path counts and latency estimates of deadline-aware sampling
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeadlineStats {
+    pub full: u64,
+    pub direct: u64,
+    pub exploit: u64,
+    pub full_latency: Duration,
+    pub direct_latency: Duration,
+}
+
+/// Latency estimates, in nanoseconds, and path counts
+#[derive(Debug, Default)]
+pub(crate) struct LatencyTracker {
+    full_ns: AtomicU64,
+    direct_ns: AtomicU64,
+    counts: [AtomicU64; 3],
+}
+
+impl LatencyTracker {
+    fn estimate(&self, path: SamplePath) -> Duration {
+        match path {
+            SamplePath::Full => Duration::from_nanos(self.full_ns.load(Ordering::Relaxed)),
+            SamplePath::Direct => Duration::from_nanos(self.direct_ns.load(Ordering::Relaxed)),
+            SamplePath::Exploit => Duration::ZERO,
+        }
+    }
+
+    fn record(&self, path: SamplePath, elapsed: Duration) {
+        let slot = match path {
+            SamplePath::Full => &self.full_ns,
+            SamplePath::Direct => &self.direct_ns,
+            SamplePath::Exploit => {
+                self.counts[2].fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        };
+        let elapsed = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        let estimate = slot.load(Ordering::Relaxed);
+        let updated = if elapsed >= estimate {
+            elapsed
+        } else {
+            estimate - (estimate - elapsed) / LATENCY_DECAY
+        };
+        slot.store(updated, Ordering::Relaxed);
+        self.counts[path as usize].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Sample parameters for a context, falling back to cheaper paths to finish by `deadline`
+    pub fn sample_with_deadline(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+        deadline: Instant,
+    ) -> Result<(Vec<f64>, SamplePath), String> {
+        let mut params = vec![0.0; self.param_count];
+        let path = self.sample_with_deadline_into(dimension_values, exploration, deadline, &mut params)?;
+        Ok((params, path))
+    }
+
+    /// Allocation-free [`sample_with_deadline`](Self::sample_with_deadline)
+    pub fn sample_with_deadline_into(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+        deadline: Instant,
+        out: &mut [f64],
+    ) -> Result<SamplePath, String> {
+        let started = Instant::now();
+        let left = deadline.saturating_duration_since(started);
+        let fits = |path| self.latency.estimate(path) * DEADLINE_MARGIN <= left && !left.is_zero();
+
+        if fits(SamplePath::Full) {
+            self.sample_into(dimension_values, exploration, out)?;
+            self.latency.record(SamplePath::Full, started.elapsed());
+            return Ok(SamplePath::Full);
+        }
+
+        if out.len() != self.param_count {
+            return Err(format!(
+                "Output buffer length mismatch: expected {}, got {}",
+                self.param_count,
+                out.len()
+            ));
+        }
+        self.check_dimension_count(dimension_values)?;
+
+        let path = if let Some(pinned) = self.active_override(dimension_values) {
+            out.copy_from_slice(pinned);
+            SamplePath::Exploit
+        } else if !fits(SamplePath::Direct) && self.learned_means_into(dimension_values, out) {
+            SamplePath::Exploit
+        } else {
+            let exploration = self.ramped_exploration(exploration);
+            self.exploration.record(exploration);
+            self.sample_raw(dimension_values, exploration, out)?;
+            for (value, bounds) in out.iter_mut().zip(&self.bounds) {
+                if let Some(b) = bounds {
+                    *value = b.clamp(*value);
+                }
+            }
+            SamplePath::Direct
+        };
+
+        self.latency.record(path, started.elapsed());
+        self.notify_sample(dimension_values, out, exploration);
+        #[cfg(feature = "metrics")]
+        self.record_samples(true, 1);
+        Ok(path)
+    }
+
+    /// Path counts and latency estimates of [`sample_with_deadline`](Self::sample_with_deadline)
+    pub fn deadline_stats(&self) -> DeadlineStats {
+        DeadlineStats {
+            full: self.latency.counts[SamplePath::Full as usize].load(Ordering::Relaxed),
+            direct: self.latency.counts[SamplePath::Direct as usize].load(Ordering::Relaxed),
+            exploit: self.latency.counts[SamplePath::Exploit as usize].load(Ordering::Relaxed),
+            full_latency: self.latency.estimate(SamplePath::Full),
+            direct_latency: self.latency.estimate(SamplePath::Direct),
+        }
+    }
+
+    /// Fill `out` with a context's learned means; false if it has no data
+    fn learned_means_into(&self, dimension_values: &[&str], out: &mut [f64]) -> bool {
+        let mut buf = [0u8; MAX_KEY_LENGTH];
+        let Some(key) = key_into(dimension_values, &mut buf) else {
+            return false;
+        };
+        unsafe {
+            let mut stats = std::ptr::null_mut();
+            if !evocore_context_get_stats_key(self.inner.as_ptr(), key.as_ptr(), &mut stats)
+                || stats.is_null()
+                || (*stats).total_experiences == 0
+                || (*stats).stats.is_null()
+                || (*(*stats).stats).stats.is_null()
+            {
+                return false;
+            }
+            let array = &*(*stats).stats;
+            for (value, p) in out.iter_mut().zip(std::slice::from_raw_parts(array.stats, array.count)) {
+                *value = p.mean;
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,146 @@
+//! Recency-weighted learning
+//!
+//! In a non-stationary environment old observations should count for less
+//! than new ones. A [`DecayConfig`] sets a half-life, applied in one of two
+//! ways:
+//!
+//! - [`DecayMode::OnLearn`]: before each learn, the context's accumulated
+//!   weights are scaled by `0.5^(age / half_life)`, where `age` is the time
+//!   since the context last learned. Means and variances are unchanged, but
+//!   the new observation (and every later one) moves them further.
+//! - [`DecayMode::OnSample`]: stored statistics are never touched; instead,
+//!   sampling a context that has not learned for a while blends its learned
+//!   distribution with uniform noise, as if `exploration` were raised, so
+//!   stale contexts drift back towards exploring.
+//!
+//! Ages are measured in whole seconds, matching the C library's timestamps.
+
+use crate::{evocore_context_get_stats_key, EvoCoreContextSystem};
+use std::ffi::CStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// When decay is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecayMode {
+    /// Down-weight existing data each time a context learns
+    OnLearn,
+    /// Leave data alone; raise exploration when sampling stale contexts
+    OnSample,
+}
+
+/// Exponential decay of old observations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecayConfig {
+    half_life: Duration,
+    mode: DecayMode,
+}
+
+impl DecayConfig {
+    /// Halve the influence of observations every `half_life`, applied at learn time
+    pub fn new(half_life: Duration) -> Self {
+        Self {
+            half_life,
+            mode: DecayMode::OnLearn,
+        }
+    }
+
+    /// Choose when decay is applied (default [`DecayMode::OnLearn`])
+    pub fn with_mode(mut self, mode: DecayMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn half_life(&self) -> Duration {
+        self.half_life
+    }
+
+    pub fn mode(&self) -> DecayMode {
+        self.mode
+    }
+
+    /// Weight remaining after `age_secs` (1.0 for no time elapsed)
+    pub fn factor(&self, age_secs: f64) -> f64 {
+        let half_life = self.half_life.as_secs_f64();
+        if half_life <= 0.0 {
+            return if age_secs > 0.0 { 0.0 } else { 1.0 };
+        }
+        0.5f64.powf(age_secs.max(0.0) / half_life)
+    }
+}
+
+pub(crate) fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+impl EvoCoreContextSystem {
+    /// Enable recency weighting
+    pub fn with_decay(mut self, config: DecayConfig) -> Self {
+        self.decay = Some(config);
+        self
+    }
+
+    /// Change or remove (`None`) recency weighting
+    pub fn set_decay(&mut self, config: Option<DecayConfig>) {
+        self.decay = config;
+    }
+
+    /// Current recency weighting, if any
+    pub fn decay(&self) -> Option<DecayConfig> {
+        self.decay
+    }
+
+    /// Scale down a context's accumulated weights by its age ([`DecayMode::OnLearn`])
+    pub(crate) fn decay_before_learn(&mut self, key: &CStr) {
+        let Some(config) = self.decay.filter(|c| c.mode == DecayMode::OnLearn) else {
+            return;
+        };
+
+        unsafe {
+            let mut stats = std::ptr::null_mut();
+            if !evocore_context_get_stats_key(self.inner.as_ptr(), key.as_ptr(), &mut stats)
+                || stats.is_null()
+                || (*stats).stats.is_null()
+            {
+                return;
+            }
+
+            let age = (unix_now() - (*stats).last_update as i64) as f64;
+            let factor = config.factor(age);
+            if factor >= 1.0 {
+                return;
+            }
+            // Keep weights strictly positive so the C update never divides by zero
+            let factor = factor.max(1e-9);
+
+            let array = &*(*stats).stats;
+            for p in std::slice::from_raw_parts_mut(array.stats, array.count) {
+                p.sum_weights *= factor;
+                p.m2 *= factor;
+                p.sum_weighted_x *= factor;
+            }
+        }
+    }
+
+    /// Exploration to sample a context with, raised by its age ([`DecayMode::OnSample`])
+    pub(crate) fn decayed_exploration(&self, key: &CStr, exploration: f64) -> f64 {
+        let Some(config) = self.decay.filter(|c| c.mode == DecayMode::OnSample) else {
+            return exploration;
+        };
+
+        let last_update = unsafe {
+            let mut stats = std::ptr::null_mut();
+            if !evocore_context_get_stats_key(self.inner.as_ptr(), key.as_ptr(), &mut stats)
+                || stats.is_null()
+            {
+                return exploration;
+            }
+            (*stats).last_update as i64
+        };
+
+        let factor = config.factor((unix_now() - last_update) as f64);
+        let exploration = exploration.clamp(0.0, 1.0);
+        1.0 - (1.0 - exploration) * factor
+    }
+}
@@ -0,0 +1,207 @@
+//! Sample-then-learn pairing
+//!
+//! Sampling without ever reporting the outcome is an easy bug to write and
+//! a hard one to notice: the system just stops improving. A [`Decision`]
+//! returned by [`sample_guarded`](EvoCoreContextSystem::sample_guarded)
+//! holds the sampled parameters and must be resolved with
+//! [`succeed`](Decision::succeed), [`fail`](Decision::fail) or
+//! [`discard`](Decision::discard). If it is dropped unresolved, the
+//! system's [`UnresolvedPolicy`] applies: warn (the default; on stderr, or
+//! as a `tracing` event with feature `tracing`), learn a penalty fitness,
+//! or ignore it. Either way the drop is counted in
+//! [`decision_stats`](EvoCoreContextSystem::decision_stats).
+//!
+//! ```ignore
+//! let decision = system.sample_guarded(&["code", "rust"], 0.1)?;
+//! match run(decision.params()) {
+//!     Ok(score) => decision.succeed(score)?,
+//!     Err(_) => decision.fail(0.0)?,
+//! }
+//! ```
+
+use crate::{EvoCoreContextSystem, SharedContextSystem};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// What happens when a [`Decision`] is dropped without an outcome
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnresolvedPolicy {
+    /// Warn through `tracing` or `log` when either feature is on, else on stderr
+    Warn,
+    /// Learn this fitness, as if the decision had failed
+    Penalty(f64),
+    /// Only count it
+    Ignore,
+}
+
+/// How guarded decisions were resolved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecisionStats {
+    pub succeeded: u64,
+    pub failed: u64,
+    pub discarded: u64,
+    /// Dropped without an outcome
+    pub unresolved: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct DecisionTracker {
+    policy: UnresolvedPolicy,
+    succeeded: AtomicU64,
+    failed: AtomicU64,
+    discarded: AtomicU64,
+    unresolved: AtomicU64,
+}
+
+impl Default for DecisionTracker {
+    fn default() -> Self {
+        Self {
+            policy: UnresolvedPolicy::Warn,
+            succeeded: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            discarded: AtomicU64::new(0),
+            unresolved: AtomicU64::new(0),
+        }
+    }
+}
+
+enum Target<'a> {
+    Owned(&'a mut EvoCoreContextSystem),
+    Shared(&'a SharedContextSystem),
+}
+
+impl Target<'_> {
+    fn learn(&mut self, dimension_values: &[&str], parameters: &[f64], fitness: f64) -> Result<(), String> {
+        match self {
+            Target::Owned(system) => system.learn(dimension_values, parameters, fitness),
+            Target::Shared(shared) => shared.learn(dimension_values, parameters, fitness),
+        }
+    }
+
+    fn with_tracker<R>(&self, f: impl FnOnce(&DecisionTracker) -> R) -> R {
+        match self {
+            Target::Owned(system) => f(&system.decisions),
+            Target::Shared(shared) => shared.read(|system| f(&system.decisions)),
+        }
+    }
+}
+
+/// Parameters sampled for one context, awaiting their outcome
+#[must_use = "resolve the decision with succeed(), fail() or discard()"]
+pub struct Decision<'a> {
+    target: Target<'a>,
+    dimension_values: Vec<String>,
+    params: Vec<f64>,
+    resolved: bool,
+}
+
+impl Decision<'_> {
+    /// The sampled parameters
+    pub fn params(&self) -> &[f64] {
+        &self.params
+    }
+
+    pub fn dimension_values(&self) -> &[String] {
+        &self.dimension_values
+    }
+
+    /// Learn a successful outcome
+    pub fn succeed(mut self, fitness: f64) -> Result<(), String> {
+        self.resolve(Some(fitness), |t| &t.succeeded)
+    }
+
+    /// Learn a failed outcome with its (usually low) fitness
+    pub fn fail(mut self, fitness: f64) -> Result<(), String> {
+        self.resolve(Some(fitness), |t| &t.failed)
+    }
+
+    /// Resolve without learning, e.g. when the request was cancelled
+    pub fn discard(mut self) {
+        let _ = self.resolve(None, |t| &t.discarded);
+    }
+
+    fn resolve(&mut self, fitness: Option<f64>, counter: fn(&DecisionTracker) -> &AtomicU64) -> Result<(), String> {
+        self.resolved = true;
+        if let Some(fitness) = fitness {
+            let dims: Vec<&str> = self.dimension_values.iter().map(String::as_str).collect();
+            self.target.learn(&dims, &self.params, fitness)?;
+        }
+        self.target.with_tracker(|t| counter(t).fetch_add(1, Ordering::Relaxed));
+        Ok(())
+    }
+}
+
+impl Drop for Decision<'_> {
+    fn drop(&mut self) {
+        if self.resolved {
+            return;
+        }
+        let policy = self.target.with_tracker(|t| {
+            t.unresolved.fetch_add(1, Ordering::Relaxed);
+            t.policy
+        });
+        match policy {
+            UnresolvedPolicy::Warn => warn_unresolved(&self.dimension_values.join(":")),
+            UnresolvedPolicy::Penalty(fitness) => {
+                let dims: Vec<&str> = self.dimension_values.iter().map(String::as_str).collect();
+                // Nowhere to report an error from a destructor
+                let _ = self.target.learn(&dims, &self.params, fitness);
+            }
+            UnresolvedPolicy::Ignore => {}
+        }
+    }
+}
+
+/// Report a decision dropped without an outcome
+fn warn_unresolved(key: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(key, "decision dropped without an outcome");
+    #[cfg(all(feature = "log", not(feature = "tracing")))]
+    log::warn!("decision for context {} dropped without an outcome", key);
+    #[cfg(not(any(feature = "log", feature = "tracing")))]
+    eprintln!("evocore: decision for context {} dropped without an outcome", key);
+}
+
+fn decision<'a>(target: Target<'a>, dimension_values: &[&str], params: Vec<f64>) -> Decision<'a> {
+    Decision {
+        target,
+        dimension_values: dimension_values.iter().map(|v| v.to_string()).collect(),
+        params,
+        resolved: false,
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Sample parameters wrapped in a [`Decision`] that must be resolved
+    pub fn sample_guarded(&mut self, dimension_values: &[&str], exploration: f64) -> Result<Decision<'_>, String> {
+        let params = self.sample(dimension_values, exploration)?;
+        Ok(decision(Target::Owned(self), dimension_values, params))
+    }
+
+    /// Choose what happens to decisions dropped unresolved (default [`UnresolvedPolicy::Warn`])
+    pub fn set_unresolved_policy(&mut self, policy: UnresolvedPolicy) {
+        self.decisions.policy = policy;
+    }
+
+    pub fn unresolved_policy(&self) -> UnresolvedPolicy {
+        self.decisions.policy
+    }
+
+    /// Counts of how guarded decisions were resolved
+    pub fn decision_stats(&self) -> DecisionStats {
+        let t = &self.decisions;
+        DecisionStats {
+            succeeded: t.succeeded.load(Ordering::Relaxed),
+            failed: t.failed.load(Ordering::Relaxed),
+            discarded: t.discarded.load(Ordering::Relaxed),
+            unresolved: t.unresolved.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl SharedContextSystem {
+    /// Sample (shared) into a [`Decision`] that learns through the write lock when resolved
+    pub fn sample_guarded(&self, dimension_values: &[&str], exploration: f64) -> Result<Decision<'_>, String> {
+        let params = self.sample(dimension_values, exploration)?;
+        Ok(decision(Target::Shared(self), dimension_values, params))
+    }
+}
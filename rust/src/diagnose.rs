@@ -0,0 +1,142 @@
+//! Configuration health checks
+//!
+//! [`EvoCoreContextSystem::diagnose`] looks over a system's schema, learned
+//! statistics, and recent sampling calls for patterns that usually mean an
+//! integration is wired up wrong, rather than anything the library can fix.
+
+use crate::EvoCoreContextSystem;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Observations a context needs before its spread is judged
+const MIN_SAMPLES_FOR_SPREAD: usize = 100;
+/// Standard deviation, as a fraction of the observed range, that counts as unconverged
+///
+/// A uniform distribution has a standard deviation of about 0.29 of its
+/// range, so anything above this is barely narrower than random guessing.
+const UNCONVERGED_SPREAD: f64 = 0.25;
+/// Observations a parameter needs before it counts as never moving
+const MIN_SAMPLES_FOR_CONSTANT: usize = 3;
+/// Sampling calls needed before zero exploration is reported
+const MIN_SAMPLES_FOR_EXPLORATION: u64 = 100;
+/// Values closer than this are treated as identical
+const EPSILON: f64 = 1e-9;
+
+/// Counts sampling calls and how many of them used no exploration
+#[derive(Debug, Default)]
+pub(crate) struct ExplorationCounter {
+    samples: AtomicU64,
+    zero: AtomicU64,
+}
+
+impl ExplorationCounter {
+    pub(crate) fn record(&self, exploration: f64) {
+        self.samples.fetch_add(1, Ordering::Relaxed);
+        if exploration <= 0.0 {
+            self.zero.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A likely configuration problem found by [`EvoCoreContextSystem::diagnose`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic {
+    /// A dimension with a single value never distinguishes contexts
+    SingleValueDimension { dimension: String },
+    /// Every context learned the same constant value for this parameter,
+    /// which usually means the caller passes a default instead of the
+    /// sampled value back to `learn()`
+    ConstantParameter { param: usize, value: f64 },
+    /// A context with many observations still spreads a parameter almost
+    /// uniformly across its range
+    UnconvergedContext {
+        context: String,
+        param: usize,
+        std: f64,
+        samples: usize,
+    },
+    /// Every `sample()` call so far used an exploration factor of 0
+    NoExploration { samples: u64 },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::SingleValueDimension { dimension } => {
+                write!(f, "dimension '{}' has only one value", dimension)
+            }
+            Diagnostic::ConstantParameter { param, value } => write!(
+                f,
+                "parameter {} is {} in every context; is the sampled value being learned?",
+                param, value
+            ),
+            Diagnostic::UnconvergedContext { context, param, std, samples } => write!(
+                f,
+                "context '{}' parameter {} has std {:.4} after {} samples",
+                context, param, std, samples
+            ),
+            Diagnostic::NoExploration { samples } => {
+                write!(f, "all {} samples used an exploration factor of 0", samples)
+            }
+        }
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Check for configurations that usually indicate an integration mistake
+    ///
+    /// Returns an empty list when nothing looks wrong.
+    pub fn diagnose(&self) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = self
+            .dimensions()
+            .into_iter()
+            .filter(|(_, values)| values.len() == 1)
+            .map(|(dimension, _)| Diagnostic::SingleValueDimension { dimension })
+            .collect();
+
+        let mut states = self.context_states();
+        states.sort_by(|a, b| a.key.cmp(&b.key));
+
+        for param in 0..self.param_count {
+            let learned: Vec<_> = states
+                .iter()
+                .map(|s| &s.params[param])
+                .filter(|p| p.count >= MIN_SAMPLES_FOR_CONSTANT)
+                .collect();
+            if let Some(first) = learned.first() {
+                let constant = learned
+                    .iter()
+                    .all(|p| p.max_value - p.min_value < EPSILON && (p.mean - first.mean).abs() < EPSILON);
+                if constant {
+                    diagnostics.push(Diagnostic::ConstantParameter { param, value: first.mean });
+                }
+            }
+        }
+
+        for state in &states {
+            for (param, stats) in state.params.iter().enumerate() {
+                let range = stats.max_value - stats.min_value;
+                if stats.count >= MIN_SAMPLES_FOR_SPREAD
+                    && range > EPSILON
+                    && stats.std() > UNCONVERGED_SPREAD * range
+                {
+                    diagnostics.push(Diagnostic::UnconvergedContext {
+                        context: state.key.clone(),
+                        param,
+                        std: stats.std(),
+                        samples: stats.count,
+                    });
+                }
+            }
+        }
+
+        let samples = self.exploration.samples.load(Ordering::Relaxed);
+        if samples >= MIN_SAMPLES_FOR_EXPLORATION
+            && self.exploration.zero.load(Ordering::Relaxed) == samples
+        {
+            diagnostics.push(Diagnostic::NoExploration { samples });
+        }
+
+        diagnostics
+    }
+}
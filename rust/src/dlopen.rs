@@ -0,0 +1,59 @@
+//! Runtime loading of libevocore (feature `dlopen`)
+//!
+//! With this feature the crate is not linked against libevocore at all.
+//! The library is opened the first time it is needed: from the path in
+//! `EVOCORE_LIBRARY` if set, otherwise by its platform name
+//! (`libevocore.so`, `libevocore.dylib`, `evocore.dll`) through the
+//! system's library search path. A library missing any function this crate
+//! declares is rejected as a whole, so a version mismatch is reported up
+//! front rather than on first use.
+//!
+//! Without a usable library the binary still starts: C-backed constructors
+//! such as [`EvoCoreContextSystem::new`](crate::EvoCoreContextSystem::new)
+//! return the load error, [`native_available`](crate::native_available)
+//! reports it beforehand, [`new_learner`](crate::new_learner) falls back
+//! to the pure-Rust backend, and [`Profile::build`](crate::Profile::build)
+//! does the same with a warning.
+
+use crate::EVOCORE_SYMBOLS;
+use libloading::Library;
+use std::sync::OnceLock;
+
+static LIBRARY: OnceLock<Result<Library, String>> = OnceLock::new();
+
+/// The loaded library, opening it on first use
+pub(crate) fn library() -> Result<&'static Library, &'static str> {
+    LIBRARY.get_or_init(open).as_ref().map_err(String::as_str)
+}
+
+fn open() -> Result<Library, String> {
+    let name = std::env::var_os("EVOCORE_LIBRARY").unwrap_or_else(|| libloading::library_filename("evocore"));
+    // SAFETY: opening runs the library's initializers; libevocore has none
+    let library = unsafe { Library::new(name) }.map_err(load_error)?;
+    for symbol in EVOCORE_SYMBOLS {
+        // SAFETY: only checks the symbol exists; the pointer is not used
+        if let Err(e) = unsafe { library.get::<*const ()>(symbol.as_bytes()) } {
+            return Err(load_error(e));
+        }
+    }
+    Ok(library)
+}
+
+/// libloading's errors name the library
+fn load_error(e: libloading::Error) -> String {
+    format!("EvoCore library not usable: {}", e)
+}
+
+/// Resolve a C function by its NUL-terminated name
+///
+/// # Safety
+/// `F` must be the function's signature.
+///
+/// # Panics
+/// If the library is not loaded. Nothing calls into the library before a
+/// constructor has checked that it is.
+pub(crate) unsafe fn symbol<F: Copy>(name: &[u8]) -> F {
+    let library = library().unwrap_or_else(|e| panic!("{}", e));
+    // Every symbol was checked when the library was opened
+    *library.get::<F>(name).unwrap_or_else(|e| panic!("{}", e))
+}
@@ -0,0 +1,228 @@
+//! Ensemble of sampling strategies with learned per-context mixing
+//!
+//! No single exploration algorithm suits every context of a heterogeneous
+//! workload. [`Ensemble`] holds several [strategies](SamplingStrategy) and,
+//! for each `sample()`, lets one of them propose the parameters, chosen at
+//! random by the context's mixing weights. When the proposal is learned,
+//! the fitness is credited to the member that made it, and each member's
+//! weight follows its recent fitness in that context:
+//! `exp(learning_rate * (fitness_m - best))`, normalized and mixed with a
+//! floor of `min_weight` so no member is starved. Members that have not
+//! been credited yet in a context count as the best one until they are.
+//!
+//! Credit is matched by parameters: a learned example that is not one of
+//! the context's recent proposals (for example because
+//! [bounds](crate::ParamBounds) resampled it) only updates the members'
+//! own histories. Every member [observes](SamplingStrategy::observe) every
+//! learn. The learning rate assumes fitness on a scale of about `[0, 1]`.
+//! Mixing state is held by the strategy and is not saved in checkpoints.
+//!
+//! ```ignore
+//! let ensemble = Ensemble::new(vec![Arc::new(Thompson), Arc::new(Ucb1 { c: 1.0 }), Arc::new(LearnedDistribution)])?;
+//! let system = system.with_sampling_strategy(Arc::new(ensemble));
+//! ```
+
+use crate::rename::move_entry;
+use crate::{SamplingStrategy, StrategyInput};
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Proposals remembered per context for crediting
+const PENDING_PROPOSALS: usize = 64;
+/// Squared distance below which a learned example matches a proposal
+const MATCH_TOLERANCE: f64 = 1e-12;
+/// Weight of the newest fitness in a member's running average
+const FITNESS_SMOOTHING: f64 = 0.1;
+
+/// How one member is doing in one context
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnsembleMember {
+    pub name: String,
+    /// Probability of proposing the next sample
+    pub weight: f64,
+    pub proposals: u64,
+    /// Learned examples credited to this member
+    pub credited: u64,
+    /// Running average of credited fitness (`None` before the first credit)
+    pub fitness: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct MixState {
+    proposals: Vec<u64>,
+    credited: Vec<u64>,
+    fitness: Vec<Option<f64>>,
+    pending: VecDeque<(usize, Vec<f64>)>,
+}
+
+impl MixState {
+    fn new(members: usize) -> Self {
+        Self {
+            proposals: vec![0; members],
+            credited: vec![0; members],
+            fitness: vec![None; members],
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// Mixes several strategies with per-context weights learned from fitness
+pub struct Ensemble {
+    members: Vec<Arc<dyn SamplingStrategy>>,
+    learning_rate: f64,
+    min_weight: f64,
+    contexts: Mutex<HashMap<String, MixState>>,
+}
+
+impl Ensemble {
+    /// Mix `members` with learning rate 10 and a weight floor of 0.05
+    pub fn new(members: Vec<Arc<dyn SamplingStrategy>>) -> Result<Self, String> {
+        if members.is_empty() {
+            return Err("Ensemble needs at least one strategy".to_string());
+        }
+        Ok(Self {
+            members,
+            learning_rate: 10.0,
+            min_weight: 0.05,
+            contexts: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// How sharply weights favour the fitter members (default 10)
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate.max(0.0);
+        self
+    }
+
+    /// Smallest weight of any member, capped at an equal share (default 0.05)
+    pub fn with_min_weight(mut self, min_weight: f64) -> Self {
+        self.min_weight = min_weight.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Member strategy names, in order
+    pub fn member_names(&self) -> Vec<&str> {
+        self.members.iter().map(|m| m.name()).collect()
+    }
+
+    /// Mixing state of every member in the context `key`, in order
+    pub fn members(&self, key: &str) -> Vec<EnsembleMember> {
+        let contexts = self.lock();
+        let fresh = MixState::new(self.members.len());
+        let state = contexts.get(key).unwrap_or(&fresh);
+        self.weights(state)
+            .into_iter()
+            .enumerate()
+            .map(|(i, weight)| EnsembleMember {
+                name: self.members[i].name().to_string(),
+                weight,
+                proposals: state.proposals[i],
+                credited: state.credited[i],
+                fitness: state.fitness[i],
+            })
+            .collect()
+    }
+
+    /// Forget every context's mixing state
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, MixState>> {
+        self.contexts.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn weights(&self, state: &MixState) -> Vec<f64> {
+        let n = self.members.len();
+        let best = state.fitness.iter().flatten().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let raw: Vec<f64> = state
+            .fitness
+            .iter()
+            .map(|f| match f {
+                Some(f) if best.is_finite() => (self.learning_rate * (f - best)).exp(),
+                _ => 1.0,
+            })
+            .collect();
+        let total: f64 = raw.iter().sum();
+        let floor = self.min_weight.min(1.0 / n as f64);
+        raw.iter().map(|w| floor + (1.0 - floor * n as f64) * w / total).collect()
+    }
+}
+
+impl SamplingStrategy for Ensemble {
+    fn name(&self) -> &str {
+        "ensemble"
+    }
+
+    fn sample(&self, input: &StrategyInput<'_>, out: &mut [f64]) -> Result<(), String> {
+        let chosen = {
+            let contexts = self.lock();
+            let weights = match contexts.get(input.key()) {
+                Some(state) => self.weights(state),
+                None => self.weights(&MixState::new(self.members.len())),
+            };
+            let mut pick = input.rng().gen::<f64>();
+            weights
+                .iter()
+                .position(|w| {
+                    pick -= w;
+                    pick < 0.0
+                })
+                .unwrap_or(self.members.len() - 1)
+        };
+
+        self.members[chosen].sample(input, out)?;
+
+        let mut contexts = self.lock();
+        let state = contexts
+            .entry(input.key().to_string())
+            .or_insert_with(|| MixState::new(self.members.len()));
+        state.proposals[chosen] += 1;
+        if state.pending.len() >= PENDING_PROPOSALS {
+            state.pending.pop_front();
+        }
+        state.pending.push_back((chosen, out.to_vec()));
+        Ok(())
+    }
+
+    fn observe(&self, key: &str, parameters: &[f64], fitness: f64) {
+        for member in &self.members {
+            member.observe(key, parameters, fitness);
+        }
+        if !fitness.is_finite() {
+            return;
+        }
+
+        let mut contexts = self.lock();
+        let Some(state) = contexts.get_mut(key) else {
+            return;
+        };
+        let matched = state.pending.iter().position(|(_, proposed)| {
+            proposed.len() == parameters.len()
+                && proposed.iter().zip(parameters).map(|(a, b)| (a - b).powi(2)).sum::<f64>() <= MATCH_TOLERANCE
+        });
+        let Some((member, _)) = matched.and_then(|i| state.pending.remove(i)) else {
+            return;
+        };
+        state.credited[member] += 1;
+        state.fitness[member] = Some(match state.fitness[member] {
+            Some(mean) => mean + FITNESS_SMOOTHING * (fitness - mean),
+            None => fitness,
+        });
+    }
+
+    fn forget(&self, key: &str) {
+        for member in &self.members {
+            member.forget(key);
+        }
+        self.lock().remove(key);
+    }
+
+    fn rename_key(&self, from: &str, to: &str) {
+        for member in &self.members {
+            member.rename_key(from, to);
+        }
+        move_entry(&mut self.lock(), from, to);
+    }
+}
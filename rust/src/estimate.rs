@@ -0,0 +1,85 @@
+//! What-if fitness estimates for candidate parameters
+//!
+//! The C library keeps fitness-weighted statistics per parameter, not a
+//! fitness model, so [`EvoCoreContextSystem::estimate_fitness`] uses a
+//! simple heuristic: parameters at the learned (fitness-weighted) mean are
+//! expected to score like the best results seen, and the estimate falls
+//! back towards the context's average fitness as the candidate moves away
+//! from that mean, measured in learned standard deviations. It is meant
+//! for sanity-checking a manual override, not for ranking close candidates.
+
+use crate::EvoCoreContextSystem;
+
+/// Floor on the standard deviation used to measure distance, so contexts
+/// that always learned the same value still give finite distances
+const MIN_STD: f64 = 0.01;
+/// z-score for the 95% interval
+const Z_95: f64 = 1.96;
+
+/// Predicted fitness of a parameter vector in one context
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitnessEstimate {
+    /// Expected fitness
+    pub expected: f64,
+    /// Lower end of an approximate 95% interval
+    pub lower: f64,
+    /// Upper end of an approximate 95% interval
+    pub upper: f64,
+    /// Root-mean-square distance from the learned means, in standard deviations
+    pub distance: f64,
+    /// Experiences backing the estimate
+    pub samples: usize,
+}
+
+impl EvoCoreContextSystem {
+    /// Estimate the fitness `parameters` would achieve in this context
+    ///
+    /// Returns `Ok(None)` if the context has no learned data.
+    pub fn estimate_fitness(
+        &self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+    ) -> Result<Option<FitnessEstimate>, String> {
+        if parameters.len() != self.param_count {
+            return Err(format!(
+                "Parameter count mismatch: expected {}, got {}",
+                self.param_count,
+                parameters.len()
+            ));
+        }
+
+        let key = self.context_key(dimension_values)?;
+        let state = match self.context_state(&key) {
+            Some(state) if state.total_experiences > 0 => state,
+            _ => return Ok(None),
+        };
+
+        let squared: f64 = state
+            .params
+            .iter()
+            .zip(parameters)
+            .map(|(stats, &x)| {
+                let z = (x - stats.mean) / stats.std().max(MIN_STD);
+                z * z
+            })
+            .sum();
+        let distance = (squared / self.param_count as f64).sqrt();
+        let similarity = (-0.5 * distance * distance).exp();
+
+        let best = state.best_fitness.max(state.avg_fitness);
+        let expected = state.avg_fitness + (best - state.avg_fitness) * similarity;
+
+        // Best-minus-average stands in for the unrecorded fitness spread;
+        // uncertainty shrinks with more data and grows with distance.
+        let spread = (best - state.avg_fitness).max(f64::EPSILON);
+        let std_error = spread / (state.total_experiences as f64).sqrt() * (1.0 + distance);
+
+        Ok(Some(FitnessEstimate {
+            expected,
+            lower: expected - Z_95 * std_error,
+            upper: expected + Z_95 * std_error,
+            distance,
+            samples: state.total_experiences,
+        }))
+    }
+}
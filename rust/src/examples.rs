@@ -0,0 +1,352 @@
+//! A complete adaptive loop to embed and adapt (feature `examples`)
+//!
+//! The wrapper gives you `sample()` and `learn()`; a working integration
+//! also needs somewhere for contexts to come from, an evaluation, periodic
+//! saves and a way to tell whether it is improving. This module is that
+//! integration in miniature, written as library code rather than a binary
+//! so it can be copied or driven directly:
+//!
+//! - [`Environment`]: where contexts come from and how parameters score.
+//!   [`FakeEnvironment`] is a synthetic one with a hidden optimum per
+//!   context, for trying things out before wiring in the real evaluator.
+//! - [`LearnerLoop`]: observe a context, sample, evaluate, learn, autosave
+//!   every few steps, and keep [`LoopMetrics`].
+//!
+//! ```ignore
+//! let env = FakeEnvironment::new(&["task"], &[vec!["code", "chat"]], 3, 7);
+//! let system = env.new_system()?;
+//! let mut lp = LearnerLoop::new(system, env, LoopConfig::new().with_autosave("state.evo", 500));
+//! let metrics = lp.run(5_000)?;
+//! println!("{}", metrics);
+//! ```
+
+use crate::strategy::gaussian;
+use crate::EvoCoreContextSystem;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Source of contexts and judge of parameters
+pub trait Environment {
+    /// The next context to act in, as dimension values
+    fn observe(&mut self) -> Vec<String>;
+
+    /// Fitness of acting in `context` with `parameters` (higher is better)
+    fn evaluate(&mut self, context: &[String], parameters: &[f64]) -> f64;
+}
+
+/// Synthetic environment: each context hides an optimum in `[0, 1]^n`
+///
+/// Fitness is `exp(-d² / (2 w²))` for the distance `d` to the context's
+/// optimum, plus Gaussian noise, so it lies roughly in `[0, 1]`.
+#[derive(Debug, Clone)]
+pub struct FakeEnvironment {
+    dimension_names: Vec<String>,
+    dimension_values: Vec<Vec<String>>,
+    param_count: usize,
+    width: f64,
+    noise: f64,
+    optima: HashMap<String, Vec<f64>>,
+    rng: StdRng,
+}
+
+impl FakeEnvironment {
+    /// A fake environment over every combination of `dimension_values`, seeded by `seed`
+    pub fn new(dimension_names: &[&str], dimension_values: &[Vec<&str>], param_count: usize, seed: u64) -> Self {
+        Self {
+            dimension_names: dimension_names.iter().map(|n| n.to_string()).collect(),
+            dimension_values: dimension_values
+                .iter()
+                .map(|values| values.iter().map(|v| v.to_string()).collect())
+                .collect(),
+            param_count,
+            width: 0.2,
+            noise: 0.05,
+            optima: HashMap::new(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Width of the fitness peak around each optimum (default 0.2)
+    pub fn with_width(mut self, width: f64) -> Self {
+        self.width = width.max(1e-6);
+        self
+    }
+
+    /// Standard deviation of the evaluation noise (default 0.05)
+    pub fn with_noise(mut self, noise: f64) -> Self {
+        self.noise = noise.max(0.0);
+        self
+    }
+
+    /// A fresh system with this environment's dimensions and parameter count
+    pub fn new_system(&self) -> Result<EvoCoreContextSystem, String> {
+        let names: Vec<&str> = self.dimension_names.iter().map(String::as_str).collect();
+        let values: Vec<Vec<&str>> = self
+            .dimension_values
+            .iter()
+            .map(|values| values.iter().map(String::as_str).collect())
+            .collect();
+        EvoCoreContextSystem::new(&names, &values, self.param_count)
+    }
+
+    /// The hidden optimum of a context
+    pub fn optimum(&mut self, context: &[String]) -> Vec<f64> {
+        let (param_count, rng) = (self.param_count, &mut self.rng);
+        self.optima
+            .entry(context.join(":"))
+            .or_insert_with(|| (0..param_count).map(|_| rng.gen::<f64>()).collect())
+            .clone()
+    }
+
+    /// Noise-free fitness of `parameters` in `context`
+    pub fn true_fitness(&mut self, context: &[String], parameters: &[f64]) -> f64 {
+        let optimum = self.optimum(context);
+        let d2: f64 = optimum.iter().zip(parameters).map(|(o, p)| (o - p).powi(2)).sum();
+        (-d2 / (2.0 * self.width * self.width)).exp()
+    }
+}
+
+impl Environment for FakeEnvironment {
+    fn observe(&mut self) -> Vec<String> {
+        let rng = &mut self.rng;
+        self.dimension_values
+            .iter()
+            .map(|values| values[rng.gen_range(0..values.len())].clone())
+            .collect()
+    }
+
+    fn evaluate(&mut self, context: &[String], parameters: &[f64]) -> f64 {
+        let fitness = self.true_fitness(context, parameters);
+        fitness + self.noise * gaussian(&mut self.rng)
+    }
+}
+
+/// Settings for a [`LearnerLoop`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopConfig {
+    exploration: f64,
+    autosave: Option<(PathBuf, u64)>,
+    window: usize,
+    clamp_negative: bool,
+}
+
+impl Default for LoopConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoopConfig {
+    /// Exploration 0.2, no autosave, metrics over the last 100 steps
+    pub fn new() -> Self {
+        Self {
+            exploration: 0.2,
+            autosave: None,
+            window: 100,
+            clamp_negative: true,
+        }
+    }
+
+    /// Exploration passed to `sample()` (default 0.2)
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Save to `path` every `every_steps` steps and when the loop finishes
+    pub fn with_autosave(mut self, path: impl Into<PathBuf>, every_steps: u64) -> Self {
+        self.autosave = Some((path.into(), every_steps.max(1)));
+        self
+    }
+
+    /// Steps averaged in [`LoopMetrics::recent_mean_fitness`] (default 100)
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Learn negative fitness as 0, since it carries no weight anyway (default true)
+    pub fn with_clamp_negative(mut self, clamp: bool) -> Self {
+        self.clamp_negative = clamp;
+        self
+    }
+}
+
+/// What a [`LearnerLoop`] has done so far
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoopMetrics {
+    pub steps: u64,
+    pub mean_fitness: f64,
+    /// Mean fitness over the most recent window of steps
+    pub recent_mean_fitness: f64,
+    pub best_fitness: Option<f64>,
+    pub saves: u64,
+    pub save_errors: u64,
+    pub learn_errors: u64,
+    pub last_error: Option<String>,
+    pub elapsed: Duration,
+}
+
+impl LoopMetrics {
+    /// Steps per second
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.steps as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for LoopMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} steps in {:.2?} ({:.0}/s): mean fitness {:.4}, recent {:.4}, best {:.4}; {} saves, {} save errors, {} learn errors",
+            self.steps,
+            self.elapsed,
+            self.throughput(),
+            self.mean_fitness,
+            self.recent_mean_fitness,
+            self.best_fitness.unwrap_or(f64::NAN),
+            self.saves,
+            self.save_errors,
+            self.learn_errors
+        )
+    }
+}
+
+/// One observe-sample-evaluate-learn iteration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub context: Vec<String>,
+    pub parameters: Vec<f64>,
+    pub fitness: f64,
+}
+
+/// The adaptive loop: observe, sample, evaluate, learn, autosave
+pub struct LearnerLoop<E: Environment> {
+    system: EvoCoreContextSystem,
+    environment: E,
+    config: LoopConfig,
+    metrics: LoopMetrics,
+    recent: VecDeque<f64>,
+    fitness_sum: f64,
+}
+
+impl<E: Environment> LearnerLoop<E> {
+    pub fn new(system: EvoCoreContextSystem, environment: E, config: LoopConfig) -> Self {
+        Self {
+            system,
+            environment,
+            config,
+            metrics: LoopMetrics::default(),
+            recent: VecDeque::new(),
+            fitness_sum: 0.0,
+        }
+    }
+
+    /// Run one iteration
+    ///
+    /// Sampling errors (e.g. a context the system does not know) are
+    /// returned; learn and save errors are counted in the metrics so a
+    /// long-running loop keeps going.
+    pub fn step(&mut self) -> Result<Step, String> {
+        let started = Instant::now();
+        let context = self.environment.observe();
+        let dims: Vec<&str> = context.iter().map(String::as_str).collect();
+        let parameters = self.system.sample(&dims, self.config.exploration)?;
+        let fitness = self.environment.evaluate(&context, &parameters);
+
+        let learned = if self.config.clamp_negative { fitness.max(0.0) } else { fitness };
+        if let Err(e) = self.system.learn(&dims, &parameters, learned) {
+            self.metrics.learn_errors += 1;
+            self.metrics.last_error = Some(e);
+        }
+        self.record(fitness);
+
+        if let Some((_, every)) = &self.config.autosave {
+            if self.metrics.steps.is_multiple_of(*every) {
+                self.save();
+            }
+        }
+        self.metrics.elapsed += started.elapsed();
+        Ok(Step {
+            context,
+            parameters,
+            fitness,
+        })
+    }
+
+    /// Run `steps` iterations, then save once more if autosave is configured
+    pub fn run(&mut self, steps: u64) -> Result<&LoopMetrics, String> {
+        for _ in 0..steps {
+            self.step()?;
+        }
+        if self.config.autosave.is_some() {
+            self.save();
+        }
+        Ok(&self.metrics)
+    }
+
+    /// Save now to the autosave path; returns false if none is configured or the save failed
+    pub fn save(&mut self) -> bool {
+        let Some((path, _)) = &self.config.autosave else {
+            return false;
+        };
+        match self.system.save(&path.to_string_lossy()) {
+            Ok(()) => {
+                self.metrics.saves += 1;
+                true
+            }
+            Err(e) => {
+                self.metrics.save_errors += 1;
+                self.metrics.last_error = Some(e);
+                false
+            }
+        }
+    }
+
+    fn record(&mut self, fitness: f64) {
+        let m = &mut self.metrics;
+        m.steps += 1;
+        self.fitness_sum += fitness;
+        m.mean_fitness = self.fitness_sum / m.steps as f64;
+        m.best_fitness = Some(m.best_fitness.map_or(fitness, |b| b.max(fitness)));
+        if self.recent.len() >= self.config.window {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(fitness);
+        m.recent_mean_fitness = self.recent.iter().sum::<f64>() / self.recent.len() as f64;
+    }
+
+    pub fn metrics(&self) -> &LoopMetrics {
+        &self.metrics
+    }
+
+    pub fn system(&self) -> &EvoCoreContextSystem {
+        &self.system
+    }
+
+    pub fn system_mut(&mut self) -> &mut EvoCoreContextSystem {
+        &mut self.system
+    }
+
+    pub fn environment(&self) -> &E {
+        &self.environment
+    }
+
+    pub fn environment_mut(&mut self) -> &mut E {
+        &mut self.environment
+    }
+
+    /// Stop the loop, returning the system and environment
+    pub fn into_parts(self) -> (EvoCoreContextSystem, E) {
+        (self.system, self.environment)
+    }
+}
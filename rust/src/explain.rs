@@ -0,0 +1,219 @@
+//! Explanations for sampled parameters
+//!
+//! [`EvoCoreContextSystem::sample_explained`] returns, alongside the sampled
+//! values, what backed them: whether the context had learned anything, the
+//! learned mean and spread of each parameter, how far the sample landed
+//! from that mean, and how much exploration was mixed in. With
+//! [`enable_explanations`](EvoCoreContextSystem::enable_explanations) every
+//! `sample()` call is recorded and can be looked up afterwards with
+//! [`explain_last_sample`](EvoCoreContextSystem::explain_last_sample).
+
+use crate::EvoCoreContextSystem;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+/// Observations a parameter needs before it is sampled from its distribution
+const MIN_SAMPLES: usize = 3;
+
+/// Where a sampled value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleSource {
+    /// The context has never been learned; values are uniform random
+    NoData,
+    /// The context has too few observations; values are uniform random
+    Warmup,
+    /// Values were drawn from the learned distribution
+    Learned,
+    /// Values were pinned with [`override_params`](EvoCoreContextSystem::override_params)
+    Override,
+}
+
+/// What backed one sampled parameter
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamExplanation {
+    /// The sampled value
+    pub value: f64,
+    /// Learned mean (the pure-exploitation choice); 0.5, the centre of the
+    /// uniform range, when the context has no data
+    pub mean: f64,
+    /// Learned standard deviation
+    pub std: f64,
+    /// Observations backing the estimate
+    pub samples: usize,
+    /// `value - mean`: the combined effect of sampling noise and exploration
+    pub deviation: f64,
+}
+
+/// Why a sample returned the values it did
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleExplanation {
+    pub key: String,
+    pub source: SampleSource,
+    /// Exploration factor of the call; this fraction of each value was
+    /// replaced with uniform noise
+    pub exploration: f64,
+    /// Context confidence (0.0 - 1.0) at the time of sampling
+    pub confidence: f64,
+    /// Experiences learned into the context at the time of sampling
+    pub total_experiences: usize,
+    pub params: Vec<ParamExplanation>,
+}
+
+/// Most recent explanation per context key
+#[derive(Debug, Default)]
+pub(crate) struct ExplanationLog {
+    last: Mutex<HashMap<String, SampleExplanation>>,
+}
+
+impl ExplanationLog {
+    pub(crate) fn record(
+        &self,
+        system: &EvoCoreContextSystem,
+        dimension_values: &[&str],
+        values: &[f64],
+        exploration: f64,
+    ) {
+        if let Ok(explanation) = system.explain(dimension_values, values, exploration) {
+            self.last
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(explanation.key.clone(), explanation);
+        }
+    }
+
+    pub(crate) fn forget(&self, key: &str) {
+        self.last
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(key);
+    }
+
+    /// Approximate heap bytes held by recorded explanations
+    pub(crate) fn approx_bytes(&self) -> usize {
+        self.last
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|e| {
+                2 * e.key.len()
+                    + std::mem::size_of::<SampleExplanation>()
+                    + e.params.len() * std::mem::size_of::<ParamExplanation>()
+            })
+            .sum()
+    }
+
+    pub(crate) fn record_override(
+        &self,
+        system: &EvoCoreContextSystem,
+        dimension_values: &[&str],
+        values: &[f64],
+    ) {
+        if let Ok(mut explanation) = system.explain(dimension_values, values, 0.0) {
+            explanation.source = SampleSource::Override;
+            self.last
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(explanation.key.clone(), explanation);
+        }
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Explain `values` as if they had just been sampled for this context
+    pub fn explain(
+        &self,
+        dimension_values: &[&str],
+        values: &[f64],
+        exploration: f64,
+    ) -> Result<SampleExplanation, String> {
+        let key = self.context_key(dimension_values)?;
+        let exploration = exploration.clamp(0.0, 1.0);
+
+        Ok(match self.context_state(&key) {
+            Some(state) => {
+                let learned = state.params.iter().all(|p| p.count >= MIN_SAMPLES);
+                SampleExplanation {
+                    source: if learned {
+                        SampleSource::Learned
+                    } else {
+                        SampleSource::Warmup
+                    },
+                    exploration,
+                    confidence: state.confidence,
+                    total_experiences: state.total_experiences,
+                    params: state
+                        .params
+                        .iter()
+                        .zip(values)
+                        .map(|(stats, &value)| ParamExplanation {
+                            value,
+                            mean: stats.mean,
+                            std: stats.std(),
+                            samples: stats.count,
+                            deviation: value - stats.mean,
+                        })
+                        .collect(),
+                    key,
+                }
+            }
+            None => SampleExplanation {
+                key,
+                source: SampleSource::NoData,
+                exploration,
+                confidence: 0.0,
+                total_experiences: 0,
+                params: values
+                    .iter()
+                    .map(|&value| ParamExplanation {
+                        value,
+                        mean: 0.5,
+                        std: 0.0,
+                        samples: 0,
+                        deviation: value - 0.5,
+                    })
+                    .collect(),
+            },
+        })
+    }
+
+    /// Sample parameters and explain the result
+    pub fn sample_explained(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+    ) -> Result<(Vec<f64>, SampleExplanation), String> {
+        let values = self.sample(dimension_values, exploration)?;
+        let explanation = self.explain(dimension_values, &values, exploration)?;
+        Ok((values, explanation))
+    }
+
+    /// Record an explanation for every `sample()` call from now on
+    ///
+    /// Costs a stats lookup and a small allocation per call, so it is off
+    /// by default.
+    pub fn enable_explanations(&mut self) {
+        if self.explanations.is_none() {
+            self.explanations = Some(ExplanationLog::default());
+        }
+    }
+
+    /// Stop recording explanations and forget the recorded ones
+    pub fn disable_explanations(&mut self) {
+        self.explanations = None;
+    }
+
+    /// Explanation of the most recent `sample()` call for this context
+    ///
+    /// `None` if explanations are not enabled or the context has not been
+    /// sampled since they were.
+    pub fn explain_last_sample(&self, dimension_values: &[&str]) -> Option<SampleExplanation> {
+        let key = self.context_key(dimension_values).ok()?;
+        self.explanations
+            .as_ref()?
+            .last
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            .cloned()
+    }
+}
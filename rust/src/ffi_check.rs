@@ -0,0 +1,19 @@
+//! Compile-time check of the FFI structs against the C headers
+//!
+//! The function declarations in `src/bindings.rs` are generated by bindgen,
+//! but the `#[repr(C)]` structs in the crate root are written by hand, so
+//! nothing stops them from drifting away from `include/evocore`. With the
+//! `bindgen` feature, the build script regenerates the functions (and the
+//! crate is built against them) and runs bindgen over every declared
+//! struct, and this module fails to compile unless each has the same size
+//! and alignment as the header's.
+//!
+//! Builds without the feature use the committed declarations, so offline
+//! builds need neither bindgen nor libclang.
+
+#[allow(dead_code, non_camel_case_types, non_snake_case, non_upper_case_globals, clippy::all)]
+mod types {
+    include!(concat!(env!("OUT_DIR"), "/ffi_types.rs"));
+}
+
+include!(concat!(env!("OUT_DIR"), "/ffi_check.rs"));
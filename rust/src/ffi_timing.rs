@@ -0,0 +1,108 @@
+//! Marshalling and C call timing of learns and samples
+//!
+//! Every learn and sample crosses into the C library in two steps: building
+//! its arguments (the context key, or one `CString` per dimension without a
+//! [key cache](EvoCoreContextSystem::enable_key_cache)) and the call itself.
+//! Both are timed separately when something would record them:
+//!
+//! - feature `metrics`, once [enabled](EvoCoreContextSystem::enable_metrics):
+//!   `render_metrics` adds `evocore_ffi_seconds_total{op, phase}` and
+//!   `evocore_ffi_calls_total{op}`, with `op` `learn` or `sample` and
+//!   `phase` `marshal` or `call`
+//! - feature `tracing`: a trace-level event with target `evocore::ffi` and
+//!   fields `op`, `marshal_ns` and `call_ns` per call
+//!
+//! Otherwise nothing is timed. Bookkeeping around the call (bounds,
+//! normalization, decay, strategies) is in neither phase, and a sample
+//! answered without a C draw is not counted. Batch learns build each
+//! distinct key once, before their first call, outside the timed phases;
+//! only the first draw of a batch sample is timed.
+
+// Nothing reads the timings without either feature
+#![cfg_attr(not(any(feature = "metrics", feature = "tracing")), allow(dead_code, unused_variables))]
+
+use crate::EvoCoreContextSystem;
+use std::time::{Duration, Instant};
+
+/// A timed FFI operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FfiOp {
+    Learn,
+    Sample,
+}
+
+impl FfiOp {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            FfiOp::Learn => "learn",
+            FfiOp::Sample => "sample",
+        }
+    }
+}
+
+/// Times one operation: started when created, split by [`marshalled`](Self::marshalled)
+/// and stopped by [`finish_ffi_timer`](EvoCoreContextSystem::finish_ffi_timer)
+pub(crate) struct FfiTimer {
+    op: FfiOp,
+    started: Option<Instant>,
+    marshalled: Option<Instant>,
+}
+
+impl FfiTimer {
+    /// Mark the arguments as built; the C call starts now
+    pub(crate) fn marshalled(&mut self) {
+        if self.started.is_some() {
+            self.marshalled = Some(Instant::now());
+        }
+    }
+
+    /// Time spent marshalling and in the C call, if timed
+    fn phases(&self) -> Option<(Duration, Duration)> {
+        let (started, marshalled) = (self.started?, self.marshalled?);
+        Some((marshalled - started, marshalled.elapsed()))
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Start timing an FFI operation, if metrics or a trace subscriber would record it
+    pub(crate) fn ffi_timer(&self, op: FfiOp) -> FfiTimer {
+        FfiTimer {
+            op,
+            started: self.ffi_timing_enabled().then(Instant::now),
+            marshalled: None,
+        }
+    }
+
+    fn ffi_timing_enabled(&self) -> bool {
+        #[cfg(feature = "metrics")]
+        if self.metrics.is_some() {
+            return true;
+        }
+        #[cfg(feature = "tracing")]
+        if tracing::enabled!(target: "evocore::ffi", tracing::Level::TRACE) {
+            return true;
+        }
+        false
+    }
+
+    /// Record a finished operation
+    ///
+    /// A timer records once: later calls, and calls on a timer never marked
+    /// as marshalled, record nothing.
+    pub(crate) fn finish_ffi_timer(&self, timer: &mut FfiTimer) {
+        let phases = timer.phases();
+        timer.started = None;
+        let Some((marshal, call)) = phases else {
+            return;
+        };
+        #[cfg(feature = "metrics")]
+        self.record_ffi(timer.op, marshal, call);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            target: "evocore::ffi",
+            op = timer.op.name(),
+            marshal_ns = marshal.as_nanos() as u64,
+            call_ns = call.as_nanos() as u64,
+        );
+    }
+}
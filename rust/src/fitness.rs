@@ -0,0 +1,176 @@
+//! Typed fitness values
+//!
+//! A bare `f64` says nothing about what was measured. When two services
+//! share one system, one reporting latency in milliseconds and the other a
+//! normalized score, the learned statistics silently mix them. A system can
+//! declare what it expects with a [`FitnessSpec`] (a unit and a valid
+//! range), and callers report a [`Fitness`] tagged with its unit through
+//! [`learn_fitness`](EvoCoreContextSystem::learn_fitness).
+//!
+//! - A [`Fitness`] whose unit differs from the declared one is rejected.
+//! - Every fitness, typed or plain `f64`, must be finite and inside the
+//!   declared range once a spec is set; plain `f64` values carry no unit
+//!   and are assumed to match.
+//!
+//! ```ignore
+//! let mut system = system.with_fitness_spec(FitnessSpec::new().with_unit("score").with_range(0.0, 1.0));
+//! system.learn_fitness(&["code"], &params, Fitness::new(0.8).with_unit("score"))?; // ok
+//! system.learn_fitness(&["code"], &params, Fitness::new(250.0).with_unit("ms"))?;  // error
+//! ```
+
+use crate::{EvoCoreContextSystem, LearnError};
+use std::fmt;
+
+/// A fitness value with an optional unit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fitness {
+    value: f64,
+    unit: Option<String>,
+}
+
+impl Fitness {
+    /// A unitless fitness
+    pub fn new(value: f64) -> Self {
+        Self { value, unit: None }
+    }
+
+    /// Tag the value with the unit it was measured in
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+}
+
+impl From<f64> for Fitness {
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl fmt::Display for Fitness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.unit {
+            Some(unit) => write!(f, "{} {}", self.value, unit),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+/// What fitness a system accepts: a unit and a range
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitnessSpec {
+    unit: Option<String>,
+    min: f64,
+    max: f64,
+}
+
+impl Default for FitnessSpec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FitnessSpec {
+    /// Any finite fitness, in any unit
+    pub fn new() -> Self {
+        Self {
+            unit: None,
+            min: f64::NEG_INFINITY,
+            max: f64::INFINITY,
+        }
+    }
+
+    /// Require typed fitness to be in `unit`
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    /// Require fitness in `[min, max]`
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.min = min.min(max);
+        self.max = max.max(min);
+        self
+    }
+
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+
+    pub fn range(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+
+    /// Check a fitness against this spec
+    pub fn check(&self, fitness: &Fitness) -> Result<(), LearnError> {
+        if let (Some(expected), Some(found)) = (&self.unit, &fitness.unit) {
+            if expected != found {
+                return Err(LearnError::InvalidFitness(format!(
+                    "Fitness unit mismatch: expected {}, got {}",
+                    expected, found
+                )));
+            }
+        }
+        self.check_value(fitness.value)
+    }
+
+    fn check_value(&self, value: f64) -> Result<(), LearnError> {
+        if !value.is_finite() {
+            return Err(LearnError::InvalidFitness(format!("Fitness must be finite, got {}", value)));
+        }
+        if value < self.min || value > self.max {
+            return Err(LearnError::InvalidFitness(format!(
+                "Fitness {} is outside [{}, {}]",
+                value, self.min, self.max
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Declare the unit and range of fitness this system accepts
+    pub fn with_fitness_spec(mut self, spec: FitnessSpec) -> Self {
+        self.fitness_spec = Some(spec);
+        self
+    }
+
+    /// Change or remove (`None`) the declared fitness spec
+    pub fn set_fitness_spec(&mut self, spec: Option<FitnessSpec>) {
+        self.fitness_spec = spec;
+    }
+
+    pub fn fitness_spec(&self) -> Option<&FitnessSpec> {
+        self.fitness_spec.as_ref()
+    }
+
+    /// Learn from a typed fitness, checking it against the declared spec
+    pub fn learn_fitness(
+        &mut self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: impl Into<Fitness>,
+    ) -> Result<(), String> {
+        let fitness = fitness.into();
+        if let Some(spec) = &self.fitness_spec {
+            spec.check(&fitness).map_err(|e| e.to_string())?;
+        }
+        self.learn(dimension_values, parameters, fitness.value)
+    }
+
+    /// Check a plain fitness value against the declared range
+    pub(crate) fn check_fitness(&self, fitness: f64) -> Result<(), LearnError> {
+        match &self.fitness_spec {
+            Some(spec) => spec.check_value(fitness),
+            None => Ok(()),
+        }
+    }
+}
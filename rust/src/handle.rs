@@ -0,0 +1,149 @@
+//! Actor-style access to a context system
+//!
+//! [`ContextSystemHandle`] moves the system onto a dedicated worker thread.
+//! Handles are cheap to clone and `Send + Sync`; every call is sent to the
+//! worker over a channel and answered on a per-call reply channel, so no
+//! caller ever holds a lock. With the `tokio` feature each call also has an
+//! `_async` variant that awaits the reply instead of blocking.
+
+use crate::EvoCoreContextSystem;
+use std::sync::mpsc;
+use std::thread;
+
+type Job = Box<dyn FnOnce(&mut EvoCoreContextSystem) + Send>;
+
+fn worker_stopped() -> String {
+    "Context system worker has stopped".to_string()
+}
+
+fn owned(dimension_values: &[&str]) -> Vec<String> {
+    dimension_values.iter().map(|v| v.to_string()).collect()
+}
+
+fn borrowed(dimension_values: &[String]) -> Vec<&str> {
+    dimension_values.iter().map(String::as_str).collect()
+}
+
+/// Cloneable handle to a context system running on its own thread
+///
+/// The worker thread exits once every handle has been dropped.
+#[derive(Clone)]
+pub struct ContextSystemHandle {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl ContextSystemHandle {
+    /// Move `system` onto a new worker thread and return a handle to it
+    pub fn spawn(system: EvoCoreContextSystem) -> Result<Self, String> {
+        let (jobs, queue) = mpsc::channel::<Job>();
+
+        thread::Builder::new()
+            .name("evocore-context".to_string())
+            .spawn(move || {
+                let mut system = system;
+                for job in queue {
+                    job(&mut system);
+                }
+            })
+            .map_err(|e| format!("Failed to spawn context system worker: {}", e))?;
+
+        Ok(Self { jobs })
+    }
+
+    /// Run `f` on the worker thread and wait for its result
+    pub fn call<R, F>(&self, f: F) -> Result<R, String>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut EvoCoreContextSystem) -> R + Send + 'static,
+    {
+        let (reply, result) = mpsc::sync_channel(1);
+        self.jobs
+            .send(Box::new(move |system| {
+                let _ = reply.send(f(system));
+            }))
+            .map_err(|_| worker_stopped())?;
+        result.recv().map_err(|_| worker_stopped())
+    }
+
+    /// Learn from experience with parameters
+    pub fn learn(
+        &self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+    ) -> Result<(), String> {
+        let dims = owned(dimension_values);
+        let params = parameters.to_vec();
+        self.call(move |system| system.learn(&borrowed(&dims), &params, fitness))?
+    }
+
+    /// Sample parameters for a context
+    pub fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        let dims = owned(dimension_values);
+        self.call(move |system| system.sample(&borrowed(&dims), exploration))?
+    }
+
+    /// Save context system to file
+    pub fn save(&self, filepath: &str) -> Result<(), String> {
+        let path = filepath.to_string();
+        self.call(move |system| system.save(&path))?
+    }
+
+    /// Get number of contexts stored
+    pub fn context_count(&self) -> Result<usize, String> {
+        self.call(|system| system.context_count())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl ContextSystemHandle {
+    /// Run `f` on the worker thread and await its result
+    pub async fn call_async<R, F>(&self, f: F) -> Result<R, String>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut EvoCoreContextSystem) -> R + Send + 'static,
+    {
+        let (reply, result) = tokio::sync::oneshot::channel();
+        self.jobs
+            .send(Box::new(move |system| {
+                let _ = reply.send(f(system));
+            }))
+            .map_err(|_| worker_stopped())?;
+        result.await.map_err(|_| worker_stopped())
+    }
+
+    /// Learn from experience without blocking the async executor
+    pub async fn learn_async(
+        &self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+    ) -> Result<(), String> {
+        let dims = owned(dimension_values);
+        let params = parameters.to_vec();
+        self.call_async(move |system| system.learn(&borrowed(&dims), &params, fitness))
+            .await?
+    }
+
+    /// Sample parameters without blocking the async executor
+    pub async fn sample_async(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+    ) -> Result<Vec<f64>, String> {
+        let dims = owned(dimension_values);
+        self.call_async(move |system| system.sample(&borrowed(&dims), exploration))
+            .await?
+    }
+
+    /// Save context system to file without blocking the async executor
+    pub async fn save_async(&self, filepath: &str) -> Result<(), String> {
+        let path = filepath.to_string();
+        self.call_async(move |system| system.save(&path)).await?
+    }
+
+    /// Get number of contexts stored without blocking the async executor
+    pub async fn context_count_async(&self) -> Result<usize, String> {
+        self.call_async(|system| system.context_count()).await
+    }
+}
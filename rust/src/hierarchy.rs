@@ -0,0 +1,185 @@
+//! Hierarchical context fallback
+//!
+//! A context seen only a handful of times has too little data to sample
+//! from, but contexts that share its most important dimensions usually
+//! behave alike. [`sample_hierarchical`](EvoCoreContextSystem::sample_hierarchical)
+//! tries the exact context first and, while it has fewer than
+//! `min_samples` experiences, drops the least important remaining
+//! dimension and pools every context that matches on the rest. With
+//! `type > domain > tools` priority the levels are `type:domain:tools`,
+//! `type:domain:*`, `type:*:*`, and finally `*:*:*`. Explicit
+//! [wildcard contexts](crate::WILDCARD) are never pooled, so seeded
+//! defaults are not counted twice.
+
+use crate::{EvoCoreContextSystem, ParamStats, WILDCARD};
+
+/// Settings for [`sample_hierarchical`](EvoCoreContextSystem::sample_hierarchical)
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchyOptions {
+    min_samples: usize,
+    priority: Vec<String>,
+    exploration: f64,
+}
+
+impl Default for HierarchyOptions {
+    fn default() -> Self {
+        Self {
+            min_samples: 5,
+            priority: Vec::new(),
+            exploration: 0.0,
+        }
+    }
+}
+
+impl HierarchyOptions {
+    /// Fall back below 5 experiences, dimensions in declaration order, no exploration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Experiences a level needs before it is used (default 5)
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.max(1);
+        self
+    }
+
+    /// Dimension names, most important first
+    ///
+    /// Dimensions not listed keep their declaration order after the listed
+    /// ones. Least important dimensions are dropped first.
+    pub fn with_priority(mut self, priority: &[&str]) -> Self {
+        self.priority = priority.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// Exploration passed to sampling (default 0)
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// Parameters sampled by [`sample_hierarchical`](EvoCoreContextSystem::sample_hierarchical)
+/// and where they came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchicalSample {
+    pub params: Vec<f64>,
+    /// Number of dimensions ignored (0 is the exact context)
+    pub level: usize,
+    /// Dimensions that had to match at this level, most important first
+    pub matched: Vec<String>,
+    /// Experiences backing the sample (0 if no level had any data)
+    pub samples: usize,
+    /// Contexts pooled at this level
+    pub contexts: usize,
+}
+
+impl EvoCoreContextSystem {
+    /// Sample from the most specific level with enough data
+    ///
+    /// If no level reaches `min_samples`, the broadest level with any data
+    /// is used; with no data at all the sample is uniform and reports
+    /// `samples == 0`.
+    pub fn sample_hierarchical(
+        &self,
+        dimension_values: &[&str],
+        options: &HierarchyOptions,
+    ) -> Result<HierarchicalSample, String> {
+        let key = self.context_key(dimension_values)?;
+        let names: Vec<String> = self.dimensions().into_iter().map(|(name, _)| name).collect();
+        let order = priority_order(&names, &options.priority)?;
+
+        let exact = self.context_state(&key).map_or(0, |s| s.total_experiences);
+        if exact >= options.min_samples || self.active_override(dimension_values).is_some() {
+            return Ok(HierarchicalSample {
+                params: self.sample(dimension_values, options.exploration)?,
+                level: 0,
+                matched: order.iter().map(|&i| names[i].clone()).collect(),
+                samples: exact,
+                contexts: 1,
+            });
+        }
+
+        let keys = self.context_keys();
+        let mut fallback = None;
+        for level in 1..=names.len() {
+            let kept = &order[..names.len() - level];
+            let mut pooled = vec![ParamStats::default(); self.param_count];
+            let mut samples = 0;
+            let mut contexts = 0;
+
+            for other in &keys {
+                let values: Vec<&str> = other.split(':').collect();
+                if values.len() != names.len()
+                    || values.contains(&WILDCARD)
+                    || kept.iter().any(|&i| values[i] != dimension_values[i])
+                {
+                    continue;
+                }
+                if let Some(state) = self.context_state(other) {
+                    for (pool, stats) in pooled.iter_mut().zip(&state.params) {
+                        pool.merge(stats);
+                    }
+                    samples += state.total_experiences;
+                    contexts += 1;
+                }
+            }
+
+            let candidate = (level, kept, pooled, samples, contexts);
+            if samples >= options.min_samples {
+                fallback = Some(candidate);
+                break;
+            }
+            if samples > 0 {
+                fallback = Some(candidate);
+            }
+        }
+
+        let matched = |kept: &[usize]| kept.iter().map(|&i| names[i].clone()).collect();
+        match fallback {
+            Some((level, kept, pooled, samples, contexts)) => {
+                let mut rng = self.rng();
+                let mut params: Vec<f64> = pooled.iter().map(|p| p.sample(options.exploration, &mut rng)).collect();
+                if self.has_bounds() {
+                    self.apply_bounds(&mut params, |out| {
+                        for (value, p) in out.iter_mut().zip(&pooled) {
+                            *value = p.sample(options.exploration, &mut rng);
+                        }
+                        Ok(())
+                    })?;
+                }
+                Ok(HierarchicalSample {
+                    params,
+                    level,
+                    matched: matched(kept),
+                    samples,
+                    contexts,
+                })
+            }
+            None => Ok(HierarchicalSample {
+                params: self.sample(dimension_values, options.exploration)?,
+                level: names.len(),
+                matched: Vec::new(),
+                samples: 0,
+                contexts: 0,
+            }),
+        }
+    }
+}
+
+/// Dimension indices, most important first
+fn priority_order(names: &[String], priority: &[String]) -> Result<Vec<usize>, String> {
+    let mut order = Vec::with_capacity(names.len());
+    for name in priority {
+        let index = names
+            .iter()
+            .position(|n| n == name)
+            .ok_or_else(|| format!("Unknown dimension: {}", name))?;
+        if !order.contains(&index) {
+            order.push(index);
+        }
+    }
+    let rest: Vec<usize> = (0..names.len()).filter(|i| !order.contains(i)).collect();
+    order.extend(rest);
+    Ok(order)
+}
@@ -0,0 +1,226 @@
+//! A/B holdout against fixed baseline parameters
+//!
+//! Learning can look busy without actually helping. In holdout mode,
+//! [`sample_holdout`](EvoCoreContextSystem::sample_holdout) serves a fixed
+//! baseline parameter vector for a configured fraction of calls and the
+//! learned distribution for the rest, tagging each result with the
+//! [`HoldoutArm`] that served it. Fitness reported through
+//! [`learn_holdout`](EvoCoreContextSystem::learn_holdout) is tracked per arm
+//! across all contexts, and [`holdout_report`](EvoCoreContextSystem::holdout_report)
+//! compares the two online.
+//!
+//! Plain `sample()` is unaffected. Baseline outcomes are learned like any
+//! other unless [`HoldoutConfig::with_learn_baseline`] turns that off.
+
+use crate::EvoCoreContextSystem;
+use rand::Rng;
+
+/// Which policy served a holdout sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldoutArm {
+    /// The learned distribution
+    Learned,
+    /// The fixed baseline parameters
+    Baseline,
+}
+
+/// Holdout settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoldoutConfig {
+    baseline: Vec<f64>,
+    fraction: f64,
+    learn_baseline: bool,
+}
+
+impl HoldoutConfig {
+    /// Serve `baseline` for `fraction` (0.0 - 1.0) of sample calls
+    pub fn new(baseline: Vec<f64>, fraction: f64) -> Self {
+        Self {
+            baseline,
+            fraction: fraction.clamp(0.0, 1.0),
+            learn_baseline: true,
+        }
+    }
+
+    /// Whether baseline outcomes are learned as well (default true)
+    pub fn with_learn_baseline(mut self, learn: bool) -> Self {
+        self.learn_baseline = learn;
+        self
+    }
+
+    pub fn baseline(&self) -> &[f64] {
+        &self.baseline
+    }
+
+    pub fn fraction(&self) -> f64 {
+        self.fraction
+    }
+}
+
+/// Running fitness of one arm
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Welford {
+    samples: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl Welford {
+    fn record(&mut self, fitness: f64) {
+        self.samples += 1;
+        let delta = fitness - self.mean;
+        self.mean += delta / self.samples as f64;
+        self.m2 += delta * (fitness - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        if self.samples < 2 {
+            0.0
+        } else {
+            self.m2 / (self.samples - 1) as f64
+        }
+    }
+}
+
+/// Holdout bookkeeping for one system
+#[derive(Debug, Clone)]
+pub(crate) struct Holdout {
+    config: HoldoutConfig,
+    learned: Welford,
+    baseline: Welford,
+}
+
+/// Fitness of one arm since holdout mode was enabled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArmSummary {
+    pub samples: usize,
+    pub mean_fitness: f64,
+    /// Sample standard deviation (0 with fewer than two samples)
+    pub std_dev: f64,
+}
+
+/// Online comparison of the learned policy against the baseline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HoldoutReport {
+    pub learned: ArmSummary,
+    pub baseline: ArmSummary,
+    /// Learned mean fitness minus baseline mean fitness
+    pub lift: f64,
+    /// Standard error of `lift` (infinite until both arms have two samples)
+    pub std_error: f64,
+}
+
+impl HoldoutReport {
+    /// `lift / std_error`; above about 2 the learned policy is reliably better
+    pub fn z_score(&self) -> f64 {
+        if self.std_error > 0.0 && self.std_error.is_finite() {
+            self.lift / self.std_error
+        } else {
+            0.0
+        }
+    }
+}
+
+fn summary(arm: &Welford) -> ArmSummary {
+    ArmSummary {
+        samples: arm.samples,
+        mean_fitness: arm.mean,
+        std_dev: arm.variance().sqrt(),
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Turn on holdout mode, replacing any previous configuration and its statistics
+    pub fn enable_holdout(&mut self, config: HoldoutConfig) -> Result<(), String> {
+        if config.baseline.len() != self.param_count {
+            return Err(format!(
+                "Baseline parameter count mismatch: expected {}, got {}",
+                self.param_count,
+                config.baseline.len()
+            ));
+        }
+        self.check_bounds(&config.baseline).map_err(|e| e.to_string())?;
+        self.holdout = Some(Holdout {
+            config,
+            learned: Welford::default(),
+            baseline: Welford::default(),
+        });
+        Ok(())
+    }
+
+    /// Turn off holdout mode and drop its statistics
+    pub fn disable_holdout(&mut self) {
+        self.holdout = None;
+    }
+
+    /// Sample from the learned policy or the baseline, returning which one served
+    ///
+    /// Pass the returned arm to [`learn_holdout`](Self::learn_holdout) with
+    /// the fitness the parameters achieved. Without holdout mode enabled,
+    /// this is `sample()` and always reports [`HoldoutArm::Learned`].
+    pub fn sample_holdout(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+    ) -> Result<(Vec<f64>, HoldoutArm), String> {
+        if let Some(holdout) = &self.holdout {
+            self.context_key(dimension_values)?;
+            if self.rng().gen::<f64>() < holdout.config.fraction {
+                return Ok((holdout.config.baseline.clone(), HoldoutArm::Baseline));
+            }
+        }
+        Ok((self.sample(dimension_values, exploration)?, HoldoutArm::Learned))
+    }
+
+    /// Learn from a holdout sample and record its fitness for the arm that served it
+    ///
+    /// The fitness must pass the [fitness spec](EvoCoreContextSystem::with_fitness_spec),
+    /// if any, and be finite even without one: a single NaN would poison
+    /// the arm's mean for good.
+    pub fn learn_holdout(
+        &mut self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+        arm: HoldoutArm,
+    ) -> Result<(), String> {
+        self.check_fitness(fitness).map_err(|e| e.to_string())?;
+        if !fitness.is_finite() {
+            return Err(format!("Fitness must be finite, got {}", fitness));
+        }
+        let learn = match (&self.holdout, arm) {
+            (Some(holdout), HoldoutArm::Baseline) => holdout.config.learn_baseline,
+            _ => true,
+        };
+        if learn {
+            self.learn(dimension_values, parameters, fitness)?;
+        } else {
+            self.context_key(dimension_values)?;
+        }
+
+        if let Some(holdout) = &mut self.holdout {
+            match arm {
+                HoldoutArm::Learned => holdout.learned.record(fitness),
+                HoldoutArm::Baseline => holdout.baseline.record(fitness),
+            }
+        }
+        Ok(())
+    }
+
+    /// Learned-versus-baseline comparison, if holdout mode is enabled
+    pub fn holdout_report(&self) -> Option<HoldoutReport> {
+        let holdout = self.holdout.as_ref()?;
+        let (learned, baseline) = (&holdout.learned, &holdout.baseline);
+        let std_error = if learned.samples < 2 || baseline.samples < 2 {
+            f64::INFINITY
+        } else {
+            (learned.variance() / learned.samples as f64 + baseline.variance() / baseline.samples as f64).sqrt()
+        };
+        Some(HoldoutReport {
+            learned: summary(learned),
+            baseline: summary(baseline),
+            lift: learned.mean - baseline.mean,
+            std_error,
+        })
+    }
+}
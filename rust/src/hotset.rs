@@ -0,0 +1,188 @@
+//! Hot-context working set, pinning and prefetching
+//!
+//! After a restart the first call for each context pays for building its
+//! key, the C library's hash lookup and faulting in its statistics, and
+//! under load those first touches dominate p99 latency. With tracking
+//! enabled ([`enable_hot_tracking`](EvoCoreContextSystem::enable_hot_tracking))
+//! every `learn()` and `sample()` counts a use of its context, and
+//! [`hot_contexts`](EvoCoreContextSystem::hot_contexts) reports the busiest.
+//!
+//! Pinned contexts are kept warm in the wrapper's caching layers: their
+//! keys are added to the [key cache](EvoCoreContextSystem::enable_key_cache)
+//! even when it is full, and [capacity](EvoCoreContextSystem::with_max_contexts)
+//! eviction skips them. [`save_working_set`](EvoCoreContextSystem::save_working_set)
+//! writes the hottest keys next to a checkpoint, and
+//! [`prefetch_working_set`](EvoCoreContextSystem::prefetch_working_set)
+//! reads them back after load, pinning and touching each one before
+//! traffic arrives.
+
+use crate::EvoCoreContextSystem;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, PoisonError};
+
+/// Use counts, halved whenever more than `limit` contexts are tracked so
+/// that old traffic fades and memory stays bounded
+#[derive(Debug)]
+struct UseCounts {
+    limit: usize,
+    counts: HashMap<String, u64>,
+}
+
+/// Working-set bookkeeping for one system
+#[derive(Debug, Default)]
+pub(crate) struct HotSet {
+    tracking: Option<Mutex<UseCounts>>,
+    pinned: HashSet<String>,
+}
+
+impl HotSet {
+    pub(crate) fn is_tracking(&self) -> bool {
+        self.tracking.is_some()
+    }
+
+    pub(crate) fn is_pinned(&self, key: &str) -> bool {
+        self.pinned.contains(key)
+    }
+
+    pub(crate) fn pin(&mut self, key: String) {
+        self.pinned.insert(key);
+    }
+
+    pub(crate) fn record(&self, key: &str) {
+        let Some(tracking) = &self.tracking else {
+            return;
+        };
+        let mut uses = tracking.lock().unwrap_or_else(PoisonError::into_inner);
+        match uses.counts.get_mut(key) {
+            Some(count) => *count += 1,
+            None => {
+                if uses.counts.len() >= uses.limit {
+                    uses.counts.retain(|_, count| {
+                        *count /= 2;
+                        *count > 0
+                    });
+                }
+                uses.counts.insert(key.to_string(), 1);
+            }
+        }
+    }
+
+    pub(crate) fn unpin(&mut self, key: &str) {
+        self.pinned.remove(key);
+    }
+
+    /// Drop the use count for `key`; an explicit pin stays
+    pub(crate) fn forget(&mut self, key: &str) {
+        if let Some(tracking) = &self.tracking {
+            tracking.lock().unwrap_or_else(PoisonError::into_inner).counts.remove(key);
+        }
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Count uses of each context, keeping up to `max_tracked` counters
+    ///
+    /// Once more contexts than that have been seen, every count is halved
+    /// and the ones that reach zero are dropped.
+    pub fn enable_hot_tracking(&mut self, max_tracked: usize) {
+        self.hot.tracking = Some(Mutex::new(UseCounts {
+            limit: max_tracked.max(1),
+            counts: HashMap::new(),
+        }));
+    }
+
+    /// Stop counting uses, keeping pinned contexts pinned
+    pub fn disable_hot_tracking(&mut self) {
+        self.hot.tracking = None;
+    }
+
+    /// The `n` most used context keys with their (decayed) use counts, busiest first
+    pub fn hot_contexts(&self, n: usize) -> Vec<(String, u64)> {
+        let Some(tracking) = &self.hot.tracking else {
+            return Vec::new();
+        };
+        let mut hot: Vec<(String, u64)> = tracking
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .counts
+            .iter()
+            .map(|(k, c)| (k.clone(), *c))
+            .collect();
+        hot.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hot.truncate(n);
+        hot
+    }
+
+    /// Keep a context warm: always in the key cache, never evicted for capacity
+    pub fn pin_context(&mut self, dimension_values: &[&str]) -> Result<(), String> {
+        let key = self.context_key(dimension_values)?;
+        if let Some(cache) = &self.key_cache {
+            cache.pin(dimension_values)?;
+        }
+        self.hot.pinned.insert(key);
+        Ok(())
+    }
+
+    /// Pin the `n` most used contexts; returns how many were pinned
+    pub fn pin_hot_contexts(&mut self, n: usize) -> usize {
+        let keys: Vec<String> = self.hot_contexts(n).into_iter().map(|(k, _)| k).collect();
+        keys.iter().filter(|key| self.pin_key(key)).count()
+    }
+
+    pub fn unpin_context(&mut self, dimension_values: &[&str]) -> Result<(), String> {
+        let key = self.context_key(dimension_values)?;
+        self.hot.pinned.remove(&key);
+        Ok(())
+    }
+
+    pub fn unpin_all(&mut self) {
+        self.hot.pinned.clear();
+    }
+
+    /// Keys of the pinned contexts, sorted
+    pub fn pinned_contexts(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.hot.pinned.iter().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// Pin and touch each context key; returns how many exist in the system
+    ///
+    /// Touching copies the context's statistics out of the C library, which
+    /// faults them in before the first real call needs them.
+    pub fn prefetch_contexts(&mut self, keys: &[String]) -> usize {
+        let mut found = 0;
+        for key in keys {
+            self.pin_key(key);
+            if self.context_state(key).is_some() {
+                found += 1;
+            }
+        }
+        found
+    }
+
+    /// Write the `n` hottest context keys to `filepath`, one per line
+    pub fn save_working_set(&self, filepath: &str, n: usize) -> Result<(), String> {
+        let mut data = String::new();
+        for (key, _) in self.hot_contexts(n) {
+            data.push_str(&key);
+            data.push('\n');
+        }
+        std::fs::write(filepath, data).map_err(|e| format!("Failed to save working set: {}", e))
+    }
+
+    /// Pin and prefetch the keys written by [`save_working_set`](Self::save_working_set)
+    ///
+    /// Returns how many of them exist in the system.
+    pub fn prefetch_working_set(&mut self, filepath: &str) -> Result<usize, String> {
+        let data = std::fs::read_to_string(filepath).map_err(|e| format!("Failed to load working set: {}", e))?;
+        let keys: Vec<String> = data.lines().filter(|l| !l.is_empty()).map(str::to_string).collect();
+        Ok(self.prefetch_contexts(&keys))
+    }
+
+    /// Pin a joined context key; false if it does not fit the dimensions
+    fn pin_key(&mut self, key: &str) -> bool {
+        let dims: Vec<&str> = key.split(':').collect();
+        self.pin_context(&dims).is_ok()
+    }
+}
@@ -0,0 +1,178 @@
+//! Cache of prebuilt context keys
+//!
+//! Every `learn()`/`sample()` call normally converts each dimension value
+//! to a `CString` before the C library joins them into a key. With the key
+//! cache enabled ([`EvoCoreContextSystem::enable_key_cache`]) the joined key
+//! is built once per distinct context and reused, so calls for hot contexts
+//! stop allocating entirely.
+
+use crate::{EvoCoreContextSystem, MAX_KEY_LENGTH};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{PoisonError, RwLock};
+
+/// Hit/miss counters and size of the key cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+type Bucket = Vec<(Box<[String]>, CString)>;
+
+/// Dimension-value tuples mapped to their C key, bucketed by hash so a hit
+/// can be found from borrowed `&[&str]` without allocating
+pub(crate) struct KeyCache {
+    capacity: usize,
+    buckets: RwLock<(HashMap<u64, Bucket>, usize)>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+fn tuple_hash(dimension_values: &[&str]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    dimension_values.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl KeyCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            buckets: RwLock::new((HashMap::new(), 0)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Call `f` with the C key for `dimension_values`, building and caching it on a miss
+    ///
+    /// Once `capacity` keys are cached, new keys are built but not stored.
+    pub(crate) fn with_key<R>(
+        &self,
+        dimension_values: &[&str],
+        f: impl FnOnce(&CStr) -> R,
+    ) -> Result<R, String> {
+        let hash = tuple_hash(dimension_values);
+
+        {
+            let guard = self.buckets.read().unwrap_or_else(PoisonError::into_inner);
+            let cached = guard.0.get(&hash).and_then(|bucket| {
+                bucket
+                    .iter()
+                    .find(|(values, _)| values.iter().map(String::as_str).eq(dimension_values.iter().copied()))
+            });
+            if let Some((_, key)) = cached {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(f(key));
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let joined = dimension_values.join(":");
+        if joined.len() >= MAX_KEY_LENGTH {
+            return Err("Failed to build context key".to_string());
+        }
+        let key = CString::new(joined)
+            .map_err(|_| format!("Invalid dimension values: {:?}", dimension_values))?;
+        let result = f(&key);
+
+        let mut guard = self.buckets.write().unwrap_or_else(PoisonError::into_inner);
+        let (buckets, entries) = &mut *guard;
+        if *entries < self.capacity {
+            let bucket = buckets.entry(hash).or_default();
+            // Another thread may have cached the same key meanwhile
+            if !bucket
+                .iter()
+                .any(|(values, _)| values.iter().map(String::as_str).eq(dimension_values.iter().copied()))
+            {
+                let values = dimension_values.iter().map(|v| v.to_string()).collect();
+                bucket.push((values, key));
+                *entries += 1;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Cache the key for `dimension_values` even if the cache is full
+    pub(crate) fn pin(&self, dimension_values: &[&str]) -> Result<(), String> {
+        let joined = dimension_values.join(":");
+        if joined.len() >= MAX_KEY_LENGTH {
+            return Err("Failed to build context key".to_string());
+        }
+        let key = CString::new(joined)
+            .map_err(|_| format!("Invalid dimension values: {:?}", dimension_values))?;
+
+        let hash = tuple_hash(dimension_values);
+        let mut guard = self.buckets.write().unwrap_or_else(PoisonError::into_inner);
+        let (buckets, entries) = &mut *guard;
+        let bucket = buckets.entry(hash).or_default();
+        if !bucket
+            .iter()
+            .any(|(values, _)| values.iter().map(String::as_str).eq(dimension_values.iter().copied()))
+        {
+            bucket.push((dimension_values.iter().map(|v| v.to_string()).collect(), key));
+            *entries += 1;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn stats(&self) -> KeyCacheStats {
+        KeyCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.buckets.read().unwrap_or_else(PoisonError::into_inner).1,
+            capacity: self.capacity,
+        }
+    }
+
+    /// Approximate heap bytes held by cached keys
+    pub(crate) fn approx_bytes(&self) -> usize {
+        let guard = self.buckets.read().unwrap_or_else(PoisonError::into_inner);
+        guard
+            .0
+            .values()
+            .flatten()
+            .map(|(values, key)| {
+                values.iter().map(|v| v.len() + std::mem::size_of::<String>()).sum::<usize>()
+                    + key.as_bytes_with_nul().len()
+            })
+            .sum()
+    }
+
+    pub(crate) fn clear(&self) {
+        *self.buckets.write().unwrap_or_else(PoisonError::into_inner) = (HashMap::new(), 0);
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Cache prebuilt keys for up to `capacity` distinct contexts
+    ///
+    /// Replaces (and empties) any existing cache.
+    pub fn enable_key_cache(&mut self, capacity: usize) {
+        self.key_cache = Some(KeyCache::new(capacity));
+    }
+
+    /// Stop caching keys and free the cache
+    pub fn disable_key_cache(&mut self) {
+        self.key_cache = None;
+    }
+
+    /// Counters for the key cache, or `None` if it is not enabled
+    pub fn key_cache_stats(&self) -> Option<KeyCacheStats> {
+        self.key_cache.as_ref().map(KeyCache::stats)
+    }
+
+    /// Drop every cached key, keeping the cache enabled and its counters
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.key_cache {
+            cache.clear();
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! Backend-agnostic learner interface
+//!
+//! [`ContextLearner`] (from `evocore-core`) is implemented by the
+//! FFI-backed [`EvoCoreContextSystem`] and the pure-Rust
+//! [`RustContextSystem`](crate::RustContextSystem), so wrappers and test
+//! harnesses can work with either.
+
+use crate::{ContextLearner, ContextState, EvoCoreContextSystem};
+
+impl ContextLearner for EvoCoreContextSystem {
+    fn learn(
+        &mut self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+    ) -> Result<(), String> {
+        EvoCoreContextSystem::learn(self, dimension_values, parameters, fitness)
+    }
+
+    fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        EvoCoreContextSystem::sample(self, dimension_values, exploration)
+    }
+
+    fn param_count(&self) -> usize {
+        EvoCoreContextSystem::param_count(self)
+    }
+
+    fn context_count(&self) -> usize {
+        EvoCoreContextSystem::context_count(self)
+    }
+
+    fn context_keys(&self) -> Vec<String> {
+        EvoCoreContextSystem::context_keys(self)
+    }
+
+    fn context_state(&self, key: &str) -> Option<ContextState> {
+        EvoCoreContextSystem::context_state(self, key)
+    }
+}
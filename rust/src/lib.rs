@@ -2,14 +2,76 @@
 //!
 //! This crate provides Rust bindings to the EvoCore C library, enabling
 //! meta-evolutionary optimization for adaptive AI behavior.
+//!
+//! Platform-independent types (parameter specs, learned state, the
+//! pure-Rust [`RustContextSystem`]) come from `evocore-core`, which also
+//! builds for `no_std` targets, and are re-exported here.
 
-use std::ffi::{c_char, CString};
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use canary::Canary;
+use capacity::Lru;
+use diagnose::ExplorationCounter;
+use explain::ExplanationLog;
+use ffi_timing::FfiOp;
+use evocore_core::{check_key_values, key_into, SeedStream, MAX_KEY_LENGTH};
+use key_cache::KeyCache;
 use std::ptr::NonNull;
+use std::sync::Arc;
+
+/// `evocore_error_t`: 0 on success, negative error codes
+#[allow(non_camel_case_types)]
+pub type evocore_error_t = i32;
+pub const EVOCORE_OK: evocore_error_t = 0;
+
+/// `evocore_log_level_t`: `EVOCORE_LOG_TRACE` (0) to `EVOCORE_LOG_FATAL` (5)
+#[allow(non_camel_case_types)]
+pub type evocore_log_level_t = c_int;
+pub const EVOCORE_LOG_TRACE: evocore_log_level_t = 0;
+pub const EVOCORE_LOG_DEBUG: evocore_log_level_t = 1;
+pub const EVOCORE_LOG_INFO: evocore_log_level_t = 2;
+pub const EVOCORE_LOG_WARN: evocore_log_level_t = 3;
+pub const EVOCORE_LOG_ERROR: evocore_log_level_t = 4;
+pub const EVOCORE_LOG_FATAL: evocore_log_level_t = 5;
+
+/// `evocore_log_callback_t`
+#[allow(non_camel_case_types)]
+pub type evocore_log_callback_t = Option<
+    unsafe extern "C" fn(
+        level: evocore_log_level_t,
+        file: *const c_char,
+        line: c_int,
+        message: *const c_char,
+        user_data: *mut c_void,
+    ),
+>;
 
-// Opaque types for EvoCore structs
 #[repr(C)]
 pub struct evocore_genome_t {
-    _private: [u8; 0],
+    pub data: *mut c_void,
+    pub size: usize,
+    pub capacity: usize,
+    pub owns_memory: bool,
+}
+
+#[repr(C)]
+pub struct evocore_individual_t {
+    pub genome: *mut evocore_genome_t,
+    pub fitness: f64,
+}
+
+#[repr(C)]
+pub struct evocore_population_t {
+    pub individuals: *mut evocore_individual_t,
+    pub size: usize,
+    pub capacity: usize,
+    pub generation: usize,
+    pub best_fitness: f64,
+    pub avg_fitness: f64,
+    pub worst_fitness: f64,
+    pub best_index: usize,
 }
 
 #[repr(C)]
@@ -21,115 +83,337 @@ pub struct evocore_context_dimension_t {
 
 #[repr(C)]
 pub struct evocore_context_system_t {
-    _private: [u8; 0],
+    pub dimensions: *mut evocore_context_dimension_t,
+    pub dimension_count: usize,
+    pub internal: *mut c_void,
+    pub param_count: usize,
+    pub total_contexts: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct evocore_weighted_stats_t {
+    pub mean: f64,
+    pub variance: f64,
+    pub sum_weights: f64,
+    pub m2: f64,
+    pub count: usize,
+    pub min_value: f64,
+    pub max_value: f64,
+    pub sum_weighted_x: f64,
 }
 
 #[repr(C)]
 pub struct evocore_weighted_array_t {
-    _private: [u8; 0],
+    pub stats: *mut evocore_weighted_stats_t,
+    pub count: usize,
 }
 
+/// C `time_t`, which libc does not define for targets without a C runtime
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+#[allow(non_camel_case_types)]
+type time_t = libc::time_t;
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[allow(non_camel_case_types)]
+type time_t = i64;
+
 #[repr(C)]
 pub struct evocore_context_stats_t {
-    _private: [u8; 0],
+    pub key: *mut c_char,
+    pub stats: *mut evocore_weighted_array_t,
+    pub param_count: usize,
+    pub confidence: f64,
+    pub first_update: time_t,
+    pub last_update: time_t,
+    pub total_experiences: usize,
+    pub avg_fitness: f64,
+    pub best_fitness: f64,
+    pub negative: *mut c_void,
+    pub failure_count: usize,
+    pub avg_failure_fitness: f64,
 }
 
-extern "C" {
-    // Context system
-    pub fn evocore_context_system_create(
-        dimensions: *const evocore_context_dimension_t,
-        dimension_count: usize,
-        param_count: usize,
-    ) -> *mut evocore_context_system_t;
-
-    pub fn evocore_context_system_free(system: *mut evocore_context_system_t);
-
-    pub fn evocore_context_add_dimension(
-        system: *mut evocore_context_system_t,
-        name: *const c_char,
-        values: *const *const c_char,
-        value_count: usize,
-    ) -> bool;
-
-    pub fn evocore_context_build_key(
-        system: *const evocore_context_system_t,
-        dimension_values: *const *const c_char,
-        out_key: *mut c_char,
-        key_size: usize,
-    ) -> bool;
-
-    // Learning operations
-    pub fn evocore_context_learn(
-        system: *mut evocore_context_system_t,
-        dimension_values: *const *const c_char,
-        parameters: *const f64,
-        param_count: usize,
-        fitness: f64,
-    ) -> bool;
+/// Declare the C library's functions from bindgen's `extern "C"` block
+///
+/// They are linked as usual, or with feature `dlopen` become functions of
+/// the same signature that call into the library loaded at runtime,
+/// resolving each symbol once. On targets without the library
+/// (`evocore_no_native`, set by the build script for WebAssembly) they
+/// exist only to keep the crate compiling; constructors refuse to create
+/// anything that would call them.
+macro_rules! evocore_functions {
+    (extern "C" { $(pub fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;)* }) => {
+        #[cfg(not(any(feature = "dlopen", evocore_no_native)))]
+        extern "C" {
+            $(pub fn $name($($arg: $ty),*) $(-> $ret)?;)*
+        }
 
-    pub fn evocore_context_learn_key(
-        system: *mut evocore_context_system_t,
-        context_key: *const c_char,
-        parameters: *const f64,
-        param_count: usize,
-        fitness: f64,
-    ) -> bool;
+        $(
+            #[cfg(all(feature = "dlopen", not(evocore_no_native)))]
+            #[allow(clippy::missing_safety_doc)]
+            pub unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+                static SYMBOL: std::sync::OnceLock<unsafe extern "C" fn($($ty),*) $(-> $ret)?> =
+                    std::sync::OnceLock::new();
+                let f = *SYMBOL.get_or_init(|| dlopen::symbol(concat!(stringify!($name), "\0").as_bytes()));
+                f($($arg),*)
+            }
+        )*
 
-    // Sampling
-    pub fn evocore_context_sample(
-        system: *const evocore_context_system_t,
-        dimension_values: *const *const c_char,
-        out_parameters: *mut f64,
-        param_count: usize,
-        exploration_factor: f64,
-        seed: *mut u32,
-    ) -> bool;
-
-    pub fn evocore_context_sample_key(
-        system: *const evocore_context_system_t,
-        context_key: *const c_char,
-        out_parameters: *mut f64,
-        param_count: usize,
-        exploration_factor: f64,
-        seed: *mut u32,
-    ) -> bool;
-
-    // Statistics
-    pub fn evocore_context_get_stats(
-        system: *mut evocore_context_system_t,
-        dimension_values: *const *const c_char,
-        out_stats: *mut *mut evocore_context_stats_t,
-    ) -> bool;
-
-    pub fn evocore_context_has_data(
-        stats: *const evocore_context_stats_t,
-        min_samples: usize,
-    ) -> bool;
-
-    // Persistence
-    pub fn evocore_context_save_json(
-        system: *const evocore_context_system_t,
-        filepath: *const c_char,
-    ) -> bool;
-
-    pub fn evocore_context_load_json(
-        filepath: *const c_char,
-        out_system: *mut *mut evocore_context_system_t,
-    ) -> bool;
-
-    pub fn evocore_context_save_binary(
-        system: *const evocore_context_system_t,
-        filepath: *const c_char,
-    ) -> bool;
-
-    pub fn evocore_context_load_binary(
-        filepath: *const c_char,
-        out_system: *mut *mut evocore_context_system_t,
-    ) -> bool;
-
-    // Utility
-    pub fn evocore_context_count(system: *const evocore_context_system_t) -> usize;
-    pub fn evocore_context_get_param_count(system: *const evocore_context_system_t) -> usize;
+        $(
+            #[cfg(evocore_no_native)]
+            #[allow(clippy::missing_safety_doc)]
+            pub unsafe fn $name($(_: $ty),*) $(-> $ret)? {
+                unreachable!("the EvoCore C library is not available on this target")
+            }
+        )*
+
+        /// Every declared function, NUL-terminated, for checking a loaded library
+        #[cfg(all(feature = "dlopen", not(evocore_no_native)))]
+        const EVOCORE_SYMBOLS: &[&str] = &[$(concat!(stringify!($name), "\0")),*];
+    };
+}
+
+// The function declarations, generated by bindgen (see build.rs)
+#[cfg(not(evocore_bindgen))]
+include!("bindings.rs");
+#[cfg(evocore_bindgen)]
+include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+mod anomaly;
+#[cfg(feature = "tokio")]
+mod async_io;
+mod chaos;
+mod bandit;
+mod batch;
+mod best;
+mod bayesopt;
+mod bounds;
+#[cfg(feature = "log")]
+mod c_log;
+mod canary;
+mod canonical;
+mod capacity;
+#[cfg(feature = "capi")]
+mod capi;
+mod census;
+#[cfg(feature = "cas")]
+mod cas;
+mod checkpoint;
+mod cmaes;
+mod coarsen;
+#[cfg(feature = "crypto")]
+mod crypto;
+mod deadline;
+mod decay;
+mod decision;
+mod diagnose;
+#[cfg(all(feature = "dlopen", not(evocore_no_native)))]
+mod dlopen;
+mod ensemble;
+mod estimate;
+#[cfg(feature = "examples")]
+pub mod examples;
+mod explain;
+#[cfg(evocore_bindgen)]
+mod ffi_check;
+mod ffi_timing;
+mod fitness;
+mod handle;
+mod hierarchy;
+mod holdout;
+mod hotset;
+mod key_cache;
+mod learner;
+mod marginal;
+mod memory;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+mod normalize;
+mod novelty;
+mod observer;
+mod overrides;
+mod population;
+mod privacy;
+mod profile;
+mod promote;
+#[cfg(feature = "proto")]
+mod proto;
+#[cfg(feature = "python")]
+mod python;
+mod prune;
+mod quickstart;
+mod ramp;
+mod ranking;
+mod rename;
+mod replica;
+mod report;
+mod reset;
+mod retire;
+mod rollback;
+mod rust_backend;
+mod schedule;
+mod self_tuning;
+mod serializer;
+mod shared;
+mod sharded;
+#[cfg(feature = "signing")]
+mod signing;
+mod similarity;
+mod slots;
+mod snapshot;
+mod snapshot_diff;
+mod state;
+mod strategy;
+mod subset;
+mod summary;
+mod sync;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod temperature;
+mod transfer;
+mod typed;
+mod update_weight;
+mod variation;
+mod versions;
+mod wildcard;
+
+pub use anomaly::{AnomalyConfig, FitnessAnomaly};
+#[cfg(feature = "tokio")]
+pub use async_io::AutosaveHandle;
+pub use bandit::{ArmStats, BanditPolicy, StrategyBandit};
+pub use batch::LearnExample;
+pub use bayesopt::BayesOpt;
+pub use bounds::LearnError;
+#[cfg(feature = "log")]
+pub use c_log::{capture_c_logs, release_c_logs};
+pub use canary::{CanaryArm, CanaryConfig, CanaryStatus};
+pub use canonical::CanonicalJsonSerializer;
+pub use capacity::CapacityStats;
+pub use census::{CensusConfig, ContextCensus};
+#[cfg(feature = "cas")]
+pub use cas::{CasPutStats, CasStore};
+pub use chaos::FaultInjector;
+pub use checkpoint::{Checkpoint, LoadError};
+pub use cmaes::CmaEs;
+pub use coarsen::{CoarseningPolicy, CoarseningStats};
+pub use deadline::{DeadlineStats, SamplePath};
+pub use decay::{DecayConfig, DecayMode};
+pub use decision::{Decision, DecisionStats, UnresolvedPolicy};
+pub use diagnose::Diagnostic;
+pub use ensemble::{Ensemble, EnsembleMember};
+pub use estimate::FitnessEstimate;
+pub use evocore_core::{
+    BoundsMode, ContextLearner, ContextState, ParamBounds, ParamKind, ParamSpec, ParamStats, ParamValue, RustContextSystem,
+};
+pub use explain::{ParamExplanation, SampleExplanation, SampleSource};
+pub use fitness::{Fitness, FitnessSpec};
+pub use handle::ContextSystemHandle;
+pub use hierarchy::{HierarchicalSample, HierarchyOptions};
+pub use holdout::{ArmSummary, HoldoutArm, HoldoutConfig, HoldoutReport};
+pub use key_cache::KeyCacheStats;
+pub use marginal::MarginalModel;
+pub use memory::MemoryStats;
+#[cfg(feature = "msgpack")]
+pub use msgpack::MessagePackSerializer;
+pub use normalize::FitnessTransform;
+pub use novelty::NoveltyArchive;
+pub use observer::ContextObserver;
+pub use overrides::ParamOverride;
+pub use population::Population;
+pub use privacy::PrivacyBudget;
+pub use profile::{Backend, Profile};
+pub use promote::{promote, PromotionChecks, PromotionError, PromotionReport};
+#[cfg(feature = "proto")]
+pub use proto::{ContextProto, DimensionProto, ParamProto, ProtoSerializer, SnapshotProto};
+pub use prune::PrunePolicy;
+pub use quickstart::{ParamProposal, QuickStart, QuickStartProposal};
+pub use ramp::UptimeRamp;
+pub use ranking::RankMetric;
+pub use replica::{ReadReplica, ReplicaStats};
+pub use report::{DimensionCoverage, LearningReport, ParamReport, ReportContext};
+pub use retire::RemovalPolicy;
+pub use rollback::{rollback_guard, GuardStatus, RollbackConfig, RollbackGuard};
+pub use schedule::ExplorationSchedule;
+pub use self_tuning::{Knob, SelfTuner, TunedKnobs};
+pub use serializer::{BinarySerializer, Format, JsonSerializer, SaveOptions, SystemSerializer};
+pub use shared::SharedContextSystem;
+pub use sharded::ShardedContextSystem;
+#[cfg(feature = "signing")]
+pub use signing::{sign_checkpoint_file, signature_path, verify_checkpoint_file};
+pub use similarity::{BootstrapOptions, BootstrapReport, DistanceFn};
+pub use snapshot::{SNAPSHOT_SCHEMA, SNAPSHOT_VERSION};
+pub use snapshot_diff::{ContextChange, SnapshotDiff, SNAPSHOT_DIFF_SCHEMA, SNAPSHOT_DIFF_VERSION};
+pub use strategy::{
+    builtin_strategy, EpsilonGreedy, LearnedDistribution, SamplingStrategy, Softmax, StrategyInput, Thompson, Ucb1,
+};
+pub use summary::{ContextDetail, ContextSummary};
+pub use sync::SyncDelta;
+pub use transfer::{ChunkImporter, ContextChunk, ExportChunks};
+pub use variation::{Crossover, Mutation, VariationOperators};
+pub use wildcard::WILDCARD;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+/// Whether the C library can be used
+///
+/// Always true when it is linked, and always false on WebAssembly targets
+/// without it. With feature `dlopen`, loads it if that has not happened yet
+/// and reports whether that worked.
+pub fn native_available() -> bool {
+    require_native().is_ok()
+}
+
+/// Create a learner on the C backend, or on the Rust backend if the C library cannot be used
+///
+/// [`EvoCoreContextSystem::new`] fails when the library is missing (feature
+/// `dlopen`, or WebAssembly targets). Code that only needs a
+/// [`ContextLearner`] can opt into this fallback instead; unlike
+/// [`Profile::build`] it logs nothing. Check [`native_available`] to find
+/// out which backend was built.
+pub fn new_learner(
+    dimension_names: &[&str],
+    dimension_values: &[Vec<&str>],
+    param_count: usize,
+) -> Result<Box<dyn ContextLearner + Send>, String> {
+    Ok(if native_available() {
+        Box::new(EvoCoreContextSystem::new(dimension_names, dimension_values, param_count)?)
+    } else {
+        Box::new(RustContextSystem::new(dimension_names, dimension_values, param_count)?)
+    })
+}
+
+/// Why the C library cannot be used, if it cannot
+fn require_native() -> Result<(), String> {
+    if cfg!(evocore_no_native) {
+        return Err("The EvoCore C library is not available on this target; use the Rust backend".to_string());
+    }
+    #[cfg(all(feature = "dlopen", not(evocore_no_native)))]
+    dlopen::library().map_err(str::to_string)?;
+    Ok(())
+}
+
+/// Free memory the C library allocated
+unsafe fn c_free(ptr: *mut c_void) {
+    // Without libc there is no C library either, so nothing to free
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    libc::free(ptr);
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    let _ = ptr;
+}
+
+/// Copy a string into memory the C library may free (null on failure)
+unsafe fn c_strdup(s: &CStr) -> *mut c_char {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    return libc::strdup(s.as_ptr());
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    {
+        let _ = s;
+        std::ptr::null_mut()
+    }
 }
 
 /// Simple Rust wrapper for EvoCore context system
@@ -138,6 +422,40 @@ extern "C" {
 pub struct EvoCoreContextSystem {
     inner: NonNull<evocore_context_system_t>,
     param_count: usize,
+    seeds: Option<SeedStream>,
+    exploration: ExplorationCounter,
+    key_cache: Option<KeyCache>,
+    explanations: Option<ExplanationLog>,
+    overrides: HashMap<String, ParamOverride>,
+    canary: Option<Canary>,
+    lru: Option<Lru>,
+    stable_slots: HashMap<String, ContextState>,
+    versions: HashMap<String, u64>,
+    version_clock: u64,
+    recent_fitness: HashMap<String, ranking::RecentFitness>,
+    decay: Option<DecayConfig>,
+    wildcard_fallback: Option<usize>,
+    param_names: Vec<String>,
+    bounds: Vec<Option<ParamBounds>>,
+    bounds_mode: BoundsMode,
+    param_kinds: Vec<ParamKind>,
+    schedule: ExplorationSchedule,
+    strategy: Option<Arc<dyn SamplingStrategy>>,
+    observer: Option<Box<dyn ContextObserver>>,
+    uptime_ramp: Option<UptimeRamp>,
+    novelty: Option<novelty::NoveltyState>,
+    decisions: decision::DecisionTracker,
+    normalizer: Option<normalize::Normalizer>,
+    holdout: Option<holdout::Holdout>,
+    fitness_spec: Option<FitnessSpec>,
+    anomalies: Option<anomaly::AnomalyDetector>,
+    coarsening: Option<coarsen::Coarsening>,
+    marginals: Option<marginal::Marginals>,
+    update_weights: Option<update_weight::UpdateWeights>,
+    latency: deadline::LatencyTracker,
+    hot: hotset::HotSet,
+    #[cfg(feature = "metrics")]
+    metrics: Option<metrics::Metrics>,
 }
 
 impl EvoCoreContextSystem {
@@ -155,6 +473,7 @@ impl EvoCoreContextSystem {
         if dimension_names.len() != dimension_values.len() {
             return Err("Dimension names and values must have same length".to_string());
         }
+        require_native()?;
 
         unsafe {
             // Build dimension structures for the C API
@@ -163,19 +482,19 @@ impl EvoCoreContextSystem {
 
             // We need to keep the CString and pointer data alive during the call
             let mut _value_strings: Vec<Vec<CString>> = Vec::with_capacity(dimension_values.len());
-            let mut _value_ptrs: Vec<Vec<*mut i8>> = Vec::with_capacity(dimension_values.len());
+            let mut _value_ptrs: Vec<Vec<*mut c_char>> = Vec::with_capacity(dimension_values.len());
 
             for (name, values) in dimension_names.iter().zip(dimension_values.iter()) {
                 let c_name = CString::new(*name).unwrap();
                 let c_values: Vec<CString> =
                     values.iter().map(|v| CString::new(*v).unwrap()).collect();
-                let c_ptrs: Vec<*mut i8> = c_values.iter().map(|s| s.as_ptr() as *mut i8).collect();
+                let c_ptrs: Vec<*mut c_char> = c_values.iter().map(|s| s.as_ptr().cast_mut()).collect();
 
                 // Create the dimension struct - note we take ownership of the c_name pointer
                 let dim = evocore_context_dimension_t {
                     name: c_name.into_raw(),
                     value_count: c_values.len(),
-                    values: c_ptrs.as_ptr() as *mut *mut i8,
+                    values: c_ptrs.as_ptr().cast_mut(),
                 };
 
                 _value_ptrs.push(c_ptrs);
@@ -201,22 +520,196 @@ impl EvoCoreContextSystem {
             Ok(Self {
                 inner: NonNull::new(system).expect("context system was null"),
                 param_count,
+                seeds: None,
+                exploration: ExplorationCounter::default(),
+                key_cache: None,
+                explanations: None,
+                overrides: HashMap::new(),
+                canary: None,
+                lru: None,
+                stable_slots: HashMap::new(),
+                versions: HashMap::new(),
+                version_clock: 0,
+                recent_fitness: HashMap::new(),
+                decay: None,
+                wildcard_fallback: None,
+                param_names: Vec::new(),
+                bounds: Vec::new(),
+                bounds_mode: BoundsMode::Clamp,
+                param_kinds: Vec::new(),
+                schedule: ExplorationSchedule::default(),
+                strategy: None,
+                observer: None,
+                uptime_ramp: None,
+                novelty: None,
+                decisions: Default::default(),
+                normalizer: None,
+                holdout: None,
+                fitness_spec: None,
+                anomalies: None,
+                coarsening: None,
+                marginals: None,
+                update_weights: None,
+                latency: Default::default(),
+                hot: Default::default(),
+                #[cfg(feature = "metrics")]
+                metrics: None,
             })
         }
     }
 
+    /// Create a context system whose randomness all derives from `master_seed`
+    ///
+    /// Sampling seeds and any RNG handed out by [`rng`](Self::rng) are drawn
+    /// from one stream, so replaying the same sequence of calls reproduces
+    /// the same learning trajectory exactly.
+    pub fn deterministic(
+        dimension_names: &[&str],
+        dimension_values: &[Vec<&str>],
+        param_count: usize,
+        master_seed: u64,
+    ) -> Result<Self, String> {
+        let mut system = Self::new(dimension_names, dimension_values, param_count)?;
+        system.set_master_seed(master_seed);
+        Ok(system)
+    }
+
+    /// Switch to deterministic mode, restarting the seed stream from `master_seed`
+    pub fn set_master_seed(&mut self, master_seed: u64) {
+        self.seeds = Some(SeedStream::new(master_seed));
+    }
+
+    /// Whether this system runs in deterministic mode
+    pub fn is_deterministic(&self) -> bool {
+        self.seeds.is_some()
+    }
+
+    /// Get an RNG for wrapper-side randomness (strategies, tie-breaking, noise)
+    ///
+    /// Derived from the master seed in deterministic mode, from OS entropy otherwise.
+    pub fn rng(&self) -> StdRng {
+        match &self.seeds {
+            Some(seeds) => seeds.rng(),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    fn next_seed(&self) -> u32 {
+        match &self.seeds {
+            Some(seeds) => seeds.next_u64() as u32,
+            None => rand::random::<u32>(),
+        }
+    }
+
     /// Learn from experience with parameters
     ///
     /// # Arguments
     /// * `dimension_values` - Values for each dimension
     /// * `parameters` - Parameter values that were used
     /// * `fitness` - Fitness score (higher is better)
+    ///
+    /// Values need not be declared for their dimension, but may not
+    /// contain `:`, the separator of saved context keys.
     pub fn learn(
         &mut self,
         dimension_values: &[&str],
         parameters: &[f64],
         fitness: f64,
     ) -> Result<(), String> {
+        self.learn_checked(dimension_values, parameters, fitness).map_err(|e| e.to_string())
+    }
+
+    /// Learn from experience, reporting a typed [`LearnError`]
+    ///
+    /// Same as [`learn`](Self::learn); parameters outside their
+    /// [`ParamBounds`] are rejected before anything is learned.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            name = "evocore.learn",
+            skip_all,
+            fields(key = %dimension_values.join(":"), fitness = fitness),
+            err(level = "warn", Display)
+        )
+    )]
+    pub fn learn_checked(
+        &mut self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+    ) -> Result<(), LearnError> {
+        let result = self.learn_unmetered(dimension_values, parameters, fitness);
+        #[cfg(feature = "metrics")]
+        self.record_learn(result.is_ok());
+        result
+    }
+
+    fn learn_unmetered(
+        &mut self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+    ) -> Result<(), LearnError> {
+        // The C library reads one value per declared dimension
+        self.check_dimension_count(dimension_values).map_err(LearnError::Failed)?;
+        check_key_values(dimension_values).map_err(LearnError::Failed)?;
+        if parameters.len() != self.param_count {
+            return Err(LearnError::ParamCountMismatch {
+                expected: self.param_count,
+                found: parameters.len(),
+            });
+        }
+        self.check_bounds(parameters)?;
+        self.check_fitness(fitness)?;
+
+        let mut buf = [0u8; MAX_KEY_LENGTH];
+        let key = key_into(dimension_values, &mut buf);
+        if let Some(key) = key.and_then(|k| k.to_str().ok()) {
+            if self.quarantine_fitness(key, fitness) {
+                return Ok(());
+            }
+        }
+        if let Some(key) = key.filter(|_| self.decay.is_some()) {
+            self.decay_before_learn(key);
+        }
+
+        let fitness = match key.and_then(|k| k.to_str().ok()) {
+            Some(key) => self.normalize_fitness(key, fitness),
+            None => fitness,
+        };
+        let snapshot = key.and_then(|k| self.update_snapshot(k));
+        self.learn_raw(dimension_values, parameters, fitness).map_err(LearnError::Failed)?;
+        if let (Some(key), Some(snapshot)) = (key, snapshot) {
+            self.damp_update(key, snapshot);
+        }
+        if let Some(key) = key.and_then(|k| k.to_str().ok()) {
+            self.after_learn(key, parameters, fitness);
+        }
+        Ok(())
+    }
+
+    /// Bookkeeping after a context learned: version, recency, use counts, capacity, strategy
+    pub(crate) fn after_learn(&mut self, key: &str, parameters: &[f64], fitness: f64) {
+        self.bump_version(key);
+        self.track_recent_fitness(key, fitness);
+        self.hot.record(key);
+        if let Some(marginals) = &mut self.marginals {
+            marginals.observe(key, parameters, fitness);
+        }
+        if let Some(strategy) = &self.strategy {
+            strategy.observe(key, parameters, fitness);
+        }
+        if let Some(observer) = &self.observer {
+            observer.on_learn(key, parameters, fitness);
+        }
+        if self.lru.is_some() {
+            self.touch_key(key);
+            self.enforce_capacity();
+        }
+    }
+
+    fn learn_raw(&mut self, dimension_values: &[&str], parameters: &[f64], fitness: f64) -> Result<(), String> {
         if parameters.len() != self.param_count {
             return Err(format!(
                 "Parameter count mismatch: expected {}, got {}",
@@ -225,21 +718,46 @@ impl EvoCoreContextSystem {
             ));
         }
 
+        let mut timer = self.ffi_timer(FfiOp::Learn);
+        if let Some(cache) = &self.key_cache {
+            self.check_dimension_count(dimension_values)?;
+            let learned = cache.with_key(dimension_values, |key| {
+                timer.marshalled();
+                unsafe {
+                    evocore_context_learn_key(
+                        self.inner.as_ptr(),
+                        key.as_ptr(),
+                        parameters.as_ptr(),
+                        self.param_count,
+                        fitness,
+                    )
+                }
+            })?;
+            self.finish_ffi_timer(&mut timer);
+            if !learned {
+                return Err("Failed to learn from context".to_string());
+            }
+            return Ok(());
+        }
+
         unsafe {
             let c_strings: Vec<CString> = dimension_values
                 .iter()
                 .map(|s| CString::new(*s).unwrap())
                 .collect();
 
-            let c_ptrs: Vec<*const c_char> = c_strings.iter().map(|s| s.as_ptr()).collect();
+            let mut c_ptrs: Vec<*const c_char> = c_strings.iter().map(|s| s.as_ptr()).collect();
 
-            if !evocore_context_learn(
+            timer.marshalled();
+            let learned = evocore_context_learn(
                 self.inner.as_ptr(),
-                c_ptrs.as_ptr(),
+                c_ptrs.as_mut_ptr(),
                 parameters.as_ptr(),
                 self.param_count,
                 fitness,
-            ) {
+            );
+            self.finish_ffi_timer(&mut timer);
+            if !learned {
                 return Err("Failed to learn from context".to_string());
             }
 
@@ -260,39 +778,174 @@ impl EvoCoreContextSystem {
         dimension_values: &[&str],
         exploration: f64,
     ) -> Result<Vec<f64>, String> {
-        unsafe {
-            let c_strings: Vec<CString> = dimension_values
-                .iter()
-                .map(|s| CString::new(*s).unwrap())
-                .collect();
+        let mut params = vec![0.0; self.param_count];
+        self.sample_into(dimension_values, exploration, &mut params)?;
+        Ok(params)
+    }
 
-            let c_ptrs: Vec<*const c_char> = c_strings.iter().map(|s| s.as_ptr()).collect();
+    /// Sample parameters for a context into a caller-provided buffer
+    ///
+    /// Same as [`sample`](Self::sample) but allocation-free (unless
+    /// explanations are enabled), for real-time loops. `out` must hold
+    /// exactly `param_count` values.
+    pub fn sample_into(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+        out: &mut [f64],
+    ) -> Result<(), String> {
+        self.sample_tempered(dimension_values, exploration, 1.0, out).map(|_| ())
+    }
 
-            let mut params = vec![0.0; self.param_count];
-            let mut seed = rand::random::<u32>();
+    /// [`sample_into`](Self::sample_into) at a given [temperature](Self::sample_with_temperature)
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "evocore.sample",
+            skip_all,
+            fields(key = %dimension_values.join(":"), exploration = exploration, temperature = temperature),
+            err(level = "warn", Display)
+        )
+    )]
+    pub(crate) fn sample_tempered(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+        temperature: f64,
+        out: &mut [f64],
+    ) -> Result<Option<f64>, String> {
+        let result = self.sample_unmetered(dimension_values, exploration, temperature, out);
+        if result.is_ok() {
+            self.notify_sample(dimension_values, out, exploration);
+        }
+        #[cfg(feature = "metrics")]
+        self.record_samples(result.is_ok(), 1);
+        result
+    }
 
-            if !evocore_context_sample(
-                self.inner.as_ptr(),
-                c_ptrs.as_ptr(),
-                params.as_mut_ptr(),
+    /// Sample into `out`, returning the propensity reported by the sampling strategy
+    fn sample_unmetered(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+        temperature: f64,
+        out: &mut [f64],
+    ) -> Result<Option<f64>, String> {
+        if out.len() != self.param_count {
+            return Err(format!(
+                "Output buffer length mismatch: expected {}, got {}",
                 self.param_count,
-                exploration,
-                &mut seed,
-            ) {
-                return Err("Failed to sample parameters".to_string());
+                out.len()
+            ));
+        }
+        self.check_dimension_count(dimension_values)?;
+
+        if self.lru.is_some() || self.hot.is_tracking() {
+            let mut buf = [0u8; MAX_KEY_LENGTH];
+            if let Some(c_key) = key_into(dimension_values, &mut buf) {
+                if let Ok(key) = c_key.to_str() {
+                    // Unlearned keys would grow the recency list without bound
+                    if self.lru.is_some() && self.has_context(c_key) {
+                        self.touch_key(key);
+                    }
+                    self.hot.record(key);
+                }
+            }
+        }
+
+        if let Some(pinned) = self.active_override(dimension_values) {
+            out.copy_from_slice(pinned);
+            if let Some(explanations) = &self.explanations {
+                explanations.record_override(self, dimension_values, out);
+            }
+            return Ok(None);
+        }
+
+        let exploration = self.ramped_exploration(exploration);
+        self.exploration.record(exploration);
+        if self.serve_last_known_good(dimension_values, out) {
+            if let Some(explanations) = &self.explanations {
+                explanations.record(self, dimension_values, out, exploration);
+            }
+            return Ok(None);
+        }
+
+        let mut propensity = self.sample_dispatch(dimension_values, exploration, temperature, out)?;
+        if self.has_bounds() {
+            // A clamped or redrawn sample no longer has the proposal's propensity
+            let proposed = propensity.map(|_| out.to_vec());
+            self.apply_bounds(out, |scratch| {
+                self.sample_dispatch(dimension_values, exploration, temperature, scratch).map(|_| ())
+            })?;
+            if proposed.is_some_and(|proposed| proposed != out) {
+                propensity = None;
+            }
+        }
+
+        if let Some(explanations) = &self.explanations {
+            explanations.record(self, dimension_values, out, exploration);
+        }
+        Ok(propensity)
+    }
+
+    /// Draw one sample from the C library, honouring wildcard and marginal fallback and decay
+    pub(crate) fn sample_raw(&self, dimension_values: &[&str], exploration: f64, out: &mut [f64]) -> Result<(), String> {
+        let mut fallback_buf = [0u8; MAX_KEY_LENGTH];
+        let fallback = self.wildcard_fallback_key(dimension_values, &mut fallback_buf);
+        if fallback.is_none() && self.sample_marginal(dimension_values, exploration, out) {
+            return Ok(());
+        }
+
+        let mut seed = self.next_seed();
+        let mut timer = self.ffi_timer(FfiOp::Sample);
+        let mut sample_key = |key: &CStr| {
+            let exploration = self.decayed_exploration(key, exploration);
+            timer.marshalled();
+            unsafe {
+                evocore_context_sample_key(
+                    self.inner.as_ptr(),
+                    key.as_ptr(),
+                    out.as_mut_ptr(),
+                    self.param_count,
+                    exploration,
+                    &mut seed,
+                )
             }
+        };
 
-            Ok(params)
+        let sampled = match (fallback, &self.key_cache) {
+            (Some(key), _) => sample_key(key),
+            (None, Some(cache)) => cache.with_key(dimension_values, sample_key)?,
+            (None, None) => {
+                let mut buf = [0u8; MAX_KEY_LENGTH];
+                key_into(dimension_values, &mut buf).is_some_and(sample_key)
+            }
+        };
+        self.finish_ffi_timer(&mut timer);
+        if !sampled {
+            return Err("Failed to sample parameters".to_string());
         }
+        Ok(())
     }
 
     /// Save context system to file
     pub fn save(&self, filepath: &str) -> Result<(), String> {
+        self.save_with(filepath, &JsonSerializer, &SaveOptions::default())
+    }
+
+    /// Save context system to file in the compact binary format
+    pub fn save_binary(&self, filepath: &str) -> Result<(), String> {
+        self.save_with(filepath, &BinarySerializer, &SaveOptions::default())
+    }
+
+    /// Export per-context statistics to CSV
+    pub fn export_csv(&self, filepath: &str) -> Result<(), String> {
         unsafe {
             let c_path = CString::new(filepath).unwrap();
 
-            if !evocore_context_save_json(self.inner.as_ptr(), c_path.as_ptr()) {
-                return Err("Failed to save context system".to_string());
+            if !evocore_context_export_csv(self.inner.as_ptr(), c_path.as_ptr()) {
+                return Err("Failed to export context statistics".to_string());
             }
 
             Ok(())
@@ -300,28 +953,162 @@ impl EvoCoreContextSystem {
     }
 
     /// Load context system from file
+    ///
+    /// The file is parsed and validated in Rust first; use
+    /// [`load_validated`](Self::load_validated) to get a typed [`LoadError`].
     pub fn load(filepath: &str) -> Result<Self, String> {
+        Self::load_validated(filepath).map_err(|e| format!("Failed to load context system: {}", e))
+    }
+
+    /// Get number of contexts stored
+    ///
+    /// See [`context_census`](Self::context_census) for how many of them are warm, converged or stale.
+    pub fn context_count(&self) -> usize {
+        unsafe { evocore_context_count(self.inner.as_ptr()) }
+    }
+
+    /// Number of parameters tracked per context
+    pub fn param_count(&self) -> usize {
+        self.param_count
+    }
+
+    /// Get the dimension definitions as `(name, values)` pairs
+    pub fn dimensions(&self) -> Vec<(String, Vec<String>)> {
         unsafe {
-            let c_path = CString::new(filepath).unwrap();
-            let mut system = std::ptr::null_mut();
+            let system = self.inner.as_ref();
+            if system.dimensions.is_null() {
+                return Vec::new();
+            }
+
+            std::slice::from_raw_parts(system.dimensions, system.dimension_count)
+                .iter()
+                .map(|dim| {
+                    let name = CStr::from_ptr(dim.name).to_string_lossy().into_owned();
+                    let values = if dim.values.is_null() {
+                        Vec::new()
+                    } else {
+                        std::slice::from_raw_parts(dim.values, dim.value_count)
+                            .iter()
+                            .map(|v| CStr::from_ptr(*v).to_string_lossy().into_owned())
+                            .collect()
+                    };
+                    (name, values)
+                })
+                .collect()
+        }
+    }
+
+    fn check_dimension_count(&self, dimension_values: &[&str]) -> Result<(), String> {
+        let dimension_count = unsafe { self.inner.as_ref().dimension_count };
+        if dimension_values.len() != dimension_count {
+            return Err(format!(
+                "Dimension count mismatch: expected {}, got {}",
+                dimension_count,
+                dimension_values.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Build the context key the C library uses for these dimension values
+    pub fn context_key(&self, dimension_values: &[&str]) -> Result<String, String> {
+        self.check_dimension_count(dimension_values)?;
 
-            if !evocore_context_load_json(c_path.as_ptr(), &mut system) {
-                return Err("Failed to load context system".to_string());
+        let c_strings = dimension_values
+            .iter()
+            .map(|s| CString::new(*s).map_err(|_| format!("Invalid dimension value: {:?}", s)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut c_ptrs: Vec<*const c_char> = c_strings.iter().map(|s| s.as_ptr()).collect();
+        let mut buf = [0 as c_char; MAX_KEY_LENGTH];
+
+        unsafe {
+            if !evocore_context_build_key(self.inner.as_ptr(), c_ptrs.as_mut_ptr(), buf.as_mut_ptr(), buf.len()) {
+                return Err("Failed to build context key".to_string());
             }
 
-            // Get param_count from loaded system instead of hardcoding
-            let param_count = evocore_context_get_param_count(system);
+            Ok(CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned())
+        }
+    }
 
-            Ok(Self {
-                inner: NonNull::new(system).expect("loaded system was null"),
-                param_count,
-            })
+    /// Get all stored context keys
+    pub fn context_keys(&self) -> Vec<String> {
+        let count = self.context_count();
+        let mut raw: Vec<*mut c_char> = vec![std::ptr::null_mut(); count];
+
+        unsafe {
+            let n = evocore_context_get_keys(self.inner.as_ptr(), raw.as_mut_ptr(), count);
+            raw.truncate(n);
+
+            raw.into_iter()
+                .map(|ptr| {
+                    let key = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+                    c_free(ptr as *mut c_void);
+                    key
+                })
+                .collect()
         }
     }
 
-    /// Get number of contexts stored
-    pub fn context_count(&self) -> usize {
-        unsafe { evocore_context_count(self.inner.as_ptr()) }
+    /// Copy out the learned state of one context, or `None` if it has never been learned
+    pub fn context_state(&self, key: &str) -> Option<ContextState> {
+        let c_key = CString::new(key).ok()?;
+        let mut stats = std::ptr::null_mut();
+
+        unsafe {
+            if !evocore_context_get_stats_key(self.inner.as_ptr(), c_key.as_ptr(), &mut stats)
+                || stats.is_null()
+            {
+                return None;
+            }
+
+            Some(state::state_from_raw(&*stats))
+        }
+    }
+
+    /// Copy out the learned state of every context
+    pub fn context_states(&self) -> Vec<ContextState> {
+        self.context_keys()
+            .iter()
+            .filter_map(|key| self.context_state(key))
+            .collect()
+    }
+
+    /// Overwrite (or create) a context with previously captured state
+    pub fn restore_context_state(&mut self, state: &ContextState) -> Result<(), String> {
+        if state.params.len() != self.param_count {
+            return Err(format!(
+                "Parameter count mismatch: expected {}, got {}",
+                self.param_count,
+                state.params.len()
+            ));
+        }
+
+        let c_key = CString::new(state.key.as_str())
+            .map_err(|_| format!("Invalid context key: {:?}", state.key))?;
+
+        unsafe {
+            let mut stats = std::ptr::null_mut();
+            if !evocore_context_get_stats_key(self.inner.as_ptr(), c_key.as_ptr(), &mut stats) {
+                // The C API only creates contexts through learning, so seed an
+                // entry and overwrite everything below.
+                let zeros = vec![0.0; self.param_count];
+                if !evocore_context_learn_key(
+                    self.inner.as_ptr(),
+                    c_key.as_ptr(),
+                    zeros.as_ptr(),
+                    self.param_count,
+                    0.0,
+                ) || !evocore_context_get_stats_key(self.inner.as_ptr(), c_key.as_ptr(), &mut stats)
+                {
+                    return Err(format!("Failed to create context {}", state.key));
+                }
+            }
+
+            state::write_state_raw(state, &mut *stats);
+        }
+
+        self.bump_version(&state.key);
+        Ok(())
     }
 }
 
@@ -339,3 +1126,7 @@ impl Drop for EvoCoreContextSystem {
 
 // Re-export rand for convenience
 pub use rand;
+
+// Re-export the signing key types checkpoints are signed with
+#[cfg(feature = "signing")]
+pub use ed25519_dalek;
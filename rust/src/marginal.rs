@@ -0,0 +1,201 @@
+//! Per-dimension marginal models as a fallback tier
+//!
+//! In a sparse key space most contexts are cold, yet every one of their
+//! dimension values has usually been seen in other contexts. With
+//! [`with_marginal_fallback`](EvoCoreContextSystem::with_marginal_fallback)
+//! the system keeps, next to the full contexts, one model per dimension
+//! value (`lang=rust`, `editor=vim`, ...) and one global model, each
+//! learned from every experience that passes through it.
+//!
+//! A context with fewer than `min_samples` experiences, and no warm
+//! [wildcard default](EvoCoreContextSystem::with_wildcard_fallback), is
+//! sampled from an additive estimate instead: the global mean plus, for
+//! each of its dimension values with a warm marginal, that value's offset
+//! from the global mean. The spread is the average spread of those
+//! marginals. With no warm marginal the global model is used alone, and
+//! with no data at all the context is sampled as usual.
+//!
+//! Marginals are learned from the (normalized) fitness-weighted updates the
+//! contexts receive, are rebuilt from the stored contexts when the fallback
+//! is enabled, and keep what removed contexts taught them until
+//! [`rebuild_marginals`](EvoCoreContextSystem::rebuild_marginals) is called.
+
+use crate::{key_into, EvoCoreContextSystem, ParamStats, MAX_KEY_LENGTH, WILDCARD};
+use std::collections::HashMap;
+
+/// Learned parameters of one dimension value (or of everything)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarginalModel {
+    pub experiences: usize,
+    pub params: Vec<ParamStats>,
+}
+
+impl MarginalModel {
+    fn new(param_count: usize) -> Self {
+        Self {
+            experiences: 0,
+            params: vec![ParamStats::default(); param_count],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Marginals {
+    min_samples: usize,
+    /// One map per dimension, keyed by value
+    values: Vec<HashMap<String, MarginalModel>>,
+    global: MarginalModel,
+}
+
+impl Marginals {
+    fn new(min_samples: usize, dimension_count: usize, param_count: usize) -> Self {
+        Self {
+            min_samples,
+            values: vec![HashMap::new(); dimension_count],
+            global: MarginalModel::new(param_count),
+        }
+    }
+
+    /// Models updated by the context `key`: one per dimension value, then
+    /// the global one; empty for wildcard contexts
+    fn models_for(&mut self, key: &str) -> Vec<&mut MarginalModel> {
+        let values: Vec<&str> = key.split(':').collect();
+        if values.len() != self.values.len() || values.contains(&WILDCARD) {
+            return Vec::new();
+        }
+        let param_count = self.global.params.len();
+        self.values
+            .iter_mut()
+            .zip(values)
+            .map(|(models, value)| {
+                models
+                    .entry(value.to_string())
+                    .or_insert_with(|| MarginalModel::new(param_count))
+            })
+            .chain(std::iter::once(&mut self.global))
+            .collect()
+    }
+
+    /// Learn one update of the context `key`
+    pub(crate) fn observe(&mut self, key: &str, parameters: &[f64], fitness: f64) {
+        for model in self.models_for(key) {
+            model.experiences += 1;
+            for (stats, &value) in model.params.iter_mut().zip(parameters) {
+                stats.update(value, fitness);
+            }
+        }
+    }
+
+    /// Additive means and average spread for `dimension_values`, if the global model is warm
+    fn estimate(&self, dimension_values: &[&str]) -> Option<Vec<ParamStats>> {
+        if self.global.experiences < self.min_samples {
+            return None;
+        }
+        let warm: Vec<&MarginalModel> = self
+            .values
+            .iter()
+            .zip(dimension_values)
+            .filter_map(|(models, value)| models.get(*value))
+            .filter(|m| m.experiences >= self.min_samples)
+            .collect();
+
+        let estimate = self
+            .global
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, global)| {
+                let mut stats = *global;
+                if !warm.is_empty() {
+                    stats.mean += warm.iter().map(|m| m.params[i].mean - global.mean).sum::<f64>();
+                    stats.variance = warm.iter().map(|m| m.params[i].variance).sum::<f64>() / warm.len() as f64;
+                    stats.count = warm.iter().map(|m| m.params[i].count).min().unwrap_or(0);
+                }
+                stats
+            })
+            .collect();
+        Some(estimate)
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Sample contexts with fewer than `min_samples` experiences from per-dimension marginals
+    pub fn with_marginal_fallback(mut self, min_samples: usize) -> Self {
+        self.set_marginal_fallback(Some(min_samples));
+        self
+    }
+
+    /// Change or disable (`None`) the marginal fallback
+    ///
+    /// Enabling it builds the marginals from the contexts stored so far.
+    pub fn set_marginal_fallback(&mut self, min_samples: Option<usize>) {
+        match (min_samples.map(|m| m.max(1)), &mut self.marginals) {
+            (Some(min_samples), Some(marginals)) => marginals.min_samples = min_samples,
+            (Some(min_samples), None) => {
+                self.marginals = Some(Marginals::new(min_samples, self.dimensions().len(), self.param_count));
+                self.rebuild_marginals();
+            }
+            (None, _) => self.marginals = None,
+        }
+    }
+
+    /// Experiences below which a context falls back to its marginals
+    pub fn marginal_fallback(&self) -> Option<usize> {
+        self.marginals.as_ref().map(|m| m.min_samples)
+    }
+
+    /// Recompute every marginal from the contexts currently stored
+    pub fn rebuild_marginals(&mut self) {
+        let Some(marginals) = &self.marginals else {
+            return;
+        };
+        let mut fresh = Marginals::new(marginals.min_samples, marginals.values.len(), self.param_count);
+        for state in self.context_states() {
+            for model in fresh.models_for(&state.key) {
+                model.experiences += state.total_experiences;
+                for (stats, other) in model.params.iter_mut().zip(&state.params) {
+                    stats.merge(other);
+                }
+            }
+        }
+        self.marginals = Some(fresh);
+    }
+
+    /// The marginal model of one dimension value, if the fallback is enabled and it has data
+    pub fn marginal_model(&self, dimension: &str, value: &str) -> Option<MarginalModel> {
+        let marginals = self.marginals.as_ref()?;
+        let index = self.dimensions().iter().position(|(name, _)| name == dimension)?;
+        marginals.values[index].get(value).cloned()
+    }
+
+    /// Parameter means the marginal fallback would sample around for a context
+    pub fn marginal_estimate(&self, dimension_values: &[&str]) -> Result<Option<Vec<f64>>, String> {
+        self.check_dimension_count(dimension_values)?;
+        Ok(self
+            .marginals
+            .as_ref()
+            .and_then(|m| m.estimate(dimension_values))
+            .map(|params| params.iter().map(|p| p.mean).collect()))
+    }
+
+    /// Sample a cold context from its marginals; false if the context is
+    /// warm or the marginals have nothing to offer
+    pub(crate) fn sample_marginal(&self, dimension_values: &[&str], exploration: f64, out: &mut [f64]) -> bool {
+        let Some(marginals) = &self.marginals else {
+            return false;
+        };
+        let mut buf = [0u8; MAX_KEY_LENGTH];
+        let exact = key_into(dimension_values, &mut buf).map_or(0, |key| self.key_experiences(key));
+        if exact >= marginals.min_samples {
+            return false;
+        }
+        let Some(estimate) = marginals.estimate(dimension_values) else {
+            return false;
+        };
+        let mut rng = self.rng();
+        for (value, stats) in out.iter_mut().zip(&estimate) {
+            *value = stats.sample(exploration, &mut rng);
+        }
+        true
+    }
+}
@@ -0,0 +1,129 @@
+//! Approximate memory accounting
+//!
+//! The C context system allocates with plain `calloc`, outside the
+//! library's tracked allocator, so [`EvoCoreContextSystem::memory_stats`]
+//! computes usage from the system's shape instead: one hash entry, key,
+//! stats block, and parameter array per context, plus the bucket array and
+//! schema strings. Allocator overhead is not included.
+
+use crate::{
+    evocore_context_stats_t, evocore_context_system_t, evocore_weighted_array_t,
+    evocore_weighted_stats_t, ContextState, EvoCoreContextSystem, ParamStats,
+};
+use std::mem::size_of;
+
+/// Mirror of `hash_entry_t` in context.c
+#[repr(C)]
+#[allow(dead_code)]
+struct HashEntry {
+    key: *mut u8,
+    stats: *mut evocore_context_stats_t,
+    next: *mut HashEntry,
+    hash: u32,
+}
+
+/// Mirror of `hash_table_t` in context.c
+#[repr(C)]
+struct HashTable {
+    entries: *mut *mut HashEntry,
+    capacity: usize,
+    count: usize,
+    dimension_count: usize,
+}
+
+/// Approximate bytes held by a context system
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Number of contexts stored
+    pub contexts: usize,
+    /// Per-context entries, keys, and parameter statistics
+    pub context_bytes: usize,
+    /// Hash table and its bucket array
+    pub table_bytes: usize,
+    /// System struct, dimension names, and dimension values
+    pub schema_bytes: usize,
+    /// Rust-side state: key cache, recorded explanations, overrides, stable slots, versions, recent fitness
+    pub wrapper_bytes: usize,
+}
+
+impl MemoryStats {
+    /// Sum of all categories
+    pub fn total_bytes(&self) -> usize {
+        self.context_bytes + self.table_bytes + self.schema_bytes + self.wrapper_bytes
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Approximate memory used by this system
+    ///
+    /// Walks every context key, so cost grows with the number of contexts;
+    /// poll it periodically rather than on a hot path.
+    pub fn memory_stats(&self) -> MemoryStats {
+        let keys = self.context_keys();
+        let per_context = size_of::<HashEntry>()
+            + size_of::<evocore_context_stats_t>()
+            + size_of::<evocore_weighted_array_t>()
+            + self.param_count * size_of::<evocore_weighted_stats_t>();
+        let context_bytes = keys.iter().map(|k| per_context + k.len() + 1).sum();
+
+        let capacity = unsafe {
+            let table = self.inner.as_ref().internal as *const HashTable;
+            if table.is_null() {
+                0
+            } else {
+                (*table).capacity
+            }
+        };
+        let table_bytes = size_of::<HashTable>() + capacity * size_of::<*mut HashEntry>();
+
+        let schema_bytes = size_of::<evocore_context_system_t>()
+            + self
+                .dimensions()
+                .iter()
+                .map(|(name, values)| {
+                    size_of::<crate::evocore_context_dimension_t>()
+                        + name.len()
+                        + 1
+                        + values
+                            .iter()
+                            .map(|v| size_of::<*mut u8>() + v.len() + 1)
+                            .sum::<usize>()
+                })
+                .sum::<usize>();
+
+        let override_bytes: usize = self
+            .overrides
+            .values()
+            .map(|o| 2 * o.key.len() + o.params.len() * size_of::<f64>())
+            .sum();
+        let stable_bytes: usize = self
+            .stable_slots
+            .values()
+            .map(|s| 2 * s.key.len() + size_of::<ContextState>() + s.params.len() * size_of::<ParamStats>())
+            .sum();
+        let version_bytes: usize = self
+            .versions
+            .keys()
+            .map(|k| k.len() + size_of::<String>() + size_of::<u64>())
+            .sum();
+        let recent_bytes: usize = self
+            .recent_fitness
+            .keys()
+            .map(|k| k.len() + size_of::<String>() + size_of::<crate::ranking::RecentFitness>())
+            .sum();
+        let wrapper_bytes = self.key_cache.as_ref().map_or(0, |c| c.approx_bytes())
+            + self.explanations.as_ref().map_or(0, |e| e.approx_bytes())
+            + override_bytes
+            + stable_bytes
+            + version_bytes
+            + recent_bytes;
+
+        MemoryStats {
+            contexts: keys.len(),
+            context_bytes,
+            table_bytes,
+            schema_bytes,
+            wrapper_bytes,
+        }
+    }
+}
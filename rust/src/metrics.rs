@@ -0,0 +1,247 @@
+//! Prometheus metrics (feature `metrics`)
+//!
+//! Once [`enable_metrics`](EvoCoreContextSystem::enable_metrics) is called
+//! the system counts learns, samples and saves, and times every save and
+//! the C calls behind learns and samples.
+//! [`render_metrics`](EvoCoreContextSystem::render_metrics) returns them in
+//! the Prometheus text exposition format, ready to be served from a
+//! `/metrics` endpoint, together with gauges read at render time: the
+//! context count and the average fitness of the most experienced contexts.
+//!
+//! | metric                                    | type      |
+//! |-------------------------------------------|-----------|
+//! | `evocore_learns_total`                    | counter   |
+//! | `evocore_learn_errors_total`              | counter   |
+//! | `evocore_samples_total`                   | counter   |
+//! | `evocore_sample_errors_total`             | counter   |
+//! | `evocore_saves_total`                     | counter   |
+//! | `evocore_save_errors_total`               | counter   |
+//! | `evocore_save_duration_seconds`           | histogram |
+//! | `evocore_ffi_seconds_total{op=…,phase=…}` | counter   |
+//! | `evocore_ffi_calls_total{op=…}`           | counter   |
+//! | `evocore_contexts`                        | gauge     |
+//! | `evocore_context_avg_fitness{context=…}`  | gauge     |
+//!
+//! [`render_metrics_private`](EvoCoreContextSystem::render_metrics_private)
+//! noises the two gauges, which are read from the learned statistics,
+//! through a [`PrivacyBudget`]. The counters and the histogram describe
+//! calls to this process rather than what was learned from them, and are
+//! rendered exactly.
+//!
+//! Per-second rates come from the counters, e.g.
+//! `rate(evocore_learns_total[1m])`. The FFI counters split the time of
+//! learns and samples between building the C call's arguments (`marshal`)
+//! and the call itself (`call`).
+
+use crate::ffi_timing::FfiOp;
+use crate::{ContextState, EvoCoreContextSystem, PrivacyBudget, SharedContextSystem};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds of the save duration buckets, in seconds
+const SAVE_BUCKETS: [f64; 10] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0];
+
+/// Counters and the save histogram of one system
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+    learns: AtomicU64,
+    learn_errors: AtomicU64,
+    samples: AtomicU64,
+    sample_errors: AtomicU64,
+    saves: AtomicU64,
+    save_errors: AtomicU64,
+    save_buckets: [AtomicU64; SAVE_BUCKETS.len()],
+    save_micros: AtomicU64,
+    /// Nanoseconds marshalling and in the C call, per [`FfiOp`]
+    ffi_nanos: [[AtomicU64; 2]; 2],
+    ffi_calls: [AtomicU64; 2],
+}
+
+impl Metrics {
+    pub(crate) fn learned(&self, ok: bool) {
+        let counter = if ok { &self.learns } else { &self.learn_errors };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn sampled(&self, ok: bool, count: u64) {
+        let counter = if ok { &self.samples } else { &self.sample_errors };
+        counter.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn saved(&self, ok: bool, elapsed: Duration) {
+        if !ok {
+            self.save_errors.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.saves.fetch_add(1, Ordering::Relaxed);
+        self.save_micros.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        let secs = elapsed.as_secs_f64();
+        if let Some(i) = SAVE_BUCKETS.iter().position(|&le| secs <= le) {
+            self.save_buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn ffi(&self, op: FfiOp, marshal: Duration, call: Duration) {
+        let nanos = &self.ffi_nanos[op as usize];
+        nanos[0].fetch_add(marshal.as_nanos() as u64, Ordering::Relaxed);
+        nanos[1].fetch_add(call.as_nanos() as u64, Ordering::Relaxed);
+        self.ffi_calls[op as usize].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn counter(out: &mut String, name: &str, help: &str, value: &AtomicU64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    let _ = writeln!(out, "{} {}", name, value.load(Ordering::Relaxed));
+}
+
+/// Escape a label value for the exposition format
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+impl EvoCoreContextSystem {
+    /// Start counting learns, samples and saves for [`render_metrics`](Self::render_metrics)
+    ///
+    /// Resets the counters if metrics were already enabled.
+    pub fn enable_metrics(&mut self) {
+        self.metrics = Some(Metrics::default());
+    }
+
+    pub fn disable_metrics(&mut self) {
+        self.metrics = None;
+    }
+
+    /// Metrics in the Prometheus text format, with the average fitness of
+    /// the `top_contexts` contexts with the most experiences
+    ///
+    /// Counters and the save histogram are only present once metrics are
+    /// enabled; the gauges are always rendered.
+    pub fn render_metrics(&self, top_contexts: usize) -> String {
+        let states = if top_contexts > 0 { self.context_states() } else { Vec::new() };
+        self.render_metrics_of(self.context_count(), states, top_contexts)
+    }
+
+    /// [`render_metrics`](Self::render_metrics) with differential-privacy
+    /// noise on the context count and per-context fitness gauges
+    ///
+    /// Spends `epsilon` from `budget`, half on the count and half on the
+    /// contexts. Contexts are ranked by their noisy experience count, and
+    /// those below the budget's [minimum count](PrivacyBudget::with_min_count)
+    /// are left out.
+    pub fn render_metrics_private(
+        &self,
+        top_contexts: usize,
+        budget: &mut PrivacyBudget,
+        epsilon: f64,
+    ) -> Result<String, String> {
+        budget.spend(epsilon)?;
+        let contexts = budget.laplace(self.context_count() as f64, 1.0, epsilon / 2.0).round().max(0.0) as usize;
+        let states = if top_contexts > 0 {
+            budget.release(&self.context_states(), epsilon / 2.0)
+        } else {
+            Vec::new()
+        };
+        Ok(self.render_metrics_of(contexts, states, top_contexts))
+    }
+
+    fn render_metrics_of(&self, contexts: usize, mut states: Vec<ContextState>, top_contexts: usize) -> String {
+        let mut out = String::new();
+        if let Some(m) = &self.metrics {
+            counter(&mut out, "evocore_learns_total", "Successful learn calls.", &m.learns);
+            counter(&mut out, "evocore_learn_errors_total", "Rejected learn calls.", &m.learn_errors);
+            counter(&mut out, "evocore_samples_total", "Successful sample calls.", &m.samples);
+            counter(&mut out, "evocore_sample_errors_total", "Failed sample calls.", &m.sample_errors);
+            counter(&mut out, "evocore_saves_total", "Successful saves.", &m.saves);
+            counter(&mut out, "evocore_save_errors_total", "Failed saves.", &m.save_errors);
+
+            let name = "evocore_save_duration_seconds";
+            let _ = writeln!(out, "# HELP {} Duration of successful saves.", name);
+            let _ = writeln!(out, "# TYPE {} histogram", name);
+            let mut cumulative = 0;
+            for (le, count) in SAVE_BUCKETS.iter().zip(&m.save_buckets) {
+                cumulative += count.load(Ordering::Relaxed);
+                let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, le, cumulative);
+            }
+            let saves = m.saves.load(Ordering::Relaxed);
+            let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, saves);
+            let _ = writeln!(out, "{}_sum {}", name, m.save_micros.load(Ordering::Relaxed) as f64 / 1e6);
+            let _ = writeln!(out, "{}_count {}", name, saves);
+
+            let name = "evocore_ffi_seconds_total";
+            let _ = writeln!(out, "# HELP {} Time spent building C arguments and in C calls.", name);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for op in [FfiOp::Learn, FfiOp::Sample] {
+                for (phase, nanos) in ["marshal", "call"].iter().zip(&m.ffi_nanos[op as usize]) {
+                    let secs = nanos.load(Ordering::Relaxed) as f64 / 1e9;
+                    let _ = writeln!(out, "{}{{op=\"{}\",phase=\"{}\"}} {}", name, op.name(), phase, secs);
+                }
+            }
+            let name = "evocore_ffi_calls_total";
+            let _ = writeln!(out, "# HELP {} Timed C calls.", name);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for op in [FfiOp::Learn, FfiOp::Sample] {
+                let calls = m.ffi_calls[op as usize].load(Ordering::Relaxed);
+                let _ = writeln!(out, "{}{{op=\"{}\"}} {}", name, op.name(), calls);
+            }
+        }
+
+        let _ = writeln!(out, "# HELP evocore_contexts Contexts currently stored.");
+        let _ = writeln!(out, "# TYPE evocore_contexts gauge");
+        let _ = writeln!(out, "evocore_contexts {}", contexts);
+
+        if top_contexts > 0 {
+            states.sort_by(|a, b| b.total_experiences.cmp(&a.total_experiences).then_with(|| a.key.cmp(&b.key)));
+            states.truncate(top_contexts);
+            let name = "evocore_context_avg_fitness";
+            let _ = writeln!(out, "# HELP {} Average fitness of the most experienced contexts.", name);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            for state in states {
+                let _ = writeln!(out, "{}{{context=\"{}\"}} {}", name, label(&state.key), state.avg_fitness);
+            }
+        }
+        out
+    }
+
+    pub(crate) fn record_learn(&self, ok: bool) {
+        if let Some(m) = &self.metrics {
+            m.learned(ok);
+        }
+    }
+
+    pub(crate) fn record_samples(&self, ok: bool, count: u64) {
+        if let Some(m) = &self.metrics {
+            m.sampled(ok, count);
+        }
+    }
+
+    pub(crate) fn record_ffi(&self, op: FfiOp, marshal: Duration, call: Duration) {
+        if let Some(m) = &self.metrics {
+            m.ffi(op, marshal, call);
+        }
+    }
+
+    pub(crate) fn record_save(&self, ok: bool, elapsed: Duration) {
+        if let Some(m) = &self.metrics {
+            m.saved(ok, elapsed);
+        }
+    }
+}
+
+impl SharedContextSystem {
+    /// [`EvoCoreContextSystem::render_metrics`] under the read lock
+    pub fn render_metrics(&self, top_contexts: usize) -> String {
+        self.read(|system| system.render_metrics(top_contexts))
+    }
+
+    /// [`EvoCoreContextSystem::render_metrics_private`] under the read lock
+    pub fn render_metrics_private(
+        &self,
+        top_contexts: usize,
+        budget: &mut PrivacyBudget,
+        epsilon: f64,
+    ) -> Result<String, String> {
+        self.read(|system| system.render_metrics_private(top_contexts, budget, epsilon))
+    }
+}
@@ -0,0 +1,137 @@
+//! MessagePack snapshot format (feature `msgpack`)
+//!
+//! Structs are encoded as maps with named fields, so any MessagePack
+//! library can read a snapshot without knowing the field order. All
+//! statistics are stored, so a round trip is lossless.
+
+use crate::{Checkpoint, ContextState, ParamStats, SystemSerializer};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    dimensions: Vec<Dimension>,
+    param_count: usize,
+    contexts: Vec<Context>,
+    #[serde(default)]
+    stable: Vec<Context>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Dimension {
+    name: String,
+    values: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Context {
+    key: String,
+    total_experiences: usize,
+    confidence: f64,
+    avg_fitness: f64,
+    best_fitness: f64,
+    first_update: i64,
+    last_update: i64,
+    params: Vec<Param>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Param {
+    mean: f64,
+    variance: f64,
+    sum_weights: f64,
+    m2: f64,
+    count: usize,
+    min_value: f64,
+    max_value: f64,
+    sum_weighted_x: f64,
+}
+
+impl From<&ContextState> for Context {
+    fn from(state: &ContextState) -> Self {
+        Self {
+            key: state.key.clone(),
+            total_experiences: state.total_experiences,
+            confidence: state.confidence,
+            avg_fitness: state.avg_fitness,
+            best_fitness: state.best_fitness,
+            first_update: state.first_update,
+            last_update: state.last_update,
+            params: state
+                .params
+                .iter()
+                .map(|p| Param {
+                    mean: p.mean,
+                    variance: p.variance,
+                    sum_weights: p.sum_weights,
+                    m2: p.m2,
+                    count: p.count,
+                    min_value: p.min_value,
+                    max_value: p.max_value,
+                    sum_weighted_x: p.sum_weighted_x,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Context> for ContextState {
+    fn from(context: Context) -> Self {
+        Self {
+            key: context.key,
+            total_experiences: context.total_experiences,
+            confidence: context.confidence,
+            avg_fitness: context.avg_fitness,
+            best_fitness: context.best_fitness,
+            first_update: context.first_update,
+            last_update: context.last_update,
+            params: context
+                .params
+                .into_iter()
+                .map(|p| ParamStats {
+                    mean: p.mean,
+                    variance: p.variance,
+                    sum_weights: p.sum_weights,
+                    m2: p.m2,
+                    count: p.count,
+                    min_value: p.min_value,
+                    max_value: p.max_value,
+                    sum_weighted_x: p.sum_weighted_x,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// MessagePack-encoded snapshot
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePackSerializer;
+
+impl SystemSerializer for MessagePackSerializer {
+    fn serialize(&self, checkpoint: &Checkpoint) -> Result<Vec<u8>, String> {
+        let snapshot = Snapshot {
+            dimensions: checkpoint
+                .dimensions
+                .iter()
+                .map(|(name, values)| Dimension {
+                    name: name.clone(),
+                    values: values.clone(),
+                })
+                .collect(),
+            param_count: checkpoint.param_count,
+            contexts: checkpoint.contexts.iter().map(Context::from).collect(),
+            stable: checkpoint.stable.iter().map(Context::from).collect(),
+        };
+        rmp_serde::to_vec_named(&snapshot).map_err(|e| e.to_string())
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<Checkpoint, String> {
+        let snapshot: Snapshot = rmp_serde::from_slice(data)
+            .map_err(|e| format!("Invalid MessagePack snapshot: {}", e))?;
+        Ok(Checkpoint {
+            dimensions: snapshot.dimensions.into_iter().map(|d| (d.name, d.values)).collect(),
+            param_count: snapshot.param_count,
+            contexts: snapshot.contexts.into_iter().map(ContextState::from).collect(),
+            stable: snapshot.stable.into_iter().map(ContextState::from).collect(),
+        })
+    }
+}
@@ -0,0 +1,157 @@
+//! Fitness normalization
+//!
+//! The learned statistics weight every example by its raw fitness, so a
+//! task that scores in the thousands drowns out one that scores in `[0, 1]`.
+//! A [`FitnessTransform`] rescales each reported fitness against the history
+//! of its own context before anything is learned:
+//!
+//! - [`ZScore`](FitnessTransform::ZScore): standard score against the
+//!   running mean and deviation of every fitness seen in the context.
+//!   Below-average outcomes come out negative, and the C library clamps
+//!   non-positive weights to a negligible minimum, so they are effectively
+//!   ignored.
+//! - [`Rank`](FitnessTransform::Rank): mid-rank among the last `window`
+//!   fitnesses, in `[0, 1]`.
+//! - [`MinMax`](FitnessTransform::MinMax): position between the lowest and
+//!   highest of the last `window` fitnesses, in `[0, 1]`.
+//!
+//! Strategies observe the normalized value as well. Normalization history
+//! is not saved in checkpoints; it rebuilds from new reports.
+
+use crate::rename::move_entry;
+use crate::EvoCoreContextSystem;
+use std::collections::{HashMap, VecDeque};
+
+/// How reported fitness is rescaled per context before learning
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitnessTransform {
+    /// Standard score against the context's running mean and deviation
+    ZScore,
+    /// Mid-rank among the last `window` fitnesses of the context
+    Rank { window: usize },
+    /// Min-max scaling over the last `window` fitnesses of the context
+    MinMax { window: usize },
+}
+
+impl FitnessTransform {
+    fn window(&self) -> usize {
+        match *self {
+            FitnessTransform::ZScore => 0,
+            FitnessTransform::Rank { window } | FitnessTransform::MinMax { window } => window.max(1),
+        }
+    }
+}
+
+/// Fitness history of one context
+#[derive(Debug, Clone, Default)]
+struct History {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    recent: VecDeque<f64>,
+}
+
+/// Transform plus per-context history
+#[derive(Debug, Clone)]
+pub(crate) struct Normalizer {
+    transform: FitnessTransform,
+    contexts: HashMap<String, History>,
+}
+
+impl Normalizer {
+    pub(crate) fn forget(&mut self, key: &str) {
+        self.contexts.remove(key);
+    }
+
+    pub(crate) fn rename_key(&mut self, from: &str, to: &str) {
+        move_entry(&mut self.contexts, from, to);
+    }
+
+
+    fn new(transform: FitnessTransform) -> Self {
+        Self {
+            transform,
+            contexts: HashMap::new(),
+        }
+    }
+
+    /// Record `fitness` for `key` and return its normalized value
+    pub(crate) fn apply(&mut self, key: &str, fitness: f64) -> f64 {
+        if !fitness.is_finite() {
+            return fitness;
+        }
+        let history = match self.contexts.get_mut(key) {
+            Some(history) => history,
+            None => self.contexts.entry(key.to_string()).or_default(),
+        };
+        match self.transform {
+            FitnessTransform::ZScore => {
+                history.count += 1;
+                let delta = fitness - history.mean;
+                history.mean += delta / history.count as f64;
+                history.m2 += delta * (fitness - history.mean);
+                let sd = (history.m2 / history.count as f64).sqrt();
+                if sd > 0.0 {
+                    (fitness - history.mean) / sd
+                } else {
+                    0.0
+                }
+            }
+            FitnessTransform::Rank { .. } => {
+                history.push(fitness, self.transform.window());
+                let below = history.recent.iter().filter(|&&f| f < fitness).count();
+                let equal = history.recent.iter().filter(|&&f| f == fitness).count();
+                if history.recent.len() > 1 {
+                    (below as f64 + (equal - 1) as f64 / 2.0) / (history.recent.len() - 1) as f64
+                } else {
+                    0.5
+                }
+            }
+            FitnessTransform::MinMax { .. } => {
+                history.push(fitness, self.transform.window());
+                let min = history.recent.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = history.recent.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                if max > min {
+                    (fitness - min) / (max - min)
+                } else {
+                    0.5
+                }
+            }
+        }
+    }
+}
+
+impl History {
+    fn push(&mut self, fitness: f64, window: usize) {
+        if self.recent.len() >= window {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(fitness);
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Normalize fitness per context before learning
+    pub fn with_fitness_transform(mut self, transform: FitnessTransform) -> Self {
+        self.set_fitness_transform(Some(transform));
+        self
+    }
+
+    /// Change or remove (`None`) fitness normalization, dropping its history
+    pub fn set_fitness_transform(&mut self, transform: Option<FitnessTransform>) {
+        self.normalizer = transform.map(Normalizer::new);
+    }
+
+    /// Current fitness normalization, if any
+    pub fn fitness_transform(&self) -> Option<FitnessTransform> {
+        self.normalizer.as_ref().map(|n| n.transform)
+    }
+
+    /// Fitness as it will be learned for `key`, recording it in the history
+    pub(crate) fn normalize_fitness(&mut self, key: &str, fitness: f64) -> f64 {
+        match &mut self.normalizer {
+            Some(normalizer) => normalizer.apply(key, fitness),
+            None => fitness,
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Novelty search
+//!
+//! On deceptive problems the path to the best solution first leads through
+//! worse ones, and selecting on fitness alone gets stuck. Novelty search
+//! also rewards behaving differently from what has been seen before. The
+//! caller describes each evaluation's behaviour as a vector (final
+//! position, action histogram, ...), and a [`NoveltyArchive`] scores it by
+//! the mean distance to its `k` nearest neighbours among archived
+//! behaviours. Behaviours at least `threshold` away are added to the
+//! archive.
+//!
+//! The score used for learning or selection blends the two:
+//! `(1 - weight) * fitness + weight * novelty`. Fitness and novelty are not
+//! rescaled, so pick `weight` (and the behaviour units) with their ranges
+//! in mind.
+//!
+//! - [`learn_with_behavior`](EvoCoreContextSystem::learn_with_behavior)
+//!   keeps one archive per context once enabled with
+//!   [`with_novelty`](EvoCoreContextSystem::with_novelty).
+//! - [`Population::evaluate_with_novelty`](crate::Population::evaluate_with_novelty)
+//!   scores a generation against a caller-owned archive.
+
+use crate::rename::move_entry;
+use crate::EvoCoreContextSystem;
+use std::collections::HashMap;
+
+/// Archive of past behaviours, scoring new ones by distance to their nearest neighbours
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoveltyArchive {
+    k: usize,
+    threshold: f64,
+    capacity: usize,
+    behaviors: Vec<Vec<f64>>,
+}
+
+impl NoveltyArchive {
+    /// Score against the `k` nearest neighbours; archive anything at least 0 away, up to 1 000 entries
+    pub fn new(k: usize) -> Self {
+        Self {
+            k: k.max(1),
+            threshold: 0.0,
+            capacity: 1_000,
+            behaviors: Vec::new(),
+        }
+    }
+
+    /// Novelty a behaviour needs to be archived (default 0)
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.max(0.0);
+        self
+    }
+
+    /// Most behaviours kept; the oldest are dropped first (default 1 000)
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Mean Euclidean distance to the `k` nearest archived behaviours (0 if empty)
+    pub fn novelty(&self, behavior: &[f64]) -> f64 {
+        self.novelty_among(behavior, &[])
+    }
+
+    /// Novelty against the archive plus `others` (e.g. the rest of a generation)
+    pub fn novelty_among(&self, behavior: &[f64], others: &[&[f64]]) -> f64 {
+        let mut distances: Vec<f64> = self
+            .behaviors
+            .iter()
+            .map(Vec::as_slice)
+            .chain(others.iter().copied())
+            .map(|other| distance(behavior, other))
+            .collect();
+        if distances.is_empty() {
+            return 0.0;
+        }
+        let k = self.k.min(distances.len());
+        distances.select_nth_unstable_by(k - 1, f64::total_cmp);
+        distances[..k].iter().sum::<f64>() / k as f64
+    }
+
+    /// Archive `behavior` if it is novel enough; returns its novelty and whether it was added
+    pub fn add(&mut self, behavior: &[f64]) -> (f64, bool) {
+        let novelty = self.novelty(behavior);
+        let added = self.behaviors.len() < self.k || novelty >= self.threshold;
+        if added {
+            self.push(behavior);
+        }
+        (novelty, added)
+    }
+
+    fn push(&mut self, behavior: &[f64]) {
+        if self.behaviors.len() >= self.capacity {
+            self.behaviors.remove(0);
+        }
+        self.behaviors.push(behavior.to_vec());
+    }
+
+    pub fn behaviors(&self) -> &[Vec<f64>] {
+        &self.behaviors
+    }
+
+    pub fn len(&self) -> usize {
+        self.behaviors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.behaviors.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.behaviors.clear();
+    }
+
+    pub(crate) fn check_dimensions(&self, behavior: &[f64]) -> Result<(), String> {
+        if behavior.iter().any(|b| !b.is_finite()) {
+            return Err("Behavior descriptor must be finite".to_string());
+        }
+        match self.behaviors.first() {
+            Some(first) if first.len() != behavior.len() => Err(format!(
+                "Behavior length mismatch: expected {}, got {}",
+                first.len(),
+                behavior.len()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt()
+}
+
+/// Blend of fitness and novelty
+pub(crate) fn blend(fitness: f64, novelty: f64, weight: f64) -> f64 {
+    (1.0 - weight) * fitness + weight * novelty
+}
+
+/// Per-context archives for [`EvoCoreContextSystem::learn_with_behavior`]
+#[derive(Debug, Clone)]
+pub(crate) struct NoveltyState {
+    template: NoveltyArchive,
+    weight: f64,
+    archives: HashMap<String, NoveltyArchive>,
+}
+
+impl NoveltyState {
+    pub(crate) fn forget(&mut self, key: &str) {
+        self.archives.remove(key);
+    }
+
+    pub(crate) fn rename_key(&mut self, from: &str, to: &str) {
+        move_entry(&mut self.archives, from, to);
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Reward novel behaviour in [`learn_with_behavior`](Self::learn_with_behavior)
+    ///
+    /// Each context gets its own copy of `archive`; `weight` (0 to 1) is the
+    /// share of the learned score that comes from novelty.
+    pub fn with_novelty(mut self, archive: NoveltyArchive, weight: f64) -> Self {
+        self.novelty = Some(NoveltyState {
+            template: archive,
+            weight: weight.clamp(0.0, 1.0),
+            archives: HashMap::new(),
+        });
+        self
+    }
+
+    /// Stop rewarding novelty and drop every archive
+    pub fn disable_novelty(&mut self) {
+        self.novelty = None;
+    }
+
+    /// Archive of behaviours seen in a context, if novelty search is enabled
+    pub fn novelty_archive(&self, dimension_values: &[&str]) -> Option<&NoveltyArchive> {
+        let key = self.context_key(dimension_values).ok()?;
+        self.novelty.as_ref()?.archives.get(&key)
+    }
+
+    /// Learn from an evaluation, scoring it by fitness and the novelty of `behavior`
+    ///
+    /// Returns the blended score that was learned. Without
+    /// [`with_novelty`](Self::with_novelty) this is a plain `learn()`.
+    pub fn learn_with_behavior(
+        &mut self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+        behavior: &[f64],
+    ) -> Result<f64, String> {
+        let key = self.context_key(dimension_values)?;
+        let Some(state) = self.novelty.as_mut() else {
+            self.learn(dimension_values, parameters, fitness)?;
+            return Ok(fitness);
+        };
+
+        let archive = state.archives.entry(key.clone()).or_insert_with(|| {
+            let mut archive = state.template.clone();
+            archive.clear();
+            archive
+        });
+        archive.check_dimensions(behavior)?;
+        let novelty = archive.novelty(behavior);
+        let score = blend(fitness, novelty, state.weight);
+
+        self.learn(dimension_values, parameters, score)?;
+        if let Some(state) = self.novelty.as_mut() {
+            if let Some(archive) = state.archives.get_mut(&key) {
+                archive.add(behavior);
+            }
+        }
+        Ok(score)
+    }
+}
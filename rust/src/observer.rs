@@ -0,0 +1,48 @@
+//! Learn and sample event hooks
+//!
+//! A [`ContextObserver`] set with
+//! [`set_observer`](EvoCoreContextSystem::set_observer) is called after
+//! every successful `learn()` and `sample()`, including the batch variants,
+//! so events can be mirrored into an analytics pipeline. Callbacks run
+//! synchronously on the calling thread (under the lock of a
+//! [`SharedContextSystem`](crate::SharedContextSystem)), so anything slow
+//! should be handed off to a channel.
+//!
+//! `on_learn` sees the fitness that was actually learned, after any
+//! [normalization](EvoCoreContextSystem::with_fitness_transform); examples
+//! held back by [anomaly quarantine](EvoCoreContextSystem::with_anomaly_detection)
+//! are not reported. `on_sample` sees the returned parameters and the
+//! exploration the caller asked for.
+
+use crate::EvoCoreContextSystem;
+
+/// Receives learn and sample events; both callbacks default to doing nothing
+pub trait ContextObserver: Send + Sync {
+    /// A context learned `params` with `fitness`
+    fn on_learn(&self, _key: &str, _params: &[f64], _fitness: f64) {}
+
+    /// `params` were sampled for a context
+    fn on_sample(&self, _key: &str, _params: &[f64], _exploration: f64) {}
+}
+
+impl EvoCoreContextSystem {
+    /// Call `observer` after every learn and sample, replacing any previous one
+    pub fn set_observer(&mut self, observer: Box<dyn ContextObserver>) {
+        self.observer = Some(observer);
+    }
+
+    /// Remove the observer, returning it
+    pub fn clear_observer(&mut self) -> Option<Box<dyn ContextObserver>> {
+        self.observer.take()
+    }
+
+    pub fn has_observer(&self) -> bool {
+        self.observer.is_some()
+    }
+
+    pub(crate) fn notify_sample(&self, dimension_values: &[&str], params: &[f64], exploration: f64) {
+        if let Some(observer) = &self.observer {
+            observer.on_sample(&dimension_values.join(":"), params, exploration);
+        }
+    }
+}
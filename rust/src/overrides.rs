@@ -0,0 +1,112 @@
+//! Manual parameter overrides with expiry
+//!
+//! [`EvoCoreContextSystem::override_params`] pins the parameters served for
+//! one context, bypassing sampling, until the override expires or is
+//! cleared. Learning into the context continues as normal, so when the pin
+//! is lifted sampling resumes from everything observed meanwhile.
+
+use crate::{key_into, EvoCoreContextSystem, MAX_KEY_LENGTH};
+use std::time::{Duration, Instant};
+
+/// Lifetime of an override whose TTL is too large to represent
+const FOREVER: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
+
+/// Parameters pinned for one context
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamOverride {
+    pub key: String,
+    pub params: Vec<f64>,
+    pub expires_at: Instant,
+}
+
+impl ParamOverride {
+    /// Time left before the override lapses (zero once expired)
+    pub fn remaining(&self) -> Duration {
+        self.expires_at.saturating_duration_since(Instant::now())
+    }
+
+    fn is_active(&self, now: Instant) -> bool {
+        now < self.expires_at
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Serve `params` for this context instead of sampling, for `ttl`
+    ///
+    /// Replaces any existing override for the context. A `ttl` too large
+    /// to represent, such as `Duration::MAX`, pins the parameters for a
+    /// century.
+    pub fn override_params(
+        &mut self,
+        dimension_values: &[&str],
+        params: &[f64],
+        ttl: Duration,
+    ) -> Result<(), String> {
+        if params.len() != self.param_count {
+            return Err(format!(
+                "Parameter count mismatch: expected {}, got {}",
+                self.param_count,
+                params.len()
+            ));
+        }
+
+        let key = self.context_key(dimension_values)?;
+        let now = Instant::now();
+        let expires_at = now
+            .checked_add(ttl)
+            .or_else(|| now.checked_add(FOREVER))
+            .ok_or_else(|| format!("Override TTL too large: {:?}", ttl))?;
+        self.overrides.retain(|_, o| o.is_active(now));
+        self.overrides.insert(
+            key.clone(),
+            ParamOverride {
+                key,
+                params: params.to_vec(),
+                expires_at,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove the override for this context; returns whether one was active
+    pub fn clear_override(&mut self, dimension_values: &[&str]) -> Result<bool, String> {
+        let key = self.context_key(dimension_values)?;
+        let now = Instant::now();
+        Ok(self
+            .overrides
+            .remove(&key)
+            .is_some_and(|o| o.is_active(now)))
+    }
+
+    /// Remove every override
+    pub fn clear_overrides(&mut self) {
+        self.overrides.clear();
+    }
+
+    /// Overrides that have not yet expired, sorted by context key
+    pub fn active_overrides(&self) -> Vec<ParamOverride> {
+        let now = Instant::now();
+        let mut active: Vec<ParamOverride> = self
+            .overrides
+            .values()
+            .filter(|o| o.is_active(now))
+            .cloned()
+            .collect();
+        active.sort_by(|a, b| a.key.cmp(&b.key));
+        active
+    }
+
+    /// Pinned parameters for a context, if an unexpired override exists
+    pub(crate) fn active_override(&self, dimension_values: &[&str]) -> Option<&[f64]> {
+        if self.overrides.is_empty() {
+            return None;
+        }
+
+        let mut buf = [0u8; MAX_KEY_LENGTH];
+        let key = key_into(dimension_values, &mut buf)?.to_str().ok()?;
+        self.overrides
+            .get(key)
+            .filter(|o| o.is_active(Instant::now()))
+            .map(|o| o.params.as_slice())
+    }
+}
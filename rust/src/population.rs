@@ -0,0 +1,393 @@
+//! Population-based evolution over raw genomes
+//!
+//! [`Population`] wraps the C library's population and genome operations so
+//! a classic generational loop can run from Rust:
+//!
+//! ```ignore
+//! let mut pop = Population::new(100, 32)?;
+//! pop.initialize(100)?;
+//! while pop.generation() < 50 {
+//!     pop.evaluate(|genome| score(genome));
+//!     pop.select(20)?;
+//!     pop.reproduce(100, 0.01)?;
+//! }
+//! ```
+//!
+//! Genomes are opaque byte strings; interpreting them is up to the fitness
+//! function. [`reproduce_with`](Population::reproduce_with) breeds with
+//! custom [`VariationOperators`] instead of the C library's.
+
+use crate::{
+    evocore_error_string, evocore_error_t, evocore_genome_cleanup, evocore_genome_crossover,
+    evocore_genome_from_data, evocore_genome_mutate, evocore_genome_t, evocore_population_add,
+    evocore_population_cleanup, evocore_population_clear, evocore_population_increment_generation,
+    evocore_population_init, evocore_population_sort, evocore_population_t,
+    evocore_population_tournament_select, evocore_population_truncate,
+    evocore_population_update_stats, VariationOperators, EVOCORE_OK,
+};
+use crate::novelty::blend;
+use crate::NoveltyArchive;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::ffi::{c_void, CStr};
+
+/// Turn a C error code into the crate's `String` errors
+fn check(err: evocore_error_t) -> Result<(), String> {
+    if err == EVOCORE_OK {
+        return Ok(());
+    }
+    let message = unsafe {
+        let s = evocore_error_string(err);
+        if s.is_null() {
+            format!("error {}", err)
+        } else {
+            CStr::from_ptr(s).to_string_lossy().into_owned()
+        }
+    };
+    Err(format!("EvoCore error: {}", message))
+}
+
+fn empty_genome() -> evocore_genome_t {
+    evocore_genome_t {
+        data: std::ptr::null_mut(),
+        size: 0,
+        capacity: 0,
+        owns_memory: false,
+    }
+}
+
+/// A fixed-capacity population of byte-string genomes
+pub struct Population {
+    inner: evocore_population_t,
+    genome_size: usize,
+    tournament_size: usize,
+    rng: StdRng,
+}
+
+// SAFETY: The population owns its individuals and genomes exclusively;
+// nothing in the C library keeps references to them.
+unsafe impl Send for Population {}
+
+impl Population {
+    /// Create an empty population holding up to `capacity` genomes of `genome_size` bytes
+    pub fn new(capacity: usize, genome_size: usize) -> Result<Self, String> {
+        if genome_size == 0 {
+            return Err("Genome size must be at least 1".to_string());
+        }
+
+        let mut inner = evocore_population_t {
+            individuals: std::ptr::null_mut(),
+            size: 0,
+            capacity: 0,
+            generation: 0,
+            best_fitness: f64::NEG_INFINITY,
+            avg_fitness: f64::NAN,
+            worst_fitness: f64::INFINITY,
+            best_index: 0,
+        };
+        crate::require_native()?;
+        check(unsafe { evocore_population_init(&mut inner, capacity) })?;
+
+        Ok(Self {
+            inner,
+            genome_size,
+            tournament_size: 3,
+            rng: StdRng::from_entropy(),
+        })
+    }
+
+    /// Seed the random number generator, for reproducible runs
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Number of individuals competing in each parent tournament (default 3)
+    pub fn with_tournament_size(mut self, tournament_size: usize) -> Self {
+        self.tournament_size = tournament_size.max(1);
+        self
+    }
+
+    /// Replace the population with `n` random, unevaluated genomes
+    pub fn initialize(&mut self, n: usize) -> Result<(), String> {
+        if n > self.inner.capacity {
+            return Err(format!("Population capacity is {}, asked for {}", self.inner.capacity, n));
+        }
+
+        unsafe { evocore_population_clear(&mut self.inner) };
+        self.inner.generation = 0;
+
+        let mut bytes = vec![0u8; self.genome_size];
+        for _ in 0..n {
+            self.rng.fill(bytes.as_mut_slice());
+            self.add(&bytes)?;
+        }
+        check(unsafe { evocore_population_update_stats(&mut self.inner) })
+    }
+
+    /// Add one genome (unevaluated)
+    pub fn add(&mut self, genome: &[u8]) -> Result<(), String> {
+        if genome.len() != self.genome_size {
+            return Err(format!(
+                "Genome size mismatch: expected {}, got {}",
+                self.genome_size,
+                genome.len()
+            ));
+        }
+
+        unsafe {
+            let mut g = empty_genome();
+            check(evocore_genome_from_data(&mut g, genome.as_ptr() as *const c_void, genome.len()))?;
+            let added = evocore_population_add(&mut self.inner, &g, f64::NAN);
+            evocore_genome_cleanup(&mut g);
+            check(added)
+        }
+    }
+
+    /// Score every individual that has not been evaluated yet
+    ///
+    /// Returning NaN marks an individual as invalid; it sorts last and is
+    /// never chosen as a parent over a scored individual. Returns the
+    /// number of individuals evaluated.
+    pub fn evaluate<F>(&mut self, mut fitness_fn: F) -> usize
+    where
+        F: FnMut(&[u8]) -> f64,
+    {
+        let mut evaluated = 0;
+        for individual in self.individuals_mut() {
+            if individual.fitness.is_nan() {
+                let genome = unsafe { genome_bytes(individual.genome) };
+                individual.fitness = fitness_fn(genome);
+                evaluated += 1;
+            }
+        }
+        if evaluated > 0 {
+            unsafe { evocore_population_update_stats(&mut self.inner) };
+        }
+        evaluated
+    }
+
+    /// Score unevaluated individuals by fitness and novelty
+    ///
+    /// `evaluate_fn` returns a genome's fitness and behaviour descriptor.
+    /// Each behaviour's novelty is measured against `archive` and the rest
+    /// of this batch, `(1 - weight) * fitness + weight * novelty` becomes
+    /// the individual's fitness, and novel behaviours are then archived.
+    /// Returns the number of individuals evaluated.
+    pub fn evaluate_with_novelty<F>(&mut self, archive: &mut NoveltyArchive, weight: f64, mut evaluate_fn: F) -> usize
+    where
+        F: FnMut(&[u8]) -> (f64, Vec<f64>),
+    {
+        let weight = weight.clamp(0.0, 1.0);
+        let mut pending = Vec::new();
+        for (index, individual) in self.individuals().iter().enumerate() {
+            if individual.fitness.is_nan() {
+                let genome = unsafe { genome_bytes(individual.genome) };
+                pending.push((index, evaluate_fn(genome)));
+            }
+        }
+        if pending.is_empty() {
+            return 0;
+        }
+
+        let behaviors: Vec<&[f64]> = pending.iter().map(|(_, (_, b))| b.as_slice()).collect();
+        let individuals = self.individuals_mut();
+        for (i, (index, (fitness, behavior))) in pending.iter().enumerate() {
+            let others: Vec<&[f64]> = behaviors
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, b)| *b)
+                .collect();
+            individuals[*index].fitness = blend(*fitness, archive.novelty_among(behavior, &others), weight);
+        }
+        unsafe { evocore_population_update_stats(&mut self.inner) };
+
+        for (_, (_, behavior)) in &pending {
+            if archive.check_dimensions(behavior).is_ok() {
+                archive.add(behavior);
+            }
+        }
+        pending.len()
+    }
+
+    /// Keep the `survivors` fittest individuals and drop the rest
+    pub fn select(&mut self, survivors: usize) -> Result<(), String> {
+        unsafe {
+            check(evocore_population_sort(&mut self.inner))?;
+            check(evocore_population_truncate(&mut self.inner, survivors))?;
+            check(evocore_population_update_stats(&mut self.inner))
+        }
+    }
+
+    /// Breed the current individuals back up to `size` and advance a generation
+    ///
+    /// Parents are picked by tournament from the individuals present before
+    /// breeding starts; each pair produces two children by uniform crossover,
+    /// and each child byte is replaced at random with probability
+    /// `mutation_rate`. Children start unevaluated.
+    pub fn reproduce(&mut self, size: usize, mutation_rate: f64) -> Result<(), String> {
+        if size > self.inner.capacity {
+            return Err(format!("Population capacity is {}, asked for {}", self.inner.capacity, size));
+        }
+        if self.inner.size == 0 {
+            return Err("Cannot reproduce an empty population".to_string());
+        }
+
+        let needed = size.saturating_sub(self.inner.size);
+        let mut children = Vec::with_capacity(needed + 1);
+        let mut seed: u32 = self.rng.gen();
+        let result = self.breed(needed, mutation_rate, &mut seed, &mut children);
+
+        for child in &mut children {
+            unsafe { evocore_genome_cleanup(child) };
+        }
+        result?;
+
+        unsafe {
+            evocore_population_update_stats(&mut self.inner);
+            evocore_population_increment_generation(&mut self.inner);
+        }
+        Ok(())
+    }
+
+    /// Breed back up to `size` with custom operators and advance a generation
+    ///
+    /// Like [`reproduce`](Self::reproduce), but crossover and mutation
+    /// follow `operators` instead of the C library's defaults.
+    pub fn reproduce_with(&mut self, size: usize, operators: &VariationOperators) -> Result<(), String> {
+        if size > self.inner.capacity {
+            return Err(format!("Population capacity is {}, asked for {}", self.inner.capacity, size));
+        }
+        if self.inner.size == 0 {
+            return Err("Cannot reproduce an empty population".to_string());
+        }
+
+        let needed = size.saturating_sub(self.inner.size);
+        let mut seed: u32 = self.rng.gen();
+        let mut children: Vec<Vec<u8>> = Vec::with_capacity(needed + 1);
+        while children.len() < needed {
+            let a = unsafe { evocore_population_tournament_select(&self.inner, self.tournament_size, &mut seed) };
+            let b = unsafe { evocore_population_tournament_select(&self.inner, self.tournament_size, &mut seed) };
+            let parents = self.individuals();
+            let (p1, p2) = unsafe { (genome_bytes(parents[a].genome), genome_bytes(parents[b].genome)) };
+
+            let (mut c1, mut c2) = operators.crossover(p1, p2, &mut self.rng)?;
+            operators.mutate(&mut c1, &mut self.rng);
+            operators.mutate(&mut c2, &mut self.rng);
+            children.push(c1);
+            children.push(c2);
+        }
+
+        for child in children.iter().take(needed) {
+            self.add(child)?;
+        }
+        unsafe {
+            evocore_population_update_stats(&mut self.inner);
+            evocore_population_increment_generation(&mut self.inner);
+        }
+        Ok(())
+    }
+
+    /// Cross and mutate parents until `children` holds at least `needed` genomes
+    ///
+    /// Children are only added to the population once all were bred; the
+    /// caller frees every genome in `children` either way.
+    fn breed(
+        &mut self,
+        needed: usize,
+        mutation_rate: f64,
+        seed: &mut u32,
+        children: &mut Vec<evocore_genome_t>,
+    ) -> Result<(), String> {
+        while children.len() < needed {
+            let parents = self.individuals();
+            let a = unsafe { evocore_population_tournament_select(&self.inner, self.tournament_size, seed) };
+            let b = unsafe { evocore_population_tournament_select(&self.inner, self.tournament_size, seed) };
+            let (p1, p2) = (parents[a].genome, parents[b].genome);
+
+            let mut c1 = empty_genome();
+            let mut c2 = empty_genome();
+            let bred = unsafe { evocore_genome_crossover(p1, p2, &mut c1, &mut c2, seed) };
+            children.push(c1);
+            children.push(c2);
+            check(bred)?;
+
+            let n = children.len();
+            for child in &mut children[n - 2..] {
+                check(unsafe { evocore_genome_mutate(child, mutation_rate, seed) })?;
+            }
+        }
+
+        for child in children.iter().take(needed) {
+            check(unsafe { evocore_population_add(&mut self.inner, child, f64::NAN) })?;
+        }
+        Ok(())
+    }
+
+    /// Generations completed by [`reproduce`](Self::reproduce)
+    pub fn generation(&self) -> usize {
+        self.inner.generation
+    }
+
+    /// Number of individuals
+    pub fn len(&self) -> usize {
+        self.inner.size
+    }
+
+    /// Whether the population has no individuals
+    pub fn is_empty(&self) -> bool {
+        self.inner.size == 0
+    }
+
+    /// Genome and fitness (NaN if unevaluated) of one individual
+    pub fn get(&self, index: usize) -> Option<(&[u8], f64)> {
+        self.individuals()
+            .get(index)
+            .map(|i| (unsafe { genome_bytes(i.genome) }, i.fitness))
+    }
+
+    /// The fittest evaluated individual
+    pub fn best(&self) -> Option<(&[u8], f64)> {
+        if self.inner.best_fitness == f64::NEG_INFINITY {
+            return None;
+        }
+        self.get(self.inner.best_index)
+    }
+
+    /// Mean fitness of evaluated individuals (NaN if none)
+    pub fn avg_fitness(&self) -> f64 {
+        self.inner.avg_fitness
+    }
+
+    fn individuals(&self) -> &[crate::evocore_individual_t] {
+        if self.inner.individuals.is_null() {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.inner.individuals, self.inner.size) }
+    }
+
+    fn individuals_mut(&mut self) -> &mut [crate::evocore_individual_t] {
+        if self.inner.individuals.is_null() {
+            return &mut [];
+        }
+        unsafe { std::slice::from_raw_parts_mut(self.inner.individuals, self.inner.size) }
+    }
+}
+
+/// View a C genome's bytes
+///
+/// # Safety
+/// `genome` must be null or point to a live genome that outlives the slice.
+unsafe fn genome_bytes<'a>(genome: *const evocore_genome_t) -> &'a [u8] {
+    if genome.is_null() || (*genome).data.is_null() {
+        return &[];
+    }
+    std::slice::from_raw_parts((*genome).data as *const u8, (*genome).size)
+}
+
+impl Drop for Population {
+    fn drop(&mut self) {
+        unsafe { evocore_population_cleanup(&mut self.inner) };
+    }
+}
@@ -0,0 +1,260 @@
+//! Differential-privacy noise for exported statistics
+//!
+//! Learned state is derived from user behavior, so statistics shared with
+//! analysts can leak individual observations. [`PrivacyBudget`] adds
+//! calibrated Laplace noise to exported per-context statistics and tracks
+//! how much of the total privacy budget (epsilon) has been spent.
+//!
+//! Each context is computed from a disjoint set of observations, so one
+//! release of all contexts costs `epsilon` once (parallel composition). Within
+//! a context, `epsilon` is split evenly across the released statistics.
+//!
+//! Noise is calibrated to fixed sensitivities derived from the configured
+//! parameter and fitness ranges, never to a context's own data: sums are
+//! noised and divided by the noisy count (or noisy total weight, for the
+//! fitness-weighted parameter means). The stored statistics are aggregates,
+//! so single observations cannot be clamped after the fact; the guarantee
+//! only holds if every learned fitness and parameter lay within the
+//! configured ranges, which [`FitnessSpec`](crate::FitnessSpec) and
+//! [bounds](crate::ParamBounds) can enforce at learn time. Context keys are
+//! released only when a context's noisy count reaches
+//! [`with_min_count`](PrivacyBudget::with_min_count), so a context with a
+//! handful of observations is revealed only with small probability (the
+//! delta of an (epsilon, delta) guarantee) rather than always.
+//!
+//! The exports meant for people outside the team each have a private
+//! variant spending from a budget:
+//!
+//! | export                                                        | private variant                                                               |
+//! |---------------------------------------------------------------|-------------------------------------------------------------------------------|
+//! | [`export_csv`](EvoCoreContextSystem::export_csv)              | [`export_csv_private`](EvoCoreContextSystem::export_csv_private)              |
+//! | [`export_snapshot`](EvoCoreContextSystem::export_snapshot)    | [`export_snapshot_private`](EvoCoreContextSystem::export_snapshot_private)    |
+//! | [`report`](EvoCoreContextSystem::report)                      | [`report_private`](EvoCoreContextSystem::report_private)                      |
+//! | [`context_census`](EvoCoreContextSystem::context_census)      | [`context_census_private`](EvoCoreContextSystem::context_census_private)      |
+//! | `render_metrics` (feature `metrics`)                          | `render_metrics_private`                                                      |
+//!
+//! Checkpoints and the other exports are full-fidelity copies of the
+//! learned state and must not leave the trust boundary.
+
+use crate::{ContextState, EvoCoreContextSystem, ParamStats};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::Write as _;
+
+/// Smallest weight the C library gives an observation
+const MIN_WEIGHT: f64 = 0.0001;
+/// Noisy count below which a context is not released
+const DEFAULT_MIN_COUNT: f64 = 10.0;
+
+/// Tracks privacy budget spent on noisy releases of learned statistics
+pub struct PrivacyBudget {
+    total: f64,
+    spent: f64,
+    param_range: (f64, f64),
+    fitness_range: (f64, f64),
+    min_count: f64,
+    rng: StdRng,
+}
+
+impl PrivacyBudget {
+    /// Create a budget allowing `total_epsilon` to be spent across all releases
+    ///
+    /// Parameters are assumed to lie in `[0, 1]` and fitness in `[0, 1]`;
+    /// override with [`with_param_range`](Self::with_param_range) and
+    /// [`with_fitness_range`](Self::with_fitness_range).
+    pub fn new(total_epsilon: f64) -> Self {
+        Self {
+            total: total_epsilon,
+            spent: 0.0,
+            param_range: (0.0, 1.0),
+            fitness_range: (0.0, 1.0),
+            min_count: DEFAULT_MIN_COUNT,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Set the range parameter values are clamped to before noising
+    pub fn with_param_range(mut self, min: f64, max: f64) -> Self {
+        self.param_range = (min, max);
+        self
+    }
+
+    /// Set the range fitness values are clamped to before noising
+    pub fn with_fitness_range(mut self, min: f64, max: f64) -> Self {
+        self.fitness_range = (min, max);
+        self
+    }
+
+    /// Withhold contexts whose noisy experience count is below `min_count` (default 10)
+    ///
+    /// Higher thresholds make it less likely that a rarely seen context is
+    /// revealed at all, at the cost of dropping more small contexts.
+    pub fn with_min_count(mut self, min_count: f64) -> Self {
+        self.min_count = min_count;
+        self
+    }
+
+    /// Use a fixed RNG seed (for reproducible tests only; never in production)
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Draw noise from `rng`, e.g. [`EvoCoreContextSystem::rng`] in deterministic mode
+    pub fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// Epsilon still available
+    pub fn remaining(&self) -> f64 {
+        (self.total - self.spent).max(0.0)
+    }
+
+    /// Epsilon spent so far
+    pub fn spent(&self) -> f64 {
+        self.spent
+    }
+
+    /// Reserve `epsilon` from the budget
+    pub fn spend(&mut self, epsilon: f64) -> Result<(), String> {
+        if !(epsilon.is_finite() && epsilon > 0.0) {
+            return Err(format!("Invalid epsilon: {}", epsilon));
+        }
+        if epsilon > self.remaining() + f64::EPSILON {
+            return Err(format!(
+                "Privacy budget exhausted: requested {}, remaining {}",
+                epsilon,
+                self.remaining()
+            ));
+        }
+        self.spent += epsilon;
+        Ok(())
+    }
+
+    /// Add Laplace noise with scale `sensitivity / epsilon`
+    ///
+    /// Does not touch the budget; callers are expected to have reserved
+    /// `epsilon` with [`spend`](Self::spend).
+    pub fn laplace(&mut self, value: f64, sensitivity: f64, epsilon: f64) -> f64 {
+        let scale = sensitivity / epsilon;
+        let u: f64 = self.rng.gen_range(-0.5..0.5);
+        value - scale * u.signum() * (1.0 - 2.0 * u.abs()).max(f64::MIN_POSITIVE).ln()
+    }
+
+    /// Produce noisy copies of `states`, spending `epsilon` once
+    ///
+    /// Released values are experience counts, average and best fitness, and
+    /// per-parameter means and standard deviations. Contexts whose noisy
+    /// count falls below the [minimum](Self::with_min_count) are left out.
+    pub fn privatize(
+        &mut self,
+        states: &[ContextState],
+        epsilon: f64,
+    ) -> Result<Vec<ContextState>, String> {
+        self.spend(epsilon)?;
+        Ok(self.release(states, epsilon))
+    }
+
+    /// [`privatize`](Self::privatize) for callers that already spent `epsilon`
+    pub(crate) fn release(&mut self, states: &[ContextState], epsilon: f64) -> Vec<ContextState> {
+        states.iter().filter_map(|s| self.privatize_one(s, epsilon)).collect()
+    }
+
+    fn privatize_one(&mut self, state: &ContextState, epsilon: f64) -> Option<ContextState> {
+        let releases = 4 + 2 * state.params.len();
+        let eps = epsilon / releases as f64;
+        let (pmin, pmax) = self.param_range;
+        let (fmin, fmax) = self.fitness_range;
+        let prange = pmax - pmin;
+        let frange = fmax - fmin;
+        // One observation's weight is its fitness, floored by the C library
+        let max_weight = fmax.max(MIN_WEIGHT);
+
+        let n = state.total_experiences as f64;
+        let noisy_count = self.laplace(n, 1.0, eps);
+        if noisy_count < self.min_count {
+            return None;
+        }
+        let count = noisy_count.round().max(1.0) as usize;
+
+        // Sums of in-range values move by at most the range per observation
+        let fitness_sum = ((state.avg_fitness - fmin) * n).clamp(0.0, frange * n);
+        let avg_fitness = fmin + (self.laplace(fitness_sum, frange, eps) / noisy_count).clamp(0.0, frange);
+        let best_fitness = self
+            .laplace(state.best_fitness.clamp(fmin, fmax), frange, eps)
+            .clamp(fmin, fmax);
+
+        // Every parameter is learned with the same weights
+        let weight = state.params.first().map_or(0.0, |p| p.sum_weights).clamp(0.0, max_weight * n);
+        let noisy_weight = self.laplace(weight, max_weight, eps).max(MIN_WEIGHT);
+
+        let params = state
+            .params
+            .iter()
+            .map(|p| {
+                let weighted_sum = ((p.mean - pmin) * weight).clamp(0.0, prange * weight);
+                let noisy_sum = self.laplace(weighted_sum, max_weight * prange, eps);
+                let mean = pmin + (noisy_sum / noisy_weight).clamp(0.0, prange);
+                let m2 = p.m2.clamp(0.0, prange * prange * weight);
+                let variance = (self.laplace(m2, max_weight * prange * prange, eps) / noisy_weight)
+                    .clamp(0.0, prange * prange);
+                ParamStats {
+                    mean,
+                    variance,
+                    sum_weights: noisy_weight,
+                    m2: variance * noisy_weight,
+                    count,
+                    ..ParamStats::default()
+                }
+            })
+            .collect();
+
+        Some(ContextState {
+            key: state.key.clone(),
+            total_experiences: count,
+            confidence: (count as f64 / 100.0).sqrt().min(1.0),
+            avg_fitness,
+            best_fitness,
+            first_update: 0,
+            last_update: 0,
+            params,
+        })
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Export per-context statistics to CSV with differential-privacy noise
+    ///
+    /// Uses the same columns as [`export_csv`](Self::export_csv) and spends
+    /// `epsilon` from `budget`. Contexts below the budget's
+    /// [minimum count](PrivacyBudget::with_min_count) are left out.
+    pub fn export_csv_private(
+        &self,
+        filepath: &str,
+        budget: &mut PrivacyBudget,
+        epsilon: f64,
+    ) -> Result<(), String> {
+        let states = budget.privatize(&self.context_states(), epsilon)?;
+
+        let mut out = String::from("context");
+        for i in 0..self.param_count() {
+            let _ = write!(out, ",param_{}_mean,param_{}_std", i, i);
+        }
+        out.push_str(",experiences,confidence,avg_fitness,best_fitness\n");
+
+        for state in &states {
+            out.push_str(&state.key);
+            for p in &state.params {
+                let _ = write!(out, ",{:.6},{:.6}", p.mean, p.std());
+            }
+            let _ = writeln!(
+                out,
+                ",{},{:.6},{:.6},{:.6}",
+                state.total_experiences, state.confidence, state.avg_fitness, state.best_fitness
+            );
+        }
+
+        std::fs::write(filepath, out).map_err(|e| format!("Failed to export statistics: {}", e))
+    }
+}
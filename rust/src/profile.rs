@@ -0,0 +1,272 @@
+//! Deployment profiles
+//!
+//! The same service runs on edge devices with a few megabytes to spare,
+//! on trainers that learn from replayed logs, and on servers that sample
+//! under load. A [`Profile`] bundles the settings that differ between them
+//! (backend, save format and layers, autosave cadence, capacity and
+//! strategy settings) under a name, so a deployment picks one name instead
+//! of a dozen knobs:
+//!
+//! | profile   | saves            | autosave | capacity | strategy                              |
+//! |-----------|------------------|----------|----------|---------------------------------------|
+//! | `edge`    | binary, checksum | none     | 1 000    | little exploration, wildcard fallback |
+//! | `trainer` | binary, checksum | 5 min    | none     | generous exploration                  |
+//! | `server`  | binary, checksum | 1 min    | 100 000  | count-based, key cache, stale decay   |
+//!
+//! All three use the C backend. Every setting can be overridden with the
+//! `with_*` methods, including [`Backend::Rust`] for the pure-Rust
+//! implementation.
+
+use crate::{
+    ContextLearner, DecayConfig, DecayMode, EvoCoreContextSystem, ExplorationSchedule, Format, RustContextSystem,
+    SaveOptions, UptimeRamp,
+};
+use std::path::Path;
+use std::time::Duration;
+
+/// Which implementation a profile builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// The C library through FFI ([`EvoCoreContextSystem`])
+    Ffi,
+    /// The pure-Rust implementation ([`RustContextSystem`])
+    Rust,
+}
+
+/// Named bundle of deployment settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    name: String,
+    backend: Backend,
+    save_options: SaveOptions,
+    autosave: Option<Duration>,
+    max_contexts: Option<usize>,
+    key_cache: Option<usize>,
+    schedule: ExplorationSchedule,
+    decay: Option<DecayConfig>,
+    wildcard_fallback: Option<usize>,
+    uptime_ramp: Option<UptimeRamp>,
+}
+
+impl Profile {
+    /// A profile with library defaults, to be customized with the `with_*` methods
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            backend: Backend::Ffi,
+            save_options: SaveOptions::default(),
+            autosave: None,
+            max_contexts: None,
+            key_cache: None,
+            schedule: ExplorationSchedule::default(),
+            decay: None,
+            wildcard_fallback: None,
+            uptime_ramp: None,
+        }
+    }
+
+    /// Small devices: bounded memory, mostly exploiting
+    pub fn edge() -> Self {
+        Self::new("edge")
+            .with_save_options(SaveOptions::format(Format::Binary).with_checksum(true))
+            .with_max_contexts(Some(1_000))
+            .with_exploration_schedule(ExplorationSchedule::CountBased { scale: 0.5, min: 0.02 })
+            .with_wildcard_fallback(Some(3))
+    }
+
+    /// Offline learners: unbounded, exploring generously, frequent checkpoints
+    pub fn trainer() -> Self {
+        Self::new("trainer")
+            .with_save_options(SaveOptions::format(Format::Binary).with_checksum(true))
+            .with_autosave(Some(Duration::from_secs(5 * 60)))
+            .with_exploration_schedule(ExplorationSchedule::CountBased { scale: 1.0, min: 0.1 })
+    }
+
+    /// Online serving: bounded, cached keys, stale contexts drift back to exploring
+    pub fn server() -> Self {
+        Self::new("server")
+            .with_save_options(SaveOptions::format(Format::Binary).with_checksum(true))
+            .with_autosave(Some(Duration::from_secs(60)))
+            .with_max_contexts(Some(100_000))
+            .with_key_cache(Some(4096))
+            .with_decay(Some(
+                DecayConfig::new(Duration::from_secs(7 * 24 * 60 * 60)).with_mode(DecayMode::OnSample),
+            ))
+            .with_wildcard_fallback(Some(5))
+    }
+
+    /// Look up a built-in profile: `edge`, `trainer` or `server`
+    pub fn named(name: &str) -> Result<Self, String> {
+        match name {
+            "edge" => Ok(Self::edge()),
+            "trainer" => Ok(Self::trainer()),
+            "server" => Ok(Self::server()),
+            _ => Err(format!("Unknown profile: {} (expected edge, trainer or server)", name)),
+        }
+    }
+
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Format and layers used by [`save`](Self::save) and autosave
+    pub fn with_save_options(mut self, options: SaveOptions) -> Self {
+        self.save_options = options;
+        self
+    }
+
+    /// Autosave interval, or `None` to save only on demand
+    pub fn with_autosave(mut self, interval: Option<Duration>) -> Self {
+        self.autosave = interval;
+        self
+    }
+
+    /// Context limit with least-recently-used eviction (C backend only)
+    pub fn with_max_contexts(mut self, max_contexts: Option<usize>) -> Self {
+        self.max_contexts = max_contexts;
+        self
+    }
+
+    /// Context key cache capacity (C backend only)
+    pub fn with_key_cache(mut self, capacity: Option<usize>) -> Self {
+        self.key_cache = capacity;
+        self
+    }
+
+    pub fn with_exploration_schedule(mut self, schedule: ExplorationSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Recency weighting (C backend only)
+    pub fn with_decay(mut self, decay: Option<DecayConfig>) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    /// Wildcard fallback threshold (C backend only)
+    pub fn with_wildcard_fallback(mut self, min_samples: Option<usize>) -> Self {
+        self.wildcard_fallback = min_samples;
+        self
+    }
+
+    /// Exploration held off after startup (C backend only)
+    pub fn with_uptime_ramp(mut self, ramp: Option<UptimeRamp>) -> Self {
+        self.uptime_ramp = ramp;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub fn save_options(&self) -> SaveOptions {
+        self.save_options
+    }
+
+    pub fn autosave(&self) -> Option<Duration> {
+        self.autosave
+    }
+
+    pub fn exploration_schedule(&self) -> ExplorationSchedule {
+        self.schedule
+    }
+
+    /// Apply this profile's capacity and strategy settings to a C-backed system
+    pub fn configure(&self, mut system: EvoCoreContextSystem) -> EvoCoreContextSystem {
+        system.set_max_contexts(self.max_contexts);
+        match self.key_cache {
+            Some(capacity) => system.enable_key_cache(capacity),
+            None => system.disable_key_cache(),
+        }
+        system.set_exploration_schedule(self.schedule);
+        system.set_decay(self.decay);
+        system.set_wildcard_fallback(self.wildcard_fallback);
+        system.set_uptime_ramp(self.uptime_ramp);
+        system
+    }
+
+    /// Build a learner with this profile's backend and settings
+    ///
+    /// Settings marked "C backend only" do not apply to the Rust backend.
+    /// If the C backend is selected but the C library could not be loaded
+    /// (feature `dlopen`), a Rust backend is built instead and a warning
+    /// logged. On WebAssembly targets without the library the Rust backend
+    /// is always built.
+    pub fn build(
+        &self,
+        dimension_names: &[&str],
+        dimension_values: &[Vec<&str>],
+        param_count: usize,
+    ) -> Result<Box<dyn ContextLearner + Send>, String> {
+        let mut backend = self.backend;
+        if backend == Backend::Ffi {
+            if let Err(e) = crate::require_native() {
+                // Expected where the library never exists, such as WebAssembly
+                if !cfg!(evocore_no_native) {
+                    warn_rust_fallback(&self.name, &e);
+                }
+                backend = Backend::Rust;
+            }
+        }
+        Ok(match backend {
+            Backend::Ffi => Box::new(EvoCoreContextSystem::with_profile(
+                dimension_names,
+                dimension_values,
+                param_count,
+                self,
+            )?),
+            Backend::Rust => Box::new(RustContextSystem::new(dimension_names, dimension_values, param_count)?),
+        })
+    }
+
+    /// Save `system` with this profile's format and layers
+    pub fn save<P: AsRef<Path>>(&self, system: &EvoCoreContextSystem, filepath: P) -> Result<(), String> {
+        system.save_as(filepath, &self.save_options)
+    }
+
+    /// Start autosaving `system` to `filepath`, if this profile autosaves (feature `tokio`)
+    #[cfg(feature = "tokio")]
+    pub fn spawn_autosave<P: AsRef<Path>>(
+        &self,
+        system: &std::sync::Arc<crate::SharedContextSystem>,
+        filepath: P,
+    ) -> Option<crate::AutosaveHandle> {
+        self.autosave
+            .map(|interval| system.spawn_autosave_with(filepath, interval, self.save_options))
+    }
+}
+
+/// Report that a profile's C backend is replaced by the Rust one
+fn warn_rust_fallback(profile: &str, error: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(profile, error, "C library unavailable, using the Rust backend");
+    #[cfg(all(feature = "log", not(feature = "tracing")))]
+    log::warn!("profile {}: C library unavailable, using the Rust backend: {}", profile, error);
+    #[cfg(not(any(feature = "log", feature = "tracing")))]
+    eprintln!("evocore: profile {}: C library unavailable, using the Rust backend: {}", profile, error);
+}
+
+impl EvoCoreContextSystem {
+    /// Create a C-backed context system configured by `profile`
+    ///
+    /// Fails for profiles that select the Rust backend; use
+    /// [`Profile::build`] to honour the backend choice.
+    pub fn with_profile(
+        dimension_names: &[&str],
+        dimension_values: &[Vec<&str>],
+        param_count: usize,
+        profile: &Profile,
+    ) -> Result<Self, String> {
+        if profile.backend != Backend::Ffi {
+            return Err(format!("Profile {} does not use the C backend", profile.name));
+        }
+        let system = Self::new(dimension_names, dimension_values, param_count)?;
+        Ok(profile.configure(system))
+    }
+}
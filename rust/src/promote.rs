@@ -0,0 +1,303 @@
+//! Promoting trained checkpoints to production
+//!
+//! A checkpoint produced by offline training or simulation should not
+//! replace the one a service loads until it has been checked. [`promote`]
+//! reads the candidate, runs the configured [`PromotionChecks`], and only
+//! then copies it over the serving file through a temporary file renamed
+//! into place, so a reader never sees a half-written checkpoint and a
+//! failed check leaves production untouched.
+//!
+//! Checks, in order:
+//!
+//! 1. **Integrity**: every context matches the candidate's own schema and
+//!    holds only finite statistics (always on).
+//! 2. **Schema**: if a production file exists, the candidate has the same
+//!    dimension names and parameter count, and every production dimension
+//!    value is still declared (new values may be added).
+//! 3. **Holdout**: held-out examples are scored against the candidate, either
+//!    by [`estimate_fitness`](EvoCoreContextSystem::estimate_fitness) error
+//!    or by a custom evaluator.
+//! 4. **Non-regression**: the candidate's experience-weighted average
+//!    fitness, over contexts present in both files, is no more than a
+//!    tolerance below production's.
+//!
+//! To watch a promotion in production and undo it automatically, promote
+//! through a [`RollbackGuard`](crate::RollbackGuard).
+
+use crate::serializer::{unwrap_envelope, write_file};
+use crate::{Checkpoint, ContextState, EvoCoreContextSystem, Format, LearnExample, LoadError};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+type Evaluator = Box<dyn Fn(&EvoCoreContextSystem) -> Result<f64, String>>;
+
+/// What a candidate checkpoint must pass before [`promote`] replaces production
+pub struct PromotionChecks {
+    format: Format,
+    schema: bool,
+    holdout: Option<(Vec<LearnExample>, f64)>,
+    evaluator: Option<(Evaluator, f64)>,
+    max_regression: Option<f64>,
+}
+
+impl Default for PromotionChecks {
+    fn default() -> Self {
+        Self {
+            format: Format::default(),
+            schema: true,
+            holdout: None,
+            evaluator: None,
+            max_regression: None,
+        }
+    }
+}
+
+impl fmt::Debug for PromotionChecks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PromotionChecks")
+            .field("format", &self.format)
+            .field("schema", &self.schema)
+            .field("holdout", &self.holdout.as_ref().map(|(e, max)| (e.len(), *max)))
+            .field("evaluator", &self.evaluator.as_ref().map(|(_, min)| *min))
+            .field("max_regression", &self.max_regression)
+            .finish()
+    }
+}
+
+impl PromotionChecks {
+    /// Integrity and schema checks on JSON files
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Format of both checkpoint files (default JSON)
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Compare the schema against production (default on)
+    pub fn with_schema_check(mut self, enabled: bool) -> Self {
+        self.schema = enabled;
+        self
+    }
+
+    /// Require the mean absolute fitness-estimate error on `examples` to be at most `max_error`
+    ///
+    /// Examples whose context the candidate has not learned are skipped; if
+    /// none can be scored the check fails.
+    pub fn with_holdout(mut self, examples: Vec<LearnExample>, max_error: f64) -> Self {
+        self.holdout = Some((examples, max_error));
+        self
+    }
+
+    /// Require `evaluator` to score the candidate at least `min_score`
+    pub fn with_evaluator(
+        mut self,
+        min_score: f64,
+        evaluator: impl Fn(&EvoCoreContextSystem) -> Result<f64, String> + 'static,
+    ) -> Self {
+        self.evaluator = Some((Box::new(evaluator), min_score));
+        self
+    }
+
+    /// Allow the weighted average fitness to drop at most `tolerance` below production
+    pub fn with_max_regression(mut self, tolerance: f64) -> Self {
+        self.max_regression = Some(tolerance.max(0.0));
+        self
+    }
+}
+
+/// Why a candidate was not promoted
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromotionError {
+    /// A file could not be read, parsed or written
+    Io(String),
+    /// The candidate failed validation against its own schema
+    Invalid(LoadError),
+    /// The candidate's schema is incompatible with production
+    SchemaMismatch(String),
+    /// The holdout error or evaluator score missed its threshold
+    HoldoutFailed { score: f64, threshold: f64 },
+    /// The candidate's fitness regressed beyond the tolerance
+    Regression { candidate: f64, production: f64, tolerance: f64 },
+}
+
+impl fmt::Display for PromotionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromotionError::Io(e) => write!(f, "I/O error: {}", e),
+            PromotionError::Invalid(e) => write!(f, "Invalid candidate: {}", e),
+            PromotionError::SchemaMismatch(e) => write!(f, "Schema mismatch: {}", e),
+            PromotionError::HoldoutFailed { score, threshold } => {
+                write!(f, "Holdout score {} misses threshold {}", score, threshold)
+            }
+            PromotionError::Regression { candidate, production, tolerance } => write!(
+                f,
+                "Fitness regressed from {} to {} (tolerance {})",
+                production, candidate, tolerance
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PromotionError {}
+
+/// What [`promote`] checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromotionReport {
+    /// Contexts in the promoted checkpoint
+    pub contexts: usize,
+    /// Whether a production file existed and was replaced
+    pub replaced: bool,
+    /// Mean absolute error on the holdout examples, if checked
+    pub holdout_error: Option<f64>,
+    /// Custom evaluator score, if checked
+    pub evaluator_score: Option<f64>,
+    /// Weighted average fitness of the candidate over shared contexts, if compared
+    pub candidate_fitness: Option<f64>,
+    /// Weighted average fitness of production over the same contexts, if compared
+    pub production_fitness: Option<f64>,
+}
+
+/// Validate the checkpoint at `from` and atomically replace `to` with it
+///
+/// `from` is left in place. On any failed check `to` is not touched.
+pub fn promote<P: AsRef<Path>, Q: AsRef<Path>>(
+    from: P,
+    to: Q,
+    checks: PromotionChecks,
+) -> Result<PromotionReport, PromotionError> {
+    let to = to.as_ref();
+    let data = std::fs::read(from.as_ref()).map_err(|e| PromotionError::Io(e.to_string()))?;
+    let candidate = decode(data.clone(), checks.format)?;
+    for state in candidate.contexts.iter().chain(&candidate.stable) {
+        candidate.validate_context(state).map_err(PromotionError::Invalid)?;
+    }
+
+    let production = if to.exists() {
+        let data = std::fs::read(to).map_err(|e| PromotionError::Io(e.to_string()))?;
+        Some(decode(data, checks.format)?)
+    } else {
+        None
+    };
+
+    if let Some(production) = production.as_ref().filter(|_| checks.schema) {
+        check_schema(&candidate, production)?;
+    }
+
+    let mut report = PromotionReport {
+        contexts: candidate.contexts.len(),
+        replaced: production.is_some(),
+        holdout_error: None,
+        evaluator_score: None,
+        candidate_fitness: None,
+        production_fitness: None,
+    };
+
+    if checks.holdout.is_some() || checks.evaluator.is_some() {
+        let system = candidate.clone().into_system().map_err(PromotionError::Invalid)?;
+        if let Some((examples, max_error)) = &checks.holdout {
+            let error = holdout_error(&system, examples);
+            if error.is_nan() || error > *max_error {
+                return Err(PromotionError::HoldoutFailed { score: error, threshold: *max_error });
+            }
+            report.holdout_error = Some(error);
+        }
+        if let Some((evaluator, min_score)) = &checks.evaluator {
+            let score = evaluator(&system).map_err(PromotionError::Io)?;
+            if score.is_nan() || score < *min_score {
+                return Err(PromotionError::HoldoutFailed { score, threshold: *min_score });
+            }
+            report.evaluator_score = Some(score);
+        }
+    }
+
+    if let (Some(tolerance), Some(production)) = (checks.max_regression, &production) {
+        if let Some((candidate_fitness, production_fitness)) = shared_fitness(&candidate, production) {
+            if candidate_fitness < production_fitness - tolerance {
+                return Err(PromotionError::Regression {
+                    candidate: candidate_fitness,
+                    production: production_fitness,
+                    tolerance,
+                });
+            }
+            report.candidate_fitness = Some(candidate_fitness);
+            report.production_fitness = Some(production_fitness);
+        }
+    }
+
+    write_file(to, &data, true).map_err(PromotionError::Io)?;
+    Ok(report)
+}
+
+pub(crate) fn decode(data: Vec<u8>, format: Format) -> Result<Checkpoint, PromotionError> {
+    let data = unwrap_envelope(data).map_err(PromotionError::Invalid)?;
+    format.serializer().deserialize(&data).map_err(PromotionError::Io)
+}
+
+fn check_schema(candidate: &Checkpoint, production: &Checkpoint) -> Result<(), PromotionError> {
+    if candidate.param_count != production.param_count {
+        return Err(PromotionError::SchemaMismatch(format!(
+            "parameter count {} differs from production's {}",
+            candidate.param_count, production.param_count
+        )));
+    }
+    let names = |c: &Checkpoint| c.dimensions.iter().map(|(n, _)| n.clone()).collect::<Vec<_>>();
+    if names(candidate) != names(production) {
+        return Err(PromotionError::SchemaMismatch(format!(
+            "dimensions {:?} differ from production's {:?}",
+            names(candidate),
+            names(production)
+        )));
+    }
+    for ((name, values), (_, production_values)) in candidate.dimensions.iter().zip(&production.dimensions) {
+        if let Some(missing) = production_values.iter().find(|v| !values.contains(v)) {
+            return Err(PromotionError::SchemaMismatch(format!(
+                "dimension {:?} no longer declares value {:?}",
+                name, missing
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Mean absolute error of fitness estimates on `examples` (infinite if none could be scored)
+fn holdout_error(system: &EvoCoreContextSystem, examples: &[LearnExample]) -> f64 {
+    let mut total = 0.0;
+    let mut scored = 0;
+    for example in examples {
+        let dims: Vec<&str> = example.dimension_values.iter().map(String::as_str).collect();
+        if let Ok(Some(estimate)) = system.estimate_fitness(&dims, &example.parameters) {
+            total += (estimate.expected - example.fitness).abs();
+            scored += 1;
+        }
+    }
+    if scored == 0 {
+        f64::INFINITY
+    } else {
+        total / scored as f64
+    }
+}
+
+/// Experience-weighted average fitness of both checkpoints over their shared contexts
+fn shared_fitness(candidate: &Checkpoint, production: &Checkpoint) -> Option<(f64, f64)> {
+    let candidates: HashMap<&str, &ContextState> =
+        candidate.contexts.iter().map(|c| (c.key.as_str(), c)).collect();
+    let mut sums = (0.0, 0.0, 0.0, 0.0);
+    for old in &production.contexts {
+        let Some(new) = candidates.get(old.key.as_str()) else {
+            continue;
+        };
+        sums.0 += new.avg_fitness * new.total_experiences as f64;
+        sums.1 += new.total_experiences as f64;
+        sums.2 += old.avg_fitness * old.total_experiences as f64;
+        sums.3 += old.total_experiences as f64;
+    }
+    if sums.1 > 0.0 && sums.3 > 0.0 {
+        Some((sums.0 / sums.1, sums.2 / sums.3))
+    } else {
+        None
+    }
+}
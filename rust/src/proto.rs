@@ -0,0 +1,173 @@
+//! Protobuf snapshot format (feature `proto`)
+//!
+//! The message types mirror `proto/snapshot.proto`, which is the source of
+//! truth for other languages; keep the two in sync. Unlike the JSON format,
+//! every statistic is stored, so a round trip is lossless.
+
+use crate::{Checkpoint, ContextState, ParamStats, SystemSerializer};
+use prost::Message;
+
+/// `evocore.v1.Snapshot`
+#[derive(Clone, PartialEq, Message)]
+pub struct SnapshotProto {
+    #[prost(message, repeated, tag = "1")]
+    pub dimensions: Vec<DimensionProto>,
+    #[prost(uint32, tag = "2")]
+    pub param_count: u32,
+    #[prost(message, repeated, tag = "3")]
+    pub contexts: Vec<ContextProto>,
+    #[prost(message, repeated, tag = "4")]
+    pub stable: Vec<ContextProto>,
+}
+
+/// `evocore.v1.Dimension`
+#[derive(Clone, PartialEq, Message)]
+pub struct DimensionProto {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, repeated, tag = "2")]
+    pub values: Vec<String>,
+}
+
+/// `evocore.v1.Context`
+#[derive(Clone, PartialEq, Message)]
+pub struct ContextProto {
+    #[prost(string, tag = "1")]
+    pub key: String,
+    #[prost(uint64, tag = "2")]
+    pub total_experiences: u64,
+    #[prost(double, tag = "3")]
+    pub confidence: f64,
+    #[prost(double, tag = "4")]
+    pub avg_fitness: f64,
+    #[prost(double, tag = "5")]
+    pub best_fitness: f64,
+    #[prost(int64, tag = "6")]
+    pub first_update: i64,
+    #[prost(int64, tag = "7")]
+    pub last_update: i64,
+    #[prost(message, repeated, tag = "8")]
+    pub params: Vec<ParamProto>,
+}
+
+/// `evocore.v1.Param`
+#[derive(Clone, PartialEq, Message)]
+pub struct ParamProto {
+    #[prost(double, tag = "1")]
+    pub mean: f64,
+    #[prost(double, tag = "2")]
+    pub variance: f64,
+    #[prost(double, tag = "3")]
+    pub sum_weights: f64,
+    #[prost(double, tag = "4")]
+    pub m2: f64,
+    #[prost(uint64, tag = "5")]
+    pub count: u64,
+    #[prost(double, tag = "6")]
+    pub min_value: f64,
+    #[prost(double, tag = "7")]
+    pub max_value: f64,
+    #[prost(double, tag = "8")]
+    pub sum_weighted_x: f64,
+}
+
+impl From<&ContextState> for ContextProto {
+    fn from(state: &ContextState) -> Self {
+        Self {
+            key: state.key.clone(),
+            total_experiences: state.total_experiences as u64,
+            confidence: state.confidence,
+            avg_fitness: state.avg_fitness,
+            best_fitness: state.best_fitness,
+            first_update: state.first_update,
+            last_update: state.last_update,
+            params: state
+                .params
+                .iter()
+                .map(|p| ParamProto {
+                    mean: p.mean,
+                    variance: p.variance,
+                    sum_weights: p.sum_weights,
+                    m2: p.m2,
+                    count: p.count as u64,
+                    min_value: p.min_value,
+                    max_value: p.max_value,
+                    sum_weighted_x: p.sum_weighted_x,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<ContextProto> for ContextState {
+    fn from(proto: ContextProto) -> Self {
+        Self {
+            key: proto.key,
+            total_experiences: proto.total_experiences as usize,
+            confidence: proto.confidence,
+            avg_fitness: proto.avg_fitness,
+            best_fitness: proto.best_fitness,
+            first_update: proto.first_update,
+            last_update: proto.last_update,
+            params: proto
+                .params
+                .into_iter()
+                .map(|p| ParamStats {
+                    mean: p.mean,
+                    variance: p.variance,
+                    sum_weights: p.sum_weights,
+                    m2: p.m2,
+                    count: p.count as usize,
+                    min_value: p.min_value,
+                    max_value: p.max_value,
+                    sum_weighted_x: p.sum_weighted_x,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&Checkpoint> for SnapshotProto {
+    fn from(checkpoint: &Checkpoint) -> Self {
+        Self {
+            dimensions: checkpoint
+                .dimensions
+                .iter()
+                .map(|(name, values)| DimensionProto {
+                    name: name.clone(),
+                    values: values.clone(),
+                })
+                .collect(),
+            param_count: checkpoint.param_count as u32,
+            contexts: checkpoint.contexts.iter().map(ContextProto::from).collect(),
+            stable: checkpoint.stable.iter().map(ContextProto::from).collect(),
+        }
+    }
+}
+
+impl From<SnapshotProto> for Checkpoint {
+    fn from(proto: SnapshotProto) -> Self {
+        Self {
+            dimensions: proto.dimensions.into_iter().map(|d| (d.name, d.values)).collect(),
+            param_count: proto.param_count as usize,
+            contexts: proto.contexts.into_iter().map(ContextState::from).collect(),
+            stable: proto.stable.into_iter().map(ContextState::from).collect(),
+        }
+    }
+}
+
+/// Protobuf-encoded [`SnapshotProto`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProtoSerializer;
+
+impl SystemSerializer for ProtoSerializer {
+    fn serialize(&self, checkpoint: &Checkpoint) -> Result<Vec<u8>, String> {
+        Ok(SnapshotProto::from(checkpoint).encode_to_vec())
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<Checkpoint, String> {
+        SnapshotProto::decode(data)
+            .map(Checkpoint::from)
+            .map_err(|e| format!("Invalid protobuf snapshot: {}", e))
+    }
+}
@@ -0,0 +1,100 @@
+//! Context pruning
+//!
+//! Systems whose dimensions include high-cardinality values (user IDs,
+//! session tags) create contexts without bound. [`EvoCoreContextSystem::prune`]
+//! removes contexts by a [`PrunePolicy`], freeing their C-side memory.
+
+use crate::decay::unix_now;
+use crate::{evocore_context_remove_key, EvoCoreContextSystem};
+use std::ffi::CString;
+use std::time::Duration;
+
+/// Rule selecting which contexts [`prune`](EvoCoreContextSystem::prune) removes
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrunePolicy {
+    /// Drop contexts with fewer than this many experiences
+    MinSamples(usize),
+    /// Drop contexts not updated within this long
+    UnseenFor(Duration),
+    /// Keep only the K contexts with the highest average fitness
+    TopKByFitness(usize),
+    /// Apply each policy in turn
+    All(Vec<PrunePolicy>),
+}
+
+impl EvoCoreContextSystem {
+    /// Remove contexts selected by `policy`; returns how many were removed
+    pub fn prune(&mut self, policy: &PrunePolicy) -> usize {
+        let mut states = self.context_states();
+        let doomed: Vec<String> = match policy {
+            PrunePolicy::MinSamples(min) => states
+                .into_iter()
+                .filter(|s| s.total_experiences < *min)
+                .map(|s| s.key)
+                .collect(),
+            PrunePolicy::UnseenFor(age) => {
+                let cutoff = unix_now() - age.as_secs() as i64;
+                states
+                    .into_iter()
+                    .filter(|s| s.last_update < cutoff)
+                    .map(|s| s.key)
+                    .collect()
+            }
+            PrunePolicy::TopKByFitness(k) => {
+                states.sort_by(|a, b| {
+                    b.avg_fitness
+                        .total_cmp(&a.avg_fitness)
+                        .then_with(|| a.key.cmp(&b.key))
+                });
+                states.into_iter().skip(*k).map(|s| s.key).collect()
+            }
+            PrunePolicy::All(policies) => {
+                return policies.iter().map(|p| self.prune(p)).sum();
+            }
+        };
+
+        doomed.iter().filter(|key| self.remove_key(key)).count()
+    }
+
+    /// Delete one context by key, along with any Rust-side state for it
+    ///
+    /// Explicit pins and overrides are configuration, not learned state,
+    /// and are kept.
+    pub(crate) fn remove_key(&mut self, key: &str) -> bool {
+        let Ok(c_key) = CString::new(key) else {
+            return false;
+        };
+        let removed = unsafe { evocore_context_remove_key(self.inner.as_ptr(), c_key.as_ptr()) };
+        if removed {
+            if let Some(explanations) = &self.explanations {
+                explanations.forget(key);
+            }
+            if let Some(canary) = &mut self.canary {
+                canary.forget(key);
+            }
+            if let Some(lru) = &self.lru {
+                lru.forget(key);
+            }
+            self.hot.forget(key);
+            self.stable_slots.remove(key);
+            self.versions.remove(key);
+            self.recent_fitness.remove(key);
+            if let Some(normalizer) = &mut self.normalizer {
+                normalizer.forget(key);
+            }
+            if let Some(anomalies) = &mut self.anomalies {
+                anomalies.forget(key);
+            }
+            if let Some(update_weights) = &mut self.update_weights {
+                update_weights.forget(key);
+            }
+            if let Some(novelty) = &mut self.novelty {
+                novelty.forget(key);
+            }
+            if let Some(strategy) = &self.strategy {
+                strategy.forget(key);
+            }
+        }
+        removed
+    }
+}
@@ -0,0 +1,152 @@
+//! Python bindings (feature `python`)
+//!
+//! Builds the cdylib as the Python extension module `evocore_sys`, whose
+//! `ContextSystem` class wraps [`EvoCoreContextSystem`] itself: the same
+//! validation, bounds and sampling as from Rust, and save files that either
+//! side loads. Build a wheel with `maturin build --release` (see
+//! `pyproject.toml`, which enables `extension-module`).
+//!
+//! ```python
+//! from evocore_sys import ContextSystem
+//!
+//! system = ContextSystem(["lang", "editor"], [["rust", "c"], ["vim", "emacs"]], 3, seed=7)
+//! system.learn(["rust", "vim"], [0.2, 0.5, 0.9], fitness=0.8)
+//! params = system.sample(["rust", "vim"], exploration=0.1)
+//! system.save("state.json")
+//! system = ContextSystem.load("state.json")
+//! ```
+//!
+//! Rejected experiences raise `ValueError`; failures of the C library and
+//! of saving or loading raise `RuntimeError`.
+
+// The pymethods expansion converts every returned error into PyErr
+#![allow(clippy::useless_conversion)]
+
+use crate::{ContextState, EvoCoreContextSystem, LearnError};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+fn runtime_error(e: String) -> PyErr {
+    PyRuntimeError::new_err(e)
+}
+
+fn learn_error(e: LearnError) -> PyErr {
+    match e {
+        LearnError::Failed(e) => PyRuntimeError::new_err(e),
+        e => PyValueError::new_err(e.to_string()),
+    }
+}
+
+fn strs(values: &[String]) -> Vec<&str> {
+    values.iter().map(String::as_str).collect()
+}
+
+fn state_dict<'py>(py: Python<'py>, state: &ContextState) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("key", &state.key)?;
+    dict.set_item("total_experiences", state.total_experiences)?;
+    dict.set_item("confidence", state.confidence)?;
+    dict.set_item("avg_fitness", state.avg_fitness)?;
+    dict.set_item("best_fitness", state.best_fitness)?;
+    dict.set_item("first_update", state.first_update)?;
+    dict.set_item("last_update", state.last_update)?;
+    dict.set_item("means", state.params.iter().map(|p| p.mean).collect::<Vec<_>>())?;
+    dict.set_item("stds", state.params.iter().map(|p| p.std()).collect::<Vec<_>>())?;
+    dict.set_item("counts", state.params.iter().map(|p| p.count).collect::<Vec<_>>())?;
+    Ok(dict)
+}
+
+/// An EvoCore context system
+#[pyclass(name = "ContextSystem", module = "evocore_sys")]
+pub struct PyContextSystem {
+    inner: EvoCoreContextSystem,
+}
+
+#[pymethods]
+impl PyContextSystem {
+    /// Create a system; with `seed`, sampling is reproducible
+    #[new]
+    #[pyo3(signature = (dimension_names, dimension_values, param_count, seed = None))]
+    fn new(
+        dimension_names: Vec<String>,
+        dimension_values: Vec<Vec<String>>,
+        param_count: usize,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        let names = strs(&dimension_names);
+        let values: Vec<Vec<&str>> = dimension_values.iter().map(|v| strs(v)).collect();
+        let inner = match seed {
+            Some(seed) => EvoCoreContextSystem::deterministic(&names, &values, param_count, seed),
+            None => EvoCoreContextSystem::new(&names, &values, param_count),
+        };
+        inner.map(|inner| Self { inner }).map_err(PyValueError::new_err)
+    }
+
+    /// Load a system saved in either format by Rust or Python
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        EvoCoreContextSystem::load(path).map(|inner| Self { inner }).map_err(runtime_error)
+    }
+
+    /// Save as JSON, or in the compact binary format with `binary=True`
+    #[pyo3(signature = (path, binary = false))]
+    fn save(&self, path: &str, binary: bool) -> PyResult<()> {
+        let result = if binary { self.inner.save_binary(path) } else { self.inner.save(path) };
+        result.map_err(runtime_error)
+    }
+
+    /// Learn from one experience
+    fn learn(&mut self, dimension_values: Vec<String>, parameters: Vec<f64>, fitness: f64) -> PyResult<()> {
+        self.inner
+            .learn_checked(&strs(&dimension_values), &parameters, fitness)
+            .map_err(learn_error)
+    }
+
+    /// Sample parameters; `exploration` runs from 0.0 (exploit) to 1.0 (explore)
+    #[pyo3(signature = (dimension_values, exploration = 0.0))]
+    fn sample(&self, dimension_values: Vec<String>, exploration: f64) -> PyResult<Vec<f64>> {
+        self.inner
+            .sample(&strs(&dimension_values), exploration)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Statistics of every learned context, as a list of dicts
+    fn stats<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.inner.context_states().iter().map(|s| state_dict(py, s)).collect()
+    }
+
+    /// Statistics of one context, or None if it has not been learned
+    fn context_stats<'py>(&self, py: Python<'py>, dimension_values: Vec<String>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let key = self.inner.context_key(&strs(&dimension_values)).map_err(PyValueError::new_err)?;
+        self.inner.context_state(&key).map(|s| state_dict(py, &s)).transpose()
+    }
+
+    fn context_keys(&self) -> Vec<String> {
+        self.inner.context_keys()
+    }
+
+    #[getter]
+    fn context_count(&self) -> usize {
+        self.inner.context_count()
+    }
+
+    #[getter]
+    fn param_count(&self) -> usize {
+        self.inner.param_count()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ContextSystem(param_count={}, contexts={})",
+            self.inner.param_count(),
+            self.inner.context_count()
+        )
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "evocore_sys")]
+fn python_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyContextSystem>()
+}
@@ -0,0 +1,297 @@
+//! Guided setup for first-time integrations
+//!
+//! [`QuickStart`] takes a handful of example contexts and the parameter
+//! values currently hard-coded in the caller, and proposes a complete
+//! configuration: dimensions and their values, a range for each parameter,
+//! and a prior centred on each default. The [`QuickStartProposal`] can be
+//! reviewed, written out as an INI config file, and turned into a ready
+//! [`EvoCoreContextSystem`] whose example contexts start out sampling
+//! near the defaults instead of uniformly at random.
+
+use crate::{ContextState, EvoCoreContextSystem, ParamStats};
+use std::fmt::Write as _;
+
+/// Observation count given to each prior, the minimum the sampler needs
+/// before it draws from the learned distribution instead of uniformly
+const PRIOR_COUNT: usize = 3;
+/// Spread of the prior around each default, in normalized units
+const PRIOR_STD: f64 = 0.1;
+/// Default total weight of a prior; a single real experience with fitness
+/// 1.0 outweighs it ten to one
+const DEFAULT_PRIOR_WEIGHT: f64 = 0.1;
+
+/// Collects examples and defaults for a [`QuickStartProposal`]
+#[derive(Debug, Clone)]
+pub struct QuickStart {
+    examples: Vec<Vec<(String, String)>>,
+    params: Vec<ParamProposal>,
+    prior_weight: f64,
+}
+
+/// One proposed parameter: its default and the range it is sampled over
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamProposal {
+    pub name: String,
+    pub default: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ParamProposal {
+    /// Map a value in `[min, max]` to the `[0, 1]` range the learner works in
+    pub fn normalize(&self, value: f64) -> f64 {
+        ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+
+    /// Map a sampled `[0, 1]` value back to `[min, max]`
+    pub fn denormalize(&self, value: f64) -> f64 {
+        self.min + value.clamp(0.0, 1.0) * (self.max - self.min)
+    }
+}
+
+/// A complete proposed configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickStartProposal {
+    /// Dimension names and values, in order of first appearance
+    pub dimensions: Vec<(String, Vec<String>)>,
+    /// Parameters, in the order they were declared
+    pub params: Vec<ParamProposal>,
+    /// Contexts seen in the examples, as dimension values in schema order
+    pub contexts: Vec<Vec<String>>,
+    /// Total weight of each context's prior
+    pub prior_weight: f64,
+    /// Things worth reviewing before relying on the proposal
+    pub notes: Vec<String>,
+}
+
+impl QuickStart {
+    /// Start an empty setup
+    pub fn new() -> Self {
+        Self {
+            examples: Vec::new(),
+            params: Vec::new(),
+            prior_weight: DEFAULT_PRIOR_WEIGHT,
+        }
+    }
+
+    /// Add an example context as `(dimension, value)` pairs
+    pub fn with_example(mut self, context: &[(&str, &str)]) -> Self {
+        self.examples.push(
+            context
+                .iter()
+                .map(|(d, v)| (d.to_string(), v.to_string()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Declare a parameter and the value the caller uses today
+    ///
+    /// The range is proposed from the default: `[0, 1]` if it already lies
+    /// there, otherwise `[0, 2 * default]` (or the mirror image for
+    /// negative defaults).
+    pub fn with_param(self, name: &str, default: f64) -> Self {
+        let (min, max) = if (0.0..=1.0).contains(&default) {
+            (0.0, 1.0)
+        } else if default > 0.0 {
+            (0.0, 2.0 * default)
+        } else {
+            (2.0 * default, 0.0)
+        };
+        self.with_param_range(name, default, min, max)
+    }
+
+    /// Declare a parameter with an explicit range
+    pub fn with_param_range(mut self, name: &str, default: f64, min: f64, max: f64) -> Self {
+        self.params.push(ParamProposal {
+            name: name.to_string(),
+            default,
+            min,
+            max,
+        });
+        self
+    }
+
+    /// Set how much weight each prior carries against real experience
+    pub fn with_prior_weight(mut self, weight: f64) -> Self {
+        self.prior_weight = weight;
+        self
+    }
+
+    /// Derive a configuration from the examples and parameters
+    pub fn propose(&self) -> Result<QuickStartProposal, String> {
+        if self.examples.is_empty() {
+            return Err("QuickStart needs at least one example context".to_string());
+        }
+        if self.params.is_empty() {
+            return Err("QuickStart needs at least one parameter".to_string());
+        }
+        if !(self.prior_weight.is_finite() && self.prior_weight > 0.0) {
+            return Err(format!("Invalid prior weight: {}", self.prior_weight));
+        }
+
+        let mut dimensions: Vec<(String, Vec<String>)> = Vec::new();
+        for example in &self.examples {
+            for (name, value) in example {
+                match dimensions.iter_mut().find(|(n, _)| n == name) {
+                    Some((_, values)) => {
+                        if !values.contains(value) {
+                            values.push(value.clone());
+                        }
+                    }
+                    None => dimensions.push((name.clone(), vec![value.clone()])),
+                }
+            }
+        }
+
+        let mut contexts: Vec<Vec<String>> = Vec::new();
+        for (i, example) in self.examples.iter().enumerate() {
+            let context = dimensions
+                .iter()
+                .map(|(name, _)| {
+                    example
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .map(|(_, v)| v.clone())
+                        .ok_or_else(|| format!("Example {} is missing dimension '{}'", i, name))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if !contexts.contains(&context) {
+                contexts.push(context);
+            }
+        }
+
+        let mut notes: Vec<String> = dimensions
+            .iter()
+            .filter(|(_, values)| values.len() == 1)
+            .map(|(name, values)| {
+                format!(
+                    "dimension '{}' only has the value '{}' in the examples; add more examples or drop it",
+                    name, values[0]
+                )
+            })
+            .collect();
+
+        for param in &self.params {
+            if !(param.min.is_finite() && param.max.is_finite() && param.min < param.max) {
+                return Err(format!(
+                    "Invalid range for parameter '{}': [{}, {}]",
+                    param.name, param.min, param.max
+                ));
+            }
+            if !(param.min..=param.max).contains(&param.default) {
+                notes.push(format!(
+                    "default {} for parameter '{}' lies outside [{}, {}]",
+                    param.default, param.name, param.min, param.max
+                ));
+            }
+        }
+
+        Ok(QuickStartProposal {
+            dimensions,
+            params: self.params.clone(),
+            contexts,
+            prior_weight: self.prior_weight,
+            notes,
+        })
+    }
+}
+
+impl Default for QuickStart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuickStartProposal {
+    /// Render the proposal as an INI config file
+    pub fn to_config(&self) -> String {
+        let mut out = String::new();
+        out.push_str("; EvoCore context system configuration (generated by QuickStart)\n");
+        for note in &self.notes {
+            let _ = writeln!(out, "; NOTE: {}", note);
+        }
+
+        out.push_str("\n[context]\n");
+        let names: Vec<&str> = self.dimensions.iter().map(|(n, _)| n.as_str()).collect();
+        let _ = writeln!(out, "dimensions = {}", names.join(","));
+        let _ = writeln!(out, "param_count = {}", self.params.len());
+        let _ = writeln!(out, "prior_weight = {}", self.prior_weight);
+
+        for (name, values) in &self.dimensions {
+            let _ = writeln!(out, "\n[dimension.{}]", name);
+            let _ = writeln!(out, "values = {}", values.join(","));
+        }
+
+        for (i, param) in self.params.iter().enumerate() {
+            let _ = writeln!(out, "\n[param.{}]", i);
+            let _ = writeln!(out, "name = {}", param.name);
+            let _ = writeln!(out, "default = {}", param.default);
+            let _ = writeln!(out, "min = {}", param.min);
+            let _ = writeln!(out, "max = {}", param.max);
+        }
+
+        out
+    }
+
+    /// Write the INI config file to `path`
+    pub fn write_config(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_config()).map_err(|e| format!("Failed to write config: {}", e))
+    }
+
+    /// Prior state for one context: a tight distribution around each default
+    fn prior(&self, key: String) -> ContextState {
+        let params = self
+            .params
+            .iter()
+            .map(|param| {
+                let mean = param.normalize(param.default);
+                let variance = PRIOR_STD * PRIOR_STD;
+                ParamStats {
+                    mean,
+                    variance,
+                    sum_weights: self.prior_weight,
+                    m2: variance * self.prior_weight,
+                    count: PRIOR_COUNT,
+                    min_value: (mean - PRIOR_STD).max(0.0),
+                    max_value: (mean + PRIOR_STD).min(1.0),
+                    sum_weighted_x: mean * self.prior_weight,
+                }
+            })
+            .collect();
+
+        ContextState {
+            key,
+            total_experiences: 0,
+            confidence: (PRIOR_COUNT as f64 / 100.0).sqrt(),
+            avg_fitness: 0.0,
+            best_fitness: 0.0,
+            first_update: 0,
+            last_update: 0,
+            params,
+        }
+    }
+
+    /// Create the proposed system with priors for every example context
+    ///
+    /// The system samples in normalized `[0, 1]` units; map values through
+    /// [`ParamProposal::denormalize`] before use and
+    /// [`ParamProposal::normalize`] before learning.
+    pub fn build(&self) -> Result<EvoCoreContextSystem, String> {
+        let names: Vec<&str> = self.dimensions.iter().map(|(n, _)| n.as_str()).collect();
+        let values: Vec<Vec<&str>> = self
+            .dimensions
+            .iter()
+            .map(|(_, vals)| vals.iter().map(String::as_str).collect())
+            .collect();
+
+        let mut system = EvoCoreContextSystem::new(&names, &values, self.params.len())?;
+        for context in &self.contexts {
+            let dims: Vec<&str> = context.iter().map(String::as_str).collect();
+            let key = system.context_key(&dims)?;
+            system.restore_context_state(&self.prior(key))?;
+        }
+
+        Ok(system)
+    }
+}
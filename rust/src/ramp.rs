@@ -0,0 +1,134 @@
+//! Uptime-aware exploration ramp
+//!
+//! Right after a deploy every cache is cold, and exploring at full rate
+//! produces a burst of erratic behaviour just when operators are watching
+//! most closely. An [`UptimeRamp`] holds exploration off for a fixed time
+//! after startup, serving each context's learned means (its last known
+//! good parameters), then raises exploration linearly back to the
+//! requested level:
+//!
+//! ```text
+//! factor  1 |            ______
+//!           |           /
+//!         0 |__________/
+//!           0   hold   hold+ramp   uptime
+//! ```
+//!
+//! Contexts with no data are sampled as usual, with exploration scaled by
+//! the same factor. The clock starts when the ramp is created, so create
+//! it during startup.
+
+use crate::EvoCoreContextSystem;
+use std::time::{Duration, Instant};
+
+/// Exploration suppressed for `hold`, then ramped up over `ramp`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UptimeRamp {
+    started: Instant,
+    hold: Duration,
+    ramp: Duration,
+}
+
+impl UptimeRamp {
+    /// Start the clock now
+    pub fn new(hold: Duration, ramp: Duration) -> Self {
+        Self {
+            started: Instant::now(),
+            hold,
+            ramp,
+        }
+    }
+
+    /// Measure uptime from `started` instead of now
+    pub fn starting_at(mut self, started: Instant) -> Self {
+        self.started = started;
+        self
+    }
+
+    pub fn hold(&self) -> Duration {
+        self.hold
+    }
+
+    pub fn ramp(&self) -> Duration {
+        self.ramp
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Fraction of the requested exploration allowed after `uptime`
+    pub fn factor_at(&self, uptime: Duration) -> f64 {
+        if uptime < self.hold {
+            return 0.0;
+        }
+        let into_ramp = (uptime - self.hold).as_secs_f64();
+        let ramp = self.ramp.as_secs_f64();
+        if ramp <= 0.0 {
+            1.0
+        } else {
+            (into_ramp / ramp).min(1.0)
+        }
+    }
+
+    /// Fraction of the requested exploration allowed now
+    pub fn factor(&self) -> f64 {
+        self.factor_at(self.uptime())
+    }
+
+    /// Whether exploration is still held off entirely
+    pub fn is_holding(&self) -> bool {
+        self.uptime() < self.hold
+    }
+
+    /// Whether the ramp has finished and no longer affects sampling
+    pub fn is_complete(&self) -> bool {
+        self.uptime() >= self.hold + self.ramp
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Suppress exploration after startup
+    pub fn with_uptime_ramp(mut self, ramp: UptimeRamp) -> Self {
+        self.uptime_ramp = Some(ramp);
+        self
+    }
+
+    /// Change or remove (`None`) the uptime ramp
+    pub fn set_uptime_ramp(&mut self, ramp: Option<UptimeRamp>) {
+        self.uptime_ramp = ramp;
+    }
+
+    pub fn uptime_ramp(&self) -> Option<UptimeRamp> {
+        self.uptime_ramp
+    }
+
+    /// Exploration scaled by the uptime ramp
+    pub(crate) fn ramped_exploration(&self, exploration: f64) -> f64 {
+        match &self.uptime_ramp {
+            Some(ramp) => exploration * ramp.factor(),
+            None => exploration,
+        }
+    }
+
+    /// While the ramp holds, fill `out` with the context's learned means
+    ///
+    /// Returns false if the ramp is not holding or the context has no data.
+    pub(crate) fn serve_last_known_good(&self, dimension_values: &[&str], out: &mut [f64]) -> bool {
+        if !self.uptime_ramp.is_some_and(|r| r.is_holding()) {
+            return false;
+        }
+        let Some(state) = self
+            .context_key(dimension_values)
+            .ok()
+            .and_then(|key| self.context_state(&key))
+            .filter(|s| s.total_experiences > 0)
+        else {
+            return false;
+        };
+        for (value, p) in out.iter_mut().zip(&state.params) {
+            *value = p.mean;
+        }
+        true
+    }
+}
@@ -0,0 +1,84 @@
+//! Ranking contexts
+//!
+//! [`top_contexts`](EvoCoreContextSystem::top_contexts) lists the contexts
+//! that lead by a [`RankMetric`]: which situations the system has mastered,
+//! which it has seen most, and which are currently getting better.
+//!
+//! Recent improvement compares a context's recent fitness, an exponential
+//! moving average over roughly its last ten experiences, with its all-time
+//! average. The wrapper keeps the moving average from the first learn
+//! after creating or loading the system; until a context has ten
+//! experiences since then it is their plain mean, and contexts not
+//! learned since rank as unchanged.
+
+use crate::{ContextSummary, EvoCoreContextSystem};
+
+/// Weight of each new fitness in the recent average
+const RECENT_WEIGHT: f64 = 0.1;
+
+/// Moving average of the fitness a context learned recently
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RecentFitness {
+    mean: f64,
+    count: u64,
+}
+
+impl RecentFitness {
+    fn add(&mut self, fitness: f64) {
+        self.count += 1;
+        let weight = (1.0 / self.count as f64).max(RECENT_WEIGHT);
+        self.mean += weight * (fitness - self.mean);
+    }
+}
+
+/// What [`top_contexts`](EvoCoreContextSystem::top_contexts) ranks by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankMetric {
+    /// Highest fitness ever learned
+    BestFitness,
+    /// Most experiences
+    Samples,
+    /// Recent fitness furthest above the context's all-time average
+    RecentImprovement,
+}
+
+impl EvoCoreContextSystem {
+    /// The `k` highest-ranked contexts by `metric`, best first, with their summaries
+    ///
+    /// Ties are broken by key.
+    pub fn top_contexts(&self, k: usize, metric: RankMetric) -> Vec<(String, ContextSummary)> {
+        let mut ranked: Vec<(f64, String, ContextSummary)> = self
+            .context_states()
+            .into_iter()
+            .filter(|s| s.total_experiences > 0)
+            .map(|s| {
+                let score = match metric {
+                    RankMetric::BestFitness => s.best_fitness,
+                    RankMetric::Samples => s.total_experiences as f64,
+                    RankMetric::RecentImprovement => self.recent_improvement(&s.key, s.avg_fitness),
+                };
+                let summary = ContextSummary::from(&s);
+                (score, s.key, summary)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        ranked.into_iter().take(k).map(|(_, key, summary)| (key, summary)).collect()
+    }
+
+    /// Recent fitness minus `avg_fitness`, or 0 if nothing was learned recently
+    fn recent_improvement(&self, key: &str, avg_fitness: f64) -> f64 {
+        self.recent_fitness.get(key).map_or(0.0, |recent| recent.mean - avg_fitness)
+    }
+
+    /// Fold a learned fitness into the context's recent average
+    pub(crate) fn track_recent_fitness(&mut self, key: &str, fitness: f64) {
+        let recent = match self.recent_fitness.get_mut(key) {
+            Some(recent) => recent,
+            None => self
+                .recent_fitness
+                .entry(key.to_string())
+                .or_insert(RecentFitness { mean: 0.0, count: 0 }),
+        };
+        recent.add(fitness);
+    }
+}
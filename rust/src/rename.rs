@@ -0,0 +1,140 @@
+//! Renaming dimension values
+//!
+//! Context keys are built from dimension values, so renaming a value in the
+//! caller (`coding` to `code`) orphans everything learned under the old
+//! name. [`rename_dimension_value`](EvoCoreContextSystem::rename_dimension_value)
+//! renames it in the system too, moving each affected context to its new
+//! key. If the new name is already a value of the dimension the two are
+//! merged: contexts that exist under both names are folded together as if
+//! they had been learned as one.
+
+use crate::coarsen::fold;
+use crate::{c_free, c_strdup, ContextState, EvoCoreContextSystem, WILDCARD};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::ffi::{c_void, CString};
+
+impl EvoCoreContextSystem {
+    /// Rename `old` to `new` in `dimension`, moving its contexts to the new keys
+    ///
+    /// Returns how many contexts were moved. Everything kept per context
+    /// moves with it: overrides, pins, stable slots, versions, canary,
+    /// normalization and anomaly history, update weights, novelty archives
+    /// and the sampling strategy's state. Where the target context already
+    /// has its own, the target's is kept.
+    pub fn rename_dimension_value(&mut self, dimension: &str, old: &str, new: &str) -> Result<usize, String> {
+        if old == WILDCARD || new == WILDCARD {
+            return Err(format!("Cannot rename to or from the wildcard value {:?}", WILDCARD));
+        }
+        if new.is_empty() || new.contains(':') {
+            return Err(format!("Invalid dimension value: {:?}", new));
+        }
+        let c_new = CString::new(new).map_err(|_| format!("Invalid dimension value: {:?}", new))?;
+        let (d, v) = self.dimension_value_index(dimension, old)?;
+        let merge_into = self.dimension_value_index(dimension, new).ok().map(|(_, v)| v);
+        if merge_into == Some(v) {
+            return Ok(0);
+        }
+
+        match merge_into {
+            Some(_) => self.drop_dimension_value(d, v),
+            None => unsafe {
+                let renamed = c_strdup(&c_new);
+                if renamed.is_null() {
+                    return Err(format!("Failed to rename dimension value {:?}", old));
+                }
+                let slot = (*self.inner.as_ref().dimensions.add(d)).values.add(v);
+                c_free(*slot as *mut c_void);
+                *slot = renamed;
+            },
+        }
+
+        let dimension_count = unsafe { self.inner.as_ref().dimension_count };
+        let param_count = self.param_count;
+
+        let mut moved = 0;
+        let mut targets: HashMap<String, ContextState> = HashMap::new();
+        for state in self.context_states() {
+            let mut values: Vec<&str> = state.key.split(':').collect();
+            if values.len() != dimension_count || values[d] != old {
+                continue;
+            }
+            values[d] = new;
+            let target_key = values.join(":");
+            let target = targets.entry(target_key.clone()).or_insert_with_key(|key| {
+                self.context_state(key)
+                    .unwrap_or_else(|| ContextState::empty(key.clone(), param_count))
+            });
+            fold(target, &state);
+            self.move_key_state(&state.key, &target_key);
+            if self.remove_key(&state.key) {
+                moved += 1;
+            }
+        }
+
+        for target in targets.values() {
+            self.restore_context_state(target)?;
+            self.touch_key(&target.key);
+        }
+
+        let renamed_overrides: Vec<String> = self
+            .overrides
+            .keys()
+            .filter(|key| key.split(':').nth(d) == Some(old) && key.split(':').count() == dimension_count)
+            .cloned()
+            .collect();
+        for key in renamed_overrides {
+            let Some(mut o) = self.overrides.remove(&key) else {
+                continue;
+            };
+            let mut values: Vec<&str> = key.split(':').collect();
+            values[d] = new;
+            o.key = values.join(":");
+            self.overrides.entry(o.key.clone()).or_insert(o);
+        }
+
+        self.clear_cache();
+        Ok(moved)
+    }
+
+    /// Carry the wrapper's per-context state from `from` over to `to`,
+    /// keeping whatever `to` already has
+    fn move_key_state(&mut self, from: &str, to: &str) {
+        if self.hot.is_pinned(from) {
+            self.hot.unpin(from);
+            self.hot.pin(to.to_string());
+        }
+        if let Some(stable) = move_entry(&mut self.stable_slots, from, to) {
+            stable.key = to.to_string();
+        }
+        move_entry(&mut self.versions, from, to);
+        move_entry(&mut self.recent_fitness, from, to);
+        if let Some(canary) = &mut self.canary {
+            canary.rename_key(from, to);
+        }
+        if let Some(normalizer) = &mut self.normalizer {
+            normalizer.rename_key(from, to);
+        }
+        if let Some(anomalies) = &mut self.anomalies {
+            anomalies.rename_key(from, to);
+        }
+        if let Some(update_weights) = &mut self.update_weights {
+            update_weights.rename_key(from, to);
+        }
+        if let Some(novelty) = &mut self.novelty {
+            novelty.rename_key(from, to);
+        }
+        if let Some(strategy) = &self.strategy {
+            strategy.rename_key(from, to);
+        }
+    }
+}
+
+/// Move `map[from]` to `to` unless `to` already has an entry; returns the moved entry
+pub(crate) fn move_entry<'a, V>(map: &'a mut HashMap<String, V>, from: &str, to: &str) -> Option<&'a mut V> {
+    let value = map.remove(from)?;
+    match map.entry(to.to_string()) {
+        Entry::Occupied(_) => None,
+        Entry::Vacant(slot) => Some(slot.insert(value)),
+    }
+}
@@ -0,0 +1,178 @@
+//! Per-thread read replicas with bounded staleness
+//!
+//! Even a read lock is a shared cache line that every sampling thread
+//! writes to. A [`ReadReplica`] is owned by one thread and keeps private
+//! copies of the contexts that thread samples, drawn from the learned
+//! distributions without touching the [`SharedContextSystem`] at all. Once
+//! the copies are older than the replica's staleness bound, the next
+//! `sample()` refreshes all of them under a single read lock. A context the
+//! replica has not seen yet is fetched on first use.
+//!
+//! Replicas sample the learned per-parameter distributions directly, clamped
+//! to the system's [bounds](crate::ParamBounds); overrides, sampling
+//! strategies, schedules and other per-call features of
+//! `EvoCoreContextSystem::sample()` are not applied.
+//!
+//! ```ignore
+//! let shared = Arc::new(SharedContextSystem::new(system));
+//! let handles: Vec<_> = (0..8).map(|_| {
+//!     let mut replica = shared.read_replica(Duration::from_millis(50));
+//!     std::thread::spawn(move || loop {
+//!         let params = replica.sample(&["code", "rust"], 0.1).unwrap();
+//!         // ...
+//!     })
+//! }).collect();
+//! ```
+
+use crate::{ContextState, ParamBounds, SharedContextSystem};
+use rand::rngs::StdRng;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Counters of one replica
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplicaStats {
+    pub samples: u64,
+    /// Samples whose context had to be fetched from the shared system
+    pub misses: u64,
+    pub refreshes: u64,
+    /// Contexts dropped to stay within capacity
+    pub evictions: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Cached {
+    state: ContextState,
+    /// Sample count of the replica when this context was last sampled
+    last_used: u64,
+}
+
+/// A thread-owned, periodically refreshed copy of hot contexts
+pub struct ReadReplica {
+    source: Arc<SharedContextSystem>,
+    max_staleness: Duration,
+    capacity: usize,
+    contexts: HashMap<String, Cached>,
+    bounds: Vec<Option<ParamBounds>>,
+    refreshed_at: Instant,
+    rng: StdRng,
+    stats: ReplicaStats,
+}
+
+impl SharedContextSystem {
+    /// A read replica of this system that is never more than `max_staleness` behind
+    pub fn read_replica(self: &Arc<Self>, max_staleness: Duration) -> ReadReplica {
+        let (bounds, rng) = self.read(|system| (system.bounds.clone(), system.rng()));
+        ReadReplica {
+            source: Arc::clone(self),
+            max_staleness,
+            capacity: 1_024,
+            contexts: HashMap::new(),
+            bounds,
+            refreshed_at: Instant::now(),
+            rng,
+            stats: ReplicaStats::default(),
+        }
+    }
+}
+
+impl ReadReplica {
+    /// Most contexts kept; the least recently sampled is dropped first (default 1 024)
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Sample parameters for a context from the local copy
+    pub fn sample(&mut self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        if self.refreshed_at.elapsed() > self.max_staleness {
+            self.refresh();
+        }
+        let key = dimension_values.join(":");
+        if !self.contexts.contains_key(&key) {
+            self.fetch(dimension_values, &key)?;
+        }
+
+        self.stats.samples += 1;
+        let exploration = exploration.clamp(0.0, 1.0);
+        let cached = self.contexts.get_mut(&key).expect("context fetched above");
+        cached.last_used = self.stats.samples;
+        let rng = &mut self.rng;
+        Ok(cached
+            .state
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let value = p.sample(exploration, rng);
+                match self.bounds.get(i).copied().flatten() {
+                    Some(bounds) => bounds.clamp(value),
+                    None => value,
+                }
+            })
+            .collect())
+    }
+
+    fn fetch(&mut self, dimension_values: &[&str], key: &str) -> Result<(), String> {
+        let state = self.source.read(|system| {
+            system.context_key(dimension_values)?;
+            Ok::<_, String>(
+                system
+                    .context_state(key)
+                    .unwrap_or_else(|| ContextState::empty(key.to_string(), system.param_count)),
+            )
+        })?;
+        self.stats.misses += 1;
+        if self.contexts.len() >= self.capacity {
+            let coldest = self
+                .contexts
+                .iter()
+                .min_by_key(|(_, c)| c.last_used)
+                .map(|(k, _)| k.clone());
+            if let Some(coldest) = coldest {
+                self.contexts.remove(&coldest);
+                self.stats.evictions += 1;
+            }
+        }
+        self.contexts.insert(key.to_string(), Cached { state, last_used: self.stats.samples });
+        Ok(())
+    }
+
+    /// Re-read every cached context from the shared system now
+    pub fn refresh(&mut self) {
+        let contexts = &mut self.contexts;
+        self.bounds = self.source.read(|system| {
+            for (key, cached) in contexts.iter_mut() {
+                cached.state = system
+                    .context_state(key)
+                    .unwrap_or_else(|| ContextState::empty(key.clone(), system.param_count));
+            }
+            system.bounds.clone()
+        });
+        self.refreshed_at = Instant::now();
+        self.stats.refreshes += 1;
+    }
+
+    /// Time since the last refresh
+    pub fn staleness(&self) -> Duration {
+        self.refreshed_at.elapsed()
+    }
+
+    pub fn max_staleness(&self) -> Duration {
+        self.max_staleness
+    }
+
+    /// Contexts currently held
+    pub fn len(&self) -> usize {
+        self.contexts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contexts.is_empty()
+    }
+
+    pub fn stats(&self) -> ReplicaStats {
+        self.stats
+    }
+}
@@ -0,0 +1,186 @@
+//! Human-readable learning reports
+//!
+//! [`EvoCoreContextSystem::report`] condenses what a system has learned
+//! into a [`LearningReport`] whose `Display` output is meant to be read by
+//! people: pasted into a log line after a training run or into a pull
+//! request that changes an integration. Use the fields directly for
+//! anything that needs to be parsed.
+//! [`report_private`](EvoCoreContextSystem::report_private) builds the same
+//! report from statistics noised by a [`PrivacyBudget`], for sharing beyond
+//! the team that owns the data.
+
+use crate::{ContextState, EvoCoreContextSystem, PrivacyBudget};
+use std::fmt;
+
+/// Contexts listed under "best contexts"
+const BEST_CONTEXTS: usize = 5;
+
+/// One of the best contexts in a [`LearningReport`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportContext {
+    pub key: String,
+    pub experiences: usize,
+    pub avg_fitness: f64,
+    /// Learned mean of each parameter
+    pub means: Vec<f64>,
+}
+
+/// Learned mean of one parameter across all contexts
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamReport {
+    /// Registered name, or the parameter's index
+    pub name: String,
+    /// Mean of the context means, weighted by experiences
+    pub mean: f64,
+    /// Smallest and largest context mean
+    pub min: f64,
+    pub max: f64,
+}
+
+/// How much of one dimension has been learned
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionCoverage {
+    pub dimension: String,
+    /// `(value, contexts, experiences)` for every declared value
+    pub values: Vec<(String, usize, usize)>,
+}
+
+impl DimensionCoverage {
+    /// Declared values with at least one learned context
+    pub fn covered(&self) -> usize {
+        self.values.iter().filter(|(_, contexts, _)| *contexts > 0).count()
+    }
+}
+
+/// Summary of everything a system has learned
+#[derive(Debug, Clone, PartialEq)]
+pub struct LearningReport {
+    pub contexts: usize,
+    pub experiences: usize,
+    /// Average fitness across contexts, weighted by experiences
+    pub mean_fitness: f64,
+    /// Highest average fitness first
+    pub best_contexts: Vec<ReportContext>,
+    pub params: Vec<ParamReport>,
+    pub coverage: Vec<DimensionCoverage>,
+}
+
+impl EvoCoreContextSystem {
+    /// Summarize what has been learned so far
+    pub fn report(&self) -> LearningReport {
+        self.report_of(self.context_states())
+    }
+
+    /// Summarize differentially private copies of the learned statistics
+    ///
+    /// Spends `epsilon` from `budget`. Contexts below the budget's
+    /// [minimum count](PrivacyBudget::with_min_count) are left out of every
+    /// section, coverage included.
+    pub fn report_private(&self, budget: &mut PrivacyBudget, epsilon: f64) -> Result<LearningReport, String> {
+        Ok(self.report_of(budget.privatize(&self.context_states(), epsilon)?))
+    }
+
+    fn report_of(&self, states: Vec<ContextState>) -> LearningReport {
+        let states: Vec<_> = states.into_iter().filter(|s| s.total_experiences > 0).collect();
+        let experiences: usize = states.iter().map(|s| s.total_experiences).sum();
+        let weighted = |value: &dyn Fn(&ContextState) -> f64| {
+            if experiences == 0 {
+                return 0.0;
+            }
+            states.iter().map(|s| value(s) * s.total_experiences as f64).sum::<f64>() / experiences as f64
+        };
+
+        let mut best: Vec<_> = states.iter().collect();
+        best.sort_by(|a, b| b.avg_fitness.total_cmp(&a.avg_fitness).then_with(|| a.key.cmp(&b.key)));
+        let best_contexts = best
+            .into_iter()
+            .take(BEST_CONTEXTS)
+            .map(|s| ReportContext {
+                key: s.key.clone(),
+                experiences: s.total_experiences,
+                avg_fitness: s.avg_fitness,
+                means: s.params.iter().map(|p| p.mean).collect(),
+            })
+            .collect();
+
+        let params = (0..self.param_count)
+            .map(|i| {
+                let means = states.iter().map(|s| s.params[i].mean);
+                ParamReport {
+                    name: self.param_names.get(i).cloned().unwrap_or_else(|| i.to_string()),
+                    mean: weighted(&|s| s.params[i].mean),
+                    min: means.clone().fold(f64::INFINITY, f64::min),
+                    max: means.fold(f64::NEG_INFINITY, f64::max),
+                }
+            })
+            .collect();
+
+        let coverage = self
+            .dimensions()
+            .into_iter()
+            .enumerate()
+            .map(|(d, (dimension, values))| {
+                let values = values
+                    .into_iter()
+                    .map(|value| {
+                        let (contexts, exps) = states
+                            .iter()
+                            .filter(|s| s.key.split(':').nth(d) == Some(value.as_str()))
+                            .fold((0, 0), |(c, e), s| (c + 1, e + s.total_experiences));
+                        (value, contexts, exps)
+                    })
+                    .collect();
+                DimensionCoverage { dimension, values }
+            })
+            .collect();
+
+        LearningReport {
+            contexts: states.len(),
+            experiences,
+            mean_fitness: weighted(&|s| s.avg_fitness),
+            best_contexts,
+            params,
+            coverage,
+        }
+    }
+}
+
+impl fmt::Display for LearningReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Learned {} contexts from {} experiences, mean fitness {:.4}",
+            self.contexts, self.experiences, self.mean_fitness
+        )?;
+        if self.contexts == 0 {
+            return Ok(());
+        }
+
+        writeln!(f, "Best contexts:")?;
+        for c in &self.best_contexts {
+            let means: Vec<String> = c.means.iter().map(|m| format!("{:.4}", m)).collect();
+            writeln!(
+                f,
+                "  {}: fitness {:.4} over {} experiences, means [{}]",
+                c.key,
+                c.avg_fitness,
+                c.experiences,
+                means.join(", ")
+            )?;
+        }
+
+        writeln!(f, "Parameter means:")?;
+        for p in &self.params {
+            writeln!(f, "  {}: {:.4} (contexts {:.4} to {:.4})", p.name, p.mean, p.min, p.max)?;
+        }
+
+        writeln!(f, "Coverage:")?;
+        for d in &self.coverage {
+            writeln!(f, "  {}: {} of {} values learned", d.dimension, d.covered(), d.values.len())?;
+            for (value, contexts, experiences) in &d.values {
+                writeln!(f, "    {}: {} contexts, {} experiences", value, contexts, experiences)?;
+            }
+        }
+        Ok(())
+    }
+}
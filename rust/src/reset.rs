@@ -0,0 +1,29 @@
+//! Resetting contexts
+//!
+//! When a context's environment changes (an upstream model is swapped, a
+//! host is replaced) what it learned no longer applies.
+//! [`reset_context`](EvoCoreContextSystem::reset_context) wipes one context
+//! so it relearns from scratch, without throwing away the rest of the
+//! system. Along with the C library's statistics it drops everything the
+//! wrapper keeps per context: stable slot, version, fitness normalization
+//! and anomaly history, per-context update weight, novelty archive and the
+//! sampling strategy's state. Overrides and pins are configuration rather
+//! than learned data and stay in place.
+
+use crate::EvoCoreContextSystem;
+
+impl EvoCoreContextSystem {
+    /// Forget everything learned for this context
+    ///
+    /// Returns whether the context had been learned.
+    pub fn reset_context(&mut self, dimension_values: &[&str]) -> Result<bool, String> {
+        let key = self.context_key(dimension_values)?;
+        Ok(self.remove_key(&key))
+    }
+
+    /// Forget everything learned for every context; returns how many were reset
+    pub fn reset_all(&mut self) -> usize {
+        let keys = self.context_keys();
+        keys.iter().filter(|key| self.remove_key(key)).count()
+    }
+}
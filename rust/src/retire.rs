@@ -0,0 +1,108 @@
+//! Retiring dimension values
+//!
+//! Dimension values come and go (tools are replaced, hosts decommissioned),
+//! and without this the contexts of dead values stay forever.
+//! [`remove_dimension_value`](EvoCoreContextSystem::remove_dimension_value)
+//! deletes a value from its dimension, so it is no longer saved or accepted
+//! by checkpoints, and either drops the contexts that used it or folds them
+//! into the wildcard context standing in for it: retiring `vim` from
+//! `code:rust:vim` folds that context into `code:rust:*`, which cold
+//! contexts reach through the
+//! [wildcard fallback](EvoCoreContextSystem::with_wildcard_fallback).
+
+use crate::coarsen::fold;
+use crate::{c_free, ContextState, EvoCoreContextSystem, WILDCARD};
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr};
+
+/// What [`remove_dimension_value`](EvoCoreContextSystem::remove_dimension_value)
+/// does with contexts that use the removed value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalPolicy {
+    /// Delete them and everything they learned
+    Drop,
+    /// Merge them into the context with [`WILDCARD`] in place of the value
+    FoldIntoWildcard,
+}
+
+impl EvoCoreContextSystem {
+    /// Remove `value` from `dimension`, handling its contexts by `policy`
+    ///
+    /// Returns how many contexts used the value. Overrides and pins for
+    /// them are cleared. A dimension must keep at least one value, and
+    /// [`WILDCARD`] cannot be removed.
+    pub fn remove_dimension_value(
+        &mut self,
+        dimension: &str,
+        value: &str,
+        policy: RemovalPolicy,
+    ) -> Result<usize, String> {
+        if value == WILDCARD {
+            return Err(format!("Cannot remove the wildcard value {:?}", WILDCARD));
+        }
+        let (d, v) = self.dimension_value_index(dimension, value)?;
+        if unsafe { (*self.inner.as_ref().dimensions.add(d)).value_count } == 1 {
+            return Err(format!("Cannot remove the only value of dimension {}", dimension));
+        }
+        let dimension_count = unsafe { self.inner.as_ref().dimension_count };
+        let param_count = self.param_count;
+
+        let mut affected = 0;
+        let mut parents: HashMap<String, ContextState> = HashMap::new();
+        for state in self.context_states() {
+            let mut values: Vec<&str> = state.key.split(':').collect();
+            if values.len() != dimension_count || values[d] != value {
+                continue;
+            }
+            if policy == RemovalPolicy::FoldIntoWildcard {
+                values[d] = WILDCARD;
+                let parent = parents.entry(values.join(":")).or_insert_with_key(|key| {
+                    self.context_state(key)
+                        .unwrap_or_else(|| ContextState::empty(key.clone(), param_count))
+                });
+                fold(parent, &state);
+            }
+            self.overrides.remove(&state.key);
+            self.hot.unpin(&state.key);
+            if self.remove_key(&state.key) {
+                affected += 1;
+            }
+        }
+
+        for parent in parents.values() {
+            self.restore_context_state(parent)?;
+            self.touch_key(&parent.key);
+        }
+
+        self.drop_dimension_value(d, v);
+        self.clear_cache();
+        Ok(affected)
+    }
+
+    /// Delete value `v` of dimension `d` from the C system's dimension list
+    pub(crate) fn drop_dimension_value(&mut self, d: usize, v: usize) {
+        unsafe {
+            let dim = &mut *self.inner.as_mut().dimensions.add(d);
+            c_free(*dim.values.add(v) as *mut c_void);
+            std::ptr::copy(dim.values.add(v + 1), dim.values.add(v), dim.value_count - v - 1);
+            dim.value_count -= 1;
+        }
+    }
+
+    /// Indexes of `dimension` and of `value` among its values
+    pub(crate) fn dimension_value_index(&self, dimension: &str, value: &str) -> Result<(usize, usize), String> {
+        unsafe {
+            let system = self.inner.as_ref();
+            let dims = std::slice::from_raw_parts(system.dimensions, system.dimension_count);
+            let d = dims
+                .iter()
+                .position(|dim| CStr::from_ptr(dim.name).to_bytes() == dimension.as_bytes())
+                .ok_or_else(|| format!("Unknown dimension: {}", dimension))?;
+            let v = std::slice::from_raw_parts(dims[d].values, dims[d].value_count)
+                .iter()
+                .position(|&v| CStr::from_ptr(v).to_bytes() == value.as_bytes())
+                .ok_or_else(|| format!("Unknown value {:?} for dimension {}", value, dimension))?;
+            Ok((d, v))
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Automatic rollback after promotion
+//!
+//! [`promote`](crate::promote) checks a candidate offline, but some
+//! regressions only show up in production traffic. A [`RollbackGuard`]
+//! snapshots the serving checkpoint before promoting over it, then watches
+//! the fitness the service reports through [`record`](RollbackGuard::record).
+//! Once the monitoring window closes it compares the mean reported fitness
+//! with a baseline (by default the previous checkpoint's
+//! experience-weighted average fitness): if it dropped by more than the
+//! threshold, the previous file is written back atomically and, if a
+//! [`SharedContextSystem`] is attached, its learned state is replaced with
+//! the previous one; its configuration (bounds, overrides, strategy, seed
+//! and so on) is kept. If there was no previous checkpoint, rolling back
+//! removes the promoted file and clears the attached system's contexts.
+//!
+//! ```ignore
+//! let mut guard = rollback_guard("prod.bin", RollbackConfig::new(500, 0.05))?
+//!     .with_system(shared.clone());
+//! guard.promote("trained.bin", PromotionChecks::new())?;
+//! // in the serving loop
+//! if let GuardStatus::RolledBack { .. } = guard.record(fitness)? { alert(); }
+//! ```
+
+use crate::promote::decode;
+use crate::serializer::write_file;
+use crate::{
+    promote, Checkpoint, Format, PromotionChecks, PromotionError, PromotionReport,
+    SharedContextSystem,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// When a [`RollbackGuard`] decides, and how much of a drop it tolerates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RollbackConfig {
+    window: usize,
+    max_duration: Option<Duration>,
+    threshold: f64,
+    baseline: Option<f64>,
+    format: Format,
+}
+
+impl RollbackConfig {
+    /// Decide after `window` fitness reports; roll back on a drop of more than `threshold`
+    pub fn new(window: usize, threshold: f64) -> Self {
+        Self {
+            window: window.max(1),
+            max_duration: None,
+            threshold: threshold.max(0.0),
+            baseline: None,
+            format: Format::default(),
+        }
+    }
+
+    /// Also decide once `duration` has passed since promotion, if anything was reported
+    pub fn with_max_duration(mut self, duration: Duration) -> Self {
+        self.max_duration = Some(duration);
+        self
+    }
+
+    /// Compare against `baseline` instead of the previous checkpoint's average fitness
+    pub fn with_baseline(mut self, baseline: f64) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    /// Format of the checkpoint files (default JSON)
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+/// Where a [`RollbackGuard`] stands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GuardStatus {
+    /// Nothing promoted through the guard yet
+    Idle,
+    /// Collecting fitness reports
+    Monitoring { observed: usize, mean: f64 },
+    /// The window closed without a significant drop
+    Passed { mean: f64, baseline: f64 },
+    /// The previous checkpoint was restored
+    RolledBack { mean: f64, baseline: f64 },
+}
+
+/// Snapshot of a serving checkpoint that can be restored if a promotion goes bad
+pub struct RollbackGuard {
+    path: PathBuf,
+    previous: Option<Vec<u8>>,
+    baseline: Option<f64>,
+    config: RollbackConfig,
+    system: Option<Arc<SharedContextSystem>>,
+    promoted_at: Option<Instant>,
+    observed: usize,
+    sum: f64,
+    status: GuardStatus,
+}
+
+/// Snapshot the checkpoint at `path` before promoting over it
+pub fn rollback_guard<P: AsRef<Path>>(path: P, config: RollbackConfig) -> Result<RollbackGuard, String> {
+    let path = path.as_ref().to_path_buf();
+    let previous = if path.exists() {
+        Some(std::fs::read(&path).map_err(|e| format!("Failed to snapshot checkpoint: {}", e))?)
+    } else {
+        None
+    };
+
+    let baseline = match (config.baseline, &previous) {
+        (Some(baseline), _) => Some(baseline),
+        (None, Some(data)) => {
+            let checkpoint = decode(data.clone(), config.format).map_err(|e| e.to_string())?;
+            average_fitness(&checkpoint)
+        }
+        (None, None) => None,
+    };
+
+    Ok(RollbackGuard {
+        path,
+        previous,
+        baseline,
+        config,
+        system: None,
+        promoted_at: None,
+        observed: 0,
+        sum: 0.0,
+        status: GuardStatus::Idle,
+    })
+}
+
+impl RollbackGuard {
+    /// Also restore the previous state into `system` on rollback
+    pub fn with_system(mut self, system: Arc<SharedContextSystem>) -> Self {
+        self.system = Some(system);
+        self
+    }
+
+    /// [`promote`] `from` over the guarded path and start monitoring
+    pub fn promote<P: AsRef<Path>>(
+        &mut self,
+        from: P,
+        checks: PromotionChecks,
+    ) -> Result<PromotionReport, PromotionError> {
+        let report = promote(from, &self.path, checks)?;
+        self.start();
+        Ok(report)
+    }
+
+    /// Start monitoring a promotion made some other way
+    pub fn start(&mut self) {
+        self.promoted_at = Some(Instant::now());
+        self.observed = 0;
+        self.sum = 0.0;
+        self.status = GuardStatus::Monitoring { observed: 0, mean: f64::NAN };
+    }
+
+    /// Report one fitness observed in production
+    ///
+    /// Non-finite values are ignored. Once the window closes the guard
+    /// decides, rolling back if needed; later reports return the decision.
+    pub fn record(&mut self, fitness: f64) -> Result<GuardStatus, String> {
+        if !matches!(self.status, GuardStatus::Monitoring { .. }) {
+            return Ok(self.status);
+        }
+        if fitness.is_finite() {
+            self.observed += 1;
+            self.sum += fitness;
+        }
+
+        let mean = if self.observed > 0 { self.sum / self.observed as f64 } else { f64::NAN };
+        let timed_out = match (self.config.max_duration, self.promoted_at) {
+            (Some(limit), Some(at)) => at.elapsed() >= limit,
+            _ => false,
+        };
+        if self.observed < self.config.window && !(timed_out && self.observed > 0) {
+            self.status = GuardStatus::Monitoring { observed: self.observed, mean };
+            return Ok(self.status);
+        }
+
+        let Some(baseline) = self.baseline else {
+            self.status = GuardStatus::Passed { mean, baseline: f64::NAN };
+            return Ok(self.status);
+        };
+        if mean < baseline - self.config.threshold {
+            self.rollback()?;
+            self.status = GuardStatus::RolledBack { mean, baseline };
+        } else {
+            self.status = GuardStatus::Passed { mean, baseline };
+        }
+        Ok(self.status)
+    }
+
+    /// Restore the snapshot now, regardless of the reported fitness
+    pub fn rollback(&mut self) -> Result<(), String> {
+        match &self.previous {
+            Some(data) => write_file(&self.path, data, true)?,
+            None if self.path.exists() => std::fs::remove_file(&self.path)
+                .map_err(|e| format!("Failed to remove promoted checkpoint: {}", e))?,
+            None => {}
+        }
+
+        if let Some(shared) = &self.system {
+            // Swap in the learned state only; the live system keeps its configuration
+            let checkpoint = match &self.previous {
+                Some(data) => decode(data.clone(), self.config.format).map_err(|e| e.to_string())?,
+                None => shared.read(|s| Checkpoint {
+                    dimensions: s.dimensions(),
+                    param_count: s.param_count(),
+                    contexts: Vec::new(),
+                    stable: Vec::new(),
+                }),
+            };
+            shared.write(|system| system.replace_learned_state(&checkpoint))?;
+        }
+
+        let mean = if self.observed > 0 { self.sum / self.observed as f64 } else { f64::NAN };
+        self.status = GuardStatus::RolledBack { mean, baseline: self.baseline.unwrap_or(f64::NAN) };
+        Ok(())
+    }
+
+    pub fn status(&self) -> GuardStatus {
+        self.status
+    }
+
+    /// Fitness the window is compared against (None without a previous checkpoint or explicit baseline)
+    pub fn baseline(&self) -> Option<f64> {
+        self.baseline
+    }
+
+    /// Whether a previous checkpoint was snapshotted
+    pub fn has_snapshot(&self) -> bool {
+        self.previous.is_some()
+    }
+}
+
+/// Experience-weighted average fitness over every context
+fn average_fitness(checkpoint: &Checkpoint) -> Option<f64> {
+    let (sum, weight) = checkpoint.contexts.iter().fold((0.0, 0.0), |(sum, weight), c| {
+        let n = c.total_experiences as f64;
+        (sum + c.avg_fitness * n, weight + n)
+    });
+    (weight > 0.0).then(|| sum / weight)
+}
@@ -0,0 +1,26 @@
+//! Persistence for the pure-Rust backend
+//!
+//! [`RustContextSystem`] itself lives in `evocore-core`, which has no file
+//! I/O. It reads and writes the same [`Checkpoint`] format as the C
+//! backend, so state can move between the two.
+
+use crate::{Checkpoint, ContextLearner, RustContextSystem};
+
+impl From<Checkpoint> for RustContextSystem {
+    /// Build a system from a parsed checkpoint
+    fn from(checkpoint: Checkpoint) -> Self {
+        RustContextSystem::from_parts(checkpoint.dimensions, checkpoint.param_count, checkpoint.contexts, rand::random())
+    }
+}
+
+impl From<&RustContextSystem> for Checkpoint {
+    /// Capture the full learned state
+    fn from(system: &RustContextSystem) -> Self {
+        Checkpoint {
+            dimensions: system.dimensions().to_vec(),
+            param_count: system.param_count(),
+            contexts: system.context_states(),
+            stable: Vec::new(),
+        }
+    }
+}
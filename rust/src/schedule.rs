@@ -0,0 +1,97 @@
+//! Exploration schedules
+//!
+//! How much to explore should depend on how much a context already knows:
+//! a fresh context should mostly explore, a context with thousands of
+//! experiences mostly exploit. An [`ExplorationSchedule`] owned by the
+//! system turns each context's experience count into an exploration
+//! factor, and [`sample_scheduled`](EvoCoreContextSystem::sample_scheduled)
+//! samples with it, so callers no longer pick one global number.
+
+use crate::{key_into, EvoCoreContextSystem, MAX_KEY_LENGTH};
+
+/// Exploration as a function of a context's experience count `n`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExplorationSchedule {
+    /// The same exploration everywhere
+    Constant(f64),
+    /// From `start` at `n = 0` down to `end` at `n = experiences`, then flat
+    Linear { start: f64, end: f64, experiences: usize },
+    /// From `start` towards `end`, halving the gap every `half_life` experiences
+    Exponential { start: f64, end: f64, half_life: f64 },
+    /// `scale / sqrt(n + 1)`, never below `min`
+    ///
+    /// The usual count-based bonus: halving the uncertainty takes four
+    /// times the data, so exploration shrinks with the square root.
+    CountBased { scale: f64, min: f64 },
+}
+
+impl Default for ExplorationSchedule {
+    fn default() -> Self {
+        ExplorationSchedule::CountBased { scale: 1.0, min: 0.05 }
+    }
+}
+
+impl ExplorationSchedule {
+    /// Exploration for a context with `experiences` experiences, in `[0, 1]`
+    pub fn exploration(&self, experiences: usize) -> f64 {
+        let n = experiences as f64;
+        let e = match *self {
+            ExplorationSchedule::Constant(e) => e,
+            ExplorationSchedule::Linear { start, end, experiences: span } => {
+                let t = if span == 0 { 1.0 } else { (n / span as f64).min(1.0) };
+                start + (end - start) * t
+            }
+            ExplorationSchedule::Exponential { start, end, half_life } => {
+                if half_life > 0.0 {
+                    end + (start - end) * 0.5f64.powf(n / half_life)
+                } else {
+                    end
+                }
+            }
+            ExplorationSchedule::CountBased { scale, min } => (scale / (n + 1.0).sqrt()).max(min),
+        };
+        if e.is_nan() {
+            0.0
+        } else {
+            e.clamp(0.0, 1.0)
+        }
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Use `schedule` for [`sample_scheduled`](Self::sample_scheduled)
+    pub fn with_exploration_schedule(mut self, schedule: ExplorationSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Replace the exploration schedule
+    pub fn set_exploration_schedule(&mut self, schedule: ExplorationSchedule) {
+        self.schedule = schedule;
+    }
+
+    /// The exploration schedule (count-based with scale 1 and floor 0.05 by default)
+    pub fn exploration_schedule(&self) -> ExplorationSchedule {
+        self.schedule
+    }
+
+    /// Exploration the schedule gives a context right now
+    pub fn scheduled_exploration(&self, dimension_values: &[&str]) -> Result<f64, String> {
+        self.check_dimension_count(dimension_values)?;
+        let mut buf = [0u8; MAX_KEY_LENGTH];
+        let experiences = key_into(dimension_values, &mut buf).map_or(0, |key| self.key_experiences(key));
+        Ok(self.schedule.exploration(experiences))
+    }
+
+    /// Sample parameters with the exploration chosen by the schedule
+    pub fn sample_scheduled(&self, dimension_values: &[&str]) -> Result<Vec<f64>, String> {
+        let exploration = self.scheduled_exploration(dimension_values)?;
+        self.sample(dimension_values, exploration)
+    }
+
+    /// Allocation-free [`sample_scheduled`](Self::sample_scheduled)
+    pub fn sample_scheduled_into(&self, dimension_values: &[&str], out: &mut [f64]) -> Result<(), String> {
+        let exploration = self.scheduled_exploration(dimension_values)?;
+        self.sample_into(dimension_values, exploration, out)
+    }
+}
@@ -0,0 +1,222 @@
+//! Self-tuning runtime knobs
+//!
+//! The right shard count, key cache size or batch size depends on load,
+//! and load changes over the day. [`SelfTuner`] picks such knobs the same
+//! way the crate picks any parameters: a small internal
+//! [`EvoCoreContextSystem`] with one context per load level and one
+//! parameter per [`Knob`]. The service asks for settings with
+//! [`choose`](SelfTuner::choose), runs with them for a while, and reports
+//! how well they did (throughput, inverse latency, anything where higher is
+//! better and never negative) with [`report`](SelfTuner::report).
+//!
+//! ```ignore
+//! let mut tuner = SelfTuner::new(vec![Knob::shard_count(1, 32), Knob::batch_size(16, 4096)])?;
+//! loop {
+//!     let knobs = tuner.choose(requests_per_sec)?;
+//!     let shards = knobs.get_usize("shard_count").unwrap();
+//!     let throughput = run_interval(shards, knobs.get_usize("batch_size").unwrap());
+//!     tuner.report(&knobs, throughput)?;
+//! }
+//! ```
+
+use crate::EvoCoreContextSystem;
+
+/// One tunable setting and its range
+#[derive(Debug, Clone, PartialEq)]
+pub struct Knob {
+    name: String,
+    min: f64,
+    max: f64,
+    integer: bool,
+}
+
+impl Knob {
+    /// A continuous knob in `[min, max]`
+    pub fn new(name: &str, min: f64, max: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            min: min.min(max),
+            max: max.max(min),
+            integer: false,
+        }
+    }
+
+    /// An integer knob in `[min, max]`
+    pub fn integer(name: &str, min: usize, max: usize) -> Self {
+        Self {
+            integer: true,
+            ..Self::new(name, min as f64, max as f64)
+        }
+    }
+
+    /// Shards of a [`ShardedContextSystem`](crate::ShardedContextSystem)
+    pub fn shard_count(min: usize, max: usize) -> Self {
+        Self::integer("shard_count", min.max(1), max)
+    }
+
+    /// Capacity passed to [`enable_key_cache`](EvoCoreContextSystem::enable_key_cache)
+    pub fn key_cache_capacity(min: usize, max: usize) -> Self {
+        Self::integer("key_cache_capacity", min.max(1), max)
+    }
+
+    /// Examples per [`learn_batch`](EvoCoreContextSystem::learn_batch) call
+    pub fn batch_size(min: usize, max: usize) -> Self {
+        Self::integer("batch_size", min.max(1), max)
+    }
+
+    /// Seconds between autosaves
+    pub fn autosave_secs(min: f64, max: f64) -> Self {
+        Self::new("autosave_secs", min.max(0.0), max)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decode(&self, unit: f64) -> f64 {
+        let value = self.min + unit.clamp(0.0, 1.0) * (self.max - self.min);
+        if self.integer {
+            value.round()
+        } else {
+            value
+        }
+    }
+
+    fn encode(&self, value: f64) -> f64 {
+        if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Knob values chosen by [`SelfTuner::choose`], to be passed back to [`SelfTuner::report`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TunedKnobs {
+    level: usize,
+    values: Vec<(String, f64)>,
+}
+
+impl TunedKnobs {
+    /// Load level the settings were chosen for (0 is the lightest)
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.values.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+
+    /// Value of an integer knob
+    pub fn get_usize(&self, name: &str) -> Option<usize> {
+        self.get(name).map(|v| v.max(0.0) as usize)
+    }
+
+    pub fn values(&self) -> &[(String, f64)] {
+        &self.values
+    }
+}
+
+/// Chooses runtime knobs per load level with an internal context system
+pub struct SelfTuner {
+    system: EvoCoreContextSystem,
+    knobs: Vec<Knob>,
+    thresholds: Vec<f64>,
+    exploration: f64,
+}
+
+impl SelfTuner {
+    /// Tune `knobs` over three load levels split at 100 and 1 000 (e.g. requests per second)
+    pub fn new(knobs: Vec<Knob>) -> Result<Self, String> {
+        Self::with_load_levels(knobs, &[100.0, 1_000.0])
+    }
+
+    /// Tune `knobs` with load levels split at the ascending `thresholds`
+    pub fn with_load_levels(knobs: Vec<Knob>, thresholds: &[f64]) -> Result<Self, String> {
+        if knobs.is_empty() {
+            return Err("Self-tuner needs at least one knob".to_string());
+        }
+        if thresholds.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("Load thresholds must be strictly ascending".to_string());
+        }
+
+        let levels: Vec<String> = (0..=thresholds.len()).map(|i| i.to_string()).collect();
+        let level_refs: Vec<&str> = levels.iter().map(String::as_str).collect();
+        let system = EvoCoreContextSystem::new(&["load"], &[level_refs], knobs.len())?;
+        Ok(Self {
+            system,
+            knobs,
+            thresholds: thresholds.to_vec(),
+            exploration: 0.2,
+        })
+    }
+
+    /// Exploration used when choosing (default 0.2)
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn knobs(&self) -> &[Knob] {
+        &self.knobs
+    }
+
+    /// Load level for an observed load
+    pub fn level(&self, load: f64) -> usize {
+        self.thresholds.iter().take_while(|&&t| load >= t).count()
+    }
+
+    /// Pick knob values for the current load
+    pub fn choose(&self, load: f64) -> Result<TunedKnobs, String> {
+        let level = self.level(load);
+        let key = level.to_string();
+        let units = self.system.sample(&[&key], self.exploration)?;
+        Ok(TunedKnobs {
+            level,
+            values: self
+                .knobs
+                .iter()
+                .zip(units)
+                .map(|(knob, unit)| (knob.name.clone(), knob.decode(unit)))
+                .collect(),
+        })
+    }
+
+    /// Report how well `knobs` performed (higher is better, not negative)
+    pub fn report(&mut self, knobs: &TunedKnobs, score: f64) -> Result<(), String> {
+        if !score.is_finite() || score < 0.0 {
+            return Err(format!("Score must be finite and non-negative, got {}", score));
+        }
+        let units: Vec<f64> = self
+            .knobs
+            .iter()
+            .map(|knob| knob.encode(knobs.get(&knob.name).unwrap_or(knob.min)))
+            .collect();
+        let key = knobs.level.to_string();
+        self.system.learn(&[&key], &units, score)
+    }
+
+    /// Best-known values per knob at a load, without exploration (None before any report)
+    pub fn best(&self, load: f64) -> Option<TunedKnobs> {
+        let level = self.level(load);
+        let state = self.system.context_state(&level.to_string())?;
+        if state.total_experiences == 0 {
+            return None;
+        }
+        Some(TunedKnobs {
+            level,
+            values: self
+                .knobs
+                .iter()
+                .zip(&state.params)
+                .map(|(knob, p)| (knob.name.clone(), knob.decode(p.mean)))
+                .collect(),
+        })
+    }
+
+    /// The internal context system, e.g. to save what was learned
+    pub fn system(&self) -> &EvoCoreContextSystem {
+        &self.system
+    }
+}
@@ -0,0 +1,368 @@
+//! Pluggable save formats
+//!
+//! A [`SystemSerializer`] turns a [`Checkpoint`] into bytes and back. The
+//! built-in JSON and binary formats are serializers like any other, so a
+//! downstream format only has to implement the trait to get the same save
+//! pipeline: [`save_with`](EvoCoreContextSystem::save_with) writes through a
+//! temporary file renamed into place, and [`SaveOptions`] can wrap the
+//! encoded bytes in an envelope with a checksum and (feature `zstd`)
+//! compression. Built-in formats can also be picked by name with
+//! [`Format`] and [`save_as`](EvoCoreContextSystem::save_as).
+//!
+//! Envelope layout: `EVCF` magic, a flags byte (bit 0 checksum, bit 1 zstd),
+//! the 64-bit FNV-1a checksum of the uncompressed payload (zero when not
+//! enabled), then the payload. Without any layer enabled the serializer's
+//! output is written as-is.
+
+use crate::checkpoint::{parse_binary, parse_json};
+use crate::{CanonicalJsonSerializer, Checkpoint, EvoCoreContextSystem, LoadError};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+
+const ENVELOPE_MAGIC: &[u8; 4] = b"EVCF";
+const ENVELOPE_HEADER: usize = 4 + 1 + 8;
+const FLAG_CHECKSUM: u8 = 1;
+const FLAG_ZSTD: u8 = 2;
+
+/// 64-bit FNV-1a
+pub(crate) fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325u64, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Converts a checkpoint to and from one file format
+pub trait SystemSerializer {
+    /// Encode a checkpoint
+    fn serialize(&self, checkpoint: &Checkpoint) -> Result<Vec<u8>, String>;
+
+    /// Decode bytes produced by [`serialize`](Self::serialize)
+    ///
+    /// The result is validated against its own schema before a system is
+    /// built from it, so implementations only need to parse.
+    fn deserialize(&self, data: &[u8]) -> Result<Checkpoint, String>;
+}
+
+/// The JSON format written by `save()`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonSerializer;
+
+/// The C library's binary format, written by `save_binary()`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BinarySerializer;
+
+fn json_contexts(contexts: &[crate::ContextState]) -> Map<String, Value> {
+    contexts
+        .iter()
+        .map(|state| {
+            let means: Vec<f64> = state.params.iter().map(|p| p.mean).collect();
+            let stds: Vec<f64> = state.params.iter().map(|p| p.std()).collect();
+            let weights: Vec<f64> = state.params.iter().map(|p| p.sum_weights).collect();
+            let context = json!({
+                "param_count": state.params.len(),
+                "total_experiences": state.total_experiences,
+                "confidence": state.confidence,
+                "avg_fitness": state.avg_fitness,
+                "best_fitness": state.best_fitness,
+                "first_update": state.first_update,
+                "last_update": state.last_update,
+                "means": means,
+                "stds": stds,
+                "sum_weights": weights,
+            });
+            (state.key.clone(), context)
+        })
+        .collect()
+}
+
+/// The document written by [`JsonSerializer`]
+pub(crate) fn json_document(checkpoint: &Checkpoint) -> Value {
+    let dimensions: Vec<Value> = checkpoint
+        .dimensions
+        .iter()
+        .map(|(name, values)| json!({ "name": name, "values": values }))
+        .collect();
+
+    let mut root = Map::new();
+    root.insert("dimensions".to_string(), Value::from(dimensions));
+    root.insert("param_count".to_string(), Value::from(checkpoint.param_count));
+    root.insert("contexts".to_string(), Value::from(json_contexts(&checkpoint.contexts)));
+    if !checkpoint.stable.is_empty() {
+        root.insert("stable".to_string(), Value::from(json_contexts(&checkpoint.stable)));
+    }
+    Value::from(root)
+}
+
+impl SystemSerializer for JsonSerializer {
+    fn serialize(&self, checkpoint: &Checkpoint) -> Result<Vec<u8>, String> {
+        let mut out = serde_json::to_vec_pretty(&json_document(checkpoint)).map_err(|e| e.to_string())?;
+        out.push(b'\n');
+        Ok(out)
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<Checkpoint, String> {
+        parse_json(data).map_err(|e| e.to_string())
+    }
+}
+
+impl SystemSerializer for BinarySerializer {
+    fn serialize(&self, checkpoint: &Checkpoint) -> Result<Vec<u8>, String> {
+        Ok(checkpoint.to_binary())
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<Checkpoint, String> {
+        parse_binary(data, false).map(|(checkpoint, _)| checkpoint).map_err(|e| e.to_string())
+    }
+}
+
+/// A built-in save format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// Human-readable: means, standard deviations, weights and timestamps ([`JsonSerializer`])
+    #[default]
+    Json,
+    /// JSON with sorted keys and fixed float precision ([`CanonicalJsonSerializer`])
+    CanonicalJson,
+    /// The C library's format ([`BinarySerializer`])
+    Binary,
+    /// Compact and lossless, readable from any language (feature `msgpack`)
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+    /// `proto/snapshot.proto` (feature `proto`)
+    #[cfg(feature = "proto")]
+    Proto,
+}
+
+impl Format {
+    /// The serializer implementing this format
+    pub fn serializer(self) -> &'static dyn SystemSerializer {
+        match self {
+            Format::Json => &JsonSerializer,
+            Format::CanonicalJson => &CanonicalJsonSerializer::DEFAULT,
+            Format::Binary => &BinarySerializer,
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => &crate::MessagePackSerializer,
+            #[cfg(feature = "proto")]
+            Format::Proto => &crate::ProtoSerializer,
+        }
+    }
+}
+
+/// Layers applied around a serializer's output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SaveOptions {
+    format: Format,
+    atomic: bool,
+    checksum: bool,
+    compression: Option<i32>,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self {
+            format: Format::Json,
+            atomic: true,
+            checksum: false,
+            compression: None,
+        }
+    }
+}
+
+impl SaveOptions {
+    /// Atomic write, no checksum, no compression
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default options saving in `format`
+    pub fn format(format: Format) -> Self {
+        Self::default().with_format(format)
+    }
+
+    /// Format used by [`save_as`](EvoCoreContextSystem::save_as) (default JSON)
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Format used by [`save_as`](EvoCoreContextSystem::save_as)
+    pub fn selected_format(&self) -> Format {
+        self.format
+    }
+
+    /// Write through a temporary file renamed into place (default on)
+    pub fn with_atomic(mut self, atomic: bool) -> Self {
+        self.atomic = atomic;
+        self
+    }
+
+    /// Store a checksum of the payload, verified on load (default off)
+    pub fn with_checksum(mut self, checksum: bool) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Compress the payload with zstd at `level` (feature `zstd`)
+    #[cfg(feature = "zstd")]
+    pub fn with_compression(mut self, level: i32) -> Self {
+        self.compression = Some(level);
+        self
+    }
+
+    /// Apply the configured layers to serialized bytes
+    pub(crate) fn wrap(&self, payload: Vec<u8>) -> Result<Vec<u8>, String> {
+        if !self.checksum && self.compression.is_none() {
+            return Ok(payload);
+        }
+
+        let mut flags = 0;
+        let mut sum = 0;
+        if self.checksum {
+            flags |= FLAG_CHECKSUM;
+            sum = checksum(&payload);
+        }
+        let payload = match self.compression {
+            #[cfg(feature = "zstd")]
+            Some(level) => {
+                flags |= FLAG_ZSTD;
+                zstd::encode_all(payload.as_slice(), level)
+                    .map_err(|e| format!("Failed to compress context system: {}", e))?
+            }
+            _ => payload,
+        };
+
+        let mut out = Vec::with_capacity(ENVELOPE_HEADER + payload.len());
+        out.extend_from_slice(ENVELOPE_MAGIC);
+        out.push(flags);
+        out.extend_from_slice(&sum.to_be_bytes());
+        out.extend_from_slice(&payload);
+        Ok(out)
+    }
+}
+
+/// Strip an envelope written by [`SaveOptions`], verifying its checksum
+///
+/// Data without an envelope is returned unchanged.
+pub(crate) fn unwrap_envelope(data: Vec<u8>) -> Result<Vec<u8>, LoadError> {
+    if !data.starts_with(ENVELOPE_MAGIC) {
+        return Ok(data);
+    }
+    if data.len() < ENVELOPE_HEADER {
+        return Err(LoadError::Truncated { offset: data.len(), expected: "envelope header" });
+    }
+
+    let flags = data[4];
+    let sum = u64::from_be_bytes(data[5..ENVELOPE_HEADER].try_into().unwrap());
+    let payload = &data[ENVELOPE_HEADER..];
+
+    let payload = if flags & FLAG_ZSTD != 0 {
+        #[cfg(feature = "zstd")]
+        {
+            zstd::decode_all(payload).map_err(|e| LoadError::Malformed {
+                location: "envelope".to_string(),
+                message: format!("zstd: {}", e),
+            })?
+        }
+        #[cfg(not(feature = "zstd"))]
+        {
+            return Err(LoadError::Malformed {
+                location: "envelope".to_string(),
+                message: "compressed with zstd; enable the `zstd` feature".to_string(),
+            });
+        }
+    } else {
+        payload.to_vec()
+    };
+
+    if flags & FLAG_CHECKSUM != 0 && checksum(&payload) != sum {
+        return Err(LoadError::Malformed {
+            location: "envelope".to_string(),
+            message: "checksum mismatch".to_string(),
+        });
+    }
+    Ok(payload)
+}
+
+/// Write `data` to `path`, through a temporary file if `atomic`
+pub(crate) fn write_file(path: &Path, data: &[u8], atomic: bool) -> Result<(), String> {
+    let err = |e: std::io::Error| format!("Failed to save context system: {}", e);
+    if !atomic {
+        return std::fs::write(path, data).map_err(err);
+    }
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, data).map_err(err)?;
+    std::fs::rename(&tmp, path).map_err(err)
+}
+
+impl EvoCoreContextSystem {
+    /// Save in the format of `serializer`, applying the layers in `options`
+    ///
+    /// The format selected in `options` is ignored; `serializer` wins.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "info",
+            name = "evocore.save",
+            skip_all,
+            fields(path = %filepath.as_ref().display(), contexts = self.context_count()),
+            err(Display)
+        )
+    )]
+    pub fn save_with<P: AsRef<Path>>(
+        &self,
+        filepath: P,
+        serializer: &dyn SystemSerializer,
+        options: &SaveOptions,
+    ) -> Result<(), String> {
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+        let result = self
+            .encode_with(serializer, options)
+            .and_then(|data| write_file(filepath.as_ref(), &data, options.atomic));
+        #[cfg(feature = "metrics")]
+        self.record_save(result.is_ok(), started.elapsed());
+        result
+    }
+
+    /// The exact bytes [`save_with`](Self::save_with) writes
+    pub(crate) fn encode_with(&self, serializer: &dyn SystemSerializer, options: &SaveOptions) -> Result<Vec<u8>, String> {
+        let payload = serializer
+            .serialize(&self.checkpoint())
+            .map_err(|e| format!("Failed to save context system: {}", e))?;
+        options.wrap(payload)
+    }
+
+    /// Save in the format selected by `options`
+    pub fn save_as<P: AsRef<Path>>(&self, filepath: P, options: &SaveOptions) -> Result<(), String> {
+        self.save_with(filepath, options.format.serializer(), options)
+    }
+
+    /// Load a file written by [`save_as`](Self::save_as) in `format`
+    pub fn load_as<P: AsRef<Path>>(filepath: P, format: Format) -> Result<Self, String> {
+        Self::load_with(filepath, format.serializer())
+    }
+
+    /// Load a file written by [`save_with`](Self::save_with) with the same serializer
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", name = "evocore.load", skip_all, fields(path = %filepath.as_ref().display()), err(Display))
+    )]
+    pub fn load_with<P: AsRef<Path>>(filepath: P, serializer: &dyn SystemSerializer) -> Result<Self, String> {
+        let data = std::fs::read(filepath).map_err(|e| format!("Failed to load context system: {}", e))?;
+        Self::decode_with(data, serializer)
+    }
+
+    /// Build a system from bytes written by [`save_with`](Self::save_with)
+    pub(crate) fn decode_with(data: Vec<u8>, serializer: &dyn SystemSerializer) -> Result<Self, String> {
+        let fail = |e: String| format!("Failed to load context system: {}", e);
+        let data = unwrap_envelope(data).map_err(|e| fail(e.to_string()))?;
+        let checkpoint = serializer.deserialize(&data).map_err(fail)?;
+        for state in checkpoint.contexts.iter().chain(&checkpoint.stable) {
+            checkpoint.validate_context(state).map_err(|e| fail(e.to_string()))?;
+        }
+        checkpoint.into_system().map_err(|e| fail(e.to_string()))
+    }
+}
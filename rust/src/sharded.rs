@@ -0,0 +1,122 @@
+//! Sharded context system for parallel learning
+//!
+//! [`ShardedContextSystem`] partitions contexts across K independent inner
+//! systems by a hash of the context key. Threads learning different
+//! contexts usually hit different shards and never contend on one global
+//! lock.
+
+use crate::{EvoCoreContextSystem, SharedContextSystem};
+
+/// Hash a context key (FNV-1a, the same function the C library uses)
+fn key_hash(key: &str) -> u32 {
+    key.bytes().fold(2166136261u32, |hash, b| {
+        (hash ^ b as u32).wrapping_mul(16777619)
+    })
+}
+
+/// A context system split into independently locked shards
+pub struct ShardedContextSystem {
+    shards: Vec<SharedContextSystem>,
+}
+
+impl ShardedContextSystem {
+    /// Create a system with `shard_count` empty shards
+    pub fn new(
+        dimension_names: &[&str],
+        dimension_values: &[Vec<&str>],
+        param_count: usize,
+        shard_count: usize,
+    ) -> Result<Self, String> {
+        if shard_count == 0 {
+            return Err("Shard count must be at least 1".to_string());
+        }
+
+        let shards = (0..shard_count)
+            .map(|_| {
+                EvoCoreContextSystem::new(dimension_names, dimension_values, param_count)
+                    .map(SharedContextSystem::new)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { shards })
+    }
+
+    /// Split an existing system into `shard_count` shards
+    pub fn from_system(system: &EvoCoreContextSystem, shard_count: usize) -> Result<Self, String> {
+        let dims = system.dimensions();
+        let names: Vec<&str> = dims.iter().map(|(n, _)| n.as_str()).collect();
+        let values: Vec<Vec<&str>> = dims
+            .iter()
+            .map(|(_, vals)| vals.iter().map(String::as_str).collect())
+            .collect();
+
+        let sharded = Self::new(&names, &values, system.param_count(), shard_count)?;
+        for state in system.context_states() {
+            sharded
+                .shard_for_key(&state.key)
+                .write(|shard| shard.restore_context_state(&state))?;
+        }
+
+        Ok(sharded)
+    }
+
+    fn shard_for_key(&self, key: &str) -> &SharedContextSystem {
+        &self.shards[key_hash(key) as usize % self.shards.len()]
+    }
+
+    fn shard_for(&self, dimension_values: &[&str]) -> &SharedContextSystem {
+        self.shard_for_key(&dimension_values.join(":"))
+    }
+
+    /// Number of shards
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Number of parameters tracked per context
+    pub fn param_count(&self) -> usize {
+        self.shards[0].read(|shard| shard.param_count())
+    }
+
+    /// Learn from experience, locking only the shard that owns the context
+    pub fn learn(
+        &self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+    ) -> Result<(), String> {
+        self.shard_for(dimension_values)
+            .learn(dimension_values, parameters, fitness)
+    }
+
+    /// Sample parameters for a context from the shard that owns it
+    pub fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        self.shard_for(dimension_values).sample(dimension_values, exploration)
+    }
+
+    /// Get number of contexts stored across all shards
+    pub fn context_count(&self) -> usize {
+        self.shards.iter().map(SharedContextSystem::context_count).sum()
+    }
+
+    /// Combine all shards into one system
+    ///
+    /// Each context lives in exactly one shard, so the result holds every
+    /// context unchanged. Useful for saving or for a read-only sampling view.
+    /// Shards are read one after another, so learns that race with the merge
+    /// may or may not be included.
+    pub fn merge_shards(&self) -> Result<EvoCoreContextSystem, String> {
+        let mut checkpoint = self.shards[0].read(|shard| shard.checkpoint());
+        for shard in &self.shards[1..] {
+            let (contexts, stable) = shard.read(|s| (s.context_states(), s.stable_states()));
+            checkpoint.contexts.extend(contexts);
+            checkpoint.stable.extend(stable);
+        }
+        checkpoint.into_system().map_err(|e| e.to_string())
+    }
+
+    /// Save all shards as one system
+    pub fn save(&self, filepath: &str) -> Result<(), String> {
+        self.merge_shards()?.save(filepath)
+    }
+}
@@ -0,0 +1,98 @@
+//! Thread-safe context system
+//!
+//! [`EvoCoreContextSystem`] is `Send` but not `Sync`. [`SharedContextSystem`]
+//! wraps it in a reader-writer lock so many threads can `sample()` at once
+//! while `learn()` calls are serialized.
+
+use crate::{CensusConfig, ContextCensus, EvoCoreContextSystem, PrivacyBudget};
+use std::sync::{PoisonError, RwLock};
+
+/// Newtype marking the system as shareable behind the lock
+struct Inner(EvoCoreContextSystem);
+
+// SAFETY: every `&self` method on EvoCoreContextSystem only reads C state
+// (the C sampling and stats lookups take `const` pointers and use
+// caller-provided seeds) or Rust state that synchronizes itself (atomic
+// counters, the lock-protected key cache), and all mutation of C state
+// goes through the write lock.
+unsafe impl Sync for Inner {}
+
+/// A context system that can be shared between threads (e.g. in an `Arc`)
+///
+/// Concurrent `sample()` calls proceed in parallel under a read lock;
+/// `learn()` takes the write lock.
+pub struct SharedContextSystem {
+    inner: RwLock<Inner>,
+}
+
+impl SharedContextSystem {
+    /// Wrap an existing system
+    pub fn new(system: EvoCoreContextSystem) -> Self {
+        Self {
+            inner: RwLock::new(Inner(system)),
+        }
+    }
+
+    /// Learn from experience (exclusive)
+    pub fn learn(
+        &self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+    ) -> Result<(), String> {
+        self.write(|system| system.learn(dimension_values, parameters, fitness))
+    }
+
+    /// Sample parameters for a context (shared)
+    pub fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        self.read(|system| system.sample(dimension_values, exploration))
+    }
+
+    /// Save context system to file (shared)
+    pub fn save(&self, filepath: &str) -> Result<(), String> {
+        self.read(|system| system.save(filepath))
+    }
+
+    /// Get number of contexts stored
+    pub fn context_count(&self) -> usize {
+        self.read(|system| system.context_count())
+    }
+
+    /// Count contexts by tier (shared)
+    pub fn context_census(&self, config: &CensusConfig) -> ContextCensus {
+        self.read(|system| system.context_census(config))
+    }
+
+    /// Count contexts by tier with privacy noise (shared)
+    pub fn context_census_private(
+        &self,
+        config: &CensusConfig,
+        budget: &mut PrivacyBudget,
+        epsilon: f64,
+    ) -> Result<ContextCensus, String> {
+        self.read(|system| system.context_census_private(config, budget, epsilon))
+    }
+
+    /// Run `f` with shared access to the system
+    pub fn read<R>(&self, f: impl FnOnce(&EvoCoreContextSystem) -> R) -> R {
+        let guard = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        f(&guard.0)
+    }
+
+    /// Run `f` with exclusive access to the system
+    pub fn write<R>(&self, f: impl FnOnce(&mut EvoCoreContextSystem) -> R) -> R {
+        let mut guard = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        f(&mut guard.0)
+    }
+
+    /// Unwrap the underlying system
+    pub fn into_inner(self) -> EvoCoreContextSystem {
+        self.inner.into_inner().unwrap_or_else(PoisonError::into_inner).0
+    }
+}
+
+impl From<EvoCoreContextSystem> for SharedContextSystem {
+    fn from(system: EvoCoreContextSystem) -> Self {
+        Self::new(system)
+    }
+}
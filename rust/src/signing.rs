@@ -0,0 +1,90 @@
+//! Detached checkpoint signatures (feature `signing`)
+//!
+//! A serving host should only load policies produced by the trusted
+//! training pipeline. [`save_signed`](EvoCoreContextSystem::save_signed)
+//! writes a checkpoint as usual plus an ed25519 signature of the file's
+//! exact bytes next to it (`<path>.sig`), and
+//! [`load_verified`](EvoCoreContextSystem::load_verified) refuses any file
+//! whose signature is missing or does not verify against one of the
+//! trusted keys. Passing several keys allows rotating the signing key
+//! without downtime.
+//!
+//! Signature file layout: `EVSG` magic followed by the 64-byte signature.
+//! Checkpoints written by other tools can be signed after the fact with
+//! [`sign_checkpoint_file`].
+
+use crate::serializer::write_file;
+use crate::{EvoCoreContextSystem, Format, SaveOptions};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey, SIGNATURE_LENGTH};
+use std::path::{Path, PathBuf};
+
+const SIGNATURE_MAGIC: &[u8; 4] = b"EVSG";
+
+/// Where the detached signature of `path` is stored
+pub fn signature_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut sig = path.as_ref().as_os_str().to_owned();
+    sig.push(".sig");
+    PathBuf::from(sig)
+}
+
+fn encode_signature(signature: &Signature) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + SIGNATURE_LENGTH);
+    out.extend_from_slice(SIGNATURE_MAGIC);
+    out.extend_from_slice(&signature.to_bytes());
+    out
+}
+
+/// Check `data` against the signature stored for `path`
+fn verify(path: &Path, data: &[u8], trusted: &[VerifyingKey]) -> Result<(), String> {
+    let sig_path = signature_path(path);
+    let raw = std::fs::read(&sig_path)
+        .map_err(|e| format!("Missing signature {}: {}", sig_path.display(), e))?;
+    if raw.len() != 4 + SIGNATURE_LENGTH || &raw[..4] != SIGNATURE_MAGIC {
+        return Err(format!("Not an EvoCore signature: {}", sig_path.display()));
+    }
+    let signature = Signature::from_bytes(raw[4..].try_into().unwrap());
+
+    if trusted.iter().any(|key| key.verify_strict(data, &signature).is_ok()) {
+        Ok(())
+    } else {
+        Err("Signature does not match any trusted key".to_string())
+    }
+}
+
+/// Sign an existing checkpoint file, writing `<path>.sig`
+pub fn sign_checkpoint_file<P: AsRef<Path>>(path: P, key: &SigningKey) -> Result<(), String> {
+    let path = path.as_ref();
+    let data = std::fs::read(path).map_err(|e| format!("Failed to sign checkpoint: {}", e))?;
+    write_file(&signature_path(path), &encode_signature(&key.sign(&data)), true)
+}
+
+/// Check that a checkpoint file was signed by one of `trusted`
+pub fn verify_checkpoint_file<P: AsRef<Path>>(path: P, trusted: &[VerifyingKey]) -> Result<(), String> {
+    let path = path.as_ref();
+    let data = std::fs::read(path).map_err(|e| format!("Failed to verify checkpoint: {}", e))?;
+    verify(path, &data, trusted)
+}
+
+impl EvoCoreContextSystem {
+    /// Save as [`save_as`](Self::save_as) does and sign the written bytes
+    ///
+    /// Both files are always written atomically. The signature goes to
+    /// [`signature_path`]`(filepath)` and is written after the checkpoint,
+    /// so a reader never sees a new signature for an old file.
+    pub fn save_signed<P: AsRef<Path>>(&self, filepath: P, options: &SaveOptions, key: &SigningKey) -> Result<(), String> {
+        let path = filepath.as_ref();
+        let data = self.encode_with(options.selected_format().serializer(), options)?;
+        write_file(path, &data, true)?;
+        write_file(&signature_path(path), &encode_signature(&key.sign(&data)), true)
+    }
+
+    /// Load a checkpoint in `format` only if it is signed by one of `trusted`
+    ///
+    /// The file is read once, and the bytes verified are the bytes loaded.
+    pub fn load_verified<P: AsRef<Path>>(filepath: P, format: Format, trusted: &[VerifyingKey]) -> Result<Self, String> {
+        let path = filepath.as_ref();
+        let data = std::fs::read(path).map_err(|e| format!("Failed to load context system: {}", e))?;
+        verify(path, &data, trusted).map_err(|e| format!("Failed to load context system: {}", e))?;
+        Self::decode_with(data, format.serializer())
+    }
+}
@@ -0,0 +1,218 @@
+//! Cold-start bootstrap from similar contexts
+//!
+//! A context that has never learned samples uniformly, which is usually the
+//! worst possible starting point when its neighbours already know a lot.
+//! [`bootstrap_context`](EvoCoreContextSystem::bootstrap_context) gives a
+//! new context a prior pooled from the nearest existing contexts instead.
+//!
+//! Nearness is measured by a distance between dimension value lists: by
+//! default the number of dimensions whose values differ, or any function
+//! passed to [`BootstrapOptions::with_distance`]. Each of the nearest
+//! `neighbors` contexts contributes with weight `1 / (1 + distance)`, and
+//! the pooled prior is scaled down to count as at most `prior_samples`
+//! experiences so the context's own data quickly takes over.
+
+use crate::decay::unix_now;
+use crate::{ContextState, EvoCoreContextSystem, ParamStats, WILDCARD};
+use std::fmt;
+use std::sync::Arc;
+
+/// Distance between two lists of dimension values (lower is more similar)
+pub type DistanceFn = dyn Fn(&[&str], &[&str]) -> f64 + Send + Sync;
+
+/// Settings for [`bootstrap_context`](EvoCoreContextSystem::bootstrap_context)
+#[derive(Clone)]
+pub struct BootstrapOptions {
+    distance: Option<Arc<DistanceFn>>,
+    neighbors: usize,
+    max_distance: f64,
+    prior_samples: usize,
+}
+
+impl fmt::Debug for BootstrapOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BootstrapOptions")
+            .field("distance", &self.distance.as_ref().map_or("shared values", |_| "custom"))
+            .field("neighbors", &self.neighbors)
+            .field("max_distance", &self.max_distance)
+            .field("prior_samples", &self.prior_samples)
+            .finish()
+    }
+}
+
+impl Default for BootstrapOptions {
+    fn default() -> Self {
+        Self {
+            distance: None,
+            neighbors: 3,
+            max_distance: f64::INFINITY,
+            prior_samples: 5,
+        }
+    }
+}
+
+impl BootstrapOptions {
+    /// Three nearest contexts by shared dimension values, worth 5 experiences
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Measure similarity with a custom distance instead of shared values
+    ///
+    /// Called with the new context's values and a candidate's values.
+    /// Candidates at a negative, NaN or infinite distance are skipped.
+    pub fn with_distance<F>(mut self, distance: F) -> Self
+    where
+        F: Fn(&[&str], &[&str]) -> f64 + Send + Sync + 'static,
+    {
+        self.distance = Some(Arc::new(distance));
+        self
+    }
+
+    /// Number of nearest contexts pooled (default 3)
+    pub fn with_neighbors(mut self, neighbors: usize) -> Self {
+        self.neighbors = neighbors.max(1);
+        self
+    }
+
+    /// Ignore contexts further away than this (default unlimited)
+    pub fn with_max_distance(mut self, max_distance: f64) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Experiences the pooled prior counts as, at most (default 5)
+    pub fn with_prior_samples(mut self, prior_samples: usize) -> Self {
+        self.prior_samples = prior_samples.max(1);
+        self
+    }
+
+    fn distance(&self, a: &[&str], b: &[&str]) -> f64 {
+        match &self.distance {
+            Some(distance) => distance(a, b),
+            None => a.iter().zip(b).filter(|(x, y)| x != y).count() as f64,
+        }
+    }
+}
+
+/// What [`bootstrap_context`](EvoCoreContextSystem::bootstrap_context) did
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootstrapReport {
+    /// Key of the bootstrapped context
+    pub key: String,
+    /// Contexts pooled, nearest first, with their distance
+    pub donors: Vec<(String, f64)>,
+    /// Experiences the prior counts as (0 if there were no donors)
+    pub prior_samples: usize,
+}
+
+impl EvoCoreContextSystem {
+    /// Contexts nearest to `dimension_values`, nearest first, with their distance
+    ///
+    /// Wildcard contexts and the context itself are never included.
+    pub fn similar_contexts(
+        &self,
+        dimension_values: &[&str],
+        options: &BootstrapOptions,
+    ) -> Result<Vec<(String, f64)>, String> {
+        let key = self.context_key(dimension_values)?;
+        let mut candidates: Vec<(String, f64)> = self
+            .context_keys()
+            .into_iter()
+            .filter(|other| *other != key)
+            .filter_map(|other| {
+                let values: Vec<&str> = other.split(':').collect();
+                if values.len() != dimension_values.len() || values.contains(&WILDCARD) {
+                    return None;
+                }
+                let distance = options.distance(dimension_values, &values);
+                (distance.is_finite() && distance >= 0.0 && distance <= options.max_distance)
+                    .then_some((other, distance))
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        candidates.truncate(options.neighbors);
+        Ok(candidates)
+    }
+
+    /// Seed a context that has no data with a prior pooled from similar contexts
+    ///
+    /// Fails if the context already has data. With no similar contexts
+    /// nothing is written and the report lists no donors.
+    pub fn bootstrap_context(
+        &mut self,
+        dimension_values: &[&str],
+        options: &BootstrapOptions,
+    ) -> Result<BootstrapReport, String> {
+        let key = self.context_key(dimension_values)?;
+        if self.context_state(&key).is_some() {
+            return Err(format!("Context {} already has data", key));
+        }
+
+        let donors = self.similar_contexts(dimension_values, options)?;
+        let mut pooled = vec![ParamStats::default(); self.param_count];
+        let mut weight_total = 0.0;
+        let mut avg_fitness = 0.0;
+        let mut confidence = 0.0;
+        for (donor, distance) in &donors {
+            let Some(state) = self.context_state(donor) else {
+                continue;
+            };
+            let weight = 1.0 / (1.0 + distance);
+            for (pool, stats) in pooled.iter_mut().zip(&state.params) {
+                pool.merge(&ParamStats {
+                    sum_weights: stats.sum_weights * weight,
+                    m2: stats.m2 * weight,
+                    sum_weighted_x: stats.sum_weighted_x * weight,
+                    ..*stats
+                });
+            }
+            weight_total += weight;
+            avg_fitness += state.avg_fitness * weight;
+            confidence += state.confidence * weight;
+        }
+
+        let pooled_count = pooled.iter().map(|p| p.count).max().unwrap_or(0);
+        if pooled_count == 0 {
+            return Ok(BootstrapReport { key, donors: Vec::new(), prior_samples: 0 });
+        }
+
+        let scale = (options.prior_samples as f64 / pooled_count as f64).min(1.0);
+        for p in &mut pooled {
+            p.sum_weights *= scale;
+            p.m2 *= scale;
+            p.sum_weighted_x *= scale;
+            p.count = ((p.count as f64 * scale).ceil() as usize).max(1);
+        }
+        let prior_samples = pooled.iter().map(|p| p.count).max().unwrap_or(0);
+        let avg_fitness = avg_fitness / weight_total;
+        let now = unix_now();
+
+        self.restore_context_state(&ContextState {
+            key: key.clone(),
+            total_experiences: prior_samples,
+            confidence: confidence / weight_total * scale,
+            avg_fitness,
+            best_fitness: avg_fitness,
+            first_update: now,
+            last_update: now,
+            params: pooled,
+        })?;
+        Ok(BootstrapReport { key, donors, prior_samples })
+    }
+
+    /// Sample a context, bootstrapping it from similar contexts first if it has no data
+    pub fn sample_or_bootstrap(
+        &mut self,
+        dimension_values: &[&str],
+        exploration: f64,
+        options: &BootstrapOptions,
+    ) -> Result<Vec<f64>, String> {
+        let key = self.context_key(dimension_values)?;
+        if self.context_state(&key).is_none() {
+            self.bootstrap_context(dimension_values, options)?;
+        }
+        self.sample(dimension_values, exploration)
+    }
+}
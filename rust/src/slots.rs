@@ -0,0 +1,89 @@
+//! Stable/candidate parameter slots per context
+//!
+//! The live C state of a context is its *candidate* slot: it keeps learning
+//! from every `learn()` call. [`promote`](EvoCoreContextSystem::promote)
+//! copies the candidate into a frozen *stable* slot, and
+//! [`rollback`](EvoCoreContextSystem::rollback) overwrites the candidate
+//! with the stable slot again, discarding everything learned since the last
+//! promotion. Stable slots are carried in [`Checkpoint`](crate::Checkpoint)s,
+//! so a rollback target survives a save and reload. They are also the
+//! stable arm of [canary mode](crate::CanaryConfig), whose arm statistics
+//! start over whenever the stable slot changes.
+
+use crate::{ContextState, EvoCoreContextSystem};
+
+impl EvoCoreContextSystem {
+    /// Freeze the candidate state of this context as its stable slot
+    ///
+    /// Replaces any previous stable slot. Fails if the context has no
+    /// learned state to promote.
+    pub fn promote(&mut self, dimension_values: &[&str]) -> Result<(), String> {
+        let key = self.context_key(dimension_values)?;
+        let state = self
+            .context_state(&key)
+            .ok_or_else(|| format!("Context {} has no learned state", key))?;
+        if let Some(canary) = &mut self.canary {
+            canary.reset_arms(&key);
+        }
+        self.stable_slots.insert(key, state);
+        Ok(())
+    }
+
+    /// Replace the candidate state of this context with its stable slot
+    ///
+    /// The stable slot is kept, so repeated rollbacks return to the same
+    /// point. Fails if the context has never been promoted.
+    pub fn rollback(&mut self, dimension_values: &[&str]) -> Result<(), String> {
+        let key = self.context_key(dimension_values)?;
+        let state = self
+            .stable_slots
+            .get(&key)
+            .cloned()
+            .ok_or_else(|| format!("Context {} has no stable slot", key))?;
+        self.restore_context_state(&state)?;
+        if let Some(explanations) = &self.explanations {
+            explanations.forget(&key);
+        }
+        if let Some(canary) = &mut self.canary {
+            canary.reset_arms(&key);
+        }
+        Ok(())
+    }
+
+    /// The stable slot of this context, if it has been promoted
+    pub fn stable_state(&self, dimension_values: &[&str]) -> Option<&ContextState> {
+        let key = self.context_key(dimension_values).ok()?;
+        self.stable_slots.get(&key)
+    }
+
+    /// Sample from the stable slot, or from the candidate if there is none
+    pub fn sample_stable(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        let key = self.context_key(dimension_values)?;
+        match self.stable_slots.get(&key) {
+            Some(stable) if self.active_override(dimension_values).is_none() => {
+                let exploration = exploration.clamp(0.0, 1.0);
+                let mut rng = self.rng();
+                Ok(stable.params.iter().map(|p| p.sample(exploration, &mut rng)).collect())
+            }
+            _ => self.sample(dimension_values, exploration),
+        }
+    }
+
+    /// Stable slots of every promoted context
+    pub fn stable_states(&self) -> Vec<ContextState> {
+        self.stable_slots.values().cloned().collect()
+    }
+
+    /// Install a stable slot directly (e.g. from a checkpoint)
+    pub fn restore_stable_state(&mut self, state: &ContextState) -> Result<(), String> {
+        if state.params.len() != self.param_count {
+            return Err(format!(
+                "Parameter count mismatch: expected {}, got {}",
+                self.param_count,
+                state.params.len()
+            ));
+        }
+        self.stable_slots.insert(state.key.clone(), state.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+//! Dashboard-friendly JSON snapshots
+//!
+//! The save formats mirror the C library's internals and change with it.
+//! [`export_snapshot`](EvoCoreContextSystem::export_snapshot) instead
+//! produces a document for frontends whose schema only changes together
+//! with its `version`. Fields may be added within a version; none are
+//! removed or renamed. Non-finite numbers are written as `null`, contexts
+//! are sorted by key, and parameters are in index order.
+//!
+//! ```text
+//! {
+//!   "schema": "evocore.snapshot",
+//!   "version": 1,
+//!   "dimensions": [{ "name": "lang", "values": ["rust", "go"] }],
+//!   "parameters": [{ "index": 0, "name": "temperature" | null,
+//!                    "bounds": { "min": 0.0, "max": 2.0 } | null }],
+//!   "stats": { "contexts": 12, "experiences": 340, "mean_fitness": 0.61 },
+//!   "contexts": [{
+//!     "key": "rust:vim",
+//!     "dimensions": { "lang": "rust", "editor": "vim" },
+//!     "experiences": 40, "confidence": 0.4,
+//!     "avg_fitness": 0.7, "best_fitness": 0.95,
+//!     "first_update": 1700000000, "last_update": 1700003600,
+//!     "parameters": [{ "mean": 0.3, "std": 0.05, "count": 40, "weight": 28.1,
+//!                      "min": 0.1 | null, "max": 0.5 | null }]
+//!   }]
+//! }
+//! ```
+//!
+//! `dimensions` of a context maps each dimension name to its value (`"*"`
+//! for [wildcard](crate::WILDCARD) contexts); `std` is the weighted
+//! standard deviation, `weight` the total fitness weight, and timestamps
+//! are Unix seconds. `stats.mean_fitness` is weighted by experiences.
+//!
+//! [`export_snapshot_private`](EvoCoreContextSystem::export_snapshot_private)
+//! writes the same schema from statistics noised by a [`PrivacyBudget`].
+//! It leaves out contexts below the budget's minimum count. Parameter
+//! `min` and `max` would reveal single observations and are always `null`;
+//! timestamps are 0.
+
+use crate::{ContextState, EvoCoreContextSystem, ParamStats, PrivacyBudget};
+use serde_json::{json, Map, Value};
+
+/// Identifies snapshot documents
+pub const SNAPSHOT_SCHEMA: &str = "evocore.snapshot";
+/// Current snapshot schema version
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// A finite number, or `null`
+fn number(value: f64) -> Value {
+    if value.is_finite() {
+        json!(value)
+    } else {
+        Value::Null
+    }
+}
+
+fn distribution(stats: &ParamStats) -> Value {
+    json!({
+        "mean": number(stats.mean),
+        "std": number(stats.std()),
+        "count": stats.count,
+        "weight": number(stats.sum_weights),
+        "min": number(stats.min_value),
+        "max": number(stats.max_value),
+    })
+}
+
+/// One context in the snapshot schema
+pub(crate) fn context_json(dimensions: &[(String, Vec<String>)], state: &ContextState) -> Value {
+    let values: Map<String, Value> = dimensions
+        .iter()
+        .zip(state.key.split(':'))
+        .map(|((name, _), value)| (name.clone(), json!(value)))
+        .collect();
+    json!({
+        "key": state.key,
+        "dimensions": values,
+        "experiences": state.total_experiences,
+        "confidence": number(state.confidence),
+        "avg_fitness": number(state.avg_fitness),
+        "best_fitness": number(state.best_fitness),
+        "first_update": state.first_update,
+        "last_update": state.last_update,
+        "parameters": state.params.iter().map(distribution).collect::<Vec<_>>(),
+    })
+}
+
+impl EvoCoreContextSystem {
+    /// A JSON snapshot of the learned state with a stable schema, for dashboards
+    pub fn export_snapshot(&self) -> Value {
+        self.snapshot_of(self.context_states())
+    }
+
+    /// A snapshot of differentially private copies of the learned state
+    ///
+    /// Spends `epsilon` from `budget`.
+    pub fn export_snapshot_private(&self, budget: &mut PrivacyBudget, epsilon: f64) -> Result<Value, String> {
+        Ok(self.snapshot_of(budget.privatize(&self.context_states(), epsilon)?))
+    }
+
+    fn snapshot_of(&self, mut states: Vec<ContextState>) -> Value {
+        let dimensions = self.dimensions();
+        states.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let experiences: usize = states.iter().map(|s| s.total_experiences).sum();
+        let mean_fitness = if experiences > 0 {
+            states.iter().map(|s| s.avg_fitness * s.total_experiences as f64).sum::<f64>() / experiences as f64
+        } else {
+            0.0
+        };
+
+        let parameters: Vec<Value> = (0..self.param_count)
+            .map(|i| {
+                json!({
+                    "index": i,
+                    "name": self.param_names.get(i),
+                    "bounds": self.bounds(i).map(|b| json!({ "min": number(b.min), "max": number(b.max) })),
+                })
+            })
+            .collect();
+
+        let contexts: Vec<Value> = states.iter().map(|state| context_json(&dimensions, state)).collect();
+
+        json!({
+            "schema": SNAPSHOT_SCHEMA,
+            "version": SNAPSHOT_VERSION,
+            "dimensions": dimensions
+                .iter()
+                .map(|(name, values)| json!({ "name": name, "values": values }))
+                .collect::<Vec<_>>(),
+            "parameters": parameters,
+            "stats": {
+                "contexts": states.len(),
+                "experiences": experiences,
+                "mean_fitness": number(mean_fitness),
+            },
+            "contexts": contexts,
+        })
+    }
+}
@@ -0,0 +1,194 @@
+//! Learning-state diffs for audits
+//!
+//! [`Checkpoint::diff`] compares two saved states context by context. The
+//! resulting [`SnapshotDiff`] prints as a short human-readable summary and
+//! serializes with [`to_json`](SnapshotDiff::to_json) to a document that,
+//! like [snapshots](crate::SNAPSHOT_SCHEMA), only changes shape together
+//! with its `version`. Context objects are exactly those of the snapshot
+//! schema, so tooling that reads one reads the other.
+//!
+//! ```text
+//! {
+//!   "schema": "evocore.snapshot_diff",
+//!   "version": 1,
+//!   "dimensions": { "before": [{ "name": "lang", "values": [...] }], "after": [...] },
+//!   "param_count": { "before": 3, "after": 3 },
+//!   "summary": { "added": 1, "removed": 0, "changed": 2 },
+//!   "added": [<context>],
+//!   "removed": [<context>],
+//!   "changed": [{ "key": "rust:vim", "before": <context>, "after": <context> }]
+//! }
+//! ```
+//!
+//! A context counts as changed if any of its statistics or timestamps
+//! differ. Lists are sorted by key; stable slots are not compared.
+
+use crate::snapshot::context_json;
+use crate::{Checkpoint, ContextState};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Identifies snapshot diff documents
+pub const SNAPSHOT_DIFF_SCHEMA: &str = "evocore.snapshot_diff";
+/// Current snapshot diff schema version
+pub const SNAPSHOT_DIFF_VERSION: u32 = 1;
+
+/// A context present on both sides whose state differs
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextChange {
+    pub key: String,
+    pub before: ContextState,
+    pub after: ContextState,
+}
+
+/// Contexts added, removed and changed between two checkpoints
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotDiff {
+    pub dimensions_before: Vec<(String, Vec<String>)>,
+    pub dimensions_after: Vec<(String, Vec<String>)>,
+    pub param_count_before: usize,
+    pub param_count_after: usize,
+    pub added: Vec<ContextState>,
+    pub removed: Vec<ContextState>,
+    pub changed: Vec<ContextChange>,
+}
+
+fn means(state: &ContextState) -> String {
+    let means: Vec<String> = state.params.iter().map(|p| format!("{:.4}", p.mean)).collect();
+    format!("[{}]", means.join(", "))
+}
+
+fn dimensions_json(dimensions: &[(String, Vec<String>)]) -> Value {
+    dimensions
+        .iter()
+        .map(|(name, values)| json!({ "name": name, "values": values }))
+        .collect()
+}
+
+impl SnapshotDiff {
+    pub fn dimensions_changed(&self) -> bool {
+        self.dimensions_before != self.dimensions_after
+    }
+
+    /// True if nothing differs
+    pub fn is_empty(&self) -> bool {
+        !self.dimensions_changed()
+            && self.param_count_before == self.param_count_after
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+
+    /// The diff in the documented JSON schema
+    pub fn to_json(&self) -> Value {
+        json!({
+            "schema": SNAPSHOT_DIFF_SCHEMA,
+            "version": SNAPSHOT_DIFF_VERSION,
+            "dimensions": {
+                "before": dimensions_json(&self.dimensions_before),
+                "after": dimensions_json(&self.dimensions_after),
+            },
+            "param_count": { "before": self.param_count_before, "after": self.param_count_after },
+            "summary": {
+                "added": self.added.len(),
+                "removed": self.removed.len(),
+                "changed": self.changed.len(),
+            },
+            "added": self
+                .added
+                .iter()
+                .map(|s| context_json(&self.dimensions_after, s))
+                .collect::<Vec<_>>(),
+            "removed": self
+                .removed
+                .iter()
+                .map(|s| context_json(&self.dimensions_before, s))
+                .collect::<Vec<_>>(),
+            "changed": self
+                .changed
+                .iter()
+                .map(|c| {
+                    json!({
+                        "key": c.key,
+                        "before": context_json(&self.dimensions_before, &c.before),
+                        "after": context_json(&self.dimensions_after, &c.after),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dimensions_changed() {
+            writeln!(f, "dimensions differ")?;
+        }
+        if self.param_count_before != self.param_count_after {
+            writeln!(f, "parameter count: {} -> {}", self.param_count_before, self.param_count_after)?;
+        }
+        for state in &self.removed {
+            writeln!(f, "- {}", state.key)?;
+        }
+        for change in &self.changed {
+            let (old, new) = (&change.before, &change.after);
+            writeln!(
+                f,
+                "~ {}: exps {} -> {}, avg {:.4} -> {:.4}, means {} -> {}",
+                change.key,
+                old.total_experiences,
+                new.total_experiences,
+                old.avg_fitness,
+                new.avg_fitness,
+                means(old),
+                means(new)
+            )?;
+        }
+        for state in &self.added {
+            writeln!(f, "+ {}", state.key)?;
+        }
+        write!(
+            f,
+            "{} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )
+    }
+}
+
+impl Checkpoint {
+    /// What changed from this checkpoint to `after`
+    pub fn diff(&self, after: &Checkpoint) -> SnapshotDiff {
+        let before: BTreeMap<&str, &ContextState> = self.contexts.iter().map(|s| (s.key.as_str(), s)).collect();
+        let after_map: BTreeMap<&str, &ContextState> = after.contexts.iter().map(|s| (s.key.as_str(), s)).collect();
+
+        let mut diff = SnapshotDiff {
+            dimensions_before: self.dimensions.clone(),
+            dimensions_after: after.dimensions.clone(),
+            param_count_before: self.param_count,
+            param_count_after: after.param_count,
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+        for (key, old) in &before {
+            match after_map.get(key) {
+                None => diff.removed.push((*old).clone()),
+                Some(new) if old != new => diff.changed.push(ContextChange {
+                    key: key.to_string(),
+                    before: (*old).clone(),
+                    after: (*new).clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        diff.added = after_map
+            .iter()
+            .filter(|(key, _)| !before.contains_key(*key))
+            .map(|(_, state)| (*state).clone())
+            .collect();
+        diff
+    }
+}
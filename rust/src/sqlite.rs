@@ -0,0 +1,381 @@
+//! SQLite-backed incremental storage
+//!
+//! [`SqliteStore`] keeps one row per context instead of rewriting the whole
+//! system on every save, so very large systems can persist only the contexts
+//! that changed and load contexts lazily as they are needed. Stable slots
+//! (see [`promote`](EvoCoreContextSystem::promote)) are kept in a table of
+//! their own with the same layout, and travel with their context.
+
+use crate::{ContextState, EvoCoreContextSystem, ParamStats};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (
+        name  TEXT PRIMARY KEY,
+        value INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS dimensions (
+        position INTEGER PRIMARY KEY,
+        name     TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS dimension_values (
+        dimension INTEGER NOT NULL,
+        position  INTEGER NOT NULL,
+        value     TEXT NOT NULL,
+        PRIMARY KEY (dimension, position)
+    );
+    CREATE TABLE IF NOT EXISTS contexts (
+        key               TEXT PRIMARY KEY,
+        total_experiences INTEGER NOT NULL,
+        confidence        REAL NOT NULL,
+        avg_fitness       REAL NOT NULL,
+        best_fitness      REAL NOT NULL,
+        first_update      INTEGER NOT NULL,
+        last_update       INTEGER NOT NULL,
+        params            BLOB NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS slots (
+        key               TEXT PRIMARY KEY,
+        total_experiences INTEGER NOT NULL,
+        confidence        REAL NOT NULL,
+        avg_fitness       REAL NOT NULL,
+        best_fitness      REAL NOT NULL,
+        first_update      INTEGER NOT NULL,
+        last_update       INTEGER NOT NULL,
+        params            BLOB NOT NULL
+    );
+";
+
+/// Tables holding [`ContextState`] rows
+const CONTEXTS: &str = "contexts";
+const SLOTS: &str = "slots";
+
+/// Bytes used to encode one [`ParamStats`] in the `params` column
+const PARAM_STATS_SIZE: usize = 64;
+
+/// Context storage backed by a SQLite database, one row per context
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Open (or create) a store at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open store: {}", e))?;
+        Self::init(conn)
+    }
+
+    /// Open a store that lives only in memory
+    pub fn open_in_memory() -> Result<Self, String> {
+        let conn = Connection::open_in_memory().map_err(|e| format!("Failed to open store: {}", e))?;
+        Self::init(conn)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to initialize store: {}", e))?;
+        Ok(Self { conn })
+    }
+
+    /// Write the system's dimensions and parameter count, replacing any previous schema
+    pub fn save_schema(&mut self, system: &EvoCoreContextSystem) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(sql_err)?;
+        write_schema(&tx, system)?;
+        tx.commit().map_err(sql_err)
+    }
+
+    /// Persist a single context and its stable slot (partial update)
+    ///
+    /// Returns `false` if the system has no data for that context.
+    pub fn save_context(
+        &self,
+        system: &EvoCoreContextSystem,
+        dimension_values: &[&str],
+    ) -> Result<bool, String> {
+        let key = system.context_key(dimension_values)?;
+        let Some(state) = system.context_state(&key) else {
+            return Ok(false);
+        };
+        let tx = self.conn.unchecked_transaction().map_err(sql_err)?;
+        write_state(&tx, CONTEXTS, &state)?;
+        match system.stable_state(dimension_values) {
+            Some(stable) => write_state(&tx, SLOTS, stable)?,
+            None => delete_state(&tx, SLOTS, &key)?,
+        }
+        tx.commit().map_err(sql_err)?;
+        Ok(true)
+    }
+
+    /// Persist the schema, every context and every stable slot in one transaction
+    ///
+    /// The stored contexts are replaced, so contexts removed from the
+    /// system since the last save (pruned, evicted, reset, renamed) are
+    /// deleted too. Returns the number of contexts written.
+    pub fn save_all(&mut self, system: &EvoCoreContextSystem) -> Result<usize, String> {
+        let tx = self.conn.transaction().map_err(sql_err)?;
+        write_schema(&tx, system)?;
+        tx.execute("DELETE FROM contexts", []).map_err(sql_err)?;
+        tx.execute("DELETE FROM slots", []).map_err(sql_err)?;
+        let mut written = 0;
+        for key in system.context_keys() {
+            if let Some(state) = system.context_state(&key) {
+                write_state(&tx, CONTEXTS, &state)?;
+                written += 1;
+            }
+        }
+        for stable in system.stable_states() {
+            write_state(&tx, SLOTS, &stable)?;
+        }
+        tx.commit().map_err(sql_err)?;
+
+        Ok(written)
+    }
+
+    /// Create an empty system from the stored schema without loading any contexts
+    pub fn load_schema(&self) -> Result<EvoCoreContextSystem, String> {
+        let param_count: i64 = self
+            .conn
+            .query_row("SELECT value FROM meta WHERE name = 'param_count'", [], |row| row.get(0))
+            .optional()
+            .map_err(sql_err)?
+            .ok_or_else(|| "Store has no schema".to_string())?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT position, name FROM dimensions ORDER BY position")
+            .map_err(sql_err)?;
+        let dims: Vec<(i64, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(sql_err)?
+            .collect::<Result<_, _>>()
+            .map_err(sql_err)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT value FROM dimension_values WHERE dimension = ?1 ORDER BY position")
+            .map_err(sql_err)?;
+        let mut values = Vec::with_capacity(dims.len());
+        for (position, _) in &dims {
+            let vals: Vec<String> = stmt
+                .query_map(params![position], |row| row.get(0))
+                .map_err(sql_err)?
+                .collect::<Result<_, _>>()
+                .map_err(sql_err)?;
+            values.push(vals);
+        }
+
+        let names: Vec<&str> = dims.iter().map(|(_, name)| name.as_str()).collect();
+        let values: Vec<Vec<&str>> = values
+            .iter()
+            .map(|vals| vals.iter().map(String::as_str).collect())
+            .collect();
+
+        EvoCoreContextSystem::new(&names, &values, param_count as usize)
+    }
+
+    /// Lazily load one context into `system`
+    ///
+    /// Does nothing if the context is already present in memory. Returns
+    /// `false` if the store has no row for that context.
+    pub fn load_context(
+        &self,
+        system: &mut EvoCoreContextSystem,
+        dimension_values: &[&str],
+    ) -> Result<bool, String> {
+        let key = system.context_key(dimension_values)?;
+        if system.context_state(&key).is_some() {
+            return Ok(true);
+        }
+
+        match read_state(&self.conn, CONTEXTS, &key)? {
+            Some(state) => {
+                system.restore_context_state(&state)?;
+                if let Some(stable) = read_state(&self.conn, SLOTS, &key)? {
+                    system.restore_stable_state(&stable)?;
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Load the schema, every stored context and every stable slot
+    pub fn load_all(&self) -> Result<EvoCoreContextSystem, String> {
+        let mut system = self.load_schema()?;
+
+        let mut stmt = self.conn.prepare(&select_all(CONTEXTS)).map_err(sql_err)?;
+        let rows = stmt.query_map([], row_to_state).map_err(sql_err)?;
+        for state in rows {
+            system.restore_context_state(&state.map_err(sql_err)??)?;
+        }
+
+        let mut stmt = self.conn.prepare(&select_all(SLOTS)).map_err(sql_err)?;
+        let rows = stmt.query_map([], row_to_state).map_err(sql_err)?;
+        for stable in rows {
+            system.restore_stable_state(&stable.map_err(sql_err)??)?;
+        }
+
+        Ok(system)
+    }
+
+    /// Remove one context and its stable slot from the store
+    pub fn delete_context(&self, key: &str) -> Result<bool, String> {
+        let tx = self.conn.unchecked_transaction().map_err(sql_err)?;
+        delete_state(&tx, SLOTS, key)?;
+        let n = tx
+            .execute("DELETE FROM contexts WHERE key = ?1", params![key])
+            .map_err(sql_err)?;
+        tx.commit().map_err(sql_err)?;
+        Ok(n > 0)
+    }
+
+    /// Number of contexts stored
+    pub fn context_count(&self) -> Result<usize, String> {
+        let n: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM contexts", [], |row| row.get(0))
+            .map_err(sql_err)?;
+        Ok(n as usize)
+    }
+}
+
+/// Every row of `table`, in the column order [`row_to_state`] expects
+fn select_all(table: &str) -> String {
+    format!(
+        "SELECT key, total_experiences, confidence, avg_fitness, \
+         best_fitness, first_update, last_update, params FROM {}",
+        table
+    )
+}
+
+fn sql_err(e: rusqlite::Error) -> String {
+    format!("SQLite error: {}", e)
+}
+
+/// Replace the stored dimensions and parameter count
+fn write_schema(conn: &Connection, system: &EvoCoreContextSystem) -> Result<(), String> {
+    conn.execute("DELETE FROM dimension_values", []).map_err(sql_err)?;
+    conn.execute("DELETE FROM dimensions", []).map_err(sql_err)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (name, value) VALUES ('param_count', ?1)",
+        params![system.param_count() as i64],
+    )
+    .map_err(sql_err)?;
+
+    for (i, (name, values)) in system.dimensions().iter().enumerate() {
+        conn.execute(
+            "INSERT INTO dimensions (position, name) VALUES (?1, ?2)",
+            params![i as i64, name],
+        )
+        .map_err(sql_err)?;
+        for (j, value) in values.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO dimension_values (dimension, position, value) VALUES (?1, ?2, ?3)",
+                params![i as i64, j as i64, value],
+            )
+            .map_err(sql_err)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_state(conn: &Connection, table: &str, state: &ContextState) -> Result<(), String> {
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO {} (key, total_experiences, confidence, avg_fitness, \
+             best_fitness, first_update, last_update, params) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            table
+        ),
+        params![
+            state.key,
+            state.total_experiences as i64,
+            state.confidence,
+            state.avg_fitness,
+            state.best_fitness,
+            state.first_update,
+            state.last_update,
+            encode_params(&state.params),
+        ],
+    )
+    .map_err(sql_err)?;
+    Ok(())
+}
+
+fn delete_state(conn: &Connection, table: &str, key: &str) -> Result<(), String> {
+    conn.execute(&format!("DELETE FROM {} WHERE key = ?1", table), params![key])
+        .map_err(sql_err)?;
+    Ok(())
+}
+
+fn read_state(conn: &Connection, table: &str, key: &str) -> Result<Option<ContextState>, String> {
+    conn.query_row(
+        &format!("{} WHERE key = ?1", select_all(table)),
+        params![key],
+        row_to_state,
+    )
+    .optional()
+    .map_err(sql_err)?
+    .transpose()
+}
+
+fn row_to_state(row: &rusqlite::Row<'_>) -> rusqlite::Result<Result<ContextState, String>> {
+    let key: String = row.get(0)?;
+    let blob: Vec<u8> = row.get(7)?;
+    let params = match decode_params(&blob) {
+        Some(params) => params,
+        None => return Ok(Err(format!("Corrupt parameter data for context {}", key))),
+    };
+
+    Ok(Ok(ContextState {
+        key,
+        total_experiences: row.get::<_, i64>(1)? as usize,
+        confidence: row.get(2)?,
+        avg_fitness: row.get(3)?,
+        best_fitness: row.get(4)?,
+        first_update: row.get(5)?,
+        last_update: row.get(6)?,
+        params,
+    }))
+}
+
+fn encode_params(params: &[ParamStats]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(params.len() * PARAM_STATS_SIZE);
+    for p in params {
+        out.extend_from_slice(&p.mean.to_le_bytes());
+        out.extend_from_slice(&p.variance.to_le_bytes());
+        out.extend_from_slice(&p.sum_weights.to_le_bytes());
+        out.extend_from_slice(&p.m2.to_le_bytes());
+        out.extend_from_slice(&(p.count as u64).to_le_bytes());
+        out.extend_from_slice(&p.min_value.to_le_bytes());
+        out.extend_from_slice(&p.max_value.to_le_bytes());
+        out.extend_from_slice(&p.sum_weighted_x.to_le_bytes());
+    }
+    out
+}
+
+fn decode_params(blob: &[u8]) -> Option<Vec<ParamStats>> {
+    if !blob.len().is_multiple_of(PARAM_STATS_SIZE) {
+        return None;
+    }
+
+    let field = |chunk: &[u8], i: usize| -> [u8; 8] {
+        chunk[i * 8..(i + 1) * 8].try_into().unwrap()
+    };
+
+    Some(
+        blob.chunks_exact(PARAM_STATS_SIZE)
+            .map(|chunk| ParamStats {
+                mean: f64::from_le_bytes(field(chunk, 0)),
+                variance: f64::from_le_bytes(field(chunk, 1)),
+                sum_weights: f64::from_le_bytes(field(chunk, 2)),
+                m2: f64::from_le_bytes(field(chunk, 3)),
+                count: u64::from_le_bytes(field(chunk, 4)) as usize,
+                min_value: f64::from_le_bytes(field(chunk, 5)),
+                max_value: f64::from_le_bytes(field(chunk, 6)),
+                sum_weighted_x: f64::from_le_bytes(field(chunk, 7)),
+            })
+            .collect(),
+    )
+}
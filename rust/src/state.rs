@@ -0,0 +1,88 @@
+//! Conversions between C-side and Rust-side learned state
+//!
+//! The C library keeps each context's statistics behind its internal hash
+//! table. [`ContextState`] (from `evocore-core`) copies that state out so
+//! it can be persisted, compared, or moved between systems, and written
+//! back with
+//! [`EvoCoreContextSystem::restore_context_state`](crate::EvoCoreContextSystem::restore_context_state).
+
+use crate::{evocore_context_stats_t, evocore_weighted_stats_t, ContextState, ParamStats};
+use std::ffi::CStr;
+
+impl From<&evocore_weighted_stats_t> for ParamStats {
+    fn from(raw: &evocore_weighted_stats_t) -> Self {
+        Self {
+            mean: raw.mean,
+            variance: raw.variance,
+            sum_weights: raw.sum_weights,
+            m2: raw.m2,
+            count: raw.count,
+            min_value: raw.min_value,
+            max_value: raw.max_value,
+            sum_weighted_x: raw.sum_weighted_x,
+        }
+    }
+}
+
+impl From<&ParamStats> for evocore_weighted_stats_t {
+    fn from(stats: &ParamStats) -> Self {
+        Self {
+            mean: stats.mean,
+            variance: stats.variance,
+            sum_weights: stats.sum_weights,
+            m2: stats.m2,
+            count: stats.count,
+            min_value: stats.min_value,
+            max_value: stats.max_value,
+            sum_weighted_x: stats.sum_weighted_x,
+        }
+    }
+}
+
+/// Copy state out of a C stats struct
+///
+/// # Safety
+/// `raw` must be a valid stats struct owned by a live context system.
+// time_t is only i64 on some targets
+#[allow(clippy::useless_conversion)]
+pub(crate) unsafe fn state_from_raw(raw: &evocore_context_stats_t) -> ContextState {
+    let params = if raw.stats.is_null() || (*raw.stats).stats.is_null() {
+        vec![ParamStats::default(); raw.param_count]
+    } else {
+        std::slice::from_raw_parts((*raw.stats).stats, (*raw.stats).count)
+            .iter()
+            .map(ParamStats::from)
+            .collect()
+    };
+
+    ContextState {
+        key: CStr::from_ptr(raw.key).to_string_lossy().into_owned(),
+        total_experiences: raw.total_experiences,
+        confidence: raw.confidence,
+        avg_fitness: raw.avg_fitness,
+        best_fitness: raw.best_fitness,
+        first_update: i64::from(raw.first_update),
+        last_update: i64::from(raw.last_update),
+        params,
+    }
+}
+
+/// Overwrite a C stats struct with `state` (the key is left untouched)
+///
+/// # Safety
+/// `raw` must be a valid stats struct owned by a live context system.
+pub(crate) unsafe fn write_state_raw(state: &ContextState, raw: &mut evocore_context_stats_t) {
+    raw.total_experiences = state.total_experiences;
+    raw.confidence = state.confidence;
+    raw.avg_fitness = state.avg_fitness;
+    raw.best_fitness = state.best_fitness;
+    raw.first_update = state.first_update as crate::time_t;
+    raw.last_update = state.last_update as crate::time_t;
+
+    if !raw.stats.is_null() && !(*raw.stats).stats.is_null() {
+        let slots = std::slice::from_raw_parts_mut((*raw.stats).stats, (*raw.stats).count);
+        for (slot, stats) in slots.iter_mut().zip(&state.params) {
+            *slot = stats.into();
+        }
+    }
+}
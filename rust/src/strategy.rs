@@ -0,0 +1,387 @@
+//! Pluggable sampling strategies
+//!
+//! By default `sample()` draws from each context's learned distribution,
+//! blended with uniform noise by `exploration`. A [`SamplingStrategy`]
+//! replaces that step: once one is set with
+//! [`set_sampling_strategy`](EvoCoreContextSystem::set_sampling_strategy),
+//! `sample()`, `sample_into()` and `sample_batch()` dispatch to it (pinned
+//! overrides and parameter bounds still apply around it). Built-in
+//! strategies:
+//!
+//! - [`LearnedDistribution`]: the default behaviour.
+//! - [`EpsilonGreedy`]: uniform with probability `epsilon`, otherwise the
+//!   learned means.
+//! - [`Ucb1`]: the learned distribution with exploration
+//!   `c * sqrt(ln(N + 1) / (n + 1))`, where `n` is the context's and `N`
+//!   the whole system's experience count.
+//! - [`Thompson`]: draws each mean from its posterior, `mean + z * std /
+//!   sqrt(count)`, then blends with uniform noise by `exploration`.
+//! - [`Softmax`]: draws several candidates and picks one with probability
+//!   proportional to `exp(log_likelihood / temperature)`, sharpening
+//!   towards the mode as the temperature drops.
+//! - [`BayesOpt`](crate::BayesOpt): expected improvement under a
+//!   Gaussian-process surrogate, for expensive fitness evaluations.
+//! - [`CmaEs`](crate::CmaEs): a full-covariance search distribution
+//!   adapted by CMA-ES, for smooth continuous landscapes.
+//! - [`Ensemble`](crate::Ensemble): several of the above, mixed per
+//!   context by how well each one's proposals did.
+//!
+//! Custom strategies implement the trait; [`StrategyInput`] gives them the
+//! context's state, the parameter specs, an RNG and the default sampler to
+//! build on, and [`observe`](SamplingStrategy::observe) lets them keep
+//! their own history of what was learned. A strategy that knows the
+//! probability density of what it drew can report it from
+//! [`sample_with_propensity`](SamplingStrategy::sample_with_propensity);
+//! [`EvoCoreContextSystem::sample_with_propensity`] returns it to the
+//! caller for logging and off-policy evaluation. The built-in strategies
+//! do not report one.
+
+use crate::{BayesOpt, CmaEs, ContextState, Ensemble, EvoCoreContextSystem, ParamBounds, ParamSpec};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::sync::Arc;
+
+/// How parameters are drawn for a context
+pub trait SamplingStrategy: Send + Sync {
+    /// Short identifier, for logs and comparisons
+    fn name(&self) -> &str;
+
+    /// Fill `out` (one value per parameter) for the context in `input`
+    fn sample(&self, input: &StrategyInput<'_>, out: &mut [f64]) -> Result<(), String>;
+
+    /// Called after each successful learn while the strategy is active
+    fn observe(&self, _key: &str, _parameters: &[f64], _fitness: f64) {}
+
+    /// Called when a context is removed or reset; drop anything kept for `key`
+    fn forget(&self, _key: &str) {}
+
+    /// Called when a context moves to a new key (a renamed dimension
+    /// value); carry anything kept for `from` over to `to`, unless `to`
+    /// already has its own
+    fn rename_key(&self, _from: &str, _to: &str) {}
+
+    /// [`sample`](Self::sample), also returning the propensity of the drawn
+    /// parameters: their probability density (or probability, for discrete
+    /// choices) under this strategy, if known
+    fn sample_with_propensity(&self, input: &StrategyInput<'_>, out: &mut [f64]) -> Result<Option<f64>, String> {
+        self.sample(input, out).map(|()| None)
+    }
+}
+
+/// What a strategy knows about the context being sampled
+pub struct StrategyInput<'a> {
+    system: &'a EvoCoreContextSystem,
+    dimension_values: &'a [&'a str],
+    key: String,
+    temperature: f64,
+    /// Exploration requested by the caller
+    pub exploration: f64,
+}
+
+impl<'a> StrategyInput<'a> {
+    pub fn dimension_values(&self) -> &[&str] {
+        self.dimension_values
+    }
+
+    /// Variability requested by the caller (1 unless sampled through
+    /// [`sample_with_temperature`](EvoCoreContextSystem::sample_with_temperature))
+    ///
+    /// `exploration` is already scaled by it; strategies with their own
+    /// randomness knob should scale that too, so 0 means "most likely
+    /// parameters" and larger values mean more variety.
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    /// Context key, as passed to [`observe`](SamplingStrategy::observe)
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Learned state of the context, if it has any
+    pub fn state(&self) -> Option<ContextState> {
+        self.system.context_state(&self.key)
+    }
+
+    /// Parameter bounds configured on the system, if any
+    pub fn bounds(&self, index: usize) -> Option<ParamBounds> {
+        self.system.bounds(index)
+    }
+
+    /// Name, bounds and kind of every parameter, in order
+    pub fn param_specs(&self) -> Vec<ParamSpec> {
+        (0..self.system.param_count)
+            .map(|index| ParamSpec {
+                index,
+                name: self.system.param_names.get(index).cloned(),
+                bounds: self.system.bounds(index),
+                kind: self.system.param_kind(index),
+            })
+            .collect()
+    }
+
+    /// The default sampler: learned distribution blended with uniform noise
+    pub fn sample_learned(&self, exploration: f64, out: &mut [f64]) -> Result<(), String> {
+        self.system.sample_raw(self.dimension_values, exploration, out)
+    }
+
+    /// Pull `out` towards the context's learned means by the temperature (below 1 only)
+    pub fn temper(&self, out: &mut [f64]) {
+        self.system.temper(&self.key, self.temperature, out);
+    }
+
+    /// Experiences across every context (linear in the number of contexts)
+    pub fn total_experiences(&self) -> usize {
+        self.system
+            .context_keys()
+            .iter()
+            .filter_map(|key| self.system.context_state(key))
+            .map(|s| s.total_experiences)
+            .sum()
+    }
+
+    /// RNG derived from the system's seed stream in deterministic mode
+    pub fn rng(&self) -> StdRng {
+        self.system.rng()
+    }
+}
+
+/// Standard normal draw (Box-Muller)
+pub(crate) fn gaussian(rng: &mut StdRng) -> f64 {
+    let u1: f64 = rng.gen::<f64>().max(1e-12);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// The default: sample the learned distribution
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LearnedDistribution;
+
+impl SamplingStrategy for LearnedDistribution {
+    fn name(&self) -> &str {
+        "learned"
+    }
+
+    fn sample(&self, input: &StrategyInput<'_>, out: &mut [f64]) -> Result<(), String> {
+        input.sample_learned(input.exploration, out)?;
+        input.temper(out);
+        Ok(())
+    }
+}
+
+/// Uniform with probability `epsilon`, otherwise the learned means
+///
+/// The caller's `exploration` is ignored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EpsilonGreedy {
+    pub epsilon: f64,
+}
+
+impl SamplingStrategy for EpsilonGreedy {
+    fn name(&self) -> &str {
+        "epsilon-greedy"
+    }
+
+    fn sample(&self, input: &StrategyInput<'_>, out: &mut [f64]) -> Result<(), String> {
+        let mut rng = input.rng();
+        match input.state().filter(|s| s.total_experiences > 0) {
+            Some(state) if rng.gen::<f64>() >= self.epsilon * input.temperature() => {
+                for (value, p) in out.iter_mut().zip(&state.params) {
+                    *value = p.mean;
+                }
+                Ok(())
+            }
+            _ => input.sample_learned(1.0, out),
+        }
+    }
+}
+
+/// Exploration from an upper-confidence bonus instead of the caller
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ucb1 {
+    pub c: f64,
+}
+
+impl SamplingStrategy for Ucb1 {
+    fn name(&self) -> &str {
+        "ucb1"
+    }
+
+    fn sample(&self, input: &StrategyInput<'_>, out: &mut [f64]) -> Result<(), String> {
+        let n = input.state().map_or(0, |s| s.total_experiences) as f64;
+        let total = input.total_experiences() as f64;
+        let c = self.c * input.temperature();
+        let exploration = (c * ((total + 1.0).ln() / (n + 1.0)).sqrt()).clamp(0.0, 1.0);
+        input.sample_learned(exploration, out)?;
+        input.temper(out);
+        Ok(())
+    }
+}
+
+/// Posterior sampling of each parameter's mean
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Thompson;
+
+impl SamplingStrategy for Thompson {
+    fn name(&self) -> &str {
+        "thompson"
+    }
+
+    fn sample(&self, input: &StrategyInput<'_>, out: &mut [f64]) -> Result<(), String> {
+        let Some(state) = input.state() else {
+            return input.sample_learned(1.0, out);
+        };
+        let mut rng = input.rng();
+        let exploration = input.exploration.clamp(0.0, 1.0);
+        for (value, p) in out.iter_mut().zip(&state.params) {
+            let drawn = if p.count < 2 {
+                rng.gen::<f64>()
+            } else {
+                p.mean + gaussian(&mut rng) * input.temperature() * p.std() / (p.count as f64).sqrt()
+            };
+            *value = (1.0 - exploration) * drawn + exploration * rng.gen::<f64>();
+        }
+        Ok(())
+    }
+}
+
+/// Pick among learned-distribution candidates by softmax over their likelihood
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Softmax {
+    pub temperature: f64,
+    /// Candidates drawn per sample
+    pub candidates: usize,
+}
+
+impl Default for Softmax {
+    fn default() -> Self {
+        Self { temperature: 1.0, candidates: 8 }
+    }
+}
+
+impl SamplingStrategy for Softmax {
+    fn name(&self) -> &str {
+        "softmax"
+    }
+
+    fn sample(&self, input: &StrategyInput<'_>, out: &mut [f64]) -> Result<(), String> {
+        let Some(state) = input.state() else {
+            return input.sample_learned(input.exploration, out);
+        };
+
+        let count = self.candidates.max(1);
+        let mut candidates = vec![0.0; count * out.len()];
+        let mut scores = Vec::with_capacity(count);
+        for candidate in candidates.chunks_mut(out.len()) {
+            input.sample_learned(input.exploration, candidate)?;
+            let log_likelihood: f64 = candidate
+                .iter()
+                .zip(&state.params)
+                .map(|(&x, p)| {
+                    let z = (x - p.mean) / p.std().max(1e-6);
+                    -0.5 * z * z
+                })
+                .sum();
+            scores.push(log_likelihood / (self.temperature * input.temperature()).max(1e-9));
+        }
+
+        let max = scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = scores.iter().map(|s| (s - max).exp()).collect();
+        let mut pick = input.rng().gen::<f64>() * weights.iter().sum::<f64>();
+        let mut chosen = count - 1;
+        for (i, w) in weights.iter().enumerate() {
+            if pick < *w {
+                chosen = i;
+                break;
+            }
+            pick -= w;
+        }
+        out.copy_from_slice(&candidates[chosen * out.len()..(chosen + 1) * out.len()]);
+        Ok(())
+    }
+}
+
+/// A built-in strategy with default settings, by [`name`](SamplingStrategy::name)
+///
+/// `epsilon-greedy` uses epsilon 0.1, `ucb1` uses c = 1, `softmax` uses
+/// temperature 1 with 8 candidates, `bayes-opt` and `cma-es` use
+/// [`BayesOpt::new`] and [`CmaEs::new`], and `ensemble` mixes `thompson`,
+/// `ucb1` and `learned`.
+pub fn builtin_strategy(name: &str) -> Option<Arc<dyn SamplingStrategy>> {
+    match name {
+        "learned" => Some(Arc::new(LearnedDistribution)),
+        "epsilon-greedy" => Some(Arc::new(EpsilonGreedy { epsilon: 0.1 })),
+        "ucb1" => Some(Arc::new(Ucb1 { c: 1.0 })),
+        "thompson" => Some(Arc::new(Thompson)),
+        "softmax" => Some(Arc::new(Softmax::default())),
+        "bayes-opt" => Some(Arc::new(BayesOpt::new())),
+        "cma-es" => Some(Arc::new(CmaEs::new())),
+        "ensemble" => Ensemble::new(vec![Arc::new(Thompson), Arc::new(Ucb1 { c: 1.0 }), Arc::new(LearnedDistribution)])
+            .ok()
+            .map(|e| Arc::new(e) as Arc<dyn SamplingStrategy>),
+        _ => None,
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Sample with `strategy` instead of the learned distribution
+    pub fn with_sampling_strategy(mut self, strategy: Arc<dyn SamplingStrategy>) -> Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    /// Change the sampling strategy, or restore the default with `None`
+    pub fn set_sampling_strategy(&mut self, strategy: Option<Arc<dyn SamplingStrategy>>) {
+        self.strategy = strategy;
+    }
+
+    /// Name of the active sampling strategy
+    pub fn sampling_strategy(&self) -> &str {
+        self.strategy.as_ref().map_or("learned", |s| s.name())
+    }
+
+    /// Sample parameters, with the propensity reported by the sampling strategy
+    ///
+    /// The propensity is `None` when the strategy does not report one, and
+    /// when the returned parameters are not the strategy's draw: pinned
+    /// overrides, last-known-good serving, and samples clamped or redrawn
+    /// to fit [bounds](crate::ParamBounds).
+    pub fn sample_with_propensity(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+    ) -> Result<(Vec<f64>, Option<f64>), String> {
+        let mut params = vec![0.0; self.param_count];
+        let propensity = self.sample_tempered(dimension_values, exploration, 1.0, &mut params)?;
+        Ok((params, propensity))
+    }
+
+    /// Draw one sample with the active strategy
+    pub(crate) fn sample_dispatch(
+        &self,
+        dimension_values: &[&str],
+        exploration: f64,
+        temperature: f64,
+        out: &mut [f64],
+    ) -> Result<Option<f64>, String> {
+        match &self.strategy {
+            Some(strategy) => {
+                let input = StrategyInput {
+                    system: self,
+                    dimension_values,
+                    key: self.context_key(dimension_values)?,
+                    temperature,
+                    exploration,
+                };
+                strategy.sample_with_propensity(&input, out)
+            }
+            None => {
+                self.sample_raw(dimension_values, exploration, out)?;
+                if temperature < 1.0 {
+                    self.temper(&self.context_key(dimension_values)?, temperature, out);
+                }
+                Ok(None)
+            }
+        }
+    }
+}
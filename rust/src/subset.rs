@@ -0,0 +1,59 @@
+//! Loading part of a checkpoint
+//!
+//! A worker that only serves `domain=web` traffic has no use for the rest
+//! of a large checkpoint. [`load_subset`](EvoCoreContextSystem::load_subset)
+//! keeps only the contexts matching a dimension filter and a context
+//! predicate; for binary checkpoints the others are dropped while the file
+//! is parsed, so they are never materialized.
+
+use crate::checkpoint::ContextFilter;
+use crate::{Checkpoint, ContextState, EvoCoreContextSystem, WILDCARD};
+use std::path::Path;
+
+impl EvoCoreContextSystem {
+    /// Load only the matching contexts of a saved system
+    ///
+    /// `dimension_filter` lists `(dimension, allowed values)` pairs; a
+    /// context is kept if, for every listed dimension, its value is one of
+    /// the allowed values (or [`WILDCARD`]), and `context_filter` accepts
+    /// it. The loaded system declares only the allowed values for filtered
+    /// dimensions. Stable slots are filtered the same way. Accepts the JSON
+    /// and binary formats.
+    pub fn load_subset<P, F>(filepath: P, dimension_filter: &[(&str, &[&str])], mut context_filter: F) -> Result<Self, String>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&ContextState) -> bool,
+    {
+        let fail = |e: String| format!("Failed to load context system: {}", e);
+
+        let keep: &mut ContextFilter<'_> = &mut |dimensions, state| {
+            let values: Vec<&str> = state.key.split(':').collect();
+            let matches = dimension_filter.iter().all(|(name, allowed)| {
+                dimensions
+                    .iter()
+                    .position(|(n, _)| n == name)
+                    .and_then(|index| values.get(index))
+                    .is_some_and(|v| *v == WILDCARD || allowed.contains(v))
+            });
+            matches && context_filter(state)
+        };
+        let mut checkpoint = Checkpoint::read_filtered(filepath.as_ref(), keep).map_err(|e| fail(e.to_string()))?;
+
+        for (name, allowed) in dimension_filter {
+            let (_, declared) = checkpoint
+                .dimensions
+                .iter_mut()
+                .find(|(n, _)| n == name)
+                .ok_or_else(|| fail(format!("Unknown dimension: {}", name)))?;
+            if let Some(value) = allowed.iter().find(|v| !declared.iter().any(|d| d == *v)) {
+                return Err(fail(format!("Unknown value {:?} for dimension {}", value, name)));
+            }
+            declared.retain(|d| allowed.contains(&d.as_str()));
+        }
+
+        for state in checkpoint.contexts.iter().chain(&checkpoint.stable) {
+            checkpoint.validate_context(state).map_err(|e| fail(e.to_string()))?;
+        }
+        checkpoint.into_system().map_err(|e| fail(e.to_string()))
+    }
+}
@@ -0,0 +1,93 @@
+//! Per-context summaries
+//!
+//! [`EvoCoreContextSystem::context_summary`] answers "what has it learned
+//! for this context?" from the C library's statistics: how many
+//! experiences, how good they were, when the last one arrived, and where
+//! each parameter's distribution sits.
+//! [`contexts_detailed`](EvoCoreContextSystem::contexts_detailed) does the
+//! same for every context, with its key parsed back into dimension values.
+
+use crate::{ContextState, EvoCoreContextSystem};
+use std::collections::HashMap;
+
+/// What a system has learned for one context
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextSummary {
+    /// Experiences learned (0 if the context has never been learned)
+    pub samples: usize,
+    pub mean_fitness: f64,
+    pub best_fitness: f64,
+    /// Learned standard deviation of each parameter
+    pub stddev: Vec<f64>,
+    /// Unix timestamp of the most recent experience (0 if none)
+    pub last_updated: i64,
+    /// Learned mean of each parameter
+    pub param_means: Vec<f64>,
+}
+
+/// One context as listed by [`contexts_detailed`](EvoCoreContextSystem::contexts_detailed)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextDetail {
+    pub key: String,
+    /// Value of each dimension, by dimension name (`None` if the key does
+    /// not split into one value per dimension)
+    pub values: Option<HashMap<String, String>>,
+    pub summary: ContextSummary,
+}
+
+impl From<&ContextState> for ContextSummary {
+    fn from(state: &ContextState) -> Self {
+        Self {
+            samples: state.total_experiences,
+            mean_fitness: state.avg_fitness,
+            best_fitness: state.best_fitness,
+            stddev: state.params.iter().map(|p| p.std()).collect(),
+            last_updated: state.last_update,
+            param_means: state.params.iter().map(|p| p.mean).collect(),
+        }
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Summarize what has been learned for this context
+    ///
+    /// A context that has never been learned has `samples` 0 and zero
+    /// means and deviations.
+    pub fn context_summary(&self, dimension_values: &[&str]) -> Result<ContextSummary, String> {
+        let key = self.context_key(dimension_values)?;
+        let state = self
+            .context_state(&key)
+            .unwrap_or_else(|| ContextState::empty(key, self.param_count));
+        Ok(ContextSummary::from(&state))
+    }
+
+    /// Every context with its dimension values and summary, sorted by key
+    ///
+    /// Wildcard contexts map dimensions to [`WILDCARD`](crate::WILDCARD).
+    /// Every context is listed, including any whose key does not split into
+    /// one value per dimension (a value containing `:`); those have no
+    /// `values`.
+    pub fn contexts_detailed(&self) -> Vec<ContextDetail> {
+        let names: Vec<String> = self.dimensions().into_iter().map(|(name, _)| name).collect();
+        let mut states = self.context_states();
+        states.sort_by(|a, b| a.key.cmp(&b.key));
+        states
+            .iter()
+            .map(|state| {
+                let values: Vec<&str> = state.key.split(':').collect();
+                let values = (values.len() == names.len()).then(|| {
+                    names
+                        .iter()
+                        .cloned()
+                        .zip(values.into_iter().map(str::to_string))
+                        .collect()
+                });
+                ContextDetail {
+                    key: state.key.clone(),
+                    values,
+                    summary: ContextSummary::from(state),
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,130 @@
+//! Delta encoding for syncing systems between hosts
+//!
+//! Shipping the full state on every sync wastes bandwidth when only a few
+//! contexts changed. Every modification gives a context a new version (see
+//! [`context_version`](EvoCoreContextSystem::context_version)), so a sender
+//! remembers the [`sync_version`](EvoCoreContextSystem::sync_version) of its
+//! last successful sync and sends only what changed since with
+//! [`delta_since`](EvoCoreContextSystem::delta_since). With the `zstd`
+//! feature the encoded delta can also be compressed for the wire.
+//!
+//! Deltas carry changed contexts only; contexts removed on the sender (by
+//! pruning or eviction) are not removed on the receiver.
+
+use crate::{Checkpoint, EvoCoreContextSystem};
+
+/// Magic bytes at the start of an encoded delta
+const DELTA_MAGIC: &[u8; 4] = b"EVSD";
+
+/// Contexts changed between two sync versions
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncDelta {
+    /// Sync version the delta starts from (exclusive)
+    pub since: u64,
+    /// Sync version of the sender when the delta was taken
+    pub head: u64,
+    /// Schema and changed contexts
+    pub checkpoint: Checkpoint,
+}
+
+impl SyncDelta {
+    /// Whether the delta carries no contexts
+    pub fn is_empty(&self) -> bool {
+        self.checkpoint.contexts.is_empty()
+    }
+
+    /// Encode for the wire
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(DELTA_MAGIC);
+        out.extend_from_slice(&self.since.to_be_bytes());
+        out.extend_from_slice(&self.head.to_be_bytes());
+        out.extend_from_slice(&self.checkpoint.to_binary());
+        out
+    }
+
+    /// Decode a delta produced by [`to_bytes`](Self::to_bytes)
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        if data.len() < 20 || &data[..4] != DELTA_MAGIC {
+            return Err("Not an EvoCore sync delta".to_string());
+        }
+        let since = u64::from_be_bytes(data[4..12].try_into().unwrap());
+        let head = u64::from_be_bytes(data[12..20].try_into().unwrap());
+        let checkpoint = Checkpoint::from_bytes(&data[20..])
+            .map_err(|e| format!("Invalid sync delta: {}", e))?;
+        Ok(Self { since, head, checkpoint })
+    }
+
+    /// Encode and compress with zstd at `level` (feature `zstd`)
+    #[cfg(feature = "zstd")]
+    pub fn to_compressed(&self, level: i32) -> Result<Vec<u8>, String> {
+        zstd::encode_all(self.to_bytes().as_slice(), level)
+            .map_err(|e| format!("Failed to compress sync delta: {}", e))
+    }
+
+    /// Decompress and decode a delta from [`to_compressed`](Self::to_compressed)
+    #[cfg(feature = "zstd")]
+    pub fn from_compressed(data: &[u8]) -> Result<Self, String> {
+        let bytes = zstd::decode_all(data)
+            .map_err(|e| format!("Failed to decompress sync delta: {}", e))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Latest version given to any context; pass it to a later [`delta_since`](Self::delta_since)
+    pub fn sync_version(&self) -> u64 {
+        self.version_clock
+    }
+
+    /// Contexts modified after sync version `since` (0 for everything)
+    pub fn delta_since(&self, since: u64) -> SyncDelta {
+        let mut keys: Vec<&String> = self
+            .versions
+            .iter()
+            .filter(|(_, &version)| version > since)
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort();
+
+        // Contexts created without a version (loaded from a file) only go
+        // out in a full sync.
+        let contexts = if since == 0 {
+            self.context_states()
+        } else {
+            keys.iter().filter_map(|key| self.context_state(key)).collect()
+        };
+
+        SyncDelta {
+            since,
+            head: self.version_clock,
+            checkpoint: Checkpoint {
+                dimensions: self.dimensions(),
+                param_count: self.param_count,
+                contexts,
+                stable: Vec::new(),
+            },
+        }
+    }
+
+    /// Overwrite this system's copies of the contexts in `delta`
+    ///
+    /// Returns the number of contexts applied. The delta must come from a
+    /// system with the same dimensions and parameter count.
+    pub fn apply_delta(&mut self, delta: &SyncDelta) -> Result<usize, String> {
+        if delta.checkpoint.param_count != self.param_count {
+            return Err(format!(
+                "Parameter count mismatch: expected {}, got {}",
+                self.param_count, delta.checkpoint.param_count
+            ));
+        }
+        if delta.checkpoint.dimensions != self.dimensions() {
+            return Err("Sync delta has different dimensions".to_string());
+        }
+
+        for state in &delta.checkpoint.contexts {
+            self.restore_context_state(state)?;
+        }
+        Ok(delta.checkpoint.contexts.len())
+    }
+}
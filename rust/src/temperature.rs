@@ -0,0 +1,58 @@
+//! Per-request sampling temperature
+//!
+//! One context often serves requests that want different amounts of
+//! variety, e.g. creative and precise tasks for the same agent.
+//! [`sample_with_temperature`](EvoCoreContextSystem::sample_with_temperature)
+//! takes a single dial instead of an exploration factor:
+//!
+//! - `0` returns the context's most likely parameters (its learned means);
+//! - `1` samples exactly as [`sample_scheduled`](EvoCoreContextSystem::sample_scheduled);
+//! - values in between pull each draw towards the learned means, and values
+//!   above 1 raise exploration.
+//!
+//! The scheduled exploration is multiplied by the temperature (capped at 1)
+//! and, below 1, each draw's distance from the learned means is scaled by
+//! it. Active [sampling strategies](crate::SamplingStrategy) receive the
+//! temperature through [`StrategyInput::temperature`](crate::StrategyInput::temperature)
+//! and scale their own knobs: epsilon, the UCB1 constant, the posterior
+//! spread, the softmax temperature, the expected-improvement margin and the
+//! CMA-ES step size. Contexts without data are sampled uniformly whatever
+//! the temperature.
+
+use crate::EvoCoreContextSystem;
+
+impl EvoCoreContextSystem {
+    /// Sample with variability set by `temperature` (0 precise, 1 normal, above 1 more varied)
+    pub fn sample_with_temperature(&self, dimension_values: &[&str], temperature: f64) -> Result<Vec<f64>, String> {
+        let mut params = vec![0.0; self.param_count];
+        self.sample_with_temperature_into(dimension_values, temperature, &mut params)?;
+        Ok(params)
+    }
+
+    /// Allocation-free [`sample_with_temperature`](Self::sample_with_temperature)
+    pub fn sample_with_temperature_into(
+        &self,
+        dimension_values: &[&str],
+        temperature: f64,
+        out: &mut [f64],
+    ) -> Result<(), String> {
+        if !temperature.is_finite() || temperature < 0.0 {
+            return Err(format!("Temperature must be finite and non-negative, got {}", temperature));
+        }
+        let exploration = (self.scheduled_exploration(dimension_values)? * temperature).min(1.0);
+        self.sample_tempered(dimension_values, exploration, temperature, out).map(|_| ())
+    }
+
+    /// Scale each value's distance from the learned mean by `temperature` (below 1 only)
+    pub(crate) fn temper(&self, key: &str, temperature: f64, out: &mut [f64]) {
+        if temperature >= 1.0 {
+            return;
+        }
+        let Some(state) = self.context_state(key).filter(|s| s.total_experiences > 0) else {
+            return;
+        };
+        for (value, p) in out.iter_mut().zip(&state.params) {
+            *value = p.mean + temperature * (*value - p.mean);
+        }
+    }
+}
@@ -0,0 +1,771 @@
+//! Helpers for testing code built on EvoCore (feature `test-util`)
+//!
+//! Golden-file snapshots: [`canonical_json`] renders a system in a stable
+//! form (sorted keys, fixed float precision, no timestamps) and
+//! [`assert_golden`] compares it against a file checked into the repository.
+//! Set `EVOCORE_UPDATE_GOLDEN=1` to (re)write golden files instead of
+//! comparing.
+//!
+//! Statistical comparison: [`assert_statistically_equivalent`] checks that two
+//! systems learned the same per-context distributions without requiring
+//! bit-identical floats.
+//!
+//! Differential testing: [`assert_backends_agree`] runs random operation
+//! sequences against two [`ContextLearner`] backends (e.g. the FFI and
+//! pure-Rust systems) and reports the shortest sequence on which they diverge.
+//!
+//! Concurrency: [`stress_learn_sample`] and [`stress_save_during_learn`] run
+//! many writer and reader threads against any [`ConcurrentLearner`] and
+//! check that no update was lost.
+
+use crate::{
+    ContextLearner, ContextSystemHandle, EvoCoreContextSystem, ShardedContextSystem,
+    SharedContextSystem,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Environment variable that switches [`assert_golden`] into update mode
+pub const UPDATE_GOLDEN_ENV: &str = "EVOCORE_UPDATE_GOLDEN";
+
+/// Decimal places used for every float in [`canonical_json`]
+const FLOAT_PRECISION: usize = 9;
+
+/// Lines of unchanged context shown around each difference
+const DIFF_CONTEXT: usize = 2;
+
+fn float(out: &mut String, value: f64) {
+    if value.is_finite() {
+        // Normalize -0.0 so it doesn't show up as a spurious diff
+        let value = if value == 0.0 { 0.0 } else { value };
+        let _ = write!(out, "{:.*}", FLOAT_PRECISION, value);
+    } else {
+        let _ = write!(out, "\"{}\"", value);
+    }
+}
+
+/// Render a system as canonical, diff-friendly JSON
+///
+/// Contexts are sorted by key, object keys are emitted in a fixed order,
+/// floats use fixed precision, and timestamps are omitted so snapshots do
+/// not change from run to run.
+pub fn canonical_json(system: &EvoCoreContextSystem) -> String {
+    let mut checkpoint = system.checkpoint();
+    checkpoint.contexts.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let mut out = String::from("{\n  \"contexts\": {");
+    for (i, state) in checkpoint.contexts.iter().enumerate() {
+        out.push_str(if i == 0 { "\n" } else { ",\n" });
+        let _ = writeln!(out, "    {}: {{", serde_json::Value::from(state.key.as_str()));
+        out.push_str("      \"avg_fitness\": ");
+        float(&mut out, state.avg_fitness);
+        out.push_str(",\n      \"best_fitness\": ");
+        float(&mut out, state.best_fitness);
+        out.push_str(",\n      \"confidence\": ");
+        float(&mut out, state.confidence);
+        out.push_str(",\n      \"params\": [");
+        for (j, p) in state.params.iter().enumerate() {
+            out.push_str(if j == 0 { "\n" } else { ",\n" });
+            out.push_str("        {\"count\": ");
+            let _ = write!(out, "{}", p.count);
+            out.push_str(", \"mean\": ");
+            float(&mut out, p.mean);
+            out.push_str(", \"std\": ");
+            float(&mut out, p.std());
+            out.push_str(", \"sum_weights\": ");
+            float(&mut out, p.sum_weights);
+            out.push('}');
+        }
+        let _ = write!(
+            out,
+            "\n      ],\n      \"total_experiences\": {}\n    }}",
+            state.total_experiences
+        );
+    }
+    if !checkpoint.contexts.is_empty() {
+        out.push_str("\n  ");
+    }
+
+    out.push_str("},\n  \"dimensions\": [");
+    for (i, (name, values)) in checkpoint.dimensions.iter().enumerate() {
+        out.push_str(if i == 0 { "\n" } else { ",\n" });
+        let values: Vec<String> = values
+            .iter()
+            .map(|v| serde_json::Value::from(v.as_str()).to_string())
+            .collect();
+        let _ = write!(
+            out,
+            "    {{\"name\": {}, \"values\": [{}]}}",
+            serde_json::Value::from(name.as_str()),
+            values.join(", ")
+        );
+    }
+    if !checkpoint.dimensions.is_empty() {
+        out.push_str("\n  ");
+    }
+    let _ = writeln!(out, "],\n  \"param_count\": {}\n}}", checkpoint.param_count);
+
+    out
+}
+
+/// Line diff between `expected` and `actual`, or `None` if they are equal
+///
+/// Output uses `-`/`+` prefixes with a few lines of surrounding context.
+pub fn diff_lines(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let a: Vec<&str> = expected.lines().collect();
+    let b: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            ops.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', a[i]));
+            i += 1;
+        } else {
+            ops.push(('+', b[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != ' ').collect();
+    let mut out = String::new();
+    let mut last_shown: Option<usize> = None;
+    for (k, (tag, line)) in ops.iter().enumerate() {
+        let near = changed
+            .iter()
+            .any(|&c| k + DIFF_CONTEXT >= c && k <= c + DIFF_CONTEXT);
+        if !near {
+            continue;
+        }
+        if matches!(last_shown, Some(l) if l + 1 != k) {
+            out.push_str("...\n");
+        }
+        let _ = writeln!(out, "{} {}", tag, line);
+        last_shown = Some(k);
+    }
+
+    if expected.ends_with('\n') != actual.ends_with('\n') {
+        out.push_str("(trailing newline differs)\n");
+    }
+
+    Some(out)
+}
+
+/// Compare `actual` against the golden file at `path`
+///
+/// Writes the file instead when it does not exist yet or when
+/// `EVOCORE_UPDATE_GOLDEN` is set. Panics with a line diff on mismatch.
+#[track_caller]
+pub fn assert_golden<P: AsRef<Path>>(path: P, actual: &str) {
+    let path = path.as_ref();
+    let update = std::env::var_os(UPDATE_GOLDEN_ENV).is_some_and(|v| !v.is_empty() && v != "0");
+
+    if update || !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .unwrap_or_else(|e| panic!("Failed to create {}: {}", parent.display(), e));
+        }
+        std::fs::write(path, actual)
+            .unwrap_or_else(|e| panic!("Failed to write golden file {}: {}", path.display(), e));
+        return;
+    }
+
+    let expected = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read golden file {}: {}", path.display(), e));
+
+    if let Some(diff) = diff_lines(&expected, actual) {
+        panic!(
+            "Snapshot does not match golden file {}\n\
+             (set {}=1 to update)\n\n{}",
+            path.display(),
+            UPDATE_GOLDEN_ENV,
+            diff
+        );
+    }
+}
+
+/// Snapshot a system's learned state against a golden file
+#[track_caller]
+pub fn assert_system_golden<P: AsRef<Path>>(system: &EvoCoreContextSystem, path: P) {
+    assert_golden(path, &canonical_json(system));
+}
+
+/// Differences between the learned distributions of two systems
+///
+/// Contexts must match by key and experience count. For every parameter
+/// the means must agree to within `tolerance` pooled standard deviations
+/// (or `tolerance` absolute when both distributions are degenerate), and
+/// the standard deviations and average fitness must agree to within a
+/// relative `tolerance`. Returns one message per violation.
+pub fn statistical_differences<A, B>(a: &A, b: &B, tolerance: f64) -> Vec<String>
+where
+    A: ContextLearner + ?Sized,
+    B: ContextLearner + ?Sized,
+{
+    let mut diffs = Vec::new();
+
+    if a.param_count() != b.param_count() {
+        diffs.push(format!(
+            "param_count differs: {} vs {}",
+            a.param_count(),
+            b.param_count()
+        ));
+        return diffs;
+    }
+
+    let mut a_states = a.context_states();
+    let mut b_states = b.context_states();
+    a_states.sort_by(|x, y| x.key.cmp(&y.key));
+    b_states.sort_by(|x, y| x.key.cmp(&y.key));
+
+    let within = |x: f64, y: f64| (x - y).abs() <= tolerance * x.abs().max(y.abs()).max(1.0);
+
+    let (mut i, mut j) = (0, 0);
+    while i < a_states.len() || j < b_states.len() {
+        let (sa, sb) = match (a_states.get(i), b_states.get(j)) {
+            (Some(sa), Some(sb)) if sa.key == sb.key => (sa, sb),
+            (Some(sa), Some(sb)) if sa.key < sb.key => {
+                diffs.push(format!("context {:?} only in first system", sa.key));
+                i += 1;
+                continue;
+            }
+            (Some(sa), None) => {
+                diffs.push(format!("context {:?} only in first system", sa.key));
+                i += 1;
+                continue;
+            }
+            (_, Some(sb)) => {
+                diffs.push(format!("context {:?} only in second system", sb.key));
+                j += 1;
+                continue;
+            }
+            (None, None) => unreachable!(),
+        };
+        i += 1;
+        j += 1;
+
+        if sa.total_experiences != sb.total_experiences {
+            diffs.push(format!(
+                "context {:?}: experiences {} vs {}",
+                sa.key, sa.total_experiences, sb.total_experiences
+            ));
+        }
+        if !within(sa.avg_fitness, sb.avg_fitness) {
+            diffs.push(format!(
+                "context {:?}: avg_fitness {} vs {}",
+                sa.key, sa.avg_fitness, sb.avg_fitness
+            ));
+        }
+
+        for (k, (pa, pb)) in sa.params.iter().zip(&sb.params).enumerate() {
+            let pooled = ((pa.std().powi(2) + pb.std().powi(2)) / 2.0).sqrt();
+            let mean_gap = (pa.mean - pb.mean).abs();
+            let mean_ok = if pooled > f64::EPSILON {
+                mean_gap / pooled <= tolerance
+            } else {
+                mean_gap <= tolerance
+            };
+            if !mean_ok {
+                diffs.push(format!(
+                    "context {:?} param {}: mean {} vs {} (pooled std {})",
+                    sa.key, k, pa.mean, pb.mean, pooled
+                ));
+            }
+            if !within(pa.std(), pb.std()) {
+                diffs.push(format!(
+                    "context {:?} param {}: std {} vs {}",
+                    sa.key,
+                    k,
+                    pa.std(),
+                    pb.std()
+                ));
+            }
+        }
+    }
+
+    diffs
+}
+
+/// Assert two systems learned statistically equivalent distributions
+///
+/// See [`statistical_differences`] for the comparison rules. Intended for
+/// checking that refactors (batching, sharding, backend swaps) do not change
+/// learning behavior, where exact float equality is too strict.
+#[track_caller]
+pub fn assert_statistically_equivalent<A, B>(a: &A, b: &B, tolerance: f64)
+where
+    A: ContextLearner + ?Sized,
+    B: ContextLearner + ?Sized,
+{
+    let diffs = statistical_differences(a, b, tolerance);
+    if !diffs.is_empty() {
+        panic!(
+            "Systems are not statistically equivalent (tolerance {}):\n  {}",
+            tolerance,
+            diffs.join("\n  ")
+        );
+    }
+}
+
+/// One step of a generated operation sequence
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    Learn {
+        dimension_values: Vec<String>,
+        parameters: Vec<f64>,
+        fitness: f64,
+    },
+    Sample {
+        dimension_values: Vec<String>,
+        exploration: f64,
+    },
+}
+
+/// Generate `len` random operations over the given schema
+///
+/// Fitness values include negatives and zero to exercise weight clamping.
+pub fn random_ops(
+    rng: &mut StdRng,
+    dimensions: &[(String, Vec<String>)],
+    param_count: usize,
+    len: usize,
+) -> Vec<Op> {
+    (0..len)
+        .map(|_| {
+            let dimension_values = dimensions
+                .iter()
+                .map(|(_, values)| values[rng.gen_range(0..values.len())].clone())
+                .collect();
+            if rng.gen_bool(0.7) {
+                Op::Learn {
+                    dimension_values,
+                    parameters: (0..param_count).map(|_| rng.gen()).collect(),
+                    fitness: match rng.gen_range(0..10) {
+                        0 => 0.0,
+                        1 => -rng.gen::<f64>(),
+                        _ => rng.gen_range(0.0..2.0),
+                    },
+                }
+            } else {
+                Op::Sample {
+                    dimension_values,
+                    exploration: rng.gen(),
+                }
+            }
+        })
+        .collect()
+}
+
+/// Apply one operation, checking the shape of any sampled output
+pub fn apply_op<L: ContextLearner + ?Sized>(learner: &mut L, op: &Op) -> Result<(), String> {
+    match op {
+        Op::Learn { dimension_values, parameters, fitness } => {
+            let dims: Vec<&str> = dimension_values.iter().map(String::as_str).collect();
+            learner.learn(&dims, parameters, *fitness)
+        }
+        Op::Sample { dimension_values, exploration } => {
+            let dims: Vec<&str> = dimension_values.iter().map(String::as_str).collect();
+            let params = learner.sample(&dims, *exploration)?;
+            if params.len() != learner.param_count() {
+                return Err(format!(
+                    "sample returned {} parameters, expected {}",
+                    params.len(),
+                    learner.param_count()
+                ));
+            }
+            if let Some(bad) = params.iter().find(|p| !p.is_finite()) {
+                return Err(format!("sample returned non-finite value {}", bad));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Run `ops` against both learners and compare the outcome
+///
+/// Each operation must succeed or fail on both backends alike; afterwards
+/// the context keys, counts, and statistics must match within `tolerance`.
+pub fn differential_check<A, B>(a: &mut A, b: &mut B, ops: &[Op], tolerance: f64) -> Result<(), String>
+where
+    A: ContextLearner + ?Sized,
+    B: ContextLearner + ?Sized,
+{
+    for (i, op) in ops.iter().enumerate() {
+        match (apply_op(a, op), apply_op(b, op)) {
+            (Ok(()), Ok(())) | (Err(_), Err(_)) => {}
+            (ra, rb) => {
+                return Err(format!("op {} ({:?}) diverged: {:?} vs {:?}", i, op, ra, rb));
+            }
+        }
+    }
+
+    if a.context_count() != b.context_count() {
+        return Err(format!(
+            "context_count differs: {} vs {}",
+            a.context_count(),
+            b.context_count()
+        ));
+    }
+
+    let diffs = statistical_differences(a, b, tolerance);
+    if diffs.is_empty() {
+        Ok(())
+    } else {
+        Err(diffs.join("\n"))
+    }
+}
+
+/// Property test: two backends agree on random schemas and operation sequences
+///
+/// `make_a` and `make_b` construct a fresh learner from dimension names,
+/// values, and parameter count (the signature of both `new` constructors).
+/// Runs `cases` random cases derived from `seed`; on failure, panics with
+/// the seed, case number, and the shortest failing prefix of operations.
+#[track_caller]
+pub fn assert_backends_agree<A, B, FA, FB>(mut make_a: FA, mut make_b: FB, cases: usize, seed: u64)
+where
+    A: ContextLearner,
+    B: ContextLearner,
+    FA: FnMut(&[&str], &[Vec<&str>], usize) -> Result<A, String>,
+    FB: FnMut(&[&str], &[Vec<&str>], usize) -> Result<B, String>,
+{
+    const TOLERANCE: f64 = 1e-9;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for case in 0..cases {
+        let dimensions: Vec<(String, Vec<String>)> = (0..rng.gen_range(1..=3))
+            .map(|d| {
+                let values = (0..rng.gen_range(1..=4)).map(|v| format!("v{}", v)).collect();
+                (format!("dim{}", d), values)
+            })
+            .collect();
+        let param_count = rng.gen_range(1..=4);
+        let len = rng.gen_range(1..=64);
+        let ops = random_ops(&mut rng, &dimensions, param_count, len);
+
+        let names: Vec<&str> = dimensions.iter().map(|(n, _)| n.as_str()).collect();
+        let values: Vec<Vec<&str>> = dimensions
+            .iter()
+            .map(|(_, vals)| vals.iter().map(String::as_str).collect())
+            .collect();
+
+        let mut run = |ops: &[Op]| -> Result<(), String> {
+            let mut a = make_a(&names, &values, param_count)?;
+            let mut b = make_b(&names, &values, param_count)?;
+            differential_check(&mut a, &mut b, ops, TOLERANCE)
+        };
+
+        if let Err(first) = run(&ops) {
+            // Shrink to the shortest failing prefix
+            let (shortest, reason) = (1..=ops.len())
+                .find_map(|n| run(&ops[..n]).err().map(|e| (n, e)))
+                .unwrap_or((ops.len(), first));
+            panic!(
+                "Backends disagree (seed {}, case {}, schema {:?}, {} params):\n{}\n\nFailing ops:\n{:#?}",
+                seed,
+                case,
+                dimensions,
+                param_count,
+                reason,
+                &ops[..shortest]
+            );
+        }
+    }
+}
+
+/// A context system that many threads can learn into and sample from at once
+///
+/// Implemented for [`SharedContextSystem`], [`ShardedContextSystem`], and
+/// [`ContextSystemHandle`] so the stress scenarios below run against any of
+/// them.
+pub trait ConcurrentLearner: Sync {
+    /// Learn from experience with parameters
+    fn learn(&self, dimension_values: &[&str], parameters: &[f64], fitness: f64)
+        -> Result<(), String>;
+
+    /// Sample parameters for a context
+    fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String>;
+
+    /// Save context system to file
+    fn save(&self, filepath: &str) -> Result<(), String>;
+
+    /// Number of parameters tracked per context
+    fn param_count(&self) -> usize;
+
+    /// Learned experience count of one context (0 if it has none)
+    fn experiences(&self, key: &str) -> usize;
+}
+
+impl ConcurrentLearner for SharedContextSystem {
+    fn learn(&self, dimension_values: &[&str], parameters: &[f64], fitness: f64) -> Result<(), String> {
+        SharedContextSystem::learn(self, dimension_values, parameters, fitness)
+    }
+
+    fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        SharedContextSystem::sample(self, dimension_values, exploration)
+    }
+
+    fn save(&self, filepath: &str) -> Result<(), String> {
+        SharedContextSystem::save(self, filepath)
+    }
+
+    fn param_count(&self) -> usize {
+        self.read(|system| system.param_count())
+    }
+
+    fn experiences(&self, key: &str) -> usize {
+        self.read(|system| system.context_state(key).map_or(0, |s| s.total_experiences))
+    }
+}
+
+impl ConcurrentLearner for ShardedContextSystem {
+    fn learn(&self, dimension_values: &[&str], parameters: &[f64], fitness: f64) -> Result<(), String> {
+        ShardedContextSystem::learn(self, dimension_values, parameters, fitness)
+    }
+
+    fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        ShardedContextSystem::sample(self, dimension_values, exploration)
+    }
+
+    fn save(&self, filepath: &str) -> Result<(), String> {
+        ShardedContextSystem::save(self, filepath)
+    }
+
+    fn param_count(&self) -> usize {
+        ShardedContextSystem::param_count(self)
+    }
+
+    fn experiences(&self, key: &str) -> usize {
+        self.merge_shards()
+            .ok()
+            .and_then(|system| system.context_state(key))
+            .map_or(0, |s| s.total_experiences)
+    }
+}
+
+impl ConcurrentLearner for ContextSystemHandle {
+    fn learn(&self, dimension_values: &[&str], parameters: &[f64], fitness: f64) -> Result<(), String> {
+        ContextSystemHandle::learn(self, dimension_values, parameters, fitness)
+    }
+
+    fn sample(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<f64>, String> {
+        ContextSystemHandle::sample(self, dimension_values, exploration)
+    }
+
+    fn save(&self, filepath: &str) -> Result<(), String> {
+        ContextSystemHandle::save(self, filepath)
+    }
+
+    fn param_count(&self) -> usize {
+        self.call(|system| system.param_count()).unwrap_or(0)
+    }
+
+    fn experiences(&self, key: &str) -> usize {
+        let key = key.to_string();
+        self.call(move |system| system.context_state(&key).map_or(0, |s| s.total_experiences))
+            .unwrap_or(0)
+    }
+}
+
+/// Shape of a stress scenario
+#[derive(Debug, Clone)]
+pub struct StressConfig {
+    /// Threads calling `learn`
+    pub writers: usize,
+    /// Threads calling `sample`
+    pub readers: usize,
+    /// Calls made by each thread
+    pub iterations: usize,
+    /// Contexts the threads spread their calls over (dimension values each)
+    pub contexts: Vec<Vec<String>>,
+}
+
+impl StressConfig {
+    /// `writers` learners and `readers` samplers, `iterations` calls each
+    pub fn new(writers: usize, readers: usize, iterations: usize, contexts: &[&[&str]]) -> Self {
+        Self {
+            writers,
+            readers,
+            iterations,
+            contexts: contexts
+                .iter()
+                .map(|dims| dims.iter().map(|v| v.to_string()).collect())
+                .collect(),
+        }
+    }
+}
+
+/// Counts gathered while a stress scenario ran
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StressReport {
+    pub learns: usize,
+    pub samples: usize,
+    pub saves: usize,
+}
+
+fn context_dims(config: &StressConfig, i: usize) -> Vec<&str> {
+    config.contexts[i % config.contexts.len()]
+        .iter()
+        .map(String::as_str)
+        .collect()
+}
+
+/// Run `config.writers` learners and `config.readers` samplers concurrently
+///
+/// Writer `w` learns its `i`-th example into context `w + i` (mod the
+/// context list), so every context sees interleaved writers. Afterwards
+/// each context must have gained exactly the number of experiences learned
+/// into it, and every sample must have had the right shape and finite
+/// values. Panics with the first violation.
+#[track_caller]
+pub fn stress_learn_sample<S: ConcurrentLearner>(system: &S, config: &StressConfig) -> StressReport {
+    run_stress(system, config, None)
+}
+
+/// Like [`stress_learn_sample`], with one more thread saving to `path` in a loop
+///
+/// Every save must succeed and produce a file that loads back into a valid
+/// system, however the saves interleave with the learns.
+#[track_caller]
+pub fn stress_save_during_learn<S: ConcurrentLearner, P: AsRef<Path>>(
+    system: &S,
+    path: P,
+    config: &StressConfig,
+) -> StressReport {
+    run_stress(system, config, Some(path.as_ref()))
+}
+
+#[track_caller]
+fn run_stress<S: ConcurrentLearner>(
+    system: &S,
+    config: &StressConfig,
+    save_path: Option<&Path>,
+) -> StressReport {
+    assert!(!config.contexts.is_empty(), "StressConfig needs at least one context");
+
+    let param_count = system.param_count();
+    let keys: Vec<String> = config.contexts.iter().map(|dims| dims.join(":")).collect();
+    let before: Vec<usize> = keys.iter().map(|key| system.experiences(key)).collect();
+    let mut expected = vec![0usize; keys.len()];
+    for w in 0..config.writers {
+        for i in 0..config.iterations {
+            expected[(w + i) % keys.len()] += 1;
+        }
+    }
+
+    let writers_done = std::sync::atomic::AtomicBool::new(false);
+    let mut report = StressReport::default();
+
+    let failures: Vec<String> = std::thread::scope(|scope| {
+        let writers: Vec<_> = (0..config.writers)
+            .map(|w| {
+                scope.spawn(move || -> Result<usize, String> {
+                    let mut rng = StdRng::seed_from_u64(w as u64);
+                    for i in 0..config.iterations {
+                        let params: Vec<f64> = (0..param_count).map(|_| rng.gen()).collect();
+                        system.learn(&context_dims(config, w + i), &params, rng.gen_range(0.0..2.0))?;
+                    }
+                    Ok(config.iterations)
+                })
+            })
+            .collect();
+
+        let readers: Vec<_> = (0..config.readers)
+            .map(|r| {
+                scope.spawn(move || -> Result<usize, String> {
+                    for i in 0..config.iterations {
+                        let params = system.sample(&context_dims(config, r + i), 0.1)?;
+                        if params.len() != param_count {
+                            return Err(format!(
+                                "sample returned {} parameters, expected {}",
+                                params.len(),
+                                param_count
+                            ));
+                        }
+                        if let Some(bad) = params.iter().find(|p| !p.is_finite()) {
+                            return Err(format!("sample returned non-finite value {}", bad));
+                        }
+                    }
+                    Ok(config.iterations)
+                })
+            })
+            .collect();
+
+        let writers_done = &writers_done;
+        let saver = save_path.map(|path| {
+            scope.spawn(move || -> Result<usize, String> {
+                let file = path.to_string_lossy();
+                let mut saves = 0;
+                loop {
+                    // Check before saving so at least one save follows the last learn
+                    let finished = writers_done.load(std::sync::atomic::Ordering::Acquire);
+                    system.save(&file)?;
+                    EvoCoreContextSystem::load(&file)?;
+                    saves += 1;
+                    if finished {
+                        return Ok(saves);
+                    }
+                }
+            })
+        });
+
+        let mut failures = Vec::new();
+        let mut collect = |result: std::thread::Result<Result<usize, String>>, count: &mut usize| {
+            match result {
+                Ok(Ok(n)) => *count += n,
+                Ok(Err(e)) => failures.push(e),
+                Err(_) => failures.push("thread panicked".to_string()),
+            }
+        };
+
+        for handle in writers {
+            collect(handle.join(), &mut report.learns);
+        }
+        writers_done.store(true, std::sync::atomic::Ordering::Release);
+        for handle in readers {
+            collect(handle.join(), &mut report.samples);
+        }
+        if let Some(handle) = saver {
+            collect(handle.join(), &mut report.saves);
+        }
+        failures
+    });
+
+    assert!(failures.is_empty(), "Stress scenario failed:\n  {}", failures.join("\n  "));
+
+    for (i, key) in keys.iter().enumerate() {
+        let actual = system.experiences(key);
+        assert_eq!(
+            actual,
+            before[i] + expected[i],
+            "Context {:?} lost updates: expected {} experiences, found {}",
+            key,
+            before[i] + expected[i],
+            actual
+        );
+    }
+
+    report
+}
@@ -0,0 +1,277 @@
+//! Chunked, resumable export and import
+//!
+//! A whole-system save has to be transferred in one piece; if the link
+//! drops at 90% the transfer starts over. [`EvoCoreContextSystem::export_chunks`]
+//! instead yields numbered [`ContextChunk`]s, each a small self-contained
+//! checkpoint with its own checksum. A [`ChunkImporter`] on the receiving
+//! side verifies and accepts them in order and reports the next sequence
+//! number it needs, so an interrupted transfer resumes from there with
+//! [`ExportChunks::resume_from`].
+
+use crate::serializer::checksum;
+use crate::{Checkpoint, ContextState, EvoCoreContextSystem};
+
+/// Magic bytes at the start of an encoded chunk
+const CHUNK_MAGIC: &[u8; 4] = b"EVCK";
+/// Fixed header: magic, export id, sequence, total, checksum, payload length
+const HEADER_LEN: usize = 4 + 8 + 8 + 8 + 8 + 4;
+
+/// One numbered piece of an export
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextChunk {
+    /// Identifies the export this chunk belongs to
+    pub export_id: u64,
+    /// Position of this chunk, starting at 0
+    pub sequence: u64,
+    /// Number of chunks in the export
+    pub total: u64,
+    /// Checksum of `payload` (64-bit FNV-1a)
+    pub checksum: u64,
+    /// Binary checkpoint holding this chunk's contexts
+    pub payload: Vec<u8>,
+}
+
+impl ContextChunk {
+    /// Whether the payload still matches its checksum
+    pub fn verify(&self) -> bool {
+        checksum(&self.payload) == self.checksum
+    }
+
+    /// Encode for the wire
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.payload.len());
+        out.extend_from_slice(CHUNK_MAGIC);
+        out.extend_from_slice(&self.export_id.to_be_bytes());
+        out.extend_from_slice(&self.sequence.to_be_bytes());
+        out.extend_from_slice(&self.total.to_be_bytes());
+        out.extend_from_slice(&self.checksum.to_be_bytes());
+        out.extend_from_slice(&(self.payload.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.payload);
+        out
+    }
+
+    /// Decode a chunk produced by [`to_bytes`](Self::to_bytes)
+    ///
+    /// Only the framing is checked here; call [`verify`](Self::verify) (or
+    /// hand the chunk to a [`ChunkImporter`]) to check the payload.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        if data.len() < HEADER_LEN {
+            return Err(format!("Chunk truncated: {} bytes, header needs {}", data.len(), HEADER_LEN));
+        }
+        if &data[..4] != CHUNK_MAGIC {
+            return Err("Not an EvoCore chunk".to_string());
+        }
+
+        let u64_at = |at: usize| u64::from_be_bytes(data[at..at + 8].try_into().unwrap());
+        let len = u32::from_be_bytes(data[36..40].try_into().unwrap()) as usize;
+        if data.len() != HEADER_LEN + len {
+            return Err(format!(
+                "Chunk length mismatch: header says {} payload bytes, found {}",
+                len,
+                data.len() - HEADER_LEN
+            ));
+        }
+
+        Ok(Self {
+            export_id: u64_at(4),
+            sequence: u64_at(12),
+            total: u64_at(20),
+            checksum: u64_at(28),
+            payload: data[HEADER_LEN..].to_vec(),
+        })
+    }
+}
+
+/// Iterator over the chunks of an export
+///
+/// The set of contexts is fixed when the export starts; each context's
+/// state is read when its chunk is produced.
+pub struct ExportChunks<'a> {
+    system: &'a EvoCoreContextSystem,
+    export_id: u64,
+    keys: Vec<String>,
+    chunk_size: usize,
+    next: u64,
+}
+
+impl ExportChunks<'_> {
+    /// Identifier shared by every chunk of this export
+    pub fn export_id(&self) -> u64 {
+        self.export_id
+    }
+
+    /// Number of chunks in the export
+    pub fn total(&self) -> u64 {
+        self.keys.len().div_ceil(self.chunk_size).max(1) as u64
+    }
+
+    /// Continue an interrupted export at `sequence`
+    ///
+    /// Resuming needs the same export id and context set, so it only makes
+    /// sense on a system that was not modified in between; otherwise start
+    /// a fresh export.
+    pub fn resume_from(mut self, export_id: u64, sequence: u64) -> Self {
+        self.export_id = export_id;
+        self.next = sequence;
+        self
+    }
+}
+
+impl Iterator for ExportChunks<'_> {
+    type Item = ContextChunk;
+
+    fn next(&mut self) -> Option<ContextChunk> {
+        let total = self.total();
+        if self.next >= total {
+            return None;
+        }
+
+        let start = (self.next as usize * self.chunk_size).min(self.keys.len());
+        let end = (start + self.chunk_size).min(self.keys.len());
+        let checkpoint = Checkpoint {
+            dimensions: self.system.dimensions(),
+            param_count: self.system.param_count(),
+            contexts: self.keys[start..end]
+                .iter()
+                .filter_map(|key| self.system.context_state(key))
+                .collect(),
+            // Stable slots ride along with the last chunk
+            stable: if self.next + 1 == total {
+                self.system.stable_states()
+            } else {
+                Vec::new()
+            },
+        };
+
+        let payload = checkpoint.to_binary();
+        let chunk = ContextChunk {
+            export_id: self.export_id,
+            sequence: self.next,
+            total,
+            checksum: checksum(&payload),
+            payload,
+        };
+        self.next += 1;
+        Some(chunk)
+    }
+}
+
+/// Receiving side of a chunked transfer
+#[derive(Debug, Default)]
+pub struct ChunkImporter {
+    export_id: Option<u64>,
+    total: Option<u64>,
+    next: u64,
+    dimensions: Vec<(String, Vec<String>)>,
+    param_count: usize,
+    contexts: Vec<ContextState>,
+    stable: Vec<ContextState>,
+}
+
+impl ChunkImporter {
+    /// Start an empty import
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sequence number of the next chunk needed (ask the sender to resume here)
+    pub fn next_sequence(&self) -> u64 {
+        self.next
+    }
+
+    /// Export being received, once the first chunk has arrived
+    pub fn export_id(&self) -> Option<u64> {
+        self.export_id
+    }
+
+    /// Whether every chunk has been accepted
+    pub fn is_complete(&self) -> bool {
+        self.total == Some(self.next)
+    }
+
+    /// Verify and accept a chunk
+    ///
+    /// Returns `Ok(false)` for a chunk that was already accepted, so
+    /// retransmissions are harmless. Chunks from a different export, out of
+    /// order, or failing their checksum are rejected without changing the
+    /// import.
+    pub fn accept(&mut self, chunk: &ContextChunk) -> Result<bool, String> {
+        if let Some(export_id) = self.export_id {
+            if chunk.export_id != export_id {
+                return Err(format!(
+                    "Chunk belongs to export {:x}, importing {:x}",
+                    chunk.export_id, export_id
+                ));
+            }
+        }
+        if let Some(total) = self.total {
+            if chunk.total != total {
+                return Err(format!("Chunk claims {} chunks, expected {}", chunk.total, total));
+            }
+        }
+        if chunk.sequence < self.next {
+            return Ok(false);
+        }
+        if chunk.sequence > self.next {
+            return Err(format!("Expected chunk {}, got {}", self.next, chunk.sequence));
+        }
+        if !chunk.verify() {
+            return Err(format!("Chunk {} failed its checksum", chunk.sequence));
+        }
+
+        let checkpoint = Checkpoint::from_bytes(&chunk.payload)
+            .map_err(|e| format!("Chunk {}: {}", chunk.sequence, e))?;
+        if self.export_id.is_some()
+            && (checkpoint.dimensions != self.dimensions || checkpoint.param_count != self.param_count)
+        {
+            return Err(format!("Chunk {} has a different schema", chunk.sequence));
+        }
+
+        self.export_id = Some(chunk.export_id);
+        self.total = Some(chunk.total);
+        self.dimensions = checkpoint.dimensions;
+        self.param_count = checkpoint.param_count;
+        self.contexts.extend(checkpoint.contexts);
+        self.stable.extend(checkpoint.stable);
+        self.next += 1;
+        Ok(true)
+    }
+
+    /// Build the imported system once every chunk has been accepted
+    pub fn finish(self) -> Result<EvoCoreContextSystem, String> {
+        if !self.is_complete() {
+            return Err(format!(
+                "Import incomplete: {} of {} chunks received",
+                self.next,
+                self.total.map_or("?".to_string(), |t| t.to_string())
+            ));
+        }
+
+        Checkpoint {
+            dimensions: self.dimensions,
+            param_count: self.param_count,
+            contexts: self.contexts,
+            stable: self.stable,
+        }
+        .into_system()
+        .map_err(|e| e.to_string())
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Export the system as chunks of at most `chunk_size` contexts
+    ///
+    /// Contexts are exported in key order. An empty system still produces
+    /// one (empty) chunk carrying the schema.
+    pub fn export_chunks(&self, chunk_size: usize) -> ExportChunks<'_> {
+        let mut keys = self.context_keys();
+        keys.sort();
+        ExportChunks {
+            system: self,
+            export_id: rand::random(),
+            keys,
+            chunk_size: chunk_size.max(1),
+            next: 0,
+        }
+    }
+}
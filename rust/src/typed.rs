@@ -0,0 +1,72 @@
+//! Integer, boolean and categorical parameters
+//!
+//! The C library learns every parameter as an `f64`. Parameters that are
+//! really integers, flags or choices among a few options are registered
+//! with a [`ParamKind`]; [`sample_typed`](EvoCoreContextSystem::sample_typed)
+//! then decodes each sampled value, and
+//! [`learn_typed`](EvoCoreContextSystem::learn_typed) encodes the values
+//! actually used back into `f64`s:
+//!
+//! | kind                | encoded as          | decoded by                    |
+//! |---------------------|---------------------|-------------------------------|
+//! | `Float`             | itself              | itself                        |
+//! | `Int`               | the integer         | rounding to nearest           |
+//! | `Bool`              | `0.0` / `1.0`       | `>= 0.5`                      |
+//! | `Categorical(n)`    | the index           | rounding, clamped to `0..n`   |
+//!
+//! Kinds are configuration, not learned state, so they are not saved in
+//! checkpoints.
+
+use crate::{EvoCoreContextSystem, ParamKind, ParamValue};
+
+impl EvoCoreContextSystem {
+    /// Declare the kind of every parameter
+    pub fn with_param_kinds(mut self, kinds: &[ParamKind]) -> Result<Self, String> {
+        if kinds.len() != self.param_count {
+            return Err(format!(
+                "Parameter count mismatch: expected {}, got {}",
+                self.param_count,
+                kinds.len()
+            ));
+        }
+        if let Some(index) = kinds.iter().position(|k| *k == ParamKind::Categorical(0)) {
+            return Err(format!("Parameter {} is categorical with no options", index));
+        }
+        self.param_kinds = if kinds.iter().all(|k| *k == ParamKind::Float) {
+            Vec::new()
+        } else {
+            kinds.to_vec()
+        };
+        Ok(self)
+    }
+
+    /// Kind of the parameter at `index` (`Float` unless declared otherwise)
+    pub fn param_kind(&self, index: usize) -> ParamKind {
+        self.param_kinds.get(index).copied().unwrap_or_default()
+    }
+
+    /// Sample parameters for a context, decoded according to their kinds
+    pub fn sample_typed(&self, dimension_values: &[&str], exploration: f64) -> Result<Vec<ParamValue>, String> {
+        let params = self.sample(dimension_values, exploration)?;
+        Ok(params
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| self.param_kind(i).decode(v))
+            .collect())
+    }
+
+    /// Learn from experience with typed parameter values
+    ///
+    /// Each value must match its parameter's kind.
+    pub fn learn_typed(&mut self, dimension_values: &[&str], parameters: &[ParamValue], fitness: f64) -> Result<(), String> {
+        let mut encoded = Vec::with_capacity(parameters.len());
+        for (i, &value) in parameters.iter().enumerate() {
+            let kind = self.param_kind(i);
+            if !kind.accepts(value) {
+                return Err(format!("Parameter {}: {:?} is not a valid {:?} value", i, value, kind));
+            }
+            encoded.push(value.to_f64());
+        }
+        self.learn(dimension_values, &encoded, fitness)
+    }
+}
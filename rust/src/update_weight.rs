@@ -0,0 +1,163 @@
+//! Damped learning for noisy fitness
+//!
+//! The C library moves a context's means by `w / (W + w)` of the distance
+//! to each new observation, where `w` is its fitness weight and `W` the
+//! weight accumulated so far: a context's second experience already pulls
+//! its means half way. With a noisy fitness signal that is too jumpy.
+//!
+//! An update weight `u` in `(0, 1]` scales every update after a context's
+//! first: means and variances move `u` times as far as the C library
+//! would have moved them, while weights, counts and fitness tracking are
+//! unchanged. `u = 1` (the default) is the library's own behaviour. A
+//! system-wide weight can be overridden per context; overrides are
+//! Rust-side state and are not saved in checkpoints. For adapting *faster*
+//! to a changing environment, use [decay](crate::DecayConfig) instead.
+
+use crate::rename::move_entry;
+use crate::{evocore_context_get_stats_key, EvoCoreContextSystem};
+use std::collections::HashMap;
+use std::ffi::CStr;
+
+/// Smallest accepted update weight
+const MIN_UPDATE_WEIGHT: f64 = 1e-6;
+
+#[derive(Debug, Clone)]
+pub(crate) struct UpdateWeights {
+    default: f64,
+    contexts: HashMap<String, f64>,
+}
+
+impl UpdateWeights {
+    pub(crate) fn forget(&mut self, key: &str) {
+        self.contexts.remove(key);
+    }
+
+    pub(crate) fn rename_key(&mut self, from: &str, to: &str) {
+        move_entry(&mut self.contexts, from, to);
+    }
+}
+
+/// Per-parameter `(mean, m2)` before an update
+pub(crate) struct UpdateSnapshot {
+    weight: f64,
+    params: Vec<(f64, f64)>,
+}
+
+fn validate(weight: f64) -> Result<f64, String> {
+    if weight.is_finite() && weight > 0.0 && weight <= 1.0 {
+        Ok(weight.max(MIN_UPDATE_WEIGHT))
+    } else {
+        Err(format!("Update weight must be in (0, 1], got {}", weight))
+    }
+}
+
+impl EvoCoreContextSystem {
+    /// Damp every context's updates by `weight`, in `(0, 1]`
+    pub fn with_update_weight(mut self, weight: f64) -> Result<Self, String> {
+        self.set_update_weight(weight)?;
+        Ok(self)
+    }
+
+    /// Change the system-wide update weight (1.0 restores the C library's behaviour)
+    pub fn set_update_weight(&mut self, weight: f64) -> Result<(), String> {
+        let weight = validate(weight)?;
+        self.update_weights
+            .get_or_insert_with(|| UpdateWeights {
+                default: 1.0,
+                contexts: HashMap::new(),
+            })
+            .default = weight;
+        Ok(())
+    }
+
+    /// System-wide update weight
+    pub fn update_weight(&self) -> f64 {
+        self.update_weights.as_ref().map_or(1.0, |w| w.default)
+    }
+
+    /// Override (or, with `None`, stop overriding) the update weight of one context
+    pub fn set_context_update_weight(&mut self, dimension_values: &[&str], weight: Option<f64>) -> Result<(), String> {
+        let key = self.context_key(dimension_values)?;
+        match weight {
+            Some(weight) => {
+                let weight = validate(weight)?;
+                self.update_weights
+                    .get_or_insert_with(|| UpdateWeights {
+                        default: 1.0,
+                        contexts: HashMap::new(),
+                    })
+                    .contexts
+                    .insert(key, weight);
+            }
+            None => {
+                if let Some(weights) = &mut self.update_weights {
+                    weights.contexts.remove(&key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Update weight a context learns with: its override, else the system-wide one
+    pub fn context_update_weight(&self, dimension_values: &[&str]) -> Result<f64, String> {
+        let key = self.context_key(dimension_values)?;
+        Ok(self.key_update_weight(&key))
+    }
+
+    fn key_update_weight(&self, key: &str) -> f64 {
+        self.update_weights
+            .as_ref()
+            .map_or(1.0, |w| w.contexts.get(key).copied().unwrap_or(w.default))
+    }
+
+    /// Record a context's distributions before learning, if its updates are damped
+    pub(crate) fn update_snapshot(&self, key: &CStr) -> Option<UpdateSnapshot> {
+        let weight = self.key_update_weight(key.to_str().ok()?);
+        if weight >= 1.0 {
+            return None;
+        }
+        unsafe {
+            let mut stats = std::ptr::null_mut();
+            if !evocore_context_get_stats_key(self.inner.as_ptr(), key.as_ptr(), &mut stats)
+                || stats.is_null()
+                || (*stats).stats.is_null()
+            {
+                return None;
+            }
+            let array = &*(*stats).stats;
+            let params = std::slice::from_raw_parts(array.stats, array.count);
+            if params.iter().any(|p| p.count == 0) {
+                return None;
+            }
+            Some(UpdateSnapshot {
+                weight,
+                params: params.iter().map(|p| (p.mean, p.m2)).collect(),
+            })
+        }
+    }
+
+    /// Pull a context's distributions back towards `snapshot` after learning
+    pub(crate) fn damp_update(&mut self, key: &CStr, snapshot: UpdateSnapshot) {
+        unsafe {
+            let mut stats = std::ptr::null_mut();
+            if !evocore_context_get_stats_key(self.inner.as_ptr(), key.as_ptr(), &mut stats)
+                || stats.is_null()
+                || (*stats).stats.is_null()
+            {
+                return;
+            }
+            let array = &*(*stats).stats;
+            let u = snapshot.weight;
+            for (p, (mean, m2)) in std::slice::from_raw_parts_mut(array.stats, array.count)
+                .iter_mut()
+                .zip(snapshot.params)
+            {
+                p.mean = mean + u * (p.mean - mean);
+                p.m2 = m2 + u * (p.m2 - m2);
+                if p.sum_weights > 0.0 {
+                    p.variance = p.m2 / p.sum_weights;
+                }
+            }
+        }
+    }
+}
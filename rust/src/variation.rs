@@ -0,0 +1,166 @@
+//! Configurable genome variation
+//!
+//! The C library breeds with uniform crossover and replaces mutated bytes
+//! with random ones. [`VariationOperators`] offers alternatives without
+//! dropping to the C API, treating each genome byte as a gene in
+//! `0..=255`:
+//!
+//! - [`Mutation`]: each gene mutates with probability `mutation_rate`,
+//!   either replaced at random ([`Mutation::Uniform`], the C behaviour) or
+//!   shifted by Gaussian or heavy-tailed Cauchy noise and clamped.
+//! - [`Crossover`]: one-point, uniform (the C behaviour) or blend (BLX-α),
+//!   which draws each child gene between the parents' values, widened by
+//!   `alpha` times their distance.
+//!
+//! Use them through [`Population::reproduce_with`](crate::Population::reproduce_with),
+//! or call [`crossover`](VariationOperators::crossover) and
+//! [`mutate`](VariationOperators::mutate) directly.
+
+use rand::Rng;
+
+/// How a mutated gene changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mutation {
+    /// Add Gaussian noise with standard deviation `sigma` (in byte units)
+    Gaussian { sigma: f64 },
+    /// Add Cauchy noise with scale `scale` (in byte units); occasional large jumps
+    Cauchy { scale: f64 },
+    /// Replace with a random byte
+    Uniform,
+}
+
+/// How two parents combine into two children
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Crossover {
+    /// Swap tails after a random cut point
+    OnePoint,
+    /// Take each gene from either parent with equal probability
+    Uniform,
+    /// Draw each gene uniformly from the parents' range widened by `alpha`
+    Blend { alpha: f64 },
+}
+
+/// Mutation and crossover settings for breeding genomes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VariationOperators {
+    mutation_rate: f64,
+    mutation: Mutation,
+    crossover: Crossover,
+}
+
+impl Default for VariationOperators {
+    fn default() -> Self {
+        Self {
+            mutation_rate: 0.01,
+            mutation: Mutation::Uniform,
+            crossover: Crossover::Uniform,
+        }
+    }
+}
+
+impl VariationOperators {
+    /// The C library's operators: uniform crossover, uniform mutation at rate 0.01
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Probability that each gene mutates (default 0.01)
+    pub fn with_mutation_rate(mut self, rate: f64) -> Self {
+        self.mutation_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_mutation(mut self, mutation: Mutation) -> Self {
+        self.mutation = mutation;
+        self
+    }
+
+    pub fn with_crossover(mut self, crossover: Crossover) -> Self {
+        self.crossover = crossover;
+        self
+    }
+
+    pub fn mutation_rate(&self) -> f64 {
+        self.mutation_rate
+    }
+
+    pub fn mutation(&self) -> Mutation {
+        self.mutation
+    }
+
+    pub fn crossover_kind(&self) -> Crossover {
+        self.crossover
+    }
+
+    /// Combine two equally long parents into two children
+    pub fn crossover<R: Rng + ?Sized>(&self, a: &[u8], b: &[u8], rng: &mut R) -> Result<(Vec<u8>, Vec<u8>), String> {
+        if a.len() != b.len() {
+            return Err(format!("Parent size mismatch: {} and {}", a.len(), b.len()));
+        }
+
+        let mut c1 = a.to_vec();
+        let mut c2 = b.to_vec();
+        match self.crossover {
+            Crossover::OnePoint => {
+                if a.len() > 1 {
+                    let cut = rng.gen_range(1..a.len());
+                    c1[cut..].copy_from_slice(&b[cut..]);
+                    c2[cut..].copy_from_slice(&a[cut..]);
+                }
+            }
+            Crossover::Uniform => {
+                for i in 0..a.len() {
+                    if rng.gen::<bool>() {
+                        c1[i] = b[i];
+                        c2[i] = a[i];
+                    }
+                }
+            }
+            Crossover::Blend { alpha } => {
+                let alpha = alpha.max(0.0);
+                for (i, (&x, &y)) in a.iter().zip(b).enumerate() {
+                    let (lo, hi) = (x.min(y) as f64, x.max(y) as f64);
+                    let spread = alpha * (hi - lo);
+                    let (lo, hi) = (lo - spread, hi + spread);
+                    c1[i] = to_gene(lo + rng.gen::<f64>() * (hi - lo));
+                    c2[i] = to_gene(lo + rng.gen::<f64>() * (hi - lo));
+                }
+            }
+        }
+        Ok((c1, c2))
+    }
+
+    /// Mutate each gene with probability `mutation_rate`
+    ///
+    /// Returns the number of genes mutated.
+    pub fn mutate<R: Rng + ?Sized>(&self, genome: &mut [u8], rng: &mut R) -> usize {
+        let mut mutated = 0;
+        for gene in genome.iter_mut() {
+            if rng.gen::<f64>() >= self.mutation_rate {
+                continue;
+            }
+            *gene = match self.mutation {
+                Mutation::Uniform => rng.gen(),
+                Mutation::Gaussian { sigma } => {
+                    let u1: f64 = rng.gen::<f64>().max(1e-12);
+                    let u2: f64 = rng.gen();
+                    let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                    to_gene(*gene as f64 + sigma * z)
+                }
+                Mutation::Cauchy { scale } => {
+                    let u: f64 = rng.gen();
+                    to_gene(*gene as f64 + scale * (std::f64::consts::PI * (u - 0.5)).tan())
+                }
+            };
+            mutated += 1;
+        }
+        mutated
+    }
+}
+
+fn to_gene(value: f64) -> u8 {
+    if value.is_nan() {
+        return 0;
+    }
+    value.round().clamp(0.0, 255.0) as u8
+}
@@ -0,0 +1,95 @@
+//! Per-context version counters for optimistic concurrency
+//!
+//! Every change to a context's learned state (a learn, a restore, a
+//! rollback) gives it a new version, drawn from one system-wide counter so
+//! versions only ever increase, even if a context is removed and later
+//! recreated. Controllers read a version, decide, and pass it back to a
+//! `*_if_version` call, which refuses to apply the change if someone else
+//! modified the context in between.
+
+use crate::{ContextState, EvoCoreContextSystem};
+
+impl EvoCoreContextSystem {
+    /// Current version of this context (0 if it has never been modified)
+    pub fn context_version(&self, dimension_values: &[&str]) -> Result<u64, String> {
+        let key = self.context_key(dimension_values)?;
+        Ok(self.versions.get(&key).copied().unwrap_or(0))
+    }
+
+    /// Learned state of this context together with its version
+    ///
+    /// Returns `Ok(None)` if the context has no learned state.
+    pub fn context_state_versioned(
+        &self,
+        dimension_values: &[&str],
+    ) -> Result<Option<(ContextState, u64)>, String> {
+        let key = self.context_key(dimension_values)?;
+        let version = self.versions.get(&key).copied().unwrap_or(0);
+        Ok(self.context_state(&key).map(|state| (state, version)))
+    }
+
+    /// Learn only if the context is still at `expected_version`
+    ///
+    /// `None` skips the check. Returns the context's new version.
+    pub fn learn_if_version(
+        &mut self,
+        dimension_values: &[&str],
+        parameters: &[f64],
+        fitness: f64,
+        expected_version: Option<u64>,
+    ) -> Result<u64, String> {
+        let key = self.context_key(dimension_values)?;
+        self.check_version(&key, expected_version)?;
+        self.learn(dimension_values, parameters, fitness)?;
+        Ok(self.versions.get(&key).copied().unwrap_or(0))
+    }
+
+    /// Restore a context only if it is still at `expected_version`
+    ///
+    /// `None` skips the check. Returns the context's new version.
+    pub fn restore_if_version(
+        &mut self,
+        state: &ContextState,
+        expected_version: Option<u64>,
+    ) -> Result<u64, String> {
+        self.check_version(&state.key, expected_version)?;
+        self.restore_context_state(state)?;
+        Ok(self.versions.get(&state.key).copied().unwrap_or(0))
+    }
+
+    /// Roll back to the stable slot only if the context is still at `expected_version`
+    ///
+    /// `None` skips the check. Returns the context's new version.
+    pub fn rollback_if_version(
+        &mut self,
+        dimension_values: &[&str],
+        expected_version: Option<u64>,
+    ) -> Result<u64, String> {
+        let key = self.context_key(dimension_values)?;
+        self.check_version(&key, expected_version)?;
+        self.rollback(dimension_values)?;
+        Ok(self.versions.get(&key).copied().unwrap_or(0))
+    }
+
+    fn check_version(&self, key: &str, expected_version: Option<u64>) -> Result<(), String> {
+        let current = self.versions.get(key).copied().unwrap_or(0);
+        match expected_version {
+            Some(expected) if expected != current => Err(format!(
+                "Version conflict on context {}: expected {}, found {}",
+                key, expected, current
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Give a context a new version after its state changed
+    pub(crate) fn bump_version(&mut self, key: &str) {
+        self.version_clock += 1;
+        match self.versions.get_mut(key) {
+            Some(version) => *version = self.version_clock,
+            None => {
+                self.versions.insert(key.to_string(), self.version_clock);
+            }
+        }
+    }
+}
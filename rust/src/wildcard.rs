@@ -0,0 +1,125 @@
+//! Wildcard (default) contexts
+//!
+//! The value [`WILDCARD`] (`"*"`) is reserved in every dimension and names
+//! a default policy: `*:*:*` is the global default, `code:*:*` the default
+//! for every context with `type == code`. Wildcard contexts are ordinary
+//! contexts, learned and sampled like any other, and can be seeded up front
+//! with [`seed_wildcard`](EvoCoreContextSystem::seed_wildcard).
+//!
+//! With [`with_wildcard_fallback`](EvoCoreContextSystem::with_wildcard_fallback),
+//! sampling a cold context (fewer than `min_samples` experiences) uses the
+//! most specific wildcard context that is warm instead. Candidates replace
+//! more dimensions with `"*"` the further they go, dropping later
+//! dimensions before earlier ones: `a:b:*`, `a:*:c`, `*:b:c`, `a:*:*`, and
+//! so on down to `*:*:*`. Systems with more than 16 dimensions never fall
+//! back.
+
+use crate::{evocore_context_get_stats_key, key_into, EvoCoreContextSystem, MAX_KEY_LENGTH};
+use std::ffi::CStr;
+
+/// Dimension value matching every value of that dimension
+pub const WILDCARD: &str = "*";
+
+/// Dimensions beyond which only the global default is tried
+const MAX_FALLBACK_DIMENSIONS: usize = 16;
+
+impl EvoCoreContextSystem {
+    /// Sample cold contexts from their wildcard defaults
+    ///
+    /// A context with fewer than `min_samples` experiences is sampled from
+    /// the most specific wildcard context with at least that many. If none
+    /// qualifies and the context has no data at all, the most specific
+    /// wildcard context with any data is used.
+    pub fn with_wildcard_fallback(mut self, min_samples: usize) -> Self {
+        self.wildcard_fallback = Some(min_samples.max(1));
+        self
+    }
+
+    /// Change or disable (`None`) the wildcard fallback
+    pub fn set_wildcard_fallback(&mut self, min_samples: Option<usize>) {
+        self.wildcard_fallback = min_samples.map(|m| m.max(1));
+    }
+
+    /// Experiences below which a context falls back to its wildcard defaults
+    pub fn wildcard_fallback(&self) -> Option<usize> {
+        self.wildcard_fallback
+    }
+
+    /// Record an experience directly in a wildcard context
+    ///
+    /// At least one of `dimension_values` must be [`WILDCARD`]. Use this to
+    /// give cold contexts a sensible starting policy before any real
+    /// traffic arrives; call it repeatedly to make the default more
+    /// confident.
+    pub fn seed_wildcard(&mut self, dimension_values: &[&str], parameters: &[f64], fitness: f64) -> Result<(), String> {
+        self.check_dimension_count(dimension_values)?;
+        if !dimension_values.contains(&WILDCARD) {
+            return Err(format!("Not a wildcard context: {}", dimension_values.join(":")));
+        }
+        self.learn(dimension_values, parameters, fitness)
+    }
+
+    /// Experiences recorded for a context key (0 if it has no data)
+    pub(crate) fn key_experiences(&self, key: &CStr) -> usize {
+        unsafe {
+            let mut stats = std::ptr::null_mut();
+            if !evocore_context_get_stats_key(self.inner.as_ptr(), key.as_ptr(), &mut stats) || stats.is_null() {
+                return 0;
+            }
+            (*stats).total_experiences
+        }
+    }
+
+    /// Key to sample instead of `dimension_values`, if the fallback applies
+    ///
+    /// Builds the key in `buf` without allocating. `None` means sample the
+    /// context itself.
+    pub(crate) fn wildcard_fallback_key<'a>(
+        &self,
+        dimension_values: &[&str],
+        buf: &'a mut [u8; MAX_KEY_LENGTH],
+    ) -> Option<&'a CStr> {
+        let min_samples = self.wildcard_fallback?;
+        let count = dimension_values.len();
+        if count == 0 || count > MAX_FALLBACK_DIMENSIONS {
+            return None;
+        }
+        let exact = key_into(dimension_values, buf).map_or(0, |key| self.key_experiences(key));
+        if exact >= min_samples {
+            return None;
+        }
+
+        let mut values = [WILDCARD; MAX_FALLBACK_DIMENSIONS];
+        let mut warm = None;
+        let mut any = None;
+        'search: for wildcards in 1..=count as u32 {
+            for mask in (1u32..1 << count).filter(|m| m.count_ones() == wildcards) {
+                mask_values(dimension_values, mask, &mut values);
+                let mut candidate = [0u8; MAX_KEY_LENGTH];
+                let experiences = key_into(&values[..count], &mut candidate).map_or(0, |key| self.key_experiences(key));
+                if experiences >= min_samples {
+                    warm = Some(mask);
+                    break 'search;
+                }
+                if experiences > 0 && any.is_none() {
+                    any = Some(mask);
+                }
+            }
+        }
+
+        let mask = warm.or(any.filter(|_| exact == 0))?;
+        mask_values(dimension_values, mask, &mut values);
+        key_into(&values[..count], buf)
+    }
+}
+
+/// Copy `dimension_values` into `out`, replacing those selected by `mask`
+/// with [`WILDCARD`]
+///
+/// Bit 0 is the last dimension, so lower masks keep earlier dimensions.
+fn mask_values<'v>(dimension_values: &[&'v str], mask: u32, out: &mut [&'v str]) {
+    let count = dimension_values.len();
+    for (i, value) in dimension_values.iter().enumerate() {
+        out[i] = if mask & (1 << (count - 1 - i)) != 0 { WILDCARD } else { value };
+    }
+}
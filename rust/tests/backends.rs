@@ -0,0 +1,98 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::test_util::{assert_backends_agree, assert_statistically_equivalent};
+use evocore_sys::{ContextLearner, EvoCoreContextSystem, RustContextSystem};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const SEED: u64 = 533;
+const DRAWS: usize = 4_000;
+const CONTEXTS: [&[&str]; 3] = [&["code", "vim"], &["code", "emacs"], &["prose", "vim"]];
+
+fn dimensions() -> (Vec<&'static str>, Vec<Vec<&'static str>>) {
+    (vec!["task", "editor"], vec![vec!["code", "prose"], vec!["vim", "emacs"]])
+}
+
+/// Learn the same fixed-seed history, centred differently per context
+fn train<L: ContextLearner>(learner: &mut L) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for (i, context) in CONTEXTS.iter().enumerate() {
+        let centre = 0.2 + 0.3 * i as f64;
+        for _ in 0..200 {
+            let params = [centre + rng.gen_range(-0.1..0.1), 0.5 + rng.gen_range(-0.3..0.3)];
+            let fitness = rng.gen_range(0.5..1.5);
+            learner.learn(context, &params, fitness).unwrap();
+        }
+    }
+}
+
+/// Mean and standard deviation of each parameter over `DRAWS` samples
+fn sample_moments<L: ContextLearner>(learner: &L, context: &[&str], exploration: f64) -> Vec<(f64, f64)> {
+    let draws: Vec<Vec<f64>> = (0..DRAWS).map(|_| learner.sample(context, exploration).unwrap()).collect();
+    (0..learner.param_count())
+        .map(|k| {
+            let mean = draws.iter().map(|d| d[k]).sum::<f64>() / DRAWS as f64;
+            let variance = draws.iter().map(|d| (d[k] - mean).powi(2)).sum::<f64>() / (DRAWS - 1) as f64;
+            (mean, variance.sqrt())
+        })
+        .collect()
+}
+
+fn trained_pair() -> (EvoCoreContextSystem, RustContextSystem) {
+    let (names, values) = dimensions();
+    let mut c = EvoCoreContextSystem::deterministic(&names, &values, 2, SEED).unwrap();
+    let mut rust = RustContextSystem::deterministic(&names, &values, 2, SEED).unwrap();
+    train(&mut c);
+    train(&mut rust);
+    (c, rust)
+}
+
+#[test]
+fn backends_learn_the_same_distributions() {
+    let (c, rust) = trained_pair();
+    assert_statistically_equivalent(&c, &rust, 1e-9);
+}
+
+#[test]
+fn backends_sample_the_same_distributions() {
+    let (c, rust) = trained_pair();
+    for exploration in [0.0, 0.3] {
+        for context in CONTEXTS {
+            let from_c = sample_moments(&c, context, exploration);
+            let from_rust = sample_moments(&rust, context, exploration);
+            for (k, ((c_mean, c_std), (rust_mean, rust_std))) in from_c.iter().zip(&from_rust).enumerate() {
+                // Five standard errors of the difference between two sample means
+                let mean_tolerance = 5.0 * ((c_std.powi(2) + rust_std.powi(2)) / DRAWS as f64).sqrt();
+                assert!(
+                    (c_mean - rust_mean).abs() <= mean_tolerance,
+                    "{:?} param {} at exploration {}: mean {} (C) vs {} (Rust)",
+                    context,
+                    k,
+                    exploration,
+                    c_mean,
+                    rust_mean
+                );
+                assert!(
+                    (c_std - rust_std).abs() <= 0.1 * c_std.max(*rust_std),
+                    "{:?} param {} at exploration {}: std {} (C) vs {} (Rust)",
+                    context,
+                    k,
+                    exploration,
+                    c_std,
+                    rust_std
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn backends_agree_on_random_histories() {
+    assert_backends_agree(
+        |names, values, params| EvoCoreContextSystem::deterministic(names, values, params, SEED),
+        |names, values, params| RustContextSystem::deterministic(names, values, params, SEED),
+        50,
+        SEED,
+    );
+}
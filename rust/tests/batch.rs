@@ -0,0 +1,67 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::{
+    CanaryConfig, CoarseningPolicy, DecayConfig, EvoCoreContextSystem, HoldoutConfig, ParamBounds, WILDCARD,
+};
+use std::time::Duration;
+
+const CONTEXTS: [&[&str]; 5] = [&["code", "vim"], &["code", "emacs"], &["prose", "vim"], &["prose", "emacs"], &["new", "vim"]];
+
+fn configured(configure: impl Fn(&mut EvoCoreContextSystem)) -> EvoCoreContextSystem {
+    let mut system =
+        EvoCoreContextSystem::deterministic(&["task", "editor"], &[vec!["code", "prose"], vec!["vim", "emacs"]], 2, 42)
+            .unwrap()
+            .with_bounds(0, ParamBounds::new(0.2, 0.8))
+            .unwrap();
+    for i in 0..20 {
+        let x = i as f64 / 20.0;
+        system.learn(&["code", "vim"], &[0.2 + 0.6 * x, 1.0 - x], 0.5 + x / 2.0).unwrap();
+        system.learn(&["prose", "vim"], &[0.3, x], 0.7).unwrap();
+    }
+    system.seed_wildcard(&["code", WILDCARD], &[0.4, 0.4], 0.9).unwrap();
+    system
+        .override_params(&["prose", "emacs"], &[0.5, 0.5], Duration::from_secs(60))
+        .unwrap();
+    configure(&mut system);
+    system
+}
+
+/// Batched sampling matches one `sample()` call per context
+fn assert_batch_matches_loop(configure: impl Fn(&mut EvoCoreContextSystem)) {
+    let batched = configured(&configure);
+    let looped = configured(&configure);
+    for exploration in [0.0, 0.3, 1.0] {
+        let expected: Vec<Vec<f64>> = CONTEXTS.iter().map(|dims| looped.sample(dims, exploration).unwrap()).collect();
+        assert_eq!(batched.sample_batch(&CONTEXTS, exploration).unwrap(), expected);
+    }
+}
+
+#[test]
+fn matches_sample_by_default() {
+    assert_batch_matches_loop(|_| {});
+}
+
+#[test]
+fn matches_sample_with_canary_holdout_and_coarsening() {
+    assert_batch_matches_loop(|system| {
+        system.enable_canary(CanaryConfig::new(0.5));
+        system.enable_holdout(HoldoutConfig::new(vec![0.5, 0.5], 0.5)).unwrap();
+        system.set_coarsening(Some(CoarseningPolicy::new()));
+    });
+}
+
+#[test]
+fn matches_sample_with_decay_and_fallbacks() {
+    assert_batch_matches_loop(|system| {
+        system.set_wildcard_fallback(Some(5));
+        system.set_decay(Some(DecayConfig::new(Duration::from_secs(3600))));
+        system.set_marginal_fallback(Some(3));
+    });
+}
+
+#[test]
+fn rejects_wrong_dimension_count() {
+    let system = configured(|_| {});
+    assert!(system.sample_batch(&[&["code"]], 0.1).is_err());
+}
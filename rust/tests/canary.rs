@@ -0,0 +1,76 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::{CanaryArm, CanaryConfig, EvoCoreContextSystem, Format, SaveOptions};
+
+fn system() -> EvoCoreContextSystem {
+    let mut system = EvoCoreContextSystem::deterministic(&["task"], &[vec!["code"]], 1, 11).unwrap();
+    for _ in 0..10 {
+        system.learn(&["code"], &[0.2], 1.0).unwrap();
+    }
+    system
+}
+
+#[test]
+fn stable_arm_is_the_stable_slot() {
+    let mut system = system();
+    system.enable_canary(CanaryConfig::new(0.0));
+    assert_eq!(system.stable_state(&["code"]).unwrap().total_experiences, 10);
+
+    for _ in 0..10 {
+        system.learn(&["code"], &[0.8], 1.0).unwrap();
+    }
+    let (params, arm) = system.sample_canary(&["code"], 0.0).unwrap();
+    assert_eq!(arm, CanaryArm::Stable);
+    assert_eq!(params, system.sample_stable(&["code"], 0.0).unwrap());
+    assert!((params[0] - 0.2).abs() < 1e-9);
+}
+
+#[test]
+fn promote_and_promote_canary_write_the_same_slot() {
+    let mut system = system();
+    system.enable_canary(CanaryConfig::new(0.0).with_min_samples(1));
+    for _ in 0..10 {
+        system.learn(&["code"], &[0.8], 1.0).unwrap();
+    }
+
+    system.promote(&["code"]).unwrap();
+    assert_eq!(system.stable_state(&["code"]).unwrap().total_experiences, 20);
+    assert_eq!(system.sample_canary(&["code"], 0.0).unwrap().1, CanaryArm::Stable);
+
+    system.learn_canary(&["code"], &[0.8], 1.0, CanaryArm::Stable).unwrap();
+    system.promote_canary(&["code"]).unwrap();
+    assert_eq!(system.stable_state(&["code"]).unwrap().total_experiences, 21);
+    let status = system.canary_status(&["code"]).unwrap();
+    assert!(status.has_stable);
+    assert_eq!((status.stable_samples, status.promotions), (0, 1));
+
+    system.learn(&["code"], &[0.0], 1.0).unwrap();
+    system.rollback(&["code"]).unwrap();
+    assert_eq!(system.context_state("code").unwrap().total_experiences, 21);
+}
+
+#[test]
+fn enabling_keeps_existing_stable_slots() {
+    let mut system = system();
+    system.promote(&["code"]).unwrap();
+    system.learn(&["code"], &[0.8], 1.0).unwrap();
+    system.enable_canary(CanaryConfig::new(0.5));
+    assert_eq!(system.stable_state(&["code"]).unwrap().total_experiences, 10);
+}
+
+#[test]
+fn canary_stable_state_survives_a_reload() {
+    let mut system = system();
+    system.enable_canary(CanaryConfig::new(0.0));
+    system.learn(&["code"], &[0.8], 1.0).unwrap();
+
+    let path = std::env::temp_dir().join(format!("evocore-canary-{}.bin", std::process::id()));
+    system.save_as(&path, &SaveOptions::format(Format::Binary)).unwrap();
+    let mut loaded = EvoCoreContextSystem::load_as(&path, Format::Binary).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    loaded.enable_canary(CanaryConfig::new(0.0));
+    assert_eq!(loaded.stable_state(&["code"]).unwrap().total_experiences, 10);
+    assert_eq!(loaded.sample_canary(&["code"], 0.0).unwrap().1, CanaryArm::Stable);
+}
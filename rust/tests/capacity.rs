@@ -0,0 +1,63 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::EvoCoreContextSystem;
+
+fn system(max_contexts: usize) -> EvoCoreContextSystem {
+    EvoCoreContextSystem::deterministic(&["user"], &[vec!["u0"]], 2, 7)
+        .unwrap()
+        .with_max_contexts(max_contexts)
+}
+
+#[test]
+fn learning_never_exceeds_the_cap() {
+    let mut system = system(3);
+    for i in 0..20 {
+        system.learn(&[&format!("u{}", i)], &[0.5, 0.5], 1.0).unwrap();
+        assert!(system.context_count() <= 3, "{} contexts after learn {}", system.context_count(), i);
+    }
+
+    let stats = system.capacity_stats().unwrap();
+    assert_eq!(stats.contexts, 3);
+    assert_eq!(stats.evictions, 17);
+}
+
+#[test]
+fn sampling_unlearned_contexts_does_not_evict() {
+    let mut system = system(2);
+    system.learn(&["a"], &[0.5, 0.5], 1.0).unwrap();
+    system.learn(&["b"], &[0.5, 0.5], 1.0).unwrap();
+    for i in 0..100 {
+        system.sample(&[&format!("unknown{}", i)], 0.1).unwrap();
+    }
+    system.learn(&["c"], &[0.5, 0.5], 1.0).unwrap();
+
+    assert_eq!(system.context_count(), 2);
+    assert_eq!(system.capacity_stats().unwrap().evictions, 1);
+}
+
+#[test]
+fn evicts_least_recently_used() {
+    let mut system = system(2);
+    system.learn(&["a"], &[0.5, 0.5], 1.0).unwrap();
+    system.learn(&["b"], &[0.5, 0.5], 1.0).unwrap();
+    system.sample(&["a"], 0.1).unwrap();
+    system.learn(&["c"], &[0.5, 0.5], 1.0).unwrap();
+
+    let mut keys = system.context_keys();
+    keys.sort();
+    assert_eq!(keys, ["a", "c"]);
+}
+
+#[test]
+fn pinned_contexts_survive_eviction() {
+    let mut system = system(2);
+    system.learn(&["a"], &[0.5, 0.5], 1.0).unwrap();
+    system.pin_context(&["a"]).unwrap();
+    for i in 0..10 {
+        system.learn(&[&format!("u{}", i)], &[0.5, 0.5], 1.0).unwrap();
+    }
+
+    assert!(system.context_state("a").is_some());
+    assert_eq!(system.context_count(), 2);
+}
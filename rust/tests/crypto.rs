@@ -0,0 +1,23 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(all(feature = "crypto", not(feature = "dlopen")))]
+
+use evocore_sys::EvoCoreContextSystem;
+
+#[test]
+fn encrypted_save_replaces_the_file_atomically() {
+    let path = std::env::temp_dir().join(format!("evocore-crypto-{}.enc", std::process::id()));
+    let path = path.to_str().unwrap();
+    let key = [7u8; 32];
+
+    let mut system = EvoCoreContextSystem::new(&["task"], &[vec!["code"]], 2).unwrap();
+    system.learn(&["code"], &[0.25, 0.75], 1.0).unwrap();
+    system.save_encrypted(path, &key).unwrap();
+    system.learn(&["code"], &[0.25, 0.75], 1.0).unwrap();
+    system.save_encrypted(path, &key).unwrap();
+
+    assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+    let loaded = EvoCoreContextSystem::load_encrypted(path, &key).unwrap();
+    assert_eq!(loaded.context_state("code").unwrap().total_experiences, 2);
+    assert!(EvoCoreContextSystem::load_encrypted(path, &[8u8; 32]).is_err());
+    let _ = std::fs::remove_file(path);
+}
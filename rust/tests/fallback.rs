@@ -0,0 +1,21 @@
+use evocore_sys::{native_available, new_learner};
+
+#[test]
+fn new_learner_works_with_or_without_the_c_library() {
+    let mut learner = new_learner(&["task"], &[vec!["code", "prose"]], 2).unwrap();
+    for _ in 0..5 {
+        learner.learn(&["code"], &[0.25, 0.75], 1.0).unwrap();
+    }
+    assert_eq!(learner.context_count(), 1);
+    assert_eq!(learner.context_state("code").unwrap().total_experiences, 5);
+    assert_eq!(learner.sample(&["code"], 0.0).unwrap().len(), 2);
+    // Without the library, the C-backed constructor reports why
+    if !native_available() {
+        assert!(evocore_sys::EvoCoreContextSystem::new(&["task"], &[vec!["code"]], 2).is_err());
+    }
+}
+
+#[test]
+fn new_learner_rejects_mismatched_dimensions() {
+    assert!(new_learner(&["task", "editor"], &[vec!["code"]], 2).is_err());
+}
@@ -0,0 +1,73 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::{EvoCoreContextSystem, PrunePolicy};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("evocore-json-{}-{}", std::process::id(), name))
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
+}
+
+fn trained() -> EvoCoreContextSystem {
+    let mut system = EvoCoreContextSystem::new(&["task"], &[vec!["code", "prose"]], 2).unwrap();
+    for i in 0..10 {
+        system.learn(&["code"], &[0.1 * i as f64, 0.5], 0.2 + 0.05 * i as f64).unwrap();
+    }
+    system.learn(&["prose"], &[0.5, 0.5], 2.0).unwrap();
+    system
+}
+
+#[test]
+fn timestamps_and_weights_survive_a_round_trip() {
+    let system = trained();
+    let path = temp_path("round-trip.json");
+    system.save(path.to_str().unwrap()).unwrap();
+    let loaded = EvoCoreContextSystem::load(path.to_str().unwrap()).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    for key in ["code", "prose"] {
+        let (before, after) = (system.context_state(key).unwrap(), loaded.context_state(key).unwrap());
+        assert_eq!(after.first_update, before.first_update);
+        assert_eq!(after.last_update, before.last_update);
+        for (b, a) in before.params.iter().zip(&after.params) {
+            assert!((a.sum_weights - b.sum_weights).abs() < 1e-9, "{}: {} vs {}", key, a.sum_weights, b.sum_weights);
+        }
+    }
+}
+
+#[test]
+fn loaded_contexts_are_not_pruned_as_unseen() {
+    let path = temp_path("prune.json");
+    trained().save(path.to_str().unwrap()).unwrap();
+    let mut loaded = EvoCoreContextSystem::load(path.to_str().unwrap()).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    assert_eq!(loaded.prune(&PrunePolicy::UnseenFor(Duration::from_secs(3600))), 0);
+    assert_eq!(loaded.context_count(), 2);
+}
+
+#[test]
+fn files_without_timestamps_count_as_seen_at_load() {
+    let path = temp_path("legacy.json");
+    trained().save(path.to_str().unwrap()).unwrap();
+    let mut document: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    for context in document["contexts"].as_object_mut().unwrap().values_mut() {
+        let context = context.as_object_mut().unwrap();
+        context.remove("first_update");
+        context.remove("last_update");
+        context.remove("sum_weights");
+    }
+    std::fs::write(&path, serde_json::to_vec(&document).unwrap()).unwrap();
+
+    let before = now();
+    let loaded = EvoCoreContextSystem::load(path.to_str().unwrap()).unwrap();
+    let _ = std::fs::remove_file(path);
+    let code = loaded.context_state("code").unwrap();
+    assert!(code.first_update >= before && code.last_update >= before);
+    assert!(code.params[0].sum_weights > 0.0);
+}
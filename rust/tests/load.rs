@@ -0,0 +1,68 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::{ContextLearner, EvoCoreContextSystem, LoadError, RustContextSystem};
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("evocore-load-{}-{}", std::process::id(), name))
+}
+
+fn with_undeclared_values() -> EvoCoreContextSystem {
+    let mut system = EvoCoreContextSystem::new(&["task", "user"], &[vec!["code"], vec!["u0"]], 2).unwrap();
+    for user in ["u0", "u1", "u2"] {
+        system.learn(&["code", user], &[0.5, 0.5], 1.0).unwrap();
+    }
+    system.learn(&["review", "u7"], &[0.5, 0.5], 1.0).unwrap();
+    system
+}
+
+#[test]
+fn undeclared_values_survive_a_round_trip() {
+    let system = with_undeclared_values();
+    let json = temp_path("undeclared.json");
+    let binary = temp_path("undeclared.bin");
+    system.save(json.to_str().unwrap()).unwrap();
+    system.save_binary(binary.to_str().unwrap()).unwrap();
+
+    for path in [&json, &binary] {
+        let loaded = EvoCoreContextSystem::load(path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded.context_count(), 4);
+        assert!(loaded.context_state("review:u7").is_some());
+        let validated = EvoCoreContextSystem::load_validated(path).unwrap();
+        assert_eq!(validated.context_count(), 4);
+        let (lenient, errors) = EvoCoreContextSystem::load_lenient(path).unwrap();
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(lenient.context_count(), 4);
+    }
+
+    let _ = std::fs::remove_file(json);
+    let _ = std::fs::remove_file(binary);
+}
+
+#[test]
+fn values_with_the_key_separator_cannot_be_learned() {
+    let mut system = with_undeclared_values();
+    assert!(system.learn(&["code", "a:b"], &[0.5, 0.5], 1.0).is_err());
+    let examples: [(&[&str], &[f64], f64); 1] = [(&["code", "a:b"], &[0.5, 0.5], 1.0)];
+    assert!(system.learn_batch(&examples).is_err());
+    assert_eq!(system.context_count(), 4);
+
+    let mut rust = RustContextSystem::new(&["task", "user"], &[vec!["code"], vec!["u0"]], 2).unwrap();
+    assert!(rust.learn(&["code", "a:b"], &[0.5, 0.5], 1.0).is_err());
+}
+
+#[test]
+fn keys_with_the_wrong_dimension_count_are_rejected() {
+    let path = temp_path("bad-count.json");
+    let system = with_undeclared_values();
+    system.save(path.to_str().unwrap()).unwrap();
+    let data = std::fs::read_to_string(&path).unwrap().replace("\"review:u7\"", "\"review:u7:extra\"");
+    std::fs::write(&path, data).unwrap();
+
+    assert!(matches!(
+        EvoCoreContextSystem::load_validated(&path),
+        Err(LoadError::DimensionCountMismatch { .. })
+    ));
+    let _ = std::fs::remove_file(path);
+}
@@ -0,0 +1,52 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(all(feature = "msgpack", not(feature = "dlopen")))]
+
+use evocore_sys::test_util::assert_statistically_equivalent;
+use evocore_sys::{EvoCoreContextSystem, Format, SaveOptions};
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("evocore-msgpack-{}-{}", std::process::id(), name))
+}
+
+fn trained() -> EvoCoreContextSystem {
+    let mut system =
+        EvoCoreContextSystem::deterministic(&["task", "editor"], &[vec!["code", "prose"], vec!["vim", "emacs"]], 2, 5)
+            .unwrap();
+    for i in 0..12 {
+        let x = i as f64 / 12.0;
+        system.learn(&["code", "vim"], &[x, 1.0 - x], 0.5 + x / 2.0).unwrap();
+        system.learn(&["prose", "emacs"], &[0.3, x], 0.7).unwrap();
+    }
+    system.promote(&["code", "vim"]).unwrap();
+    system
+}
+
+#[test]
+fn round_trip_is_lossless() {
+    let system = trained();
+    let path = temp_path("lossless.msgpack");
+    system.save_as(&path, &SaveOptions::format(Format::MessagePack)).unwrap();
+    let loaded = EvoCoreContextSystem::load_as(&path, Format::MessagePack).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    let mut keys = loaded.context_keys();
+    keys.sort();
+    assert_eq!(keys, ["code:vim", "prose:emacs"]);
+    for key in system.context_keys() {
+        assert_eq!(loaded.context_state(&key), system.context_state(&key), "{}", key);
+    }
+    assert_eq!(loaded.stable_state(&["code", "vim"]), system.stable_state(&["code", "vim"]));
+    assert_statistically_equivalent(&system, &loaded, 0.0);
+}
+
+#[test]
+fn rejects_truncated_snapshots() {
+    let path = temp_path("truncated.msgpack");
+    trained().save_as(&path, &SaveOptions::format(Format::MessagePack)).unwrap();
+    let data = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &data[..data.len() / 2]).unwrap();
+
+    assert!(EvoCoreContextSystem::load_as(&path, Format::MessagePack).is_err());
+    let _ = std::fs::remove_file(path);
+}
@@ -0,0 +1,76 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::{CensusConfig, EvoCoreContextSystem, PrivacyBudget};
+
+/// One well-observed context and one seen only twice
+fn system() -> EvoCoreContextSystem {
+    let mut system = EvoCoreContextSystem::new(&["user"], &[vec!["many", "few"]], 1).unwrap();
+    for i in 0..500 {
+        system.learn(&["many"], &[0.3 + 0.001 * (i % 10) as f64], 0.8).unwrap();
+    }
+    system.learn(&["few"], &[0.9], 0.2).unwrap();
+    system.learn(&["few"], &[0.95], 0.3).unwrap();
+    system
+}
+
+fn budget() -> PrivacyBudget {
+    PrivacyBudget::new(10.0).with_seed(530)
+}
+
+#[test]
+fn report_withholds_rare_contexts_and_spends_budget() {
+    let system = system();
+    let mut budget = budget();
+    let report = system.report_private(&mut budget, 2.0).unwrap();
+    assert_eq!(budget.spent(), 2.0);
+    assert_eq!(report.contexts, 1);
+    assert_eq!(report.best_contexts[0].key, "many");
+    assert!(report.experiences != 500 || report.mean_fitness != 0.8, "nothing was noised");
+    assert!((report.params[0].mean - 0.3).abs() < 0.05);
+    assert!(report.coverage[0].values.iter().all(|(value, contexts, _)| value != "few" || *contexts == 0));
+}
+
+#[test]
+fn snapshot_withholds_rare_contexts_and_extremes() {
+    let system = system();
+    let snapshot = system.export_snapshot_private(&mut budget(), 2.0).unwrap();
+    let contexts = snapshot["contexts"].as_array().unwrap();
+    assert_eq!(contexts.len(), 1);
+    assert_eq!(contexts[0]["key"], "many");
+    assert!(contexts[0]["parameters"][0]["min"].is_null());
+    assert!(contexts[0]["parameters"][0]["max"].is_null());
+    assert_eq!(contexts[0]["last_update"], 0);
+}
+
+#[test]
+fn census_counts_are_noised_and_consistent() {
+    let system = system();
+    let config = CensusConfig::new();
+    let mut budget = budget();
+    for _ in 0..20 {
+        let census = system.context_census_private(&config, &mut budget, 0.5).unwrap();
+        assert!(census.warm <= census.total && census.converged <= census.warm && census.stale <= census.total);
+    }
+    assert!(system.context_census_private(&config, &mut budget, 0.5).is_err(), "budget should be exhausted");
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn metrics_gauges_are_private() {
+    let system = system();
+    let mut budget = budget();
+    let metrics = system.render_metrics_private(5, &mut budget, 2.0).unwrap();
+    assert_eq!(budget.spent(), 2.0);
+    assert!(metrics.contains("evocore_context_avg_fitness{context=\"many\"}"));
+    assert!(!metrics.contains("context=\"few\""));
+}
+
+#[test]
+fn exhausted_budget_releases_nothing() {
+    let system = system();
+    let mut budget = PrivacyBudget::new(1.0);
+    assert!(system.report_private(&mut budget, 2.0).is_err());
+    assert!(system.export_snapshot_private(&mut budget, 2.0).is_err());
+    assert_eq!(budget.spent(), 0.0);
+}
@@ -0,0 +1,59 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::{CmaEs, EvoCoreContextSystem};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn system(cmaes: &Arc<CmaEs>) -> EvoCoreContextSystem {
+    EvoCoreContextSystem::deterministic(&["task", "editor"], &[vec!["code", "prose"], vec!["vim", "emacs"]], 2, 3)
+        .unwrap()
+        .with_sampling_strategy(cmaes.clone())
+}
+
+fn train(system: &mut EvoCoreContextSystem, dims: &[&str], rounds: usize) {
+    for _ in 0..rounds {
+        let params = system.sample(dims, 0.5).unwrap();
+        let fitness = 1.0 - (params[0] - 0.3).abs();
+        system.learn(dims, &params, fitness).unwrap();
+    }
+}
+
+#[test]
+fn rename_moves_per_context_state() {
+    let cmaes = Arc::new(CmaEs::new().with_population(2));
+    let mut system = system(&cmaes);
+    train(&mut system, &["code", "vim"], 10);
+    system.promote(&["code", "vim"]).unwrap();
+    system.pin_context(&["code", "vim"]).unwrap();
+    system.override_params(&["code", "vim"], &[0.1, 0.2], Duration::from_secs(60)).unwrap();
+    let before = system.context_state("code:vim").unwrap();
+    let generation = cmaes.generation("code:vim").unwrap();
+    let version = system.context_version(&["code", "vim"]).unwrap();
+
+    assert_eq!(system.rename_dimension_value("task", "code", "coding").unwrap(), 1);
+
+    assert!(system.context_state("code:vim").is_none());
+    let after = system.context_state("coding:vim").unwrap();
+    assert_eq!(after.total_experiences, before.total_experiences);
+    assert!((after.params[0].mean - before.params[0].mean).abs() < 1e-12);
+    assert_eq!(cmaes.generation("coding:vim"), Some(generation));
+    assert_eq!(cmaes.generation("code:vim"), None);
+    assert_eq!(system.pinned_contexts(), ["coding:vim"]);
+    assert_eq!(system.stable_state(&["coding", "vim"]).unwrap().key, "coding:vim");
+    assert!(system.context_version(&["coding", "vim"]).unwrap() >= version);
+    assert_eq!(system.active_overrides()[0].key, "coding:vim");
+}
+
+#[test]
+fn rename_onto_existing_value_merges_contexts() {
+    let cmaes = Arc::new(CmaEs::new().with_population(2));
+    let mut system = system(&cmaes);
+    train(&mut system, &["code", "vim"], 6);
+    train(&mut system, &["prose", "vim"], 4);
+
+    assert_eq!(system.rename_dimension_value("task", "code", "prose").unwrap(), 1);
+
+    assert_eq!(system.context_keys(), ["prose:vim"]);
+    assert_eq!(system.context_state("prose:vim").unwrap().total_experiences, 10);
+}
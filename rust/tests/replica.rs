@@ -0,0 +1,38 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::{EvoCoreContextSystem, SharedContextSystem};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn shared() -> Arc<SharedContextSystem> {
+    let mut system = EvoCoreContextSystem::deterministic(&["user"], &[vec!["a"]], 1, 9).unwrap();
+    for user in ["a", "b", "c", "d"] {
+        system.learn(&[user], &[0.5], 1.0).unwrap();
+    }
+    Arc::new(SharedContextSystem::new(system))
+}
+
+#[test]
+fn full_replica_evicts_least_recently_sampled() {
+    let mut replica = shared().read_replica(Duration::from_secs(3600)).with_capacity(2);
+    for _ in 0..10 {
+        replica.sample(&["a"], 0.1).unwrap();
+    }
+    replica.sample(&["b"], 0.1).unwrap();
+    replica.sample(&["c"], 0.1).unwrap();
+    // "a" was sampled most but least recently
+    assert_eq!(replica.stats().evictions, 1);
+
+    // A newcomer stays cached while it is in use
+    let misses = replica.stats().misses;
+    for _ in 0..5 {
+        replica.sample(&["c"], 0.1).unwrap();
+        replica.sample(&["b"], 0.1).unwrap();
+    }
+    assert_eq!(replica.stats().misses, misses);
+
+    replica.sample(&["a"], 0.1).unwrap();
+    assert_eq!(replica.stats().misses, misses + 1);
+    assert_eq!(replica.len(), 2);
+}
@@ -0,0 +1,58 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::{CmaEs, EvoCoreContextSystem};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn system(cmaes: &Arc<CmaEs>) -> EvoCoreContextSystem {
+    EvoCoreContextSystem::deterministic(&["task", "editor"], &[vec!["code", "prose"], vec!["vim", "emacs"]], 2, 3)
+        .unwrap()
+        .with_sampling_strategy(cmaes.clone())
+}
+
+fn train(system: &mut EvoCoreContextSystem, dims: &[&str], rounds: usize) {
+    for _ in 0..rounds {
+        let params = system.sample(dims, 0.5).unwrap();
+        let fitness = 1.0 - (params[0] - 0.3).abs();
+        system.learn(dims, &params, fitness).unwrap();
+    }
+}
+
+#[test]
+fn reset_clears_learned_state_and_keeps_configuration() {
+    let cmaes = Arc::new(CmaEs::new().with_population(2));
+    let mut system = system(&cmaes);
+    train(&mut system, &["code", "vim"], 10);
+    train(&mut system, &["prose", "vim"], 10);
+    system.promote(&["code", "vim"]).unwrap();
+    system.pin_context(&["code", "vim"]).unwrap();
+    system.override_params(&["code", "vim"], &[0.1, 0.2], Duration::from_secs(60)).unwrap();
+    assert!(cmaes.generation("code:vim").unwrap() > 0);
+
+    assert!(system.reset_context(&["code", "vim"]).unwrap());
+
+    assert!(system.context_state("code:vim").is_none());
+    assert!(system.stable_state(&["code", "vim"]).is_none());
+    assert_eq!(cmaes.generation("code:vim"), None);
+    assert_eq!(system.pinned_contexts(), ["code:vim"]);
+    assert_eq!(system.active_overrides()[0].key, "code:vim");
+
+    // Other contexts are untouched
+    assert_eq!(system.context_state("prose:vim").unwrap().total_experiences, 10);
+    assert!(cmaes.generation("prose:vim").is_some());
+    assert!(!system.reset_context(&["code", "vim"]).unwrap());
+}
+
+#[test]
+fn reset_all_forgets_every_context() {
+    let cmaes = Arc::new(CmaEs::new().with_population(2));
+    let mut system = system(&cmaes);
+    train(&mut system, &["code", "vim"], 4);
+    train(&mut system, &["prose", "emacs"], 4);
+
+    assert_eq!(system.reset_all(), 2);
+    assert_eq!(system.context_count(), 0);
+    assert_eq!(cmaes.generation("code:vim"), None);
+    assert_eq!(cmaes.generation("prose:emacs"), None);
+}
@@ -0,0 +1,93 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::{
+    rollback_guard, EvoCoreContextSystem, GuardStatus, ParamBounds, PromotionChecks, RollbackConfig,
+    SharedContextSystem,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("evocore-rollback-{}-{}", std::process::id(), name))
+}
+
+fn system() -> EvoCoreContextSystem {
+    EvoCoreContextSystem::deterministic(&["task"], &[vec!["code", "prose"]], 2, 11).unwrap()
+}
+
+#[test]
+fn rollback_restores_learned_state_and_keeps_configuration() {
+    let prod = temp_path("prod.json");
+    let candidate = temp_path("candidate.json");
+
+    let mut previous = system();
+    for _ in 0..5 {
+        previous.learn(&["code"], &[0.2, 0.4], 0.9).unwrap();
+    }
+    previous.save(prod.to_str().unwrap()).unwrap();
+
+    let mut trained = system();
+    for _ in 0..5 {
+        trained.learn(&["code"], &[0.8, 0.6], 0.9).unwrap();
+        trained.learn(&["prose"], &[0.5, 0.5], 0.9).unwrap();
+    }
+    trained.save(candidate.to_str().unwrap()).unwrap();
+
+    // The serving system runs the candidate with its own configuration
+    let mut serving = EvoCoreContextSystem::load(candidate.to_str().unwrap())
+        .unwrap()
+        .with_bounds(0, ParamBounds::new(0.0, 0.5))
+        .unwrap();
+    serving.override_params(&["prose"], &[0.1, 0.1], Duration::from_secs(60)).unwrap();
+    let shared = Arc::new(SharedContextSystem::new(serving));
+
+    let mut guard = rollback_guard(&prod, RollbackConfig::new(3, 0.1)).unwrap().with_system(shared.clone());
+    guard.promote(&candidate, PromotionChecks::new()).unwrap();
+    guard.record(0.1).unwrap();
+    guard.record(0.1).unwrap();
+    assert!(matches!(guard.record(0.1).unwrap(), GuardStatus::RolledBack { .. }));
+
+    shared.read(|system| {
+        assert_eq!(system.context_keys(), ["code"]);
+        let code = system.context_state("code").unwrap();
+        assert_eq!(code.total_experiences, 5);
+        assert!((code.params[0].mean - 0.2).abs() < 1e-6);
+        assert_eq!(system.bounds(0), Some(ParamBounds::new(0.0, 0.5)));
+        assert_eq!(system.active_overrides().len(), 1);
+    });
+    let restored = EvoCoreContextSystem::load(prod.to_str().unwrap()).unwrap();
+    assert_eq!(restored.context_keys(), ["code"]);
+
+    let _ = std::fs::remove_file(prod);
+    let _ = std::fs::remove_file(candidate);
+}
+
+#[test]
+fn rollback_without_previous_checkpoint_clears_contexts() {
+    let prod = temp_path("fresh.json");
+    let candidate = temp_path("fresh-candidate.json");
+    let _ = std::fs::remove_file(&prod);
+
+    let mut trained = system();
+    trained.learn(&["code"], &[0.8, 0.6], 0.9).unwrap();
+    trained.save(candidate.to_str().unwrap()).unwrap();
+
+    let serving = EvoCoreContextSystem::load(candidate.to_str().unwrap())
+        .unwrap()
+        .with_bounds(1, ParamBounds::new(0.2, 0.8))
+        .unwrap();
+    let shared = Arc::new(SharedContextSystem::new(serving));
+
+    let mut guard = rollback_guard(&prod, RollbackConfig::new(10, 0.1)).unwrap().with_system(shared.clone());
+    assert!(!guard.has_snapshot());
+    guard.promote(&candidate, PromotionChecks::new()).unwrap();
+    guard.rollback().unwrap();
+
+    assert!(!prod.exists());
+    assert_eq!(shared.context_count(), 0);
+    assert_eq!(shared.read(|system| system.bounds(1)), Some(ParamBounds::new(0.2, 0.8)));
+
+    let _ = std::fs::remove_file(candidate);
+}
@@ -0,0 +1,49 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(all(feature = "sqlite", not(feature = "dlopen")))]
+
+use evocore_sys::{EvoCoreContextSystem, SqliteStore};
+
+fn promoted() -> EvoCoreContextSystem {
+    let mut system = EvoCoreContextSystem::new(&["task"], &[vec!["code", "prose"]], 1).unwrap();
+    for _ in 0..5 {
+        system.learn(&["code"], &[0.2], 1.0).unwrap();
+    }
+    system.promote(&["code"]).unwrap();
+    for _ in 0..5 {
+        system.learn(&["code"], &[0.8], 1.0).unwrap();
+    }
+    system.learn(&["prose"], &[0.5], 1.0).unwrap();
+    system
+}
+
+#[test]
+fn stable_slots_survive_save_all_and_load_all() {
+    let mut store = SqliteStore::open_in_memory().unwrap();
+    store.save_all(&promoted()).unwrap();
+
+    let mut loaded = store.load_all().unwrap();
+    assert_eq!(loaded.stable_state(&["code"]).unwrap().total_experiences, 5);
+    assert!(loaded.stable_state(&["prose"]).is_none());
+    loaded.rollback(&["code"]).unwrap();
+    assert_eq!(loaded.context_state("code").unwrap().total_experiences, 5);
+}
+
+#[test]
+fn stable_slots_travel_with_single_contexts() {
+    let mut store = SqliteStore::open_in_memory().unwrap();
+    let mut system = promoted();
+    store.save_schema(&system).unwrap();
+    assert!(store.save_context(&system, &["code"]).unwrap());
+
+    let mut lazy = store.load_schema().unwrap();
+    assert!(store.load_context(&mut lazy, &["code"]).unwrap());
+    assert_eq!(lazy.stable_state(&["code"]).unwrap().total_experiences, 5);
+
+    // A context saved without a stable slot drops the stored one
+    system.reset_context(&["code"]).unwrap();
+    system.learn(&["code"], &[0.5], 1.0).unwrap();
+    store.save_context(&system, &["code"]).unwrap();
+    let mut lazy = store.load_schema().unwrap();
+    store.load_context(&mut lazy, &["code"]).unwrap();
+    assert!(lazy.stable_state(&["code"]).is_none());
+}
@@ -0,0 +1,35 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::{EvoCoreContextSystem, ParamBounds};
+
+fn system() -> EvoCoreContextSystem {
+    EvoCoreContextSystem::new(&["task", "editor"], &[vec!["code"], vec!["vim"]], 2)
+        .unwrap()
+        .with_bounds(0, ParamBounds::new(0.0, 1.0))
+        .unwrap()
+}
+
+#[test]
+fn learn_rejects_wrong_dimension_count() {
+    let mut system = system();
+    assert!(system.learn(&["code"], &[0.5, 0.5], 1.0).is_err());
+    assert!(system.learn(&["code", "vim", "extra"], &[0.5, 0.5], 1.0).is_err());
+    assert_eq!(system.context_count(), 0);
+}
+
+#[test]
+fn learn_batch_rejects_wrong_dimension_count() {
+    let mut system = system();
+    let examples: [(&[&str], &[f64], f64); 2] = [(&["code", "vim"], &[0.5, 0.5], 1.0), (&["code"], &[0.5, 0.5], 1.0)];
+    assert!(system.learn_batch(&examples).is_err());
+    assert_eq!(system.context_count(), 0);
+}
+
+#[test]
+fn learn_rejects_out_of_bounds_parameters() {
+    let mut system = system();
+    assert!(system.learn(&["code", "vim"], &[1.5, 0.5], 1.0).is_err());
+    system.learn(&["code", "vim"], &[1.0, 0.5], 1.0).unwrap();
+    assert_eq!(system.context_count(), 1);
+}
@@ -0,0 +1,57 @@
+// Needs the C library, which `dlopen` may not find
+#![cfg(not(feature = "dlopen"))]
+
+use evocore_sys::test_util::assert_statistically_equivalent;
+use evocore_sys::{EvoCoreContextSystem, Format, SaveOptions, WILDCARD};
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("evocore-wildcard-{}-{}", std::process::id(), name))
+}
+
+/// A system with ordinary, partially wildcarded and fully wildcarded contexts
+fn trained() -> EvoCoreContextSystem {
+    let mut system =
+        EvoCoreContextSystem::deterministic(&["task", "editor"], &[vec!["code", "prose"], vec!["vim", "emacs"]], 2, 5)
+            .unwrap();
+    for i in 0..12 {
+        let x = i as f64 / 12.0;
+        system.learn(&["code", "vim"], &[x, 1.0 - x], 0.5 + x / 2.0).unwrap();
+        system.seed_wildcard(&["code", WILDCARD], &[0.3, x], 0.7).unwrap();
+    }
+    system.seed_wildcard(&[WILDCARD, WILDCARD], &[0.5, 0.5], 0.6).unwrap();
+    system
+}
+
+fn round_trip(system: &EvoCoreContextSystem, format: Format, name: &str) -> EvoCoreContextSystem {
+    let path = temp_path(name);
+    system.save_as(&path, &SaveOptions::format(format)).unwrap();
+    let loaded = EvoCoreContextSystem::load_as(&path, format).unwrap();
+    let _ = std::fs::remove_file(path);
+    loaded
+}
+
+#[test]
+fn wildcard_contexts_survive_a_round_trip() {
+    let system = trained();
+    for (format, name) in [(Format::Json, "wildcard.json"), (Format::Binary, "wildcard.bin")] {
+        let loaded = round_trip(&system, format, name);
+        let mut keys = loaded.context_keys();
+        keys.sort();
+        assert_eq!(keys, ["*:*", "code:*", "code:vim"], "{:?}", format);
+        assert_statistically_equivalent(&system, &loaded, 1e-6);
+    }
+}
+
+#[test]
+fn loaded_wildcards_serve_cold_contexts() {
+    let loaded = round_trip(&trained(), Format::Json, "fallback.json").with_wildcard_fallback(5);
+    assert_eq!(loaded.context_state("code:*").unwrap().total_experiences, 12);
+    assert_eq!(loaded.sample(&["code", "emacs"], 0.0).unwrap().len(), 2);
+}
+
+#[test]
+fn seeding_requires_a_wildcard() {
+    let mut system = trained();
+    assert!(system.seed_wildcard(&["code", "vim"], &[0.5, 0.5], 1.0).is_err());
+}
@@ -1269,6 +1269,38 @@ void evocore_context_reset_all(evocore_context_system_t *system) {
     }
 }
 
+bool evocore_context_remove_key(
+    evocore_context_system_t *system,
+    const char *context_key
+) {
+    if (!system || !context_key) return false;
+
+    hash_table_t *table = (hash_table_t*)system->internal;
+    uint32_t hash = hash_string(context_key);
+    size_t index = hash % table->capacity;
+
+    hash_entry_t **link = &table->entries[index];
+    while (*link) {
+        hash_entry_t *entry = *link;
+        if (entry->hash == hash && strcmp(entry->key, context_key) == 0) {
+            *link = entry->next;
+            free(entry->key);
+            if (entry->stats) {
+                if (entry->stats->stats) {
+                    evocore_weighted_array_free(entry->stats->stats);
+                }
+                free(entry->stats);
+            }
+            free(entry);
+            table->count--;
+            return true;
+        }
+        link = &entry->next;
+    }
+
+    return false;
+}
+
 double evocore_context_confidence(const evocore_context_stats_t *stats) {
     if (!stats) return 0.0;
     return stats->confidence;
@@ -13,6 +13,8 @@
 static evocore_log_level_t g_log_level = EVOCORE_LOG_INFO;
 static FILE *g_log_file = NULL;
 static bool g_log_color = true;
+static evocore_log_callback_t g_log_callback = NULL;
+static void *g_log_callback_data = NULL;
 static const char *g_log_level_names[] = {
     "TRACE", "DEBUG", "INFO", "WARN", "ERROR", "FATAL"
 };
@@ -63,6 +65,11 @@ void evocore_log_set_color(bool enabled) {
     g_log_color = enabled;
 }
 
+void evocore_log_set_callback(evocore_log_callback_t callback, void *user_data) {
+    g_log_callback = callback;
+    g_log_callback_data = user_data;
+}
+
 void evocore_log_close(void) {
     if (g_log_file != NULL) {
         fclose(g_log_file);
@@ -97,19 +104,25 @@ void evocore_log_message(evocore_log_level_t level,
     va_list args;
     va_start(args, fmt);
 
-    /* Console output */
-    if (g_log_color) {
-        fprintf(stderr, "%s%s %-5s %s:%d%s ",
-                g_color_levels[level], time_buf,
-                g_log_level_names[level], filename, line,
-                g_color_reset);
+    /* Callback output replaces the console */
+    if (g_log_callback != NULL) {
+        char message[1024];
+        vsnprintf(message, sizeof(message), fmt, args);
+        g_log_callback(level, filename, line, message, g_log_callback_data);
     } else {
-        fprintf(stderr, "%s %-5s %s:%d ",
-                time_buf, g_log_level_names[level], filename, line);
+        if (g_log_color) {
+            fprintf(stderr, "%s%s %-5s %s:%d%s ",
+                    g_color_levels[level], time_buf,
+                    g_log_level_names[level], filename, line,
+                    g_color_reset);
+        } else {
+            fprintf(stderr, "%s %-5s %s:%d ",
+                    time_buf, g_log_level_names[level], filename, line);
+        }
+
+        vfprintf(stderr, fmt, args);
+        fprintf(stderr, "\n");
     }
-
-    vfprintf(stderr, fmt, args);
-    fprintf(stderr, "\n");
     va_end(args);
 
     /* File output (no colors) */